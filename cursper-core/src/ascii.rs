@@ -0,0 +1,35 @@
+// Plain-ASCII fallback for targets that only pass ASCII keystrokes through, such as
+// remote desktop and VM windows, where other characters arrive garbled or not at all.
+
+// Characters without a sensible ASCII spelling
+const UNKNOWN: &str = "?";
+
+// Transliterate text to ASCII: accents are dropped ("Grüße" -> "Grusse"), typographic
+// quotes and dashes become their plain forms, and other scripts are romanized
+pub fn to_ascii(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    // Low and angle quotation marks would otherwise come out as ",," and "<<"
+    let text: String = text
+        .chars()
+        .map(|c| match c {
+            '„' | '«' | '»' => '"',
+            '‚' | '‹' | '›' => '\'',
+            c => c,
+        })
+        .collect();
+    deunicode::deunicode_with_tofu(&text, UNKNOWN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn transliterates_to_ascii() {
+        assert_eq!(to_ascii("Café „naïve“ – 5 €"), "Cafe \"naive\" - 5 EUR");
+        assert_eq!(to_ascii("line one\nline two"), "line one\nline two");
+        assert!(to_ascii("Grüße, 你好").is_ascii());
+    }
+}
@@ -0,0 +1,476 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Audio format token advertised by backends that accept raw PCM uploads
+pub const PCM_FORMAT: &str = "pcm_s16le";
+
+// Audio format token advertised by backends that accept Ogg Opus uploads
+pub const OPUS_FORMAT: &str = "opus";
+
+// How audio is uploaded to the backend
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadFormat {
+    // Raw PCM when the backend advertises support, WAV otherwise
+    #[default]
+    Auto,
+    // A complete WAV file sent as audio/wav
+    Wav,
+    // 16-bit little-endian PCM with X-Sample-Rate / X-Channels headers
+    Pcm,
+}
+
+// Raw samples captured from the input device
+pub struct RecordedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub duration: Duration,
+    // Samples lost because the recorder fell behind the input device
+    pub dropped_samples: u64,
+}
+
+// Which input channels end up in the uploaded audio
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputChannelMode {
+    // Keep every channel as captured
+    #[default]
+    All,
+    // Average all channels into mono; halves the level of a mic that is only on one channel
+    Downmix,
+    // Use only the first or second channel, e.g. an interface with the mic on one side
+    Left,
+    Right,
+    // Use a single channel (1-based), e.g. the mic input of a stereo interface
+    Channel(u16),
+    // Use whichever channel carries the most energy
+    Loudest,
+}
+
+impl InputChannelMode {
+    // 0-based index of the one channel kept out of this many, for single-channel modes
+    fn fixed_channel(self, channels: usize) -> Option<usize> {
+        let channel = match self {
+            InputChannelMode::Left => 1,
+            InputChannelMode::Right => 2,
+            InputChannelMode::Channel(channel) => channel.max(1) as usize,
+            _ => return None,
+        };
+        Some((channel - 1).min(channels.saturating_sub(1)))
+    }
+}
+
+// Reduce interleaved multi-channel audio to mono according to the channel mode
+pub fn map_channels(recorded: RecordedAudio, mode: InputChannelMode) -> RecordedAudio {
+    let channels = recorded.channels as usize;
+    if channels <= 1 || mode == InputChannelMode::All {
+        return recorded;
+    }
+    
+    let frames = recorded.samples.chunks_exact(channels);
+    let samples: Vec<f32> = match mode {
+        InputChannelMode::All => unreachable!(),
+        InputChannelMode::Downmix => frames
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect(),
+        InputChannelMode::Left | InputChannelMode::Right | InputChannelMode::Channel(_) => {
+            let index = mode.fixed_channel(channels).unwrap_or(0);
+            println!("🎚️ Using input channel {} of {}", index + 1, channels);
+            frames.map(|frame| frame[index]).collect()
+        }
+        InputChannelMode::Loudest => {
+            let mut energy = vec![0.0f64; channels];
+            for frame in recorded.samples.chunks_exact(channels) {
+                for (channel, sample) in frame.iter().enumerate() {
+                    energy[channel] += (*sample as f64) * (*sample as f64);
+                }
+            }
+            let index = energy
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(index, _)| index)
+                .unwrap_or(0);
+            println!("🎚️ Loudest input channel is {} of {}", index + 1, channels);
+            frames.map(|frame| frame[index]).collect()
+        }
+    };
+    
+    RecordedAudio {
+        samples,
+        channels: 1,
+        ..recorded
+    }
+}
+
+// Fade the start of the capture in so the activation hotkey's click isn't transcribed
+pub fn gate_key_click(mut recorded: RecordedAudio, gate_ms: u64) -> RecordedAudio {
+    let channels = recorded.channels.max(1) as usize;
+    let gate_frames = (recorded.sample_rate as u64 * gate_ms / 1000) as usize;
+    if gate_frames == 0 {
+        return recorded;
+    }
+    
+    for (index, sample) in recorded.samples.iter_mut().enumerate() {
+        let frame = index / channels;
+        if frame >= gate_frames {
+            break;
+        }
+        // Raised-cosine ramp from silence to full level
+        let progress = frame as f32 / gate_frames as f32;
+        *sample *= 0.5 - 0.5 * (std::f32::consts::PI * progress).cos();
+    }
+    recorded
+}
+
+// Length of the windows used to measure loudness when trimming silence
+const SILENCE_WINDOW_MS: u64 = 10;
+
+// Sample range left after dropping leading and trailing audio whose RMS stays under the
+// threshold, keeping some padding. Returns None when the whole recording is silent.
+pub fn silence_bounds(samples: &[f32], sample_rate: u32, channels: u16, threshold: f32, padding_ms: u64) -> Option<(usize, usize)> {
+    let channels = channels.max(1) as usize;
+    let window = ((sample_rate as u64 * SILENCE_WINDOW_MS / 1000) as usize).max(1) * channels;
+    
+    let loud: Vec<bool> = samples
+        .chunks(window)
+        .map(|chunk| {
+            let sum: f32 = chunk.iter().map(|sample| sample * sample).sum();
+            (sum / chunk.len() as f32).sqrt() >= threshold
+        })
+        .collect();
+    
+    let first = loud.iter().position(|is_loud| *is_loud)?;
+    let last = loud.iter().rposition(|is_loud| *is_loud)?;
+    
+    let padding = (padding_ms / SILENCE_WINDOW_MS) as usize;
+    let start = first.saturating_sub(padding) * window;
+    let end = ((last + 1 + padding) * window).min(samples.len());
+    Some((start, end))
+}
+
+fn log_trimmed(removed: usize, sample_rate: u32, channels: u16) {
+    if removed > 0 {
+        println!("✂️ Trimmed {} ms of silence", removed as u64 * 1000 / (sample_rate as u64 * channels as u64).max(1));
+    }
+}
+
+// Drop leading and trailing audio whose RMS stays under the threshold, keeping some padding.
+// Returns None when the whole recording is silent.
+pub fn trim_silence(recorded: RecordedAudio, threshold: f32, padding_ms: u64) -> Option<RecordedAudio> {
+    let (start, end) = silence_bounds(&recorded.samples, recorded.sample_rate, recorded.channels, threshold, padding_ms)?;
+    let kept = end - start;
+    log_trimmed(recorded.samples.len() - kept, recorded.sample_rate, recorded.channels);
+    
+    Some(RecordedAudio {
+        samples: recorded.samples[start..end].to_vec(),
+        ..recorded
+    })
+}
+
+// Average interleaved channels into mono and resample linearly to target_rate, e.g. ahead of
+// a codec that only takes a few fixed rates
+pub fn resample_mono(samples: &[f32], sample_rate: u32, channels: u16, target_rate: u32) -> Vec<f32> {
+    let channels = channels.max(1) as usize;
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    if sample_rate == target_rate || mono.is_empty() {
+        return mono;
+    }
+    
+    let step = sample_rate as f64 / target_rate as f64;
+    let length = (mono.len() as f64 / step).floor() as usize;
+    (0..length)
+        .map(|index| {
+            let position = index as f64 * step;
+            let before = position.floor() as usize;
+            let after = (before + 1).min(mono.len() - 1);
+            let weight = (position - before as f64) as f32;
+            mono[before] * (1.0 - weight) + mono[after] * weight
+        })
+        .collect()
+}
+
+fn pcm_sample(sample: f32) -> [u8; 2] {
+    ((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes()
+}
+
+// Convert audio samples to raw 16-bit little-endian PCM
+pub fn convert_to_pcm(samples: &[f32]) -> Vec<u8> {
+    samples.iter().flat_map(|&sample| pcm_sample(sample)).collect()
+}
+
+// Wrap 16-bit little-endian PCM in a WAV header
+pub fn pcm_to_wav(pcm: &[u8], sample_rate: u32, channels: u16) -> Vec<u8> {
+    let data_len = pcm.len() as u32;
+    let block_align = channels * 2;
+    let mut wav = Vec::with_capacity(44 + pcm.len());
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    wav.extend_from_slice(pcm);
+    wav
+}
+
+// Processed audio and its PCM encoding, produced by the incremental encoder
+pub struct EncodedAudio {
+    pub recorded: RecordedAudio,
+    pub pcm: Vec<u8>,
+}
+
+// Applies the key-click gate and channel mapping and encodes PCM as chunks arrive, so a
+// recording is already encoded when capture stops and only silence trimming is left.
+// Picking the loudest channel needs the whole clip, so that mode is not supported.
+pub struct IncrementalEncoder {
+    sample_rate: u32,
+    channels: u16,
+    mode: InputChannelMode,
+    gate_frames: usize,
+    frames_seen: usize,
+    // Samples of a frame split across chunks
+    pending: Vec<f32>,
+    samples: Vec<f32>,
+    pcm: Vec<u8>,
+}
+
+impl IncrementalEncoder {
+    pub fn new(sample_rate: u32, channels: u16, mode: InputChannelMode, gate_ms: u64) -> Option<Self> {
+        if channels > 1 && mode == InputChannelMode::Loudest {
+            return None;
+        }
+        Some(Self {
+            sample_rate,
+            channels: channels.max(1),
+            mode,
+            gate_frames: (sample_rate as u64 * gate_ms / 1000) as usize,
+            frames_seen: 0,
+            pending: Vec::new(),
+            samples: Vec::new(),
+            pcm: Vec::new(),
+        })
+    }
+    
+    fn output_channels(&self) -> u16 {
+        if self.mode == InputChannelMode::All { self.channels } else { 1 }
+    }
+    
+    // Process and encode the next interleaved chunk
+    pub fn push(&mut self, chunk: &[f32]) {
+        let channels = self.channels as usize;
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.extend_from_slice(chunk);
+        let whole = pending.len() / channels * channels;
+        
+        for frame in pending[..whole].chunks_exact(channels) {
+            // Same raised-cosine ramp as gate_key_click
+            let gain = if self.frames_seen < self.gate_frames {
+                let progress = self.frames_seen as f32 / self.gate_frames as f32;
+                0.5 - 0.5 * (std::f32::consts::PI * progress).cos()
+            } else {
+                1.0
+            };
+            self.frames_seen += 1;
+            
+            match (self.mode, self.mode.fixed_channel(channels)) {
+                (InputChannelMode::Downmix, _) if channels > 1 => {
+                    self.emit(gain * frame.iter().sum::<f32>() / channels as f32);
+                }
+                (_, Some(index)) if channels > 1 => {
+                    self.emit(gain * frame[index]);
+                }
+                _ => {
+                    for sample in frame {
+                        self.emit(gain * sample);
+                    }
+                }
+            }
+        }
+        
+        pending.drain(..whole);
+        self.pending = pending;
+    }
+    
+    fn emit(&mut self, sample: f32) {
+        self.samples.push(sample);
+        self.pcm.extend_from_slice(&pcm_sample(sample));
+    }
+    
+    // Trim silence (threshold, padding) if requested and hand out the result, keeping the
+    // capture's duration and drop count; None when nothing was captured or all is silent
+    pub fn finish(self, captured: &RecordedAudio, trim: Option<(f32, u64)>) -> Option<EncodedAudio> {
+        let channels = self.output_channels();
+        let (start, end) = match trim {
+            Some((threshold, padding_ms)) => silence_bounds(&self.samples, self.sample_rate, channels, threshold, padding_ms)?,
+            None if self.samples.is_empty() => return None,
+            None => (0, self.samples.len()),
+        };
+        log_trimmed(self.samples.len() - (end - start), self.sample_rate, channels);
+        
+        Some(EncodedAudio {
+            pcm: self.pcm[start * 2..end * 2].to_vec(),
+            recorded: RecordedAudio {
+                samples: self.samples[start..end].to_vec(),
+                sample_rate: self.sample_rate,
+                channels,
+                duration: captured.duration,
+                dropped_samples: captured.dropped_samples,
+            },
+        })
+    }
+}
+
+// Convert audio samples to WAV format
+pub fn convert_to_wav(samples: &[f32], sample_rate: u32, channels: u16) -> Result<Vec<u8>, String> {
+    use std::io::Cursor;
+    use hound::{WavWriter, WavSpec};
+    
+    let spec = WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer = WavWriter::new(&mut cursor, spec)
+        .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
+    
+    // Convert f32 samples to i16 and write
+    for &sample in samples {
+        let sample_i16 = (sample * i16::MAX as f32) as i16;
+        writer.write_sample(sample_i16)
+            .map_err(|e| format!("Failed to write sample: {}", e))?;
+    }
+    
+    writer.finalize()
+        .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+    
+    Ok(cursor.into_inner())
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn recorded(samples: Vec<f32>, sample_rate: u32, channels: u16) -> RecordedAudio {
+        RecordedAudio {
+            samples,
+            sample_rate,
+            channels,
+            duration: Duration::from_secs(1),
+            dropped_samples: 0,
+        }
+    }
+    
+    #[test]
+    fn map_channels_picks_downmixes_and_finds_loudest() {
+        let stereo = vec![0.2, 0.8, 0.4, 0.6];
+        
+        let downmixed = map_channels(recorded(stereo.clone(), 1000, 2), InputChannelMode::Downmix);
+        assert_eq!(downmixed.channels, 1);
+        assert_eq!(downmixed.samples, vec![0.5, 0.5]);
+        
+        let right = map_channels(recorded(stereo.clone(), 1000, 2), InputChannelMode::Channel(2));
+        assert_eq!(right.samples, vec![0.8, 0.6]);
+        
+        let left = map_channels(recorded(stereo.clone(), 1000, 2), InputChannelMode::Left);
+        assert_eq!(left.samples, vec![0.2, 0.4]);
+        let right = map_channels(recorded(stereo.clone(), 1000, 2), InputChannelMode::Right);
+        assert_eq!(right.samples, vec![0.8, 0.6]);
+        let mono_right = map_channels(recorded(vec![0.3, 0.7], 1000, 1), InputChannelMode::Right);
+        assert_eq!(mono_right.samples, vec![0.3, 0.7]);
+        
+        let loudest = map_channels(recorded(stereo.clone(), 1000, 2), InputChannelMode::Loudest);
+        assert_eq!(loudest.samples, vec![0.8, 0.6]);
+        
+        let all = map_channels(recorded(stereo.clone(), 1000, 2), InputChannelMode::All);
+        assert_eq!(all.samples, stereo);
+    }
+    
+    #[test]
+    fn gate_key_click_fades_in_from_silence() {
+        let gated = gate_key_click(recorded(vec![1.0; 2000], 1000, 1), 100);
+        assert_eq!(gated.samples[0], 0.0);
+        assert!(gated.samples[50] > 0.0 && gated.samples[50] < 1.0);
+        assert_eq!(gated.samples[100], 1.0);
+    }
+    
+    #[test]
+    fn trim_silence_keeps_padding_around_speech() {
+        // 1 kHz mono: 500 ms silence, 200 ms speech, 500 ms silence
+        let mut samples = vec![0.0; 500];
+        samples.extend(vec![0.5; 200]);
+        samples.extend(vec![0.0; 500]);
+        
+        let trimmed = trim_silence(recorded(samples, 1000, 1), 0.01, 100).unwrap();
+        assert_eq!(trimmed.samples.len(), 400);
+        assert!(trim_silence(recorded(vec![0.0; 1000], 1000, 1), 0.01, 100).is_none());
+    }
+    
+    #[test]
+    fn resample_mono_downmixes_and_changes_rate() {
+        let stereo = vec![0.25, 0.75, 0.5, 1.0, 1.0, 1.0, 0.0, 0.0];
+        assert_eq!(resample_mono(&stereo, 16000, 2, 16000), vec![0.5, 0.75, 1.0, 0.0]);
+        
+        let ramp: Vec<f32> = (0..48).map(|i| i as f32).collect();
+        let resampled = resample_mono(&ramp, 48000, 1, 16000);
+        assert_eq!(resampled.len(), 16);
+        assert_eq!(resampled[..3], [0.0, 3.0, 6.0]);
+        
+        let upsampled = resample_mono(&[0.0, 1.0], 8000, 1, 16000);
+        assert_eq!(upsampled, vec![0.0, 0.5, 1.0, 1.0]);
+    }
+    
+    #[test]
+    fn encodes_pcm_and_wav() {
+        let pcm = convert_to_pcm(&[0.0, 1.0, -1.0]);
+        assert_eq!(pcm, vec![0, 0, 0xff, 0x7f, 0x01, 0x80]);
+        
+        let wav = convert_to_wav(&[0.0, 0.5], 16000, 1).unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(wav.len(), 44 + 4);
+    }
+    
+    #[test]
+    fn incremental_encoder_matches_whole_clip_processing() {
+        // 1 kHz stereo: silence, a tone on the right channel, silence
+        let mut samples = vec![0.0; 1000];
+        for index in 0..400 {
+            samples.push(0.1);
+            samples.push(if index % 2 == 0 { 0.5 } else { -0.5 });
+        }
+        samples.extend(vec![0.0; 1000]);
+        
+        let mut encoder = IncrementalEncoder::new(1000, 2, InputChannelMode::Channel(2), 50).unwrap();
+        // Odd chunk sizes split frames across pushes
+        for chunk in samples.chunks(7) {
+            encoder.push(chunk);
+        }
+        let captured = recorded(samples, 1000, 2);
+        let encoded = encoder.finish(&captured, Some((0.01, 100))).unwrap();
+        
+        let expected = map_channels(gate_key_click(captured, 50), InputChannelMode::Channel(2));
+        let expected = trim_silence(expected, 0.01, 100).unwrap();
+        assert_eq!(encoded.recorded.channels, 1);
+        assert_eq!(encoded.recorded.samples, expected.samples);
+        assert_eq!(encoded.pcm, convert_to_pcm(&expected.samples));
+        assert_eq!(pcm_to_wav(&encoded.pcm, 1000, 1), convert_to_wav(&expected.samples, 1000, 1).unwrap());
+    }
+    
+    #[test]
+    fn incremental_encoder_rejects_loudest_channel() {
+        assert!(IncrementalEncoder::new(1000, 2, InputChannelMode::Loudest, 0).is_none());
+        assert!(IncrementalEncoder::new(1000, 1, InputChannelMode::Loudest, 0).is_some());
+    }
+}
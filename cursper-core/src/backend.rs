@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// How long a health check may take before the backend counts as offline
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Parsed /health response; older backends leave out most fields
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendHealth {
+    pub status: String,
+    pub current_model: Option<String>,
+    pub model_loaded: bool,
+    // Upload formats accepted by /transcribe_raw; empty means WAV only
+    pub audio_formats: Vec<String>,
+}
+
+// Check that the backend is up and read what it supports
+pub async fn check_health(client: &reqwest::Client, backend_url: &str) -> Result<BackendHealth, String> {
+    let response = client
+        .get(format!("{}/health", backend_url))
+        .timeout(HEALTH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Backend not available: {}", e))?;
+    
+    if !response.status().is_success() {
+        return Err(format!("Backend unhealthy: {}", response.status()));
+    }
+    
+    let health_text = response
+        .text()
+        .await
+        .map_err(|e| format!("Could not read health response: {}", e))?;
+    println!("📋 Backend health: {}", health_text);
+    Ok(serde_json::from_str(&health_text).unwrap_or_default())
+}
+
+// Models offered by the backend, from either {"available_models": [...]} or a plain list
+pub async fn fetch_models(client: &reqwest::Client, backend_url: &str) -> Result<Vec<String>, String> {
+    let response = client
+        .get(format!("{}/models", backend_url))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to get models from backend: {}", e))?;
+    
+    if !response.status().is_success() {
+        return Err(format!("Backend returned error: {}", response.status()));
+    }
+    
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse models response: {}", e))?;
+    let models = body.get("available_models").cloned().unwrap_or(body);
+    serde_json::from_value(models).map_err(|e| format!("Failed to parse models response: {}", e))
+}
+
+// A downloaded model in the backend's cache
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CachedModel {
+    pub name: String,
+    pub size_bytes: u64,
+    // Unix seconds of the last load, or of the download for models never loaded
+    pub last_used: Option<u64>,
+    // The backend's current model, which can't be deleted
+    pub loaded: bool,
+}
+
+#[derive(Deserialize)]
+struct CachedModelsResponse {
+    cached_models: Vec<CachedModel>,
+}
+
+// Models the backend has downloaded, with their size on disk
+pub async fn fetch_cached_models(client: &reqwest::Client, backend_url: &str) -> Result<Vec<CachedModel>, String> {
+    let response = client
+        .get(format!("{}/models/cache", backend_url))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to get cached models from backend: {}", e))?;
+    
+    if !response.status().is_success() {
+        return Err(format!("Backend returned error: {}", response.status()));
+    }
+    
+    let body: CachedModelsResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse cached models response: {}", e))?;
+    Ok(body.cached_models)
+}
+
+// Delete a downloaded model from the backend's cache
+pub async fn delete_cached_model(client: &reqwest::Client, backend_url: &str, name: &str) -> Result<(), String> {
+    let mut url = reqwest::Url::parse(backend_url).map_err(|e| format!("Invalid backend URL: {}", e))?;
+    url.path_segments_mut()
+        .map_err(|_| "Invalid backend URL".to_string())?
+        .pop_if_empty()
+        .extend(["models", "cache", name]);
+    let response = client
+        .delete(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to delete cached model: {}", e))?;
+    
+    if !response.status().is_success() {
+        let status = response.status();
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        return Err(match body.get("error").and_then(|error| error.as_str()) {
+            Some(error) => format!("Backend refused to delete {}: {}", name, error),
+            None => format!("Backend returned error: {}", status),
+        });
+    }
+    Ok(())
+}
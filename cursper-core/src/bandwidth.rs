@@ -0,0 +1,86 @@
+use std::time::{Duration, Instant};
+
+// Upload throughput tracking: when uploads are slow, later ones switch to compressed
+// audio until the connection recovers
+
+// Uploads smaller than this finish within a round trip or two, so their throughput
+// mostly measures latency and is ignored
+pub const MIN_MEASURED_BYTES: usize = 64 * 1024;
+
+// Compressed uploads are usually too small to measure, so lossless audio is tried again
+// this long after the connection was last found slow
+pub const RETRY_LOSSLESS_AFTER: Duration = Duration::from_secs(5 * 60);
+
+// Measured throughput in kilobits per second
+pub fn throughput_kbps(bytes: usize, upload_ms: u64) -> f64 {
+    bytes as f64 * 8.0 / upload_ms.max(1) as f64
+}
+
+// Upload quality switched by BandwidthGovernor::record
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QualityChange {
+    // Uploads are slow; send compressed audio from now on
+    Degraded,
+    // Uploads are fast again; back to lossless audio
+    Restored,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BandwidthGovernor {
+    // When an upload was last found slower than the threshold
+    slow_since: Option<Instant>,
+}
+
+impl BandwidthGovernor {
+    pub const fn new() -> Self {
+        BandwidthGovernor { slow_since: None }
+    }
+    
+    // Whether the next upload should be compressed
+    pub fn should_compress(&self, now: Instant) -> bool {
+        self.slow_since
+            .is_some_and(|slow_since| now.saturating_duration_since(slow_since) < RETRY_LOSSLESS_AFTER)
+    }
+    
+    // Record a finished upload; returns the change in upload quality it causes, if any
+    pub fn record(&mut self, bytes: usize, upload_ms: u64, threshold_kbps: f64, now: Instant) -> Option<QualityChange> {
+        if bytes < MIN_MEASURED_BYTES {
+            return None;
+        }
+        let was_slow = self.slow_since.is_some();
+        if throughput_kbps(bytes, upload_ms) < threshold_kbps {
+            self.slow_since = Some(now);
+            (!was_slow).then_some(QualityChange::Degraded)
+        } else {
+            self.slow_since = None;
+            was_slow.then_some(QualityChange::Restored)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn compresses_after_slow_uploads_until_one_is_fast() {
+        let start = Instant::now();
+        let mut governor = BandwidthGovernor::default();
+        // 320 KB in 200 ms is 12800 kbit/s
+        assert_eq!(governor.record(320_000, 200, 500.0, start), None);
+        // Tiny uploads say nothing about bandwidth
+        assert_eq!(governor.record(1_000, 5_000, 500.0, start), None);
+        assert!(!governor.should_compress(start));
+        
+        // 320 KB in 8 s is 320 kbit/s
+        assert_eq!(governor.record(320_000, 8_000, 500.0, start), Some(QualityChange::Degraded));
+        assert!(governor.should_compress(start));
+        assert_eq!(governor.record(320_000, 8_000, 500.0, start), None);
+        
+        // Lossless audio gets another chance after a while
+        let later = start + RETRY_LOSSLESS_AFTER;
+        assert!(!governor.should_compress(later));
+        assert_eq!(governor.record(320_000, 200, 500.0, later), Some(QualityChange::Restored));
+        assert!(!governor.should_compress(later));
+    }
+}
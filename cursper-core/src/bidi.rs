@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+// Right-to-left dictation (Hebrew, Arabic, ...) typed into fields laid out left to right
+// shows trailing punctuation and embedded Latin words or numbers on the wrong side.
+// Unicode directional marks fix that without changing the visible text.
+
+const RLM: char = '\u{200F}';
+const RLI: char = '\u{2067}';
+const PDI: char = '\u{2069}';
+
+// How right-to-left dictations are marked before insertion
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DirectionalMarks {
+    #[default]
+    Off,
+    // Right-to-left marks at the start and after punctuation that would otherwise
+    // attach to the wrong side
+    Marks,
+    // Wrap the whole insertion in a right-to-left isolate, for editors that honour them
+    Isolate,
+}
+
+// Hebrew, Arabic, Syriac, Thaana, N'Ko and their presentation forms
+pub fn is_rtl_char(c: char) -> bool {
+    matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF)
+}
+
+pub fn contains_rtl(text: &str) -> bool {
+    text.chars().any(is_rtl_char)
+}
+
+// Whether the first letter decides the text reads right to left
+fn starts_rtl(text: &str) -> bool {
+    text.chars().find(|c| c.is_alphabetic()).is_some_and(is_rtl_char)
+}
+
+fn with_marks(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len() + 8);
+    output.push(RLM);
+    let mut after_ltr = false;
+    for (index, &c) in chars.iter().enumerate() {
+        output.push(c);
+        if c.is_alphanumeric() {
+            // Digits count as left-to-right here: punctuation after them drifts the same way
+            after_ltr = !is_rtl_char(c);
+            continue;
+        }
+        if c.is_whitespace() {
+            continue;
+        }
+        // Punctuation ending a word sticks to the text before it unless a mark follows
+        let ends_word = chars.get(index + 1).is_none_or(|next| next.is_whitespace());
+        if ends_word && (after_ltr || index + 1 == chars.len()) {
+            output.push(RLM);
+        }
+    }
+    output
+}
+
+// Add directional marks to a right-to-left dictation; anything else is returned as is
+pub fn add_directional_marks(text: &str, mode: DirectionalMarks) -> String {
+    if !starts_rtl(text) {
+        return text.to_string();
+    }
+    match mode {
+        DirectionalMarks::Off => text.to_string(),
+        DirectionalMarks::Marks => with_marks(text),
+        DirectionalMarks::Isolate => format!("{}{}{}", RLI, text, PDI),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn detects_right_to_left_scripts() {
+        assert!(contains_rtl("שלום world"));
+        assert!(contains_rtl("مرحبا"));
+        assert!(!contains_rtl("héllo wörld"));
+    }
+    
+    #[test]
+    fn marks_punctuation_that_would_drift() {
+        assert_eq!(add_directional_marks("שלום.", DirectionalMarks::Marks), "\u{200F}שלום.\u{200F}");
+        // A comma between two Hebrew words already sits right
+        assert_eq!(add_directional_marks("שלום, עולם", DirectionalMarks::Marks), "\u{200F}שלום, עולם");
+        assert_eq!(
+            add_directional_marks("המחיר 25$, תודה", DirectionalMarks::Marks),
+            "\u{200F}המחיר 25$,\u{200F} תודה"
+        );
+    }
+    
+    #[test]
+    fn leaves_left_to_right_text_alone() {
+        assert_eq!(add_directional_marks("Hello, שלום.", DirectionalMarks::Marks), "Hello, שלום.");
+        assert_eq!(add_directional_marks("שלום", DirectionalMarks::Off), "שלום");
+        assert_eq!(add_directional_marks("שלום", DirectionalMarks::Isolate), "\u{2067}שלום\u{2069}");
+    }
+}
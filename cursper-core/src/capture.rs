@@ -0,0 +1,957 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+#[cfg(feature = "capture")]
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::audio::RecordedAudio;
+
+// Environment variable selecting a fake input instead of the microphone:
+// "sine", "sine:<hz>" or the path of a WAV fixture
+pub const FAKE_AUDIO_ENV: &str = "CURSPER_FAKE_AUDIO";
+
+// Maximum 30 seconds to prevent infinite recording
+pub const MAX_RECORDING_DURATION: Duration = Duration::from_secs(30);
+
+// Returned when a recording was cancelled rather than stopped; callers drop it silently
+pub const RECORDING_CANCELLED_ERROR: &str = "Recording cancelled";
+
+// Longest the recorder blocks waiting for samples before checking whether to stop,
+// which bounds how long stopping a recording takes
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(20);
+
+// Chunk size of the fake source, similar to a typical device buffer
+const FAKE_CHUNK_MS: u64 = 10;
+
+// How much audio the device buffer holds before new samples are dropped
+#[cfg(feature = "capture")]
+const DEVICE_BUFFER_SECONDS: usize = 2;
+
+// Most monitoring audio queued for the output device; more would be heard as an echo
+#[cfg(feature = "capture")]
+const MONITOR_BUFFER_MS: usize = 60;
+
+// Number of input sources currently open, so the UI can match the OS microphone indicator
+static ACTIVE_CAPTURES: AtomicUsize = AtomicUsize::new(0);
+
+// Whether an input source is open right now
+pub fn is_capturing_audio() -> bool {
+    ACTIVE_CAPTURES.load(Ordering::SeqCst) > 0
+}
+
+// Counts an open source for as long as it lives
+struct CaptureGuard;
+
+impl CaptureGuard {
+    fn start() -> Self {
+        ACTIVE_CAPTURES.fetch_add(1, Ordering::SeqCst);
+        CaptureGuard
+    }
+}
+
+impl Drop for CaptureGuard {
+    fn drop(&mut self) {
+        ACTIVE_CAPTURES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Which device to record from and whether to play it back while recording
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CaptureOptions {
+    // Input device name (None = the system default)
+    pub device: Option<String>,
+    // Play the input on the default output at this volume (0.0-1.0) while recording
+    pub monitor_volume: Option<f32>,
+}
+
+// What the recorder does next, asked between chunks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureControl {
+    Record,
+    // Keep the input open but drop what it captures
+    Pause,
+    // Finish and return what was recorded
+    Stop,
+    // Finish and discard what was recorded
+    Cancel,
+}
+
+// Result of polling an audio source
+pub enum SourcePoll {
+    // Interleaved samples captured since the last poll
+    Samples(Vec<f32>),
+    // Nothing new yet
+    Pending,
+    // The source has no more audio (device gone or fixture exhausted)
+    Ended,
+}
+
+// Something that produces interleaved f32 samples, e.g. a microphone or a test fixture
+pub trait AudioSource {
+    fn sample_rate(&self) -> u32;
+    fn channels(&self) -> u16;
+    fn poll(&mut self) -> SourcePoll;
+    
+    // Block for up to timeout until samples arrive; Pending if none did. Sources that
+    // can't signal new samples poll again after sleeping.
+    fn wait(&mut self, timeout: Duration) -> SourcePoll {
+        match self.poll() {
+            SourcePoll::Pending => {
+                thread::sleep(timeout);
+                self.poll()
+            }
+            polled => polled,
+        }
+    }
+    
+    // Samples lost so far because they weren't polled in time
+    fn dropped_samples(&self) -> u64 {
+        0
+    }
+}
+
+// Bounded buffer between a real-time audio callback and the recorder. When the recorder
+// falls behind, incoming frames are dropped and counted rather than queued without limit.
+pub struct SampleBuffer {
+    state: Mutex<SampleBufferState>,
+    // Signalled whenever samples are queued
+    ready: Condvar,
+    capacity: usize,
+    channels: usize,
+}
+
+struct SampleBufferState {
+    samples: VecDeque<f32>,
+    dropped: u64,
+}
+
+impl SampleBuffer {
+    // Capacity is in samples and rounded down to whole frames
+    pub fn new(capacity: usize, channels: u16) -> Self {
+        let channels = channels.max(1) as usize;
+        let capacity = (capacity / channels).max(1) * channels;
+        Self {
+            state: Mutex::new(SampleBufferState {
+                samples: VecDeque::with_capacity(capacity),
+                dropped: 0,
+            }),
+            ready: Condvar::new(),
+            capacity,
+            channels,
+        }
+    }
+    
+    // Queue as many whole frames as fit; returns how many samples were dropped
+    pub fn push(&self, data: &[f32]) -> usize {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let free = self.capacity - state.samples.len();
+        let accepted = data.len().min(free) / self.channels * self.channels;
+        state.samples.extend(&data[..accepted]);
+        
+        let dropped = data.len() - accepted;
+        state.dropped += dropped as u64;
+        drop(state);
+        
+        if accepted > 0 {
+            self.ready.notify_all();
+        }
+        dropped
+    }
+    
+    // Take everything queued so far
+    pub fn drain(&self) -> Vec<f32> {
+        self.wait_and_drain(Duration::ZERO)
+    }
+    
+    // Take up to max queued samples, in whole frames
+    pub fn take(&self, max: usize) -> Vec<f32> {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let count = state.samples.len().min(max) / self.channels * self.channels;
+        state.samples.drain(..count).collect()
+    }
+    
+    // Take everything queued, first waiting up to timeout for samples if there are none
+    pub fn wait_and_drain(&self, timeout: Duration) -> Vec<f32> {
+        let state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let mut state = match self.ready.wait_timeout_while(state, timeout, |state| state.samples.is_empty()) {
+            Ok((state, _)) => state,
+            Err(poisoned) => poisoned.into_inner().0,
+        };
+        state.samples.drain(..).collect()
+    }
+    
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().map(|state| state.dropped).unwrap_or(0)
+    }
+}
+
+// Collect audio from a source until control says to stop, the source ends or the
+// maximum duration is reached. Time spent paused doesn't count towards the maximum.
+pub fn record_from_source(
+    source: &mut dyn AudioSource,
+    control: &mut dyn FnMut() -> CaptureControl,
+    max_duration: Duration,
+) -> Result<RecordedAudio, String> {
+    record_from_source_streaming(source, control, max_duration, &mut |_, _, _| {})
+}
+
+// Like record_from_source, also handing each captured chunk to on_samples as it arrives,
+// along with the source's sample rate and channel count
+pub fn record_from_source_streaming(
+    source: &mut dyn AudioSource,
+    control: &mut dyn FnMut() -> CaptureControl,
+    max_duration: Duration,
+    on_samples: &mut dyn FnMut(&[f32], u32, u16),
+) -> Result<RecordedAudio, String> {
+    let mut all_audio_data = Vec::new();
+    let start_time = Instant::now();
+    let mut paused_for = Duration::ZERO;
+    
+    loop {
+        let recorded_for = start_time.elapsed().saturating_sub(paused_for);
+        if recorded_for >= max_duration {
+            break;
+        }
+        
+        let paused = match control() {
+            CaptureControl::Record => false,
+            CaptureControl::Pause => true,
+            CaptureControl::Stop => {
+                println!("🛑 Recording stopped by user input");
+                break;
+            }
+            CaptureControl::Cancel => {
+                println!("🚫 Recording cancelled, discarding audio");
+                return Err(RECORDING_CANCELLED_ERROR.to_string());
+            }
+        };
+        
+        let waited_from = Instant::now();
+        let timeout = STOP_CHECK_INTERVAL.min(max_duration - recorded_for);
+        match source.wait(timeout) {
+            SourcePoll::Samples(data) if !paused => {
+                on_samples(&data, source.sample_rate(), source.channels());
+                all_audio_data.extend(data);
+            }
+            // Audio captured while paused is drained so the device buffer doesn't overflow
+            SourcePoll::Samples(_) | SourcePoll::Pending => {}
+            SourcePoll::Ended => break,
+        }
+        if paused {
+            paused_for += waited_from.elapsed();
+        }
+    }
+    
+    let recording_time = start_time.elapsed().saturating_sub(paused_for);
+    println!("🎤 Audio recording completed. Recorded for {:.2} seconds, collected {} samples",
+             recording_time.as_secs_f64(), all_audio_data.len());
+    let dropped_samples = source.dropped_samples();
+    if dropped_samples > 0 {
+        println!("⚠️ Dropped {} samples the recorder couldn't keep up with", dropped_samples);
+    }
+    
+    if all_audio_data.is_empty() {
+        return Err("No audio data recorded".to_string());
+    }
+    
+    Ok(RecordedAudio {
+        samples: all_audio_data,
+        sample_rate: source.sample_rate(),
+        channels: source.channels(),
+        duration: recording_time,
+        dropped_samples,
+    })
+}
+
+// A source counted by is_capturing_audio for as long as it is open
+struct CountedSource {
+    // Declared before the guard so the device is released first
+    source: Box<dyn AudioSource>,
+    _capturing: CaptureGuard,
+}
+
+impl AudioSource for CountedSource {
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+    
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+    
+    fn poll(&mut self) -> SourcePoll {
+        self.source.poll()
+    }
+    
+    fn wait(&mut self, timeout: Duration) -> SourcePoll {
+        self.source.wait(timeout)
+    }
+    
+    fn dropped_samples(&self) -> u64 {
+        self.source.dropped_samples()
+    }
+}
+
+// Open the fake source when CURSPER_FAKE_AUDIO is set, otherwise the input device the
+// options name
+pub fn open_source(options: &CaptureOptions) -> Result<Box<dyn AudioSource>, String> {
+    let capturing = CaptureGuard::start();
+    let source = match fake_source_from_env()? {
+        Some(source) => {
+            println!("🧪 Recording from fake audio source ({})", FAKE_AUDIO_ENV);
+            Box::new(source) as Box<dyn AudioSource>
+        }
+        None => device_source(options)?,
+    };
+    Ok(Box::new(CountedSource { source, _capturing: capturing }))
+}
+
+#[cfg(feature = "capture")]
+fn device_source(options: &CaptureOptions) -> Result<Box<dyn AudioSource>, String> {
+    Ok(Box::new(CpalAudioSource::open(options)?))
+}
+
+#[cfg(not(feature = "capture"))]
+fn device_source(_options: &CaptureOptions) -> Result<Box<dyn AudioSource>, String> {
+    Err(format!("Microphone capture is not built in; set {} to use a fake source", FAKE_AUDIO_ENV))
+}
+
+// Names of the input devices recordings can be pointed at
+#[cfg(feature = "capture")]
+pub fn input_device_names() -> Result<Vec<String>, String> {
+    use cpal::traits::{DeviceTrait, HostTrait};
+    
+    let devices = cpal::default_host()
+        .input_devices()
+        .map_err(|e| format!("Failed to list input devices: {}", e))?;
+    Ok(devices.filter_map(|device| device.name().ok()).collect())
+}
+
+#[cfg(not(feature = "capture"))]
+pub fn input_device_names() -> Result<Vec<String>, String> {
+    Ok(Vec::new())
+}
+
+// Fake source configured through CURSPER_FAKE_AUDIO, if set
+pub fn fake_source_from_env() -> Result<Option<FakeAudioSource>, String> {
+    match std::env::var(FAKE_AUDIO_ENV) {
+        Ok(spec) if !spec.trim().is_empty() => FakeAudioSource::from_spec(spec.trim()).map(Some),
+        _ => Ok(None),
+    }
+}
+
+enum FakeSignal {
+    Sine { frequency: f32, amplitude: f32 },
+    Samples(Vec<f32>),
+}
+
+// Deterministic stand-in for a microphone: an endless sine tone or a fixed fixture,
+// delivered in real-time chunks like a device would
+pub struct FakeAudioSource {
+    signal: FakeSignal,
+    sample_rate: u32,
+    channels: u16,
+    chunk_frames: usize,
+    realtime: bool,
+    started: Option<Instant>,
+    delivered_frames: usize,
+}
+
+impl FakeAudioSource {
+    // Endless sine tone, identical in every channel
+    pub fn sine(frequency: f32, amplitude: f32, sample_rate: u32, channels: u16) -> Self {
+        Self::new(FakeSignal::Sine { frequency, amplitude }, sample_rate, channels)
+    }
+    
+    // Fixed interleaved samples that end the recording once played
+    pub fn from_samples(samples: Vec<f32>, sample_rate: u32, channels: u16) -> Self {
+        Self::new(FakeSignal::Samples(samples), sample_rate, channels)
+    }
+    
+    // Samples of a WAV fixture
+    pub fn from_wav(path: &str) -> Result<Self, String> {
+        let mut reader = hound::WavReader::open(path)
+            .map_err(|e| format!("Failed to open audio fixture {}: {}", path, e))?;
+        let spec = reader.spec();
+        let samples: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>(),
+            hound::SampleFormat::Int => {
+                let scale = (1i64 << (spec.bits_per_sample.max(1) - 1)) as f32;
+                reader
+                    .samples::<i32>()
+                    .map(|sample| sample.map(|sample| sample as f32 / scale))
+                    .collect::<Result<_, _>>()
+            }
+        }
+        .map_err(|e| format!("Failed to read audio fixture {}: {}", path, e))?;
+        
+        Ok(Self::from_samples(samples, spec.sample_rate, spec.channels))
+    }
+    
+    // "sine", "sine:<hz>" or a WAV path
+    pub fn from_spec(spec: &str) -> Result<Self, String> {
+        match spec.strip_prefix("sine") {
+            Some("") => Ok(Self::sine(440.0, 0.5, 16000, 1)),
+            Some(frequency) => {
+                let frequency = frequency
+                    .trim_start_matches(':')
+                    .parse::<f32>()
+                    .map_err(|_| format!("Invalid {} value: {}", FAKE_AUDIO_ENV, spec))?;
+                Ok(Self::sine(frequency, 0.5, 16000, 1))
+            }
+            None => Self::from_wav(spec),
+        }
+    }
+    
+    fn new(signal: FakeSignal, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            signal,
+            sample_rate,
+            channels: channels.max(1),
+            chunk_frames: ((sample_rate as u64 * FAKE_CHUNK_MS / 1000) as usize).max(1),
+            realtime: true,
+            started: None,
+            delivered_frames: 0,
+        }
+    }
+    
+    // Hand out every chunk immediately instead of pacing them in real time
+    pub fn without_pacing(mut self) -> Self {
+        self.realtime = false;
+        self
+    }
+    
+    pub fn with_chunk_frames(mut self, chunk_frames: usize) -> Self {
+        self.chunk_frames = chunk_frames.max(1);
+        self
+    }
+}
+
+impl AudioSource for FakeAudioSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+    
+    fn poll(&mut self) -> SourcePoll {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        if self.realtime {
+            let due_frames = (started.elapsed().as_secs_f64() * self.sample_rate as f64) as usize;
+            if self.delivered_frames + self.chunk_frames > due_frames {
+                return SourcePoll::Pending;
+            }
+        }
+        
+        let channels = self.channels as usize;
+        let first_frame = self.delivered_frames;
+        let chunk = match &self.signal {
+            FakeSignal::Sine { frequency, amplitude } => (first_frame..first_frame + self.chunk_frames)
+                .flat_map(|frame| {
+                    let t = frame as f32 / self.sample_rate as f32;
+                    let sample = amplitude * (2.0 * std::f32::consts::PI * frequency * t).sin();
+                    std::iter::repeat_n(sample, channels)
+                })
+                .collect::<Vec<f32>>(),
+            FakeSignal::Samples(samples) => {
+                let start = (first_frame * channels).min(samples.len());
+                let end = ((first_frame + self.chunk_frames) * channels).min(samples.len());
+                samples[start..end].to_vec()
+            }
+        };
+        
+        if chunk.is_empty() {
+            return SourcePoll::Ended;
+        }
+        self.delivered_frames += chunk.len() / channels;
+        SourcePoll::Samples(chunk)
+    }
+    
+    // Sleep only until the next chunk is due rather than the whole timeout
+    fn wait(&mut self, timeout: Duration) -> SourcePoll {
+        match self.poll() {
+            SourcePoll::Pending => {}
+            polled => return polled,
+        }
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let next_due = Duration::from_secs_f64(
+            (self.delivered_frames + self.chunk_frames) as f64 / self.sample_rate.max(1) as f64,
+        );
+        thread::sleep(next_due.saturating_sub(started.elapsed()).min(timeout));
+        self.poll()
+    }
+}
+
+// The default input device, read through CPAL (Cross-Platform Audio Library)
+#[cfg(feature = "capture")]
+pub struct CpalAudioSource {
+    // Capture stops when the stream is dropped
+    stream: cpal::Stream,
+    // Plays the input back on the default output device (sidetone)
+    monitor: Option<cpal::Stream>,
+    buffer: Arc<SampleBuffer>,
+    sample_rate: u32,
+    channels: u16,
+}
+
+#[cfg(feature = "capture")]
+impl CpalAudioSource {
+    // Open and start the default input device
+    pub fn open_default() -> Result<Self, String> {
+        Self::open(&CaptureOptions::default())
+    }
+    
+    // Open and start the input device the options name, falling back to the default input
+    // device when it isn't connected
+    pub fn open(options: &CaptureOptions) -> Result<Self, String> {
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+        
+        println!("🎤 Initializing CPAL audio recording...");
+        
+        // Get the default audio host and the requested input device
+        let host = cpal::default_host();
+        let named = match options.device.as_deref() {
+            Some(name) => {
+                let device = host
+                    .input_devices()
+                    .ok()
+                    .and_then(|mut devices| devices.find(|device| device.name().is_ok_and(|found| found == name)));
+                if device.is_none() {
+                    println!("⚠️ Input device '{}' not found, using the default", name);
+                }
+                device
+            }
+            None => None,
+        };
+        let device = match named {
+            Some(device) => device,
+            None => host.default_input_device().ok_or("No input device available")?,
+        };
+        
+        println!("🎤 Using audio device: {}", device.name().unwrap_or("Unknown".to_string()));
+        
+        let config = device.default_input_config()
+            .map_err(|e| format!("Failed to get default input config: {}", e))?;
+        
+        let sample_rate = config.sample_rate().0;
+        let channels = config.channels();
+        let sample_format = config.sample_format();
+        
+        println!("🎤 Audio config: {} Hz, {} channels", sample_rate, channels);
+        
+        // Bounded buffer the callback fills and the recorder drains
+        let buffer = Arc::new(SampleBuffer::new(
+            sample_rate as usize * channels as usize * DEVICE_BUFFER_SECONDS,
+            channels,
+        ));
+        
+        // Mono mix for the monitor, kept short so it is heard without a noticeable delay
+        let monitor_buffer = options.monitor_volume.map(|volume| {
+            let buffer = Arc::new(SampleBuffer::new(sample_rate as usize * MONITOR_BUFFER_MS / 1000, 1));
+            (buffer, volume.clamp(0.0, 1.0))
+        });
+        let monitor = match &monitor_buffer {
+            Some((monitor_buffer, _)) => match open_monitor(&host, sample_rate, monitor_buffer.clone()) {
+                Ok(monitor) => {
+                    println!("🎧 Monitoring input on the default output");
+                    Some(monitor)
+                }
+                Err(e) => {
+                    println!("⚠️ Input monitoring unavailable: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let sink = CaptureSink {
+            buffer: buffer.clone(),
+            monitor: monitor_buffer.filter(|_| monitor.is_some()),
+            channels: channels as usize,
+        };
+        
+        // Create the audio stream
+        let stream = match sample_format {
+            cpal::SampleFormat::F32 => {
+                device.build_input_stream(
+                    &config.into(),
+                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                        sink.push(data);
+                    },
+                    |err| eprintln!("❌ Audio stream error: {}", err),
+                    None,
+                )
+            },
+            cpal::SampleFormat::I16 => {
+                device.build_input_stream(
+                    &config.into(),
+                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                        let f32_data: Vec<f32> = data.iter().map(|&sample| sample as f32 / i16::MAX as f32).collect();
+                        sink.push(&f32_data);
+                    },
+                    |err| eprintln!("❌ Audio stream error: {}", err),
+                    None,
+                )
+            },
+            _ => return Err("Unsupported sample format".to_string()),
+        }.map_err(|e| format!("Failed to build input stream: {}", e))?;
+        
+        stream.play().map_err(|e| format!("Failed to start audio stream: {}", e))?;
+        
+        Ok(Self {
+            stream,
+            monitor,
+            buffer,
+            sample_rate,
+            channels,
+        })
+    }
+}
+
+// Where the input callback delivers samples: the recorder's buffer and, when monitoring,
+// a mono mix for the output stream
+#[cfg(feature = "capture")]
+struct CaptureSink {
+    buffer: Arc<SampleBuffer>,
+    monitor: Option<(Arc<SampleBuffer>, f32)>,
+    channels: usize,
+}
+
+#[cfg(feature = "capture")]
+impl CaptureSink {
+    fn push(&self, data: &[f32]) {
+        self.buffer.push(data);
+        if let Some((monitor, volume)) = &self.monitor {
+            let mono: Vec<f32> = data
+                .chunks(self.channels.max(1))
+                .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32 * volume)
+                .collect();
+            // A full monitor buffer means the output is behind; dropping keeps latency low
+            monitor.push(&mono);
+        }
+    }
+}
+
+// Play the monitor buffer on the default output device at the input's sample rate
+#[cfg(feature = "capture")]
+fn open_monitor(host: &cpal::Host, sample_rate: u32, buffer: Arc<SampleBuffer>) -> Result<cpal::Stream, String> {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    
+    let device = host.default_output_device().ok_or("No output device available")?;
+    let default_config = device
+        .default_output_config()
+        .map_err(|e| format!("Failed to get default output config: {}", e))?;
+    let channels = default_config.channels() as usize;
+    let config = cpal::StreamConfig {
+        channels: default_config.channels(),
+        sample_rate: cpal::SampleRate(sample_rate),
+        buffer_size: cpal::BufferSize::Default,
+    };
+    
+    // Each mono sample fills a whole output frame; silence when the input is behind
+    let fill = move |data: &mut [f32]| {
+        let mut mono = buffer.take(data.len() / channels.max(1)).into_iter();
+        for frame in data.chunks_mut(channels.max(1)) {
+            frame.fill(mono.next().unwrap_or(0.0));
+        }
+    };
+    let stream = match default_config.sample_format() {
+        cpal::SampleFormat::F32 => device.build_output_stream(
+            &config,
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| fill(data),
+            |err| eprintln!("❌ Monitor stream error: {}", err),
+            None,
+        ),
+        cpal::SampleFormat::I16 => {
+            let mut samples = Vec::new();
+            device.build_output_stream(
+                &config,
+                move |data: &mut [i16], _: &cpal::OutputCallbackInfo| {
+                    samples.resize(data.len(), 0.0);
+                    fill(&mut samples);
+                    for (out, sample) in data.iter_mut().zip(&samples) {
+                        *out = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+                    }
+                },
+                |err| eprintln!("❌ Monitor stream error: {}", err),
+                None,
+            )
+        }
+        _ => return Err("Unsupported output sample format".to_string()),
+    }
+    .map_err(|e| format!("Failed to build monitor stream: {}", e))?;
+    
+    stream.play().map_err(|e| format!("Failed to start monitor stream: {}", e))?;
+    Ok(stream)
+}
+
+#[cfg(feature = "capture")]
+impl AudioSource for CpalAudioSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+    
+    fn poll(&mut self) -> SourcePoll {
+        self.wait(Duration::ZERO)
+    }
+    
+    // Wake as soon as the device callback queues samples
+    fn wait(&mut self, timeout: Duration) -> SourcePoll {
+        let data = self.buffer.wait_and_drain(timeout);
+        if data.is_empty() {
+            SourcePoll::Pending
+        } else {
+            SourcePoll::Samples(data)
+        }
+    }
+    
+    fn dropped_samples(&self) -> u64 {
+        self.buffer.dropped()
+    }
+}
+
+// Stop the stream explicitly before it is dropped; some hosts keep the device (and the
+// OS microphone indicator) alive until the stream is paused
+#[cfg(feature = "capture")]
+impl Drop for CpalAudioSource {
+    fn drop(&mut self) {
+        use cpal::traits::StreamTrait;
+        
+        if let Err(e) = self.stream.pause() {
+            println!("⚠️ Failed to pause audio stream: {}", e);
+        }
+        if let Some(monitor) = &self.monitor {
+            let _ = monitor.pause();
+        }
+        println!("🎤 Microphone released");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    
+    fn keep_recording() -> CaptureControl {
+        CaptureControl::Record
+    }
+    
+    // Records until the flag is cleared, like a user pressing stop
+    fn until_cleared(recording: &AtomicBool) -> impl FnMut() -> CaptureControl + '_ {
+        move || if recording.load(Ordering::SeqCst) { CaptureControl::Record } else { CaptureControl::Stop }
+    }
+    
+    #[test]
+    fn fixture_plays_to_the_end_in_whole_frames() {
+        let samples: Vec<f32> = (0..1000).map(|i| i as f32 / 1000.0).collect();
+        let mut source = FakeAudioSource::from_samples(samples.clone(), 1000, 2)
+            .without_pacing()
+            .with_chunk_frames(64);
+        
+        let recorded = record_from_source(&mut source, &mut keep_recording, Duration::from_secs(5)).unwrap();
+        assert_eq!(recorded.samples, samples);
+        assert_eq!(recorded.sample_rate, 1000);
+        assert_eq!(recorded.channels, 2);
+    }
+    
+    #[test]
+    fn streaming_sees_every_chunk_as_captured() {
+        let samples: Vec<f32> = (0..1000).map(|i| i as f32 / 1000.0).collect();
+        let mut source = FakeAudioSource::from_samples(samples.clone(), 1000, 1)
+            .without_pacing()
+            .with_chunk_frames(100);
+        
+        let mut streamed = Vec::new();
+        let mut chunks = 0;
+        let recorded = record_from_source_streaming(&mut source, &mut keep_recording, Duration::from_secs(5), &mut |chunk, _, _| {
+            streamed.extend_from_slice(chunk);
+            chunks += 1;
+        })
+        .unwrap();
+        assert_eq!(streamed, recorded.samples);
+        assert_eq!(chunks, 10);
+    }
+    
+    #[test]
+    fn chunks_never_split_a_frame() {
+        let mut source = FakeAudioSource::sine(440.0, 0.5, 8000, 2)
+            .without_pacing()
+            .with_chunk_frames(37);
+        for _ in 0..10 {
+            match source.poll() {
+                SourcePoll::Samples(chunk) => assert_eq!(chunk.len(), 74),
+                _ => panic!("an endless sine should always have samples"),
+            }
+        }
+    }
+    
+    #[test]
+    fn recording_stops_at_the_duration_cap() {
+        let mut source = FakeAudioSource::sine(440.0, 0.5, 16000, 1);
+        let recorded = record_from_source(&mut source, &mut keep_recording, Duration::from_millis(200)).unwrap();
+        
+        // Paced in real time, so roughly 200 ms of audio arrives
+        let captured_ms = recorded.samples.len() as u64 * 1000 / 16000;
+        assert!((100..=260).contains(&captured_ms), "captured {} ms", captured_ms);
+        assert!(recorded.duration >= Duration::from_millis(200));
+    }
+    
+    #[test]
+    fn stopping_ends_the_recording() {
+        let recording = Arc::new(AtomicBool::new(true));
+        let stopper = recording.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(150));
+            stopper.store(false, Ordering::SeqCst);
+        });
+        
+        let mut source = FakeAudioSource::sine(440.0, 0.5, 16000, 1);
+        let recorded = record_from_source(&mut source, &mut until_cleared(&recording), Duration::from_secs(5)).unwrap();
+        handle.join().unwrap();
+        
+        assert!(recorded.duration < Duration::from_secs(1));
+        assert!(!recorded.samples.is_empty());
+    }
+    
+    #[test]
+    fn cancelling_discards_the_recording() {
+        let mut source = FakeAudioSource::sine(440.0, 0.5, 16000, 1).without_pacing();
+        let mut polls = 0;
+        let result = record_from_source(&mut source, &mut || {
+            polls += 1;
+            if polls < 5 { CaptureControl::Record } else { CaptureControl::Cancel }
+        }, Duration::from_secs(5));
+        assert_eq!(result.err().as_deref(), Some(RECORDING_CANCELLED_ERROR));
+    }
+    
+    #[test]
+    fn audio_captured_while_paused_is_dropped() {
+        let samples: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+        let mut source = FakeAudioSource::from_samples(samples.clone(), 1000, 1)
+            .without_pacing()
+            .with_chunk_frames(100);
+        
+        // Chunks 3 to 5 arrive while paused
+        let mut polls = 0;
+        let recorded = record_from_source(&mut source, &mut || {
+            polls += 1;
+            if (3..=5).contains(&polls) { CaptureControl::Pause } else { CaptureControl::Record }
+        }, Duration::from_secs(5))
+        .unwrap();
+        let expected: Vec<f32> = samples[..200].iter().chain(&samples[500..]).copied().collect();
+        assert_eq!(recorded.samples, expected);
+    }
+    
+    #[test]
+    fn capture_flag_is_set_only_while_a_source_is_open() {
+        std::env::set_var(FAKE_AUDIO_ENV, "sine");
+        assert!(!is_capturing_audio());
+        
+        let source = open_source(&CaptureOptions::default());
+        std::env::remove_var(FAKE_AUDIO_ENV);
+        assert!(is_capturing_audio());
+        drop(source);
+        assert!(!is_capturing_audio());
+    }
+    
+    #[test]
+    fn empty_fixture_is_an_error() {
+        let mut source = FakeAudioSource::from_samples(Vec::new(), 16000, 1).without_pacing();
+        let result = record_from_source(&mut source, &mut keep_recording, Duration::from_secs(1));
+        assert_eq!(result.err().as_deref(), Some("No audio data recorded"));
+    }
+    
+    #[test]
+    fn specs_select_sine_or_fixture() {
+        assert_eq!(FakeAudioSource::from_spec("sine").unwrap().sample_rate(), 16000);
+        assert!(FakeAudioSource::from_spec("sine:1000").is_ok());
+        assert!(FakeAudioSource::from_spec("sine:loud").is_err());
+        assert!(FakeAudioSource::from_spec("/nonexistent/fixture.wav").is_err());
+        
+        let path = std::env::temp_dir().join("cursper-fake-audio-fixture.wav");
+        let wav = crate::audio::convert_to_wav(&[0.0, 0.5, -0.5, 0.25], 8000, 2).unwrap();
+        std::fs::write(&path, wav).unwrap();
+        let mut source = FakeAudioSource::from_spec(path.to_str().unwrap()).unwrap().without_pacing();
+        assert_eq!((source.sample_rate(), source.channels()), (8000, 2));
+        match source.poll() {
+            SourcePoll::Samples(samples) => assert_eq!(samples.len(), 4),
+            _ => panic!("fixture should yield its samples"),
+        }
+        let _ = std::fs::remove_file(path);
+    }
+    
+    #[test]
+    fn full_buffer_drops_whole_frames_and_counts_them() {
+        let buffer = SampleBuffer::new(5, 2);
+        assert_eq!(buffer.push(&[0.1, 0.2, 0.3]), 1);
+        assert_eq!(buffer.push(&[0.4, 0.5, 0.6, 0.7]), 2);
+        assert_eq!(buffer.dropped(), 3);
+        assert_eq!(buffer.drain(), vec![0.1, 0.2, 0.4, 0.5]);
+        
+        assert_eq!(buffer.push(&[0.8, 0.9, 1.0, 1.1]), 0);
+        assert_eq!(buffer.drain(), vec![0.8, 0.9, 1.0, 1.1]);
+        assert!(buffer.drain().is_empty());
+        assert_eq!(buffer.dropped(), 3);
+    }
+    
+    #[test]
+    fn buffer_hands_out_whole_frames_up_to_the_limit() {
+        let buffer = SampleBuffer::new(10, 2);
+        buffer.push(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        assert_eq!(buffer.take(3), vec![0.1, 0.2]);
+        assert_eq!(buffer.take(10), vec![0.3, 0.4, 0.5, 0.6]);
+        assert!(buffer.take(10).is_empty());
+    }
+    
+    #[test]
+    fn waiting_buffer_wakes_when_samples_arrive() {
+        let buffer = Arc::new(SampleBuffer::new(100, 1));
+        let producer = buffer.clone();
+        let pushing = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            producer.push(&[0.5, 0.25]);
+        });
+        
+        let started = Instant::now();
+        assert_eq!(buffer.wait_and_drain(Duration::from_secs(5)), vec![0.5, 0.25]);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        pushing.join().unwrap();
+        
+        let started = Instant::now();
+        assert!(buffer.wait_and_drain(Duration::from_millis(10)).is_empty());
+        assert!(started.elapsed() >= Duration::from_millis(10));
+    }
+    
+    #[test]
+    fn stop_is_noticed_within_the_check_interval() {
+        let recording = Arc::new(AtomicBool::new(true));
+        let stopper = recording.clone();
+        let stopping = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            stopper.store(false, Ordering::SeqCst);
+            Instant::now()
+        });
+        
+        let mut source = FakeAudioSource::sine(440.0, 0.5, 16000, 1);
+        let recorded = record_from_source(&mut source, &mut until_cleared(&recording), Duration::from_secs(5)).unwrap();
+        let stopped_at = stopping.join().unwrap();
+        assert!(stopped_at.elapsed() < STOP_CHECK_INTERVAL + Duration::from_millis(30));
+        assert!(recorded.duration < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,29 @@
+// Chinese, Japanese and Korean text, which input methods intercept when it is typed key
+// by key, so it is better pasted.
+
+// Kana, CJK ideographs, Hangul and their punctuation and full-width forms
+pub fn is_cjk_char(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1100..=0x11FF | 0x2E80..=0x2FDF | 0x3000..=0x9FFF | 0xA960..=0xA97F | 0xAC00..=0xD7FF
+            | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFFEF | 0x20000..=0x3FFFF
+    )
+}
+
+pub fn contains_cjk(text: &str) -> bool {
+    text.chars().any(is_cjk_char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn detects_chinese_japanese_and_korean() {
+        assert!(contains_cjk("你好"));
+        assert!(contains_cjk("meeting は 3時"));
+        assert!(contains_cjk("안녕하세요"));
+        assert!(contains_cjk("（注）"));
+        assert!(!contains_cjk("Grüße, שלום"));
+    }
+}
@@ -0,0 +1,74 @@
+// "Fix that": replace the last sentence of an insertion with a re-dictated one by
+// pressing backspace and typing, touching only the part that actually changed.
+
+// Characters that end a sentence
+const SENTENCE_ENDS: &[char] = &['.', '!', '?', '…'];
+
+// Keystrokes that turn the inserted text into the corrected one
+#[derive(Clone, Debug, PartialEq)]
+pub struct Correction {
+    // Characters to delete from the end of the insertion
+    pub backspaces: usize,
+    // Text to type afterwards
+    pub insert: String,
+}
+
+// Byte index where the last sentence of the text starts, ignoring trailing whitespace
+pub fn last_sentence_start(text: &str) -> usize {
+    let body = text.trim_end();
+    let body = body.trim_end_matches(SENTENCE_ENDS);
+    body.char_indices()
+        .rev()
+        .find(|&(index, c)| c.is_whitespace() && body[..index].ends_with(SENTENCE_ENDS))
+        .map(|(index, c)| index + c.len_utf8())
+        .map(|start| start + body[start..].len() - body[start..].trim_start().len())
+        .unwrap_or(text.len() - text.trim_start().len())
+}
+
+// Replace the last sentence of `inserted` with `replacement`, keeping whatever whitespace
+// followed it (e.g. a space appended after insertion). Backspaces count characters, which
+// matches what most fields delete per key press outside of emoji and combining marks.
+pub fn correct_last_sentence(inserted: &str, replacement: &str) -> Correction {
+    let start = last_sentence_start(inserted);
+    let old_tail = &inserted[start..];
+    let trailing = &old_tail[old_tail.trim_end().len()..];
+    let new_tail = format!("{}{}", replacement.trim(), trailing);
+    
+    let common = old_tail
+        .chars()
+        .zip(new_tail.chars())
+        .take_while(|(old, new)| old == new)
+        .count();
+    Correction {
+        backspaces: old_tail.chars().count() - common,
+        insert: new_tail.chars().skip(common).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn finds_the_last_sentence() {
+        assert_eq!(last_sentence_start("Only one sentence. "), 0);
+        assert_eq!(last_sentence_start("First one. Second one?  "), 11);
+        assert_eq!(last_sentence_start("Wait... what"), 8);
+        assert_eq!(last_sentence_start("  indented"), 2);
+    }
+    
+    #[test]
+    fn replaces_only_what_changed() {
+        let correction = correct_last_sentence("Hi there. Meet me at five. ", "Meet me at nine.");
+        assert_eq!(correction, Correction { backspaces: 6, insert: "nine. ".to_string() });
+        
+        let correction = correct_last_sentence("Send it to Bob", "Send it to Rob");
+        assert_eq!(correction, Correction { backspaces: 3, insert: "Rob".to_string() });
+        
+        let correction = correct_last_sentence("Same. ", " Same. ");
+        assert_eq!(correction, Correction { backspaces: 0, insert: String::new() });
+        
+        let correction = correct_last_sentence("Grüße. Bis später.\n", "Bis morgen.");
+        assert_eq!(correction, Correction { backspaces: 8, insert: "morgen.\n".to_string() });
+    }
+}
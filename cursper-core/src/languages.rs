@@ -0,0 +1,24 @@
+// Dictation languages offered in quick switchers (Whisper code, display name)
+pub const DICTATION_LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("de", "German"),
+    ("fr", "French"),
+    ("es", "Spanish"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("nl", "Dutch"),
+    ("pl", "Polish"),
+    ("ru", "Russian"),
+    ("uk", "Ukrainian"),
+    ("ja", "Japanese"),
+    ("zh", "Chinese"),
+    ("ko", "Korean"),
+];
+
+// Whisper code for a language display name ("english" -> "en")
+pub fn language_code(name: &str) -> Option<&'static str> {
+    DICTATION_LANGUAGES
+        .iter()
+        .find(|(_, language)| language.eq_ignore_ascii_case(name))
+        .map(|(code, _)| *code)
+}
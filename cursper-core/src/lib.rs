@@ -0,0 +1,25 @@
+// Tauri-independent core of Cursper: audio capture and processing, transcription
+// providers, text post-processing and ordered insertion. The desktop app wraps these
+// in commands and events; other frontends (e.g. a CLI) can use them directly.
+
+pub mod ascii;
+pub mod audio;
+pub mod backend;
+pub mod bandwidth;
+pub mod bidi;
+pub mod capture;
+pub mod cjk;
+pub mod correction;
+pub mod languages;
+pub mod locale;
+pub mod normalize;
+#[cfg(feature = "opus")]
+pub mod opus;
+pub mod profanity;
+pub mod providers;
+pub mod recorder;
+pub mod redact;
+pub mod search;
+pub mod sequencer;
+pub mod spacing;
+pub mod transcript;
@@ -0,0 +1,121 @@
+// Typographic conventions of the user's locale, applied when post-processing dictated
+// text: the decimal separator and currency placement of normalized amounts, and which
+// quotation marks wrap quoted speech.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LocaleFormat {
+    pub decimal_separator: char,
+    // Put the currency symbol after the amount ("5,50 €") instead of before it ("$5.50")
+    pub currency_after: bool,
+    pub open_quote: &'static str,
+    pub close_quote: &'static str,
+}
+
+impl LocaleFormat {
+    // Whisper's own conventions, which are left untouched
+    pub const ENGLISH: LocaleFormat = LocaleFormat {
+        decimal_separator: '.',
+        currency_after: false,
+        open_quote: "\"",
+        close_quote: "\"",
+    };
+    
+    // Whether quoted speech gets different quotation marks than Whisper writes
+    pub fn localizes_quotes(&self) -> bool {
+        self.open_quote != "\"" || self.close_quote != "\""
+    }
+}
+
+impl Default for LocaleFormat {
+    fn default() -> Self {
+        Self::ENGLISH
+    }
+}
+
+const fn format(decimal_separator: char, currency_after: bool, open_quote: &'static str, close_quote: &'static str) -> LocaleFormat {
+    LocaleFormat { decimal_separator, currency_after, open_quote, close_quote }
+}
+
+// Locales by language tag; a region-specific entry wins over the language alone
+const LOCALE_FORMATS: &[(&str, LocaleFormat)] = &[
+    ("en", LocaleFormat::ENGLISH),
+    ("en-za", format(',', false, "\"", "\"")),
+    ("de", format(',', true, "„", "“")),
+    ("de-ch", format('.', true, "«", "»")),
+    ("fr", format(',', true, "«\u{a0}", "\u{a0}»")),
+    ("fr-ch", format('.', true, "«\u{a0}", "\u{a0}»")),
+    ("es", format(',', true, "«", "»")),
+    ("it", format(',', true, "«", "»")),
+    ("pt", format(',', true, "«", "»")),
+    ("pt-br", format(',', false, "“", "”")),
+    ("nl", format(',', false, "“", "”")),
+    ("pl", format(',', true, "„", "”")),
+    ("cs", format(',', true, "„", "“")),
+    ("ru", format(',', true, "«", "»")),
+    ("uk", format(',', true, "«", "»")),
+    ("sv", format(',', true, "”", "”")),
+    ("fi", format(',', true, "”", "”")),
+    ("da", format(',', true, "»", "«")),
+    ("nb", format(',', true, "«", "»")),
+];
+
+// Conventions for a locale such as "de-DE", "fr_CH" or "pt"; unknown locales get English ones
+pub fn locale_format(locale: &str) -> LocaleFormat {
+    let tag = locale.trim().replace('_', "-").to_lowercase();
+    // Drop an encoding or variant suffix as in "de_DE.UTF-8"
+    let tag = tag.split(['.', '@']).next().unwrap_or_default();
+    let language = tag.split('-').next().unwrap_or_default();
+    let region = tag.split('-').nth(1);
+    
+    let lookup = |key: &str| LOCALE_FORMATS.iter().find(|(tag, _)| *tag == key).map(|(_, format)| *format);
+    region
+        .and_then(|region| lookup(&format!("{}-{}", language, region)))
+        .or_else(|| lookup(language))
+        .unwrap_or_default()
+}
+
+// Replace straight and curly double quotes around quoted speech with the locale's
+// quotation marks; an unpaired quote is left as it is
+pub fn localize_quotes(text: &str, format: &LocaleFormat) -> String {
+    if !format.localizes_quotes() {
+        return text.to_string();
+    }
+    let quotes: Vec<usize> = text
+        .char_indices()
+        .filter(|(_, c)| matches!(c, '"' | '“' | '”'))
+        .map(|(index, _)| index)
+        .collect();
+    let paired = quotes.len() - quotes.len() % 2;
+    
+    let mut output = String::with_capacity(text.len());
+    let mut rest = 0;
+    for (position, &index) in quotes[..paired].iter().enumerate() {
+        output.push_str(&text[rest..index]);
+        output.push_str(if position % 2 == 0 { format.open_quote } else { format.close_quote });
+        rest = index + text[index..].chars().next().map(char::len_utf8).unwrap_or(1);
+    }
+    output.push_str(&text[rest..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn resolves_regions_before_languages() {
+        assert_eq!(locale_format("de-DE").decimal_separator, ',');
+        assert_eq!(locale_format("de_CH.UTF-8").decimal_separator, '.');
+        assert_eq!(locale_format("en-US"), LocaleFormat::ENGLISH);
+        assert_eq!(locale_format("tlh"), LocaleFormat::ENGLISH);
+    }
+    
+    #[test]
+    fn localizes_paired_quotes() {
+        assert_eq!(localize_quotes("Er sagte \"hallo\" und \"tschüss\".", &locale_format("de")), "Er sagte „hallo“ und „tschüss“.");
+        assert_eq!(localize_quotes("Il a dit “oui”.", &locale_format("fr")), "Il a dit «\u{a0}oui\u{a0}».");
+        // The odd quote out has no partner to pair with
+        assert_eq!(localize_quotes("\"a\" and \"b", &locale_format("de")), "„a“ and \"b");
+        assert_eq!(localize_quotes("\"as is\"", &LocaleFormat::ENGLISH), "\"as is\"");
+    }
+}
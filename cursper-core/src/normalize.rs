@@ -0,0 +1,462 @@
+// Rewrites spoken numbers, currencies and dates into digits and ISO dates.
+// Whisper is inconsistent here ("twenty five" vs "25"), so this runs after transcription.
+// The word tables are English; other languages only get their quotation marks localized.
+
+use crate::locale::{localize_quotes, LocaleFormat};
+
+// Which normalizations to apply
+#[derive(Clone, Copy, Default)]
+pub struct NormalizationOptions {
+    pub numbers: bool,
+    pub currency: bool,
+    pub dates: bool,
+    // Decimal separator and currency placement of amounts, and quotation marks
+    pub format: LocaleFormat,
+}
+
+impl NormalizationOptions {
+    pub fn any(&self) -> bool {
+        self.numbers || self.currency || self.dates || self.format.localizes_quotes()
+    }
+}
+
+const UNITS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    "ten", "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+
+const TENS: &[(&str, u64)] = &[
+    ("twenty", 20), ("thirty", 30), ("forty", 40), ("fifty", 50),
+    ("sixty", 60), ("seventy", 70), ("eighty", 80), ("ninety", 90),
+];
+
+const SCALES: &[(&str, u64)] = &[
+    ("thousand", 1_000), ("million", 1_000_000), ("billion", 1_000_000_000),
+];
+
+const ORDINALS: &[(&str, u32)] = &[
+    ("first", 1), ("second", 2), ("third", 3), ("fourth", 4), ("fifth", 5),
+    ("sixth", 6), ("seventh", 7), ("eighth", 8), ("ninth", 9), ("tenth", 10),
+    ("eleventh", 11), ("twelfth", 12), ("thirteenth", 13), ("fourteenth", 14), ("fifteenth", 15),
+    ("sixteenth", 16), ("seventeenth", 17), ("eighteenth", 18), ("nineteenth", 19),
+    ("twentieth", 20), ("thirtieth", 30),
+];
+
+const MONTHS: &[&str] = &[
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+// Currency words and their symbols; "pounds" is left alone since it's also a weight
+const CURRENCIES: &[(&str, &str)] = &[
+    ("dollar", "$"), ("dollars", "$"), ("bucks", "$"),
+    ("euro", "€"), ("euros", "€"),
+    ("yen", "¥"),
+];
+
+// A whitespace-separated word with its surrounding punctuation split off
+#[derive(Clone)]
+struct Token {
+    lead: String,
+    core: String,
+    trail: String,
+}
+
+impl Token {
+    fn lower(&self) -> String {
+        self.core.to_lowercase()
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for word in text.split_whitespace() {
+        let core_start = word.find(|c: char| c.is_alphanumeric()).unwrap_or(word.len());
+        let core_end = word
+            .rfind(|c: char| c.is_alphanumeric())
+            .map(|i| i + word[i..].chars().next().map(|c| c.len_utf8()).unwrap_or(1))
+            .unwrap_or(core_start);
+        let lead = word[..core_start].to_string();
+        let core = &word[core_start..core_end.max(core_start)];
+        let trail = word[core_end.max(core_start)..].to_string();
+        
+        // Split hyphenated number words ("twenty-five") so they parse like separate words
+        let parts: Vec<&str> = core.split('-').collect();
+        if parts.len() > 1 && parts.iter().all(|part| number_word(&part.to_lowercase()).is_some()) {
+            let last = parts.len() - 1;
+            for (index, part) in parts.into_iter().enumerate() {
+                tokens.push(Token {
+                    lead: if index == 0 { lead.clone() } else { String::new() },
+                    core: part.to_string(),
+                    trail: if index == last { trail.clone() } else { String::new() },
+                });
+            }
+        } else {
+            tokens.push(Token { lead, core: core.to_string(), trail });
+        }
+    }
+    tokens
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum NumberWord {
+    Unit(u64),
+    Teen(u64),
+    Tens(u64),
+    Hundred,
+    Scale(u64),
+}
+
+fn number_word(word: &str) -> Option<NumberWord> {
+    if let Some(value) = UNITS.iter().position(|unit| *unit == word) {
+        let value = value as u64;
+        return Some(if value < 10 { NumberWord::Unit(value) } else { NumberWord::Teen(value) });
+    }
+    if let Some((_, value)) = TENS.iter().find(|(tens, _)| *tens == word) {
+        return Some(NumberWord::Tens(*value));
+    }
+    if word == "hundred" {
+        return Some(NumberWord::Hundred);
+    }
+    SCALES
+        .iter()
+        .find(|(scale, _)| *scale == word)
+        .map(|(_, value)| NumberWord::Scale(*value))
+}
+
+// Parse a run of number words starting at `start`; returns the value and tokens consumed
+fn parse_number(tokens: &[Token], start: usize) -> Option<(u64, usize)> {
+    let mut total = 0;
+    let mut current = 0;
+    let mut last: Option<NumberWord> = None;
+    let mut last_scale = u64::MAX;
+    let mut index = start;
+    
+    while index < tokens.len() {
+        // Punctuation before this word ends the number
+        if index > start && !tokens[index - 1].trail.is_empty() {
+            break;
+        }
+        
+        let lower = tokens[index].lower();
+        let word = match number_word(&lower) {
+            Some(word) => word,
+            None if lower == "and" && matches!(last, Some(NumberWord::Hundred) | Some(NumberWord::Scale(_))) => {
+                // "one hundred and five": only keep "and" when a number follows
+                let follows = tokens
+                    .get(index + 1)
+                    .and_then(|next| number_word(&next.lower()))
+                    .map(|next| matches!(next, NumberWord::Unit(_) | NumberWord::Teen(_) | NumberWord::Tens(_)))
+                    .unwrap_or(false);
+                if follows && tokens[index].trail.is_empty() {
+                    index += 1;
+                    continue;
+                }
+                break;
+            }
+            None => break,
+        };
+        
+        let accepted = match word {
+            NumberWord::Unit(0) => last.is_none(),
+            NumberWord::Unit(_) => matches!(last, None | Some(NumberWord::Tens(_)) | Some(NumberWord::Hundred) | Some(NumberWord::Scale(_))),
+            NumberWord::Teen(_) | NumberWord::Tens(_) => {
+                matches!(last, None | Some(NumberWord::Hundred) | Some(NumberWord::Scale(_)))
+            }
+            NumberWord::Hundred => {
+                matches!(last, Some(NumberWord::Unit(_)) | Some(NumberWord::Teen(_)) | Some(NumberWord::Tens(_))) && current < 100
+            }
+            NumberWord::Scale(scale) => {
+                matches!(last, Some(NumberWord::Unit(_)) | Some(NumberWord::Teen(_)) | Some(NumberWord::Tens(_)) | Some(NumberWord::Hundred))
+                    && scale < last_scale
+            }
+        };
+        if !accepted {
+            break;
+        }
+        
+        match word {
+            NumberWord::Unit(value) | NumberWord::Teen(value) | NumberWord::Tens(value) => current += value,
+            NumberWord::Hundred => current *= 100,
+            NumberWord::Scale(scale) => {
+                total += current * scale;
+                current = 0;
+                last_scale = scale;
+            }
+        }
+        
+        last = Some(word);
+        index += 1;
+        
+        // "zero" never combines with anything
+        if word == NumberWord::Unit(0) {
+            break;
+        }
+    }
+    
+    // Don't swallow a trailing "and"
+    while index > start && tokens[index - 1].lower() == "and" {
+        index -= 1;
+    }
+    
+    last.map(|_| (total + current, index - start))
+}
+
+// Parse a number written with digits, e.g. "25" or "1,200"
+fn parse_digits(token: &Token) -> Option<u64> {
+    let digits: String = token.core.chars().filter(|c| *c != ',').collect();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+fn month_index(word: &str) -> Option<usize> {
+    MONTHS.iter().position(|month| month.eq_ignore_ascii_case(word))
+}
+
+// Parse a day of the month: "fifth", "twenty first", "5th", "twenty five"
+fn parse_day(tokens: &[Token], start: usize) -> Option<(u32, usize)> {
+    let token = tokens.get(start)?;
+    let lower = token.lower();
+    
+    let suffixes = ["st", "nd", "rd", "th"];
+    let numeric = suffixes
+        .iter()
+        .find_map(|suffix| lower.strip_suffix(suffix))
+        .unwrap_or(&lower);
+    if !numeric.is_empty() && numeric.chars().all(|c| c.is_ascii_digit()) {
+        let day: u32 = numeric.parse().ok()?;
+        return (1..=31).contains(&day).then_some((day, 1));
+    }
+    
+    if let Some((_, day)) = ORDINALS.iter().find(|(ordinal, _)| *ordinal == lower) {
+        return Some((*day, 1));
+    }
+    
+    // "twenty first"
+    if let Some(NumberWord::Tens(tens)) = number_word(&lower) {
+        if token.trail.is_empty() {
+            if let Some(next) = tokens.get(start + 1) {
+                let next_lower = next.lower();
+                if let Some((_, unit)) = ORDINALS.iter().take(9).find(|(ordinal, _)| *ordinal == next_lower) {
+                    let day = tens as u32 + unit;
+                    return (day <= 31).then_some((day, 2));
+                }
+            }
+        }
+    }
+    
+    let (day, consumed) = parse_number(tokens, start)?;
+    (1..=31).contains(&day).then_some((day as u32, consumed))
+}
+
+// Parse a year: "2024", "twenty twenty four", "nineteen ninety nine", "two thousand five"
+fn parse_year(tokens: &[Token], start: usize) -> Option<(u32, usize)> {
+    let token = tokens.get(start)?;
+    if token.core.len() == 4 {
+        if let Some(year) = parse_digits(token) {
+            return (1000..=2999).contains(&year).then_some((year as u32, 1));
+        }
+    }
+    
+    // Spoken as two pairs: "twenty" + "twenty four"
+    let century = match number_word(&token.lower()) {
+        Some(NumberWord::Teen(value)) | Some(NumberWord::Tens(value)) if token.trail.is_empty() => Some(value),
+        _ => None,
+    };
+    if let Some(century) = century {
+        if let Some((rest, consumed)) = parse_number(tokens, start + 1) {
+            if (10..100).contains(&rest) {
+                return Some(((century * 100 + rest) as u32, consumed + 1));
+            }
+        }
+    }
+    
+    let (year, consumed) = parse_number(tokens, start)?;
+    (1000..=2999).contains(&year).then_some((year as u32, consumed))
+}
+
+// Try to read a date at `start`; returns the replacement and tokens consumed
+fn parse_date(tokens: &[Token], start: usize) -> Option<(String, usize)> {
+    let month = month_index(&tokens[start].core)?;
+    if !tokens[start].trail.is_empty() {
+        return None;
+    }
+    
+    let (day, day_consumed) = parse_day(tokens, start + 1)?;
+    let day_end = start + 1 + day_consumed;
+    
+    // A comma between day and year is dropped ("March 5th, 2024")
+    let day_trail = &tokens[day_end - 1].trail;
+    if day_trail.is_empty() || day_trail == "," {
+        if let Some((year, year_consumed)) = parse_year(tokens, day_end) {
+            return Some((format!("{:04}-{:02}-{:02}", year, month + 1, day), day_end + year_consumed - start));
+        }
+    }
+    
+    Some((format!("{} {}", MONTHS[month], day), day_end - start))
+}
+
+fn currency_symbol(token: &Token) -> Option<&'static str> {
+    let lower = token.lower();
+    CURRENCIES
+        .iter()
+        .find(|(word, _)| *word == lower)
+        .map(|(_, symbol)| *symbol)
+}
+
+fn format_amount(symbol: &str, value: u64, cents: Option<u64>, format: &LocaleFormat) -> String {
+    let number = match cents {
+        Some(cents) => format!("{}{}{:02}", value, format.decimal_separator, cents),
+        None => value.to_string(),
+    };
+    if format.currency_after {
+        format!("{} {}", number, symbol)
+    } else {
+        format!("{}{}", symbol, number)
+    }
+}
+
+// Try to read an amount at `start`; returns the replacement and tokens consumed
+fn parse_amount(tokens: &[Token], start: usize, options: &NormalizationOptions) -> Option<(String, usize)> {
+    let (value, consumed) = match parse_digits(&tokens[start]) {
+        Some(value) => (value, 1),
+        None => parse_number(tokens, start)?,
+    };
+    let spelled = parse_digits(&tokens[start]).is_none();
+    let end = start + consumed;
+    
+    if options.currency && tokens[end - 1].trail.is_empty() {
+        if let Some(symbol) = tokens.get(end).and_then(currency_symbol) {
+            // "and fifty cents"
+            let mut cents = None;
+            let mut amount_end = end + 1;
+            if tokens[end].trail.is_empty() && tokens.get(end + 1).map(|t| t.lower() == "and").unwrap_or(false) {
+                if let Some((cent_value, cent_consumed)) = parse_number(tokens, end + 2) {
+                    let cents_index = end + 2 + cent_consumed;
+                    let is_cents = tokens
+                        .get(cents_index)
+                        .map(|t| matches!(t.lower().as_str(), "cent" | "cents"))
+                        .unwrap_or(false);
+                    if is_cents && cent_value < 100 && tokens[cents_index - 1].trail.is_empty() {
+                        cents = Some(cent_value);
+                        amount_end = cents_index + 1;
+                    }
+                }
+            }
+            
+            return Some((format_amount(symbol, value, cents, &options.format), amount_end - start));
+        }
+    }
+    
+    // Years are spoken in pairs ("nineteen ninety nine")
+    if options.numbers && spelled {
+        if let Some((year, year_consumed)) = parse_year(tokens, start) {
+            if year_consumed > consumed {
+                return Some((year.to_string(), year_consumed));
+            }
+        }
+    }
+    
+    // Small numbers read better as words ("three cats"), larger ones as digits
+    if options.numbers && spelled && value >= 10 {
+        return Some((value.to_string(), consumed));
+    }
+    None
+}
+
+// Apply the enabled normalizations to an English transcription
+pub fn normalize_text(text: &str, options: &NormalizationOptions) -> String {
+    if !options.any() {
+        return text.to_string();
+    }
+    
+    let tokens = tokenize(text);
+    let mut output: Vec<String> = Vec::new();
+    let mut index = 0;
+    
+    while index < tokens.len() {
+        let replacement = if options.dates {
+            parse_date(&tokens, index)
+        } else {
+            None
+        }
+        .or_else(|| parse_amount(&tokens, index, options));
+        
+        match replacement {
+            Some((replacement, consumed)) => {
+                let last = &tokens[index + consumed - 1];
+                output.push(format!("{}{}{}", tokens[index].lead, replacement, last.trail));
+                index += consumed;
+            }
+            None => {
+                let token = &tokens[index];
+                output.push(format!("{}{}{}", token.lead, token.core, token.trail));
+                index += 1;
+            }
+        }
+    }
+    
+    localize_quotes(&output.join(" "), &options.format)
+}
+
+// Normalize a transcription if its language is English (or unknown); quotation marks
+// are localized whatever the language
+pub fn normalize_transcription(text: &str, language: Option<&str>, options: &NormalizationOptions) -> String {
+    match language {
+        Some(language) if !language.to_lowercase().starts_with("en") => localize_quotes(text, &options.format),
+        _ => normalize_text(text, options),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::locale_format;
+    
+    const ALL: NormalizationOptions = NormalizationOptions {
+        numbers: true,
+        currency: true,
+        dates: true,
+        format: LocaleFormat::ENGLISH,
+    };
+    
+    #[test]
+    fn converts_number_words_and_years() {
+        assert_eq!(normalize_text("I have twenty five apples", &ALL), "I have 25 apples");
+        assert_eq!(normalize_text("It was nineteen ninety nine.", &ALL), "It was 1999.");
+        // Small numbers read better as words
+        assert_eq!(normalize_text("one or two", &ALL), "one or two");
+    }
+    
+    #[test]
+    fn converts_currency_amounts() {
+        assert_eq!(normalize_text("It costs five dollars and fifty cents", &ALL), "It costs $5.50");
+    }
+    
+    #[test]
+    fn formats_amounts_for_the_locale() {
+        let german = NormalizationOptions { format: locale_format("de-DE"), ..ALL };
+        assert_eq!(normalize_text("It costs five dollars and fifty cents", &german), "It costs 5,50 $");
+        assert_eq!(normalize_text("twenty euros", &german), "20 €");
+    }
+    
+    #[test]
+    fn localizes_quotes_in_any_language() {
+        let options = NormalizationOptions { format: locale_format("fr"), ..NormalizationOptions::default() };
+        assert_eq!(normalize_transcription("Il a dit \"non\"", Some("fr"), &options), "Il a dit «\u{a0}non\u{a0}»");
+    }
+    
+    #[test]
+    fn skips_non_english_transcriptions() {
+        let text = "zwanzig Euro";
+        assert_eq!(normalize_transcription(text, Some("de"), &ALL), text);
+    }
+    
+    #[test]
+    fn disabled_options_leave_text_alone() {
+        let text = "twenty five dollars";
+        assert_eq!(normalize_text(text, &NormalizationOptions::default()), text);
+    }
+}
@@ -0,0 +1,86 @@
+use audiopus::coder::Encoder;
+use audiopus::{Application, Bitrate, Channels, SampleRate};
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use crate::audio::resample_mono;
+
+// Speech is encoded as 16 kHz mono in 20 ms frames
+const OPUS_SAMPLE_RATE: u32 = 16000;
+const FRAME_SAMPLES: usize = 320;
+
+// Ogg granule positions always count 48 kHz samples
+const GRANULE_RATE: u64 = 48000;
+
+// Largest packet the encoder may produce, as recommended by libopus
+const MAX_PACKET_BYTES: usize = 4000;
+
+const OGG_SERIAL: u32 = 0x4355_5253;
+
+fn opus_head(pre_skip: u16) -> Vec<u8> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1);
+    head.push(1);
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&OPUS_SAMPLE_RATE.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes());
+    head.push(0);
+    head
+}
+
+fn opus_tags() -> Vec<u8> {
+    let vendor = b"cursper";
+    let mut tags = Vec::with_capacity(16 + vendor.len());
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags
+}
+
+// Encode a recording as an Ogg Opus file (RFC 7845) at the given bitrate, a fraction of
+// the size of WAV for uploads over slow connections
+pub fn encode_ogg_opus(samples: &[f32], sample_rate: u32, channels: u16, bitrate: u32) -> Result<Vec<u8>, String> {
+    let mut encoder = Encoder::new(SampleRate::Hz16000, Channels::Mono, Application::Voip)
+        .map_err(|e| format!("Failed to create Opus encoder: {}", e))?;
+    encoder
+        .set_bitrate(Bitrate::BitsPerSecond(bitrate as i32))
+        .map_err(|e| format!("Failed to set Opus bitrate: {}", e))?;
+    let lookahead = encoder.lookahead().map_err(|e| format!("Failed to query Opus encoder: {}", e))? as usize;
+    
+    let mut audio = resample_mono(samples, sample_rate, channels, OPUS_SAMPLE_RATE);
+    let length = audio.len();
+    // Flush the encoder's lookahead and pad the last frame; the final granule position
+    // tells decoders where the real audio ends
+    audio.resize((length + lookahead).div_ceil(FRAME_SAMPLES).max(1) * FRAME_SAMPLES, 0.0);
+    
+    let to_granule = |samples: usize| samples as u64 * GRANULE_RATE / OPUS_SAMPLE_RATE as u64;
+    let pre_skip = to_granule(lookahead);
+    let mut writer = PacketWriter::new(Vec::new());
+    let write_error = |e: std::io::Error| format!("Failed to write Ogg page: {}", e);
+    writer
+        .write_packet(opus_head(pre_skip as u16).into_boxed_slice(), OGG_SERIAL, PacketWriteEndInfo::EndPage, 0)
+        .map_err(write_error)?;
+    writer
+        .write_packet(opus_tags().into_boxed_slice(), OGG_SERIAL, PacketWriteEndInfo::EndPage, 0)
+        .map_err(write_error)?;
+    
+    let frames = audio.len() / FRAME_SAMPLES;
+    let mut packet = [0u8; MAX_PACKET_BYTES];
+    for (index, frame) in audio.chunks(FRAME_SAMPLES).enumerate() {
+        let size = encoder
+            .encode_float(frame, &mut packet)
+            .map_err(|e| format!("Failed to encode Opus frame: {}", e))?;
+        let last = index + 1 == frames;
+        let granule = if last {
+            pre_skip + to_granule(length)
+        } else {
+            to_granule((index + 1) * FRAME_SAMPLES)
+        };
+        let end = if last { PacketWriteEndInfo::EndStream } else { PacketWriteEndInfo::NormalPacket };
+        writer
+            .write_packet(packet[..size].to_vec().into_boxed_slice(), OGG_SERIAL, end, granule)
+            .map_err(write_error)?;
+    }
+    
+    Ok(writer.into_inner())
+}
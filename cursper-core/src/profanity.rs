@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+// Words filtered by default; users can add more via settings
+const DEFAULT_PROFANITY: &[&str] = &[
+    "fuck", "fucking", "fucked", "motherfucker", "shit", "shitty", "bullshit",
+    "bitch", "bastard", "asshole", "dick", "cunt", "piss", "pissed", "crap", "damn",
+];
+
+// How profanity is handled before insertion
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfanityFilter {
+    #[default]
+    Off,
+    // Keep the first letter and mask the rest ("f***")
+    Mask,
+    // Remove the word entirely
+    Drop,
+}
+
+fn is_profane(word: &str, extra_words: &[String]) -> bool {
+    let lower = word.to_lowercase();
+    DEFAULT_PROFANITY.contains(&lower.as_str())
+        || extra_words.iter().any(|extra| extra.to_lowercase() == lower)
+}
+
+fn mask(word: &str) -> String {
+    word.chars()
+        .enumerate()
+        .map(|(index, c)| if index == 0 { c } else { '*' })
+        .collect()
+}
+
+// Mask or drop profane words, keeping surrounding punctuation
+pub fn filter_profanity(text: &str, mode: ProfanityFilter, extra_words: &[String]) -> String {
+    if mode == ProfanityFilter::Off {
+        return text.to_string();
+    }
+    
+    let mut output: Vec<String> = Vec::new();
+    let mut filtered = 0;
+    
+    for word in text.split_whitespace() {
+        let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+        if core.is_empty() || !is_profane(core, extra_words) {
+            output.push(word.to_string());
+            continue;
+        }
+        
+        filtered += 1;
+        match mode {
+            ProfanityFilter::Mask => output.push(word.replacen(core, &mask(core), 1)),
+            ProfanityFilter::Drop => {
+                // Keep trailing punctuation attached to the previous word,
+                // unless the word was wrapped ("(crap)") and goes as a whole
+                let start = word.find(core).unwrap_or(0);
+                let trail = &word[start + core.len()..];
+                if start == 0 && !trail.is_empty() {
+                    match output.last_mut() {
+                        Some(previous) => previous.push_str(trail),
+                        None => output.push(trail.to_string()),
+                    }
+                }
+            }
+            ProfanityFilter::Off => {}
+        }
+    }
+    
+    if filtered > 0 {
+        println!("🧼 Filtered {} word(s)", filtered);
+    }
+    output.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn masks_keeping_first_letter_and_punctuation() {
+        assert_eq!(filter_profanity("Well, shit!", ProfanityFilter::Mask, &[]), "Well, s***!");
+    }
+    
+    #[test]
+    fn drops_words_and_wrapped_words() {
+        assert_eq!(filter_profanity("That is crap, really", ProfanityFilter::Drop, &[]), "That is, really");
+        assert_eq!(filter_profanity("Nice (crap) work", ProfanityFilter::Drop, &[]), "Nice work");
+    }
+    
+    #[test]
+    fn honours_extra_words_and_off() {
+        let extra = vec!["heck".to_string()];
+        assert_eq!(filter_profanity("Oh heck", ProfanityFilter::Mask, &extra), "Oh h***");
+        assert_eq!(filter_profanity("Oh shit", ProfanityFilter::Off, &extra), "Oh shit");
+    }
+}
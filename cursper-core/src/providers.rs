@@ -0,0 +1,504 @@
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::languages::language_code;
+
+// Protocol spoken by a transcription provider
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    // The bundled Flask/Whisper server (/transcribe_raw)
+    Cursper,
+    // OpenAI-compatible /v1/audio/transcriptions endpoint
+    OpenAi,
+}
+
+// One entry in the failover chain
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackendProvider {
+    pub name: String,
+    pub kind: ProviderKind,
+    pub url: String,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    // Cloud providers are skipped unless cloud fallback is allowed
+    #[serde(default)]
+    pub cloud: bool,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    // Daily limits enforced before upload (None = unlimited)
+    #[serde(default)]
+    pub max_audio_seconds_per_day: Option<u64>,
+    #[serde(default)]
+    pub max_cost_per_day: Option<f64>,
+    // Price estimate used for the cost limit
+    #[serde(default)]
+    pub cost_per_minute: Option<f64>,
+    // Longer clips are never routed to this provider
+    #[serde(default)]
+    pub max_clip_seconds: Option<u64>,
+}
+
+// Audio and estimated cost sent to one provider today
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ProviderUsage {
+    pub audio_seconds: f64,
+    pub cost: f64,
+}
+
+// Per-provider usage for a single day, persisted to provider_usage.json
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyUsage {
+    // Days since the Unix epoch (UTC)
+    pub day: u64,
+    pub providers: HashMap<String, ProviderUsage>,
+}
+
+pub fn default_timeout_secs() -> u64 {
+    60
+}
+
+// Advanced Whisper decoding settings; unset fields keep the provider's defaults
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecodingOptions {
+    // 0 decodes greedily; higher values sample more freely
+    pub temperature: Option<f32>,
+    // Beams searched when decoding at temperature 0
+    pub beam_size: Option<u32>,
+    // Candidates sampled when decoding above temperature 0
+    pub best_of: Option<u32>,
+    // Segments more likely silent than this are dropped, curbing hallucinations
+    pub no_speech_threshold: Option<f32>,
+}
+
+impl DecodingOptions {
+    // Set options as query parameters for /transcribe_raw
+    fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+        if let Some(temperature) = self.temperature {
+            pairs.push(("temperature", temperature.to_string()));
+        }
+        if let Some(beam_size) = self.beam_size {
+            pairs.push(("beam_size", beam_size.to_string()));
+        }
+        if let Some(best_of) = self.best_of {
+            pairs.push(("best_of", best_of.to_string()));
+        }
+        if let Some(threshold) = self.no_speech_threshold {
+            pairs.push(("no_speech_threshold", threshold.to_string()));
+        }
+        pairs
+    }
+}
+
+// Model setting that picks the model per clip, see AdaptiveModel
+pub const AUTO_MODEL: &str = "auto";
+
+// Sends short clips to a fast model and longer ones to a more accurate one, trading
+// accuracy on quick replies for latency and vice versa on long paragraphs
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveModel {
+    pub short_model: String,
+    pub long_model: String,
+    // Clips shorter than this go to short_model
+    pub threshold_seconds: f64,
+}
+
+impl Default for AdaptiveModel {
+    fn default() -> Self {
+        AdaptiveModel {
+            short_model: "base".to_string(),
+            long_model: "small".to_string(),
+            threshold_seconds: 10.0,
+        }
+    }
+}
+
+impl AdaptiveModel {
+    pub fn model_for_clip(&self, clip_seconds: f64) -> &str {
+        if clip_seconds < self.threshold_seconds {
+            &self.short_model
+        } else {
+            &self.long_model
+        }
+    }
+}
+
+// Pick the model for a clip on every Cursper provider that doesn't pin one
+pub fn route_adaptive_model(providers: &mut [BackendProvider], adaptive: &AdaptiveModel, clip_seconds: f64) {
+    route_model(providers, adaptive.model_for_clip(clip_seconds));
+}
+
+// Use a model for this dictation on every Cursper provider that doesn't pin one
+pub fn route_model(providers: &mut [BackendProvider], model: &str) {
+    for provider in providers.iter_mut() {
+        if provider.kind == ProviderKind::Cursper && provider.model.is_none() {
+            provider.model = Some(model.to_string());
+        }
+    }
+}
+
+// Error returned when an upload is aborted through UploadControl::cancel
+pub const UPLOAD_CANCELLED_ERROR: &str = "Upload cancelled";
+
+// Bytes handed to the HTTP client at a time; progress is reported at this granularity
+const UPLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+// Called with the bytes sent so far and the total size of the upload
+pub type UploadProgress = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
+// Hooks for watching and aborting a long upload; with neither set the body is sent in one go
+#[derive(Clone, Default)]
+pub struct UploadControl {
+    pub progress: Option<UploadProgress>,
+    // Set to abort the upload mid-flight
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl UploadControl {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::SeqCst))
+    }
+}
+
+// Audio as it goes over the wire
+pub struct UploadAudio {
+    pub data: Vec<u8>,
+    // Sample rate and channel count when sending raw PCM, None for WAV
+    pub pcm: Option<(u32, u16)>,
+    // Ogg Opus instead of WAV when pcm is None
+    pub opus: bool,
+    pub control: UploadControl,
+}
+
+// Stream the audio in chunks, reporting progress and stopping at the next chunk once cancelled
+fn upload_body(data: Vec<u8>, control: &UploadControl) -> reqwest::Body {
+    if control.progress.is_none() && control.cancel.is_none() {
+        return data.into();
+    }
+    
+    let total = data.len();
+    let control = control.clone();
+    let mut sent = 0;
+    let chunks = futures_util::stream::iter((0..total).step_by(UPLOAD_CHUNK_BYTES)).map(move |start| {
+        if control.is_cancelled() {
+            return Err(std::io::Error::new(std::io::ErrorKind::Interrupted, UPLOAD_CANCELLED_ERROR));
+        }
+        let chunk = data[start..(start + UPLOAD_CHUNK_BYTES).min(total)].to_vec();
+        sent += chunk.len();
+        if let Some(progress) = &control.progress {
+            progress(sent as u64, total as u64);
+        }
+        Ok(chunk)
+    });
+    reqwest::Body::wrap_stream(chunks)
+}
+
+// The request failed because the upload was cancelled rather than for a network error
+fn send_error(provider: &BackendProvider, control: &UploadControl, error: reqwest::Error) -> String {
+    if control.is_cancelled() {
+        UPLOAD_CANCELLED_ERROR.to_string()
+    } else {
+        format!("Failed to send audio to {}: {}", provider.name, error)
+    }
+}
+
+// The primary backend followed by the fallbacks, in order; cloud fallbacks only when allowed
+pub fn provider_chain(primary_url: &str, fallbacks: &[BackendProvider], allow_cloud: bool) -> Vec<BackendProvider> {
+    let primary = BackendProvider {
+        name: "Local".to_string(),
+        kind: ProviderKind::Cursper,
+        url: primary_url.to_string(),
+        api_key: None,
+        model: None,
+        cloud: false,
+        timeout_secs: default_timeout_secs(),
+        max_audio_seconds_per_day: None,
+        max_cost_per_day: None,
+        cost_per_minute: None,
+        max_clip_seconds: None,
+    };
+    
+    std::iter::once(primary)
+        .chain(
+            fallbacks
+                .iter()
+                .filter(|provider| !provider.cloud || allow_cloud)
+                .cloned(),
+        )
+        .collect()
+}
+
+// Estimated price of sending a clip to a provider
+pub fn clip_cost(provider: &BackendProvider, clip_seconds: f64) -> f64 {
+    provider.cost_per_minute.unwrap_or(0.0) * clip_seconds / 60.0
+}
+
+// Whether the provider tracks usage at all
+pub fn has_budget(provider: &BackendProvider) -> bool {
+    provider.max_audio_seconds_per_day.is_some() || provider.max_cost_per_day.is_some()
+}
+
+// Refuse a clip that exceeds the provider's length limit or would exceed today's budget
+pub fn check_clip_budget(provider: &BackendProvider, used: &ProviderUsage, clip_seconds: f64) -> Result<(), String> {
+    if let Some(max_clip) = provider.max_clip_seconds {
+        if clip_seconds > max_clip as f64 {
+            return Err(format!("{}: clip of {:.0}s exceeds the {}s limit", provider.name, clip_seconds, max_clip));
+        }
+    }
+    
+    if let Some(max_seconds) = provider.max_audio_seconds_per_day {
+        if used.audio_seconds + clip_seconds > max_seconds as f64 {
+            return Err(format!("{}: daily audio budget of {}s reached", provider.name, max_seconds));
+        }
+    }
+    if let Some(max_cost) = provider.max_cost_per_day {
+        if used.cost + clip_cost(provider, clip_seconds) > max_cost {
+            return Err(format!("{}: daily cost budget of {:.2} reached", provider.name, max_cost));
+        }
+    }
+    Ok(())
+}
+
+// Send audio to one provider; the response is normalized to the Cursper backend's JSON shape
+pub async fn transcribe_with_provider(
+    client: &reqwest::Client,
+    provider: &BackendProvider,
+    audio: UploadAudio,
+    language: Option<&str>,
+    word_timestamps: bool,
+    decoding: &DecodingOptions,
+    prompt: Option<&str>,
+) -> Result<serde_json::Value, String> {
+    match provider.kind {
+        ProviderKind::Cursper => transcribe_cursper(client, provider, audio, language, word_timestamps, decoding, prompt).await,
+        ProviderKind::OpenAi => transcribe_openai(client, provider, audio, language, word_timestamps, decoding, prompt).await,
+    }
+}
+
+async fn transcribe_cursper(
+    client: &reqwest::Client,
+    provider: &BackendProvider,
+    audio: UploadAudio,
+    language: Option<&str>,
+    word_timestamps: bool,
+    decoding: &DecodingOptions,
+    prompt: Option<&str>,
+) -> Result<serde_json::Value, String> {
+    let mut request = client
+        .post(format!("{}/transcribe_raw", provider.url))
+        .timeout(Duration::from_secs(provider.timeout_secs));
+    if let Some(language) = language {
+        request = request.query(&[("language", language)]);
+    }
+    if word_timestamps {
+        request = request.query(&[("word_timestamps", "1")]);
+    }
+    request = request.query(&decoding.query_pairs());
+    if let Some(prompt) = prompt {
+        request = request.query(&[("prompt", prompt)]);
+    }
+    // Without one the backend uses its current model
+    if let Some(model) = &provider.model {
+        request = request.query(&[("model", model)]);
+    }
+    request = match audio.pcm {
+        Some((sample_rate, channels)) => request
+            .header("Content-Type", "audio/pcm")
+            .header("X-Sample-Rate", sample_rate.to_string())
+            .header("X-Channels", channels.to_string()),
+        None if audio.opus => request.header("Content-Type", "audio/ogg"),
+        None => request.header("Content-Type", "audio/wav"),
+    };
+    
+    // A streamed body has no length of its own; the Flask backend needs one
+    let response = request
+        .header("Content-Length", audio.data.len().to_string())
+        .body(upload_body(audio.data, &audio.control))
+        .send()
+        .await
+        .map_err(|e| send_error(provider, &audio.control, e))?;
+    
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("{} returned error {}: {}", provider.name, status, error_text));
+    }
+    
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response from {}: {}", provider.name, e))
+}
+
+async fn transcribe_openai(
+    client: &reqwest::Client,
+    provider: &BackendProvider,
+    audio: UploadAudio,
+    language: Option<&str>,
+    word_timestamps: bool,
+    decoding: &DecodingOptions,
+    prompt: Option<&str>,
+) -> Result<serde_json::Value, String> {
+    if audio.pcm.is_some() {
+        return Err(format!("{} only accepts WAV uploads", provider.name));
+    }
+    
+    let model = provider.model.clone().unwrap_or_else(|| "whisper-1".to_string());
+    let length = audio.data.len() as u64;
+    let (file_name, mime) = if audio.opus { ("recording.ogg", "audio/ogg") } else { ("recording.wav", "audio/wav") };
+    let file = reqwest::multipart::Part::stream_with_length(upload_body(audio.data, &audio.control), length)
+        .file_name(file_name)
+        .mime_str(mime)
+        .map_err(|e| format!("Failed to build upload: {}", e))?;
+    let mut form = reqwest::multipart::Form::new()
+        .part("file", file)
+        .text("model", model.clone())
+        .text("response_format", "verbose_json");
+    if let Some(language) = language {
+        form = form.text("language", language.to_string());
+    }
+    // The OpenAI API only takes a temperature; the other options are local-only
+    if let Some(temperature) = decoding.temperature {
+        form = form.text("temperature", temperature.to_string());
+    }
+    if let Some(prompt) = prompt {
+        form = form.text("prompt", prompt.to_string());
+    }
+    if word_timestamps {
+        form = form
+            .text("timestamp_granularities[]", "word")
+            .text("timestamp_granularities[]", "segment");
+    }
+    
+    let mut request = client
+        .post(format!("{}/v1/audio/transcriptions", provider.url.trim_end_matches('/')))
+        .timeout(Duration::from_secs(provider.timeout_secs))
+        .multipart(form);
+    if let Some(api_key) = &provider.api_key {
+        request = request.bearer_auth(api_key);
+    }
+    
+    let response = request
+        .send()
+        .await
+        .map_err(|e| send_error(provider, &audio.control, e))?;
+    
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(format!("{} returned error {}: {}", provider.name, status, error_text));
+    }
+    
+    let mut result: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response from {}: {}", provider.name, e))?;
+    
+    // OpenAI reports the language by name ("english") and words outside the segments
+    if let Some(name) = result.get("language").and_then(|l| l.as_str()).map(|l| l.to_string()) {
+        if let Some(code) = language_code(&name) {
+            result["language"] = serde_json::Value::from(code);
+        }
+    }
+    if let Some(words) = result.get("words").and_then(|w| w.as_array()).cloned() {
+        if let Some(segments) = result.get_mut("segments").and_then(|s| s.as_array_mut()) {
+            for segment in segments.iter_mut() {
+                segment["words"] = serde_json::Value::Array(Vec::new());
+            }
+            for word in words {
+                let start = word.get("start").and_then(|s| s.as_f64()).unwrap_or(0.0);
+                let index = segments
+                    .iter()
+                    .position(|segment| start < segment.get("end").and_then(|e| e.as_f64()).unwrap_or(f64::MAX))
+                    .unwrap_or(segments.len().saturating_sub(1));
+                if let Some(list) = segments.get_mut(index).and_then(|segment| segment["words"].as_array_mut()) {
+                    list.push(word);
+                }
+            }
+        }
+    }
+    result["model_used"] = serde_json::Value::from(model);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn provider(name: &str, cloud: bool) -> BackendProvider {
+        BackendProvider {
+            name: name.to_string(),
+            kind: ProviderKind::OpenAi,
+            url: "https://api.example.com".to_string(),
+            api_key: None,
+            model: None,
+            cloud,
+            timeout_secs: default_timeout_secs(),
+            max_audio_seconds_per_day: None,
+            max_cost_per_day: None,
+            cost_per_minute: None,
+            max_clip_seconds: None,
+        }
+    }
+    
+    #[test]
+    fn chain_starts_with_local_and_skips_cloud_unless_allowed() {
+        let fallbacks = vec![provider("Office", false), provider("Cloud", true)];
+        
+        let names = |chain: Vec<BackendProvider>| chain.into_iter().map(|p| p.name).collect::<Vec<_>>();
+        assert_eq!(names(provider_chain("http://localhost:8000", &fallbacks, false)), vec!["Local", "Office"]);
+        assert_eq!(names(provider_chain("http://localhost:8000", &fallbacks, true)), vec!["Local", "Office", "Cloud"]);
+    }
+    
+    #[test]
+    fn adaptive_model_routes_by_clip_length() {
+        let adaptive = AdaptiveModel::default();
+        let mut chain = provider_chain("http://localhost:8000", &[provider("Cloud", true)], true);
+        let mut pinned = chain[0].clone();
+        pinned.model = Some("large".to_string());
+        chain.push(pinned);
+        
+        let mut short = chain.clone();
+        route_adaptive_model(&mut short, &adaptive, 4.0);
+        assert_eq!(short[0].model.as_deref(), Some("base"));
+        // Other protocols and pinned models are left alone
+        assert_eq!(short[1].model, None);
+        assert_eq!(short[2].model.as_deref(), Some("large"));
+        
+        route_adaptive_model(&mut chain, &adaptive, 10.0);
+        assert_eq!(chain[0].model.as_deref(), Some("small"));
+        
+        let mut accurate = provider_chain("http://localhost:8000", &[], false);
+        route_model(&mut accurate, "medium");
+        assert_eq!(accurate[0].model.as_deref(), Some("medium"));
+    }
+    
+    #[test]
+    fn budget_rejects_long_clips_and_exhausted_days() {
+        let mut limited = provider("Cloud", true);
+        limited.max_clip_seconds = Some(30);
+        limited.max_audio_seconds_per_day = Some(60);
+        limited.max_cost_per_day = Some(1.0);
+        limited.cost_per_minute = Some(0.5);
+        
+        let fresh = ProviderUsage::default();
+        assert!(check_clip_budget(&limited, &fresh, 20.0).is_ok());
+        assert!(check_clip_budget(&limited, &fresh, 40.0).is_err());
+        
+        let used = ProviderUsage { audio_seconds: 50.0, cost: 0.0 };
+        assert!(check_clip_budget(&limited, &used, 20.0).is_err());
+        
+        let spent = ProviderUsage { audio_seconds: 0.0, cost: 0.95 };
+        assert!(check_clip_budget(&limited, &spent, 20.0).is_err());
+        assert_eq!(clip_cost(&limited, 60.0), 0.5);
+    }
+}
@@ -0,0 +1,254 @@
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use crate::audio::RecordedAudio;
+use crate::capture::{open_source, record_from_source_streaming, AudioSource, CaptureControl, CaptureOptions, MAX_RECORDING_DURATION};
+
+// Error for a recording started while another one is still running
+pub const ALREADY_RECORDING_ERROR: &str = "Already recording";
+
+// Receives each captured chunk along with the source's sample rate and channel count
+pub type SampleSink = Box<dyn FnMut(&[f32], u32, u16) + Send>;
+
+// Messages the recorder actor responds to; anything but Start is ignored while idle
+pub enum RecorderCommand {
+    // Open the input and record until stopped, then send the audio back on reply
+    Start {
+        options: CaptureOptions,
+        on_samples: SampleSink,
+        reply: Sender<Result<RecordedAudio, String>>,
+    },
+    Stop,
+    Cancel,
+    Pause,
+    Resume,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecorderState {
+    Idle,
+    Recording,
+    Paused,
+}
+
+// Handle to the recorder actor: a long-lived thread that owns the input stream (CPAL
+// streams can't move between threads) and runs one recording at a time. Clones talk to
+// the same actor, which exits once every handle is dropped.
+#[derive(Clone)]
+pub struct Recorder {
+    commands: Sender<RecorderCommand>,
+    state: Arc<Mutex<RecorderState>>,
+}
+
+impl Recorder {
+    // Record from the fake source when CURSPER_FAKE_AUDIO is set, otherwise from the
+    // input device each recording's options name
+    pub fn spawn() -> Self {
+        Self::spawn_with_source(open_source, MAX_RECORDING_DURATION)
+    }
+    
+    // Record from whatever open returns, e.g. a fixture in tests
+    pub fn spawn_with_source<F>(open: F, max_duration: Duration) -> Self
+    where
+        F: Fn(&CaptureOptions) -> Result<Box<dyn AudioSource>, String> + Send + 'static,
+    {
+        let (commands, received) = mpsc::channel();
+        let state = Arc::new(Mutex::new(RecorderState::Idle));
+        let actor_state = state.clone();
+        thread::spawn(move || run_recorder(received, actor_state, open, max_duration));
+        Self { commands, state }
+    }
+    
+    pub fn state(&self) -> RecorderState {
+        self.state.lock().map(|state| *state).unwrap_or(RecorderState::Idle)
+    }
+    
+    // Record until stop or cancel is sent through another handle, blocking until then
+    pub fn record(&self, options: CaptureOptions, on_samples: SampleSink) -> Result<RecordedAudio, String> {
+        let (reply, result) = mpsc::channel();
+        self.send(RecorderCommand::Start { options, on_samples, reply })?;
+        result
+            .recv()
+            .map_err(|_| "Recorder exited during the recording".to_string())?
+    }
+    
+    // Finish the recording; record returns what was captured
+    pub fn stop(&self) -> Result<(), String> {
+        self.send(RecorderCommand::Stop)
+    }
+    
+    // Finish the recording and discard it; record returns RECORDING_CANCELLED_ERROR
+    pub fn cancel(&self) -> Result<(), String> {
+        self.send(RecorderCommand::Cancel)
+    }
+    
+    // Keep the input open but leave out what it captures until resumed
+    pub fn pause(&self) -> Result<(), String> {
+        self.send(RecorderCommand::Pause)
+    }
+    
+    pub fn resume(&self) -> Result<(), String> {
+        self.send(RecorderCommand::Resume)
+    }
+    
+    fn send(&self, command: RecorderCommand) -> Result<(), String> {
+        self.commands
+            .send(command)
+            .map_err(|_| "Recorder is not running".to_string())
+    }
+}
+
+fn set_state(state: &Mutex<RecorderState>, new_state: RecorderState) {
+    if let Ok(mut state) = state.lock() {
+        *state = new_state;
+    }
+}
+
+fn run_recorder<F>(commands: Receiver<RecorderCommand>, state: Arc<Mutex<RecorderState>>, open: F, max_duration: Duration)
+where
+    F: Fn(&CaptureOptions) -> Result<Box<dyn AudioSource>, String>,
+{
+    while let Ok(command) = commands.recv() {
+        if let RecorderCommand::Start { options, mut on_samples, reply } = command {
+            let result = open(&options).and_then(|mut source| {
+                set_state(&state, RecorderState::Recording);
+                println!("🎤 Starting audio recording... (will record until stopped or max {} seconds)", max_duration.as_secs());
+                let mut control = CaptureControl::Record;
+                record_from_source_streaming(
+                    source.as_mut(),
+                    &mut || {
+                        control = next_control(&commands, control);
+                        set_state(&state, if control == CaptureControl::Pause { RecorderState::Paused } else { RecorderState::Recording });
+                        control
+                    },
+                    max_duration,
+                    on_samples.as_mut(),
+                )
+                // The source is dropped here, releasing the device before any transcription work
+            });
+            set_state(&state, RecorderState::Idle);
+            let _ = reply.send(result);
+        }
+    }
+}
+
+// Apply the commands that arrived since the last chunk
+fn next_control(commands: &Receiver<RecorderCommand>, mut control: CaptureControl) -> CaptureControl {
+    loop {
+        control = match (commands.try_recv(), control) {
+            (Ok(RecorderCommand::Start { reply, .. }), control) => {
+                let _ = reply.send(Err(ALREADY_RECORDING_ERROR.to_string()));
+                control
+            }
+            (Ok(RecorderCommand::Cancel), _) => CaptureControl::Cancel,
+            (Ok(RecorderCommand::Stop), CaptureControl::Record | CaptureControl::Pause) => CaptureControl::Stop,
+            (Ok(RecorderCommand::Pause), CaptureControl::Record) => CaptureControl::Pause,
+            (Ok(RecorderCommand::Resume), CaptureControl::Pause) => CaptureControl::Record,
+            (Ok(_), control) => control,
+            (Err(TryRecvError::Empty), control) => return control,
+            // Nobody is left to stop the recording
+            (Err(TryRecvError::Disconnected), CaptureControl::Cancel) => return CaptureControl::Cancel,
+            (Err(TryRecvError::Disconnected), _) => return CaptureControl::Stop,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{FakeAudioSource, RECORDING_CANCELLED_ERROR};
+    use std::time::Instant;
+    
+    fn sine_recorder() -> Recorder {
+        Recorder::spawn_with_source(
+            |_: &CaptureOptions| Ok(Box::new(FakeAudioSource::sine(440.0, 0.5, 16000, 1)) as Box<dyn AudioSource>),
+            Duration::from_secs(5),
+        )
+    }
+    
+    // Start a recording on another thread and wait until it is running
+    fn start(recorder: &Recorder) -> thread::JoinHandle<Result<RecordedAudio, String>> {
+        let recording = recorder.clone();
+        let handle = thread::spawn(move || recording.record(CaptureOptions::default(), Box::new(|_, _, _| {})));
+        let started = Instant::now();
+        while recorder.state() != RecorderState::Recording {
+            assert!(started.elapsed() < Duration::from_secs(5), "recording never started");
+            thread::sleep(Duration::from_millis(5));
+        }
+        handle
+    }
+    
+    #[test]
+    fn stop_returns_the_recording_and_goes_idle() {
+        let recorder = sine_recorder();
+        assert_eq!(recorder.state(), RecorderState::Idle);
+        
+        let recording = start(&recorder);
+        thread::sleep(Duration::from_millis(100));
+        recorder.stop().unwrap();
+        let recorded = recording.join().unwrap().unwrap();
+        assert!(!recorded.samples.is_empty());
+        assert!(recorded.duration < Duration::from_secs(1));
+        assert_eq!(recorder.state(), RecorderState::Idle);
+        
+        // The actor outlives the recording and can record again
+        let recording = start(&recorder);
+        recorder.stop().unwrap();
+        assert!(recording.join().unwrap().is_ok());
+    }
+    
+    #[test]
+    fn cancel_discards_the_recording() {
+        let recorder = sine_recorder();
+        let recording = start(&recorder);
+        recorder.cancel().unwrap();
+        assert_eq!(recording.join().unwrap().err().as_deref(), Some(RECORDING_CANCELLED_ERROR));
+    }
+    
+    #[test]
+    fn pause_and_resume_are_reflected_in_the_state() {
+        let recorder = sine_recorder();
+        let recording = start(&recorder);
+        
+        recorder.pause().unwrap();
+        let started = Instant::now();
+        while recorder.state() != RecorderState::Paused {
+            assert!(started.elapsed() < Duration::from_secs(5), "recording never paused");
+            thread::sleep(Duration::from_millis(5));
+        }
+        recorder.resume().unwrap();
+        recorder.stop().unwrap();
+        assert!(recording.join().unwrap().is_ok());
+    }
+    
+    #[test]
+    fn only_one_recording_runs_at_a_time() {
+        let recorder = sine_recorder();
+        let recording = start(&recorder);
+        
+        let second = recorder.record(CaptureOptions::default(), Box::new(|_, _, _| {}));
+        assert_eq!(second.err().as_deref(), Some(ALREADY_RECORDING_ERROR));
+        recorder.stop().unwrap();
+        assert!(recording.join().unwrap().is_ok());
+    }
+    
+    #[test]
+    fn commands_while_idle_are_ignored() {
+        let recorder = sine_recorder();
+        recorder.stop().unwrap();
+        recorder.cancel().unwrap();
+        
+        let recording = start(&recorder);
+        recorder.stop().unwrap();
+        assert!(recording.join().unwrap().is_ok());
+    }
+    
+    #[test]
+    fn failing_to_open_the_input_is_reported() {
+        let recorder = Recorder::spawn_with_source(|_: &CaptureOptions| Err("No microphone".to_string()), Duration::from_secs(5));
+        let result = recorder.record(CaptureOptions::default(), Box::new(|_, _, _| {}));
+        assert_eq!(result.err().as_deref(), Some("No microphone"));
+        assert_eq!(recorder.state(), RecorderState::Idle);
+    }
+}
@@ -0,0 +1,53 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+// Built-in PII patterns and their replacement labels
+static PII_PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+
+fn pii_patterns() -> &'static [(Regex, &'static str)] {
+    PII_PATTERNS.get_or_init(|| {
+        [
+            (r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}", "[email]"),
+            // 13-19 digits, optionally grouped by spaces or dashes
+            (r"\b(?:\d[ -]?){12,18}\d\b", "[card]"),
+            (r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{2,4}\)|\d{2,4})[ .-]?\d{3,4}[ .-]?\d{3,4}\b", "[phone]"),
+        ]
+        .into_iter()
+        .filter_map(|(pattern, label)| Regex::new(pattern).ok().map(|regex| (regex, label)))
+        .collect()
+    })
+}
+
+// Redact emails, card numbers, phone numbers and user-defined patterns
+pub fn redact_pii(text: &str, extra_patterns: &[String]) -> String {
+    let mut redacted = text.to_string();
+    for (regex, label) in pii_patterns() {
+        redacted = regex.replace_all(&redacted, *label).into_owned();
+    }
+    
+    for pattern in extra_patterns {
+        match Regex::new(pattern) {
+            Ok(regex) => redacted = regex.replace_all(&redacted, "[redacted]").into_owned(),
+            Err(e) => println!("⚠️ Ignoring invalid redaction pattern '{}': {}", pattern, e),
+        }
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn redacts_builtin_patterns() {
+        let text = "Mail jane.doe@example.com or call +1 555 123 4567, card 4111 1111 1111 1111";
+        let redacted = redact_pii(text, &[]);
+        assert_eq!(redacted, "Mail [email] or call [phone], card [card]");
+    }
+    
+    #[test]
+    fn redacts_custom_patterns_and_skips_invalid_ones() {
+        let patterns = vec![r"ACME-\d+".to_string(), "(".to_string()];
+        assert_eq!(redact_pii("Ticket ACME-42 is done", &patterns), "Ticket [redacted] is done");
+    }
+}
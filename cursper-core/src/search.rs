@@ -0,0 +1,28 @@
+// Turn free text typed into a search box into an SQLite FTS5 MATCH expression. Every
+// word becomes a quoted prefix term, so punctuation in e.g. "555-1234" or "c++" can't
+// be read as query syntax, and all words must appear in the entry.
+pub fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn quotes_every_word_as_a_prefix_term() {
+        assert_eq!(fts_query("phone 555-1234").as_deref(), Some("\"phone\"* \"555-1234\"*"));
+        assert_eq!(fts_query("say \"hi\" OR NOT").as_deref(), Some("\"say\"* \"\"\"hi\"\"\"* \"OR\"* \"NOT\"*"));
+        assert_eq!(fts_query("  - * "), None);
+        assert_eq!(fts_query(""), None);
+    }
+}
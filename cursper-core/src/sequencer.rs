@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+// Orders completed transcriptions so text is inserted in the order it was spoken,
+// even when later jobs finish before earlier ones. Items are usually the text to type,
+// but can be any edit that has to happen in turn.
+pub struct InsertionSequencer<T = String> {
+    next_ticket: u64,
+    next_to_insert: u64,
+    // Completed jobs waiting for earlier ones; None marks a job with nothing to insert
+    pending: BTreeMap<u64, Option<T>>,
+}
+
+impl<T> Default for InsertionSequencer<T> {
+    fn default() -> Self {
+        Self {
+            next_ticket: 0,
+            next_to_insert: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> InsertionSequencer<T> {
+    // Reserve a position in the insertion order when a job starts
+    pub fn issue_ticket(&mut self) -> u64 {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        ticket
+    }
+    
+    // Mark a job complete and return every text that is now ready, in order
+    pub fn complete(&mut self, ticket: u64, text: Option<T>) -> Vec<T> {
+        self.pending.insert(ticket, text);
+        
+        let mut ready = Vec::new();
+        while let Some(text) = self.pending.remove(&self.next_to_insert) {
+            if let Some(text) = text {
+                ready.push(text);
+            }
+            self.next_to_insert += 1;
+        }
+        ready
+    }
+    
+    // Jobs holding a ticket whose text hasn't been returned yet
+    pub fn outstanding(&self) -> u64 {
+        self.next_ticket - self.next_to_insert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn holds_back_jobs_that_finish_early() {
+        let mut sequencer = InsertionSequencer::default();
+        let first = sequencer.issue_ticket();
+        let second = sequencer.issue_ticket();
+        
+        assert!(sequencer.complete(second, Some("world".to_string())).is_empty());
+        assert_eq!(sequencer.complete(first, Some("hello".to_string())), vec!["hello", "world"]);
+    }
+    
+    #[test]
+    fn skipped_jobs_release_later_ones() {
+        let mut sequencer = InsertionSequencer::default();
+        let first = sequencer.issue_ticket();
+        let second = sequencer.issue_ticket();
+        
+        assert!(sequencer.complete(second, Some("kept".to_string())).is_empty());
+        assert_eq!(sequencer.complete(first, None), vec!["kept"]);
+    }
+    
+    #[test]
+    fn counts_jobs_until_their_text_is_released() {
+        let mut sequencer = InsertionSequencer::default();
+        let first = sequencer.issue_ticket();
+        let second = sequencer.issue_ticket();
+        assert_eq!(sequencer.outstanding(), 2);
+        
+        sequencer.complete(second, Some("held back".to_string()));
+        assert_eq!(sequencer.outstanding(), 2);
+        sequencer.complete(first, None);
+        assert_eq!(sequencer.outstanding(), 0);
+    }
+}
@@ -0,0 +1,118 @@
+// Fits a transcription into the text around the caret the way native dictation does:
+// a space is added or dropped at the start, the first letter follows the sentence it
+// lands in, and a space separates it from a word that follows.
+
+// Characters after which the inserted text should not start with a space
+const OPENERS: &[char] = &['(', '[', '{', '"', '\'', '“', '„', '‘', '«', '/', '-'];
+
+// Characters that end a sentence, so the next word is capitalized
+const SENTENCE_ENDS: &[char] = &['.', '!', '?', '…'];
+
+// Punctuation that attaches to the previous word without a space
+const ATTACHED: &[char] = &[',', '.', ';', ':', '!', '?', ')', ']', '}', '…'];
+
+// Whether a word should keep its capital even in the middle of a sentence: "I",
+// contractions like "I'm", and acronyms
+fn keeps_capital(word: &str) -> bool {
+    let word = word.trim_end_matches(|c: char| !c.is_alphanumeric());
+    if word == "I" || word.starts_with("I'") || word.starts_with("I’") {
+        return true;
+    }
+    word.chars().nth(1).is_some_and(|c| c.is_uppercase())
+}
+
+fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn lowercase_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// Adjust text for insertion between `before` and `after`, the field contents on either
+// side of the caret (empty at the start or end of the field)
+pub fn fit_to_context(text: &str, before: &str, after: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    
+    let previous = before.chars().last();
+    let last_visible = before.trim_end_matches([' ', '\t']).chars().last();
+    let sentence_start = match last_visible {
+        None | Some('\n') | Some('\r') => true,
+        Some(c) => SENTENCE_ENDS.contains(&c),
+    };
+    
+    let mut fitted = if sentence_start {
+        capitalize_first(trimmed)
+    } else if keeps_capital(trimmed.split_whitespace().next().unwrap_or_default()) {
+        trimmed.to_string()
+    } else {
+        lowercase_first(trimmed)
+    };
+    
+    // Whisper ends most transcriptions with a period; drop it when continuing a sentence
+    let next = after.chars().next();
+    if next.is_some_and(|c| c.is_alphanumeric()) && fitted.ends_with('.') && !fitted.ends_with("..") {
+        fitted.pop();
+    }
+    
+    let starts_attached = fitted.starts_with(ATTACHED);
+    let needs_leading_space = match previous {
+        Some(c) => !c.is_whitespace() && !OPENERS.contains(&c) && !starts_attached,
+        None => false,
+    };
+    if needs_leading_space {
+        fitted.insert(0, ' ');
+    }
+    
+    if next.is_some_and(|c| !c.is_whitespace() && !ATTACHED.contains(&c)) {
+        fitted.push(' ');
+    }
+    
+    fitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn adds_a_space_after_a_word() {
+        assert_eq!(fit_to_context("Quick brown fox.", "The", ""), " quick brown fox.");
+        assert_eq!(fit_to_context(" quick", "The ", ""), "quick");
+    }
+    
+    #[test]
+    fn capitalizes_at_sentence_starts() {
+        assert_eq!(fit_to_context("hello there.", "", ""), "Hello there.");
+        assert_eq!(fit_to_context("hello there.", "Done.", ""), " Hello there.");
+        assert_eq!(fit_to_context("hello there.", "First line\n", ""), "Hello there.");
+    }
+    
+    #[test]
+    fn keeps_pronoun_and_acronym_capitals() {
+        assert_eq!(fit_to_context("I think so", "and ", ""), "I think so");
+        assert_eq!(fit_to_context("NASA launched it", "and ", ""), "NASA launched it");
+    }
+    
+    #[test]
+    fn separates_from_the_following_word() {
+        assert_eq!(fit_to_context("Very.", "a ", "good day"), "very ");
+        assert_eq!(fit_to_context("Very", "a ", ", he said"), "very");
+    }
+    
+    #[test]
+    fn no_space_after_an_opening_bracket() {
+        assert_eq!(fit_to_context("Aside", "(", ")"), "aside");
+    }
+}
@@ -0,0 +1,245 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// A single word with its timing inside the clip, in seconds
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WordTimestamp {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+    #[serde(default)]
+    pub probability: Option<f64>,
+}
+
+// A Whisper segment (roughly a sentence or phrase)
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TranscriptSegment {
+    pub id: u32,
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    #[serde(default)]
+    pub words: Vec<WordTimestamp>,
+}
+
+// Per-stage latency of a dictation, in milliseconds
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct StageTimings {
+    pub capture_ms: u64,
+    pub encode_ms: u64,
+    pub upload_ms: u64,
+    pub backend_ms: Option<u64>,
+    pub post_process_ms: Option<u64>,
+    pub insert_ms: Option<u64>,
+}
+
+// Speaking pace of a dictation, derived from the transcript segments
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SpeechRate {
+    pub words: u64,
+    pub words_per_minute: f64,
+    // Time covered by speech segments vs. gaps between and around them
+    pub speaking_ms: u64,
+    pub pause_ms: u64,
+    // Share of the recording spent speaking, 0.0-1.0
+    pub speaking_ratio: f64,
+}
+
+// Result of a single transcription returned by the backend
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TranscriptionResult {
+    #[serde(default)]
+    pub job_id: u64,
+    pub text: String,
+    pub language: Option<String>,
+    pub model_used: Option<String>,
+    #[serde(default)]
+    pub segments: Vec<TranscriptSegment>,
+    #[serde(default)]
+    pub timings: StageTimings,
+    #[serde(default)]
+    pub speech_rate: SpeechRate,
+    // Retained recording this was transcribed from, relative to the recordings directory
+    #[serde(default)]
+    pub audio_file: Option<String>,
+}
+
+// Fields read from a provider response in the Cursper backend's JSON shape
+pub struct BackendResponse {
+    pub text: String,
+    pub language: Option<String>,
+    pub model_used: Option<String>,
+    pub segments: Vec<TranscriptSegment>,
+    // Time the backend spent transcribing, when it reports it
+    pub processing_ms: Option<u64>,
+}
+
+// Read text, language, model, segments and processing time from a provider response
+pub fn parse_backend_response(response: &serde_json::Value) -> BackendResponse {
+    let text = response
+        .get("text")
+        .and_then(|t| t.as_str())
+        .unwrap_or("No text returned")
+        .to_string();
+    
+    let language = response
+        .get("language")
+        .and_then(|l| l.as_str())
+        .map(|l| l.to_string());
+    
+    let model_used = response
+        .get("model_used")
+        .and_then(|m| m.as_str())
+        .map(|m| m.to_string());
+    
+    let segments = response
+        .get("segments")
+        .cloned()
+        .map(|segments| serde_json::from_value(segments).unwrap_or_else(|e| {
+            println!("⚠️ Failed to parse transcript segments: {}", e);
+            Vec::new()
+        }))
+        .unwrap_or_default();
+    
+    BackendResponse {
+        text,
+        language,
+        model_used,
+        segments,
+        processing_ms: response.get("processing_ms").and_then(|ms| ms.as_u64()),
+    }
+}
+
+// Words per minute and speaking/pause split of a single dictation.
+// Without segments the whole recording counts as speech.
+pub fn compute_speech_rate(text: &str, segments: &[TranscriptSegment], recording_ms: u64) -> SpeechRate {
+    let words = text.split_whitespace().count() as u64;
+    let speaking_ms = if segments.is_empty() {
+        recording_ms
+    } else {
+        let spoken: f64 = segments
+            .iter()
+            .map(|segment| (segment.end - segment.start).max(0.0))
+            .sum();
+        ((spoken * 1000.0) as u64).min(recording_ms.max(1))
+    };
+    let pause_ms = recording_ms.saturating_sub(speaking_ms);
+    
+    let words_per_minute = if speaking_ms > 0 {
+        words as f64 * 60_000.0 / speaking_ms as f64
+    } else {
+        0.0
+    };
+    let speaking_ratio = if recording_ms > 0 {
+        speaking_ms as f64 / recording_ms as f64
+    } else {
+        0.0
+    };
+    
+    SpeechRate {
+        words,
+        words_per_minute,
+        speaking_ms,
+        pause_ms,
+        speaking_ratio,
+    }
+}
+
+// The end of a transcript, at most max_chars long, to condition the next chunk on.
+// Starts at the last sentence that fits, else at a word boundary.
+pub fn context_tail(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    let total = text.chars().count();
+    if total <= max_chars {
+        return text.to_string();
+    }
+    
+    let start = text
+        .char_indices()
+        .nth(total - max_chars)
+        .map(|(index, _)| index)
+        .unwrap_or(0);
+    let tail = &text[start..];
+    let sentence_start = tail
+        .char_indices()
+        .find(|&(index, c)| index > 0 && c.is_whitespace() && tail[..index].ends_with(['.', '!', '?']))
+        .map(|(index, _)| index);
+    let word_start = tail.find(char::is_whitespace);
+    match sentence_start.or(word_start) {
+        Some(index) => tail[index..].trim_start().to_string(),
+        None => tail.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn segment(start: f64, end: f64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            id: 0,
+            start,
+            end,
+            text: text.to_string(),
+            words: Vec::new(),
+        }
+    }
+    
+    #[test]
+    fn speech_rate_counts_only_segment_time_as_speaking() {
+        let segments = [segment(0.5, 2.0, "one two three"), segment(3.0, 4.5, "four five six")];
+        let rate = compute_speech_rate("one two three four five six", &segments, 6000);
+        
+        assert_eq!(rate.words, 6);
+        assert_eq!(rate.speaking_ms, 3000);
+        assert_eq!(rate.pause_ms, 3000);
+        assert_eq!(rate.words_per_minute, 120.0);
+        assert_eq!(rate.speaking_ratio, 0.5);
+    }
+    
+    #[test]
+    fn speech_rate_without_segments_uses_whole_recording() {
+        let rate = compute_speech_rate("hello there", &[], 1000);
+        assert_eq!(rate.speaking_ms, 1000);
+        assert_eq!(rate.pause_ms, 0);
+        assert_eq!(rate.words_per_minute, 120.0);
+    }
+    
+    #[test]
+    fn parses_backend_response() {
+        let response = serde_json::json!({
+            "text": "Hello world",
+            "language": "en",
+            "model_used": "base",
+            "processing_ms": 420,
+            "segments": [{"id": 0, "start": 0.0, "end": 1.2, "text": "Hello world"}],
+        });
+        let parsed = parse_backend_response(&response);
+        
+        assert_eq!(parsed.text, "Hello world");
+        assert_eq!(parsed.language.as_deref(), Some("en"));
+        assert_eq!(parsed.model_used.as_deref(), Some("base"));
+        assert_eq!(parsed.processing_ms, Some(420));
+        assert_eq!(parsed.segments.len(), 1);
+    }
+    
+    #[test]
+    fn malformed_segments_are_dropped() {
+        let response = serde_json::json!({"text": "Hi", "segments": "nope"});
+        let parsed = parse_backend_response(&response);
+        assert!(parsed.segments.is_empty());
+        assert_eq!(parsed.processing_ms, None);
+    }
+    
+    #[test]
+    fn context_tail_cuts_at_sentences_then_words() {
+        assert_eq!(context_tail("  Short enough. ", 40), "Short enough.");
+        assert_eq!(
+            context_tail("First sentence here. Second one follows", 30),
+            "Second one follows"
+        );
+        assert_eq!(context_tail("one two three four five", 12), "four five");
+        assert_eq!(context_tail("unbroken", 4), "oken");
+        assert_eq!(context_tail("Grüße aus München", 7), "München");
+    }
+}
@@ -0,0 +1,476 @@
+// End-to-end tests of the transcription pipeline against an in-process mock of the
+// Flask backend (and an OpenAI-compatible provider), including its failure modes.
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use cursper_core::audio::{convert_to_pcm, convert_to_wav, gate_key_click, map_channels, trim_silence, InputChannelMode, RecordedAudio, PCM_FORMAT};
+use cursper_core::backend::{check_health, delete_cached_model, fetch_cached_models, fetch_models};
+use cursper_core::normalize::{normalize_transcription, NormalizationOptions};
+use cursper_core::profanity::{filter_profanity, ProfanityFilter};
+use cursper_core::providers::{default_timeout_secs, provider_chain, transcribe_with_provider, BackendProvider, DecodingOptions, ProviderKind, UploadAudio, UploadControl, UPLOAD_CANCELLED_ERROR};
+use cursper_core::sequencer::InsertionSequencer;
+use cursper_core::transcript::parse_backend_response;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// How the mock answers /transcribe_raw
+#[derive(Clone, Copy)]
+enum Behavior {
+    Transcribe,
+    ServerError,
+    MalformedJson,
+    // Answers after longer than the provider timeout
+    Slow,
+}
+
+// What the mock saw of an upload
+#[derive(Clone, Debug)]
+struct ReceivedUpload {
+    content_type: String,
+    sample_rate: Option<String>,
+    channels: Option<String>,
+    query: HashMap<String, String>,
+    bytes: usize,
+}
+
+#[derive(Clone)]
+struct Mock {
+    behavior: Behavior,
+    transcript: &'static str,
+    uploads: Arc<Mutex<Vec<ReceivedUpload>>>,
+}
+
+struct MockBackend {
+    url: String,
+    uploads: Arc<Mutex<Vec<ReceivedUpload>>>,
+}
+
+impl MockBackend {
+    fn uploads(&self) -> Vec<ReceivedUpload> {
+        self.uploads.lock().unwrap().clone()
+    }
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(json!({
+        "status": "healthy",
+        "current_model": "base",
+        "model_loaded": true,
+        "available_models": ["tiny", "base"],
+        "audio_formats": ["wav", PCM_FORMAT],
+    }))
+}
+
+async fn models() -> Json<serde_json::Value> {
+    Json(json!({"available_models": ["tiny", "base"], "current_model": "base"}))
+}
+
+async fn cached_models() -> Json<serde_json::Value> {
+    Json(json!({
+        "cache_dir": "/tmp/whisper",
+        "cached_models": [
+            {"name": "base", "size_bytes": 145262807, "last_used": 1700000000, "loaded": true},
+            {"name": "large-v3", "size_bytes": 3087371615u64, "last_used": 1690000000, "loaded": false},
+        ],
+    }))
+}
+
+async fn delete_cached(Path(name): Path<String>) -> Response {
+    match name.as_str() {
+        "large-v3" => Json(json!({"message": "Deleted large-v3"})).into_response(),
+        "base" => (StatusCode::CONFLICT, Json(json!({"error": "Model base is in use"}))).into_response(),
+        _ => (StatusCode::NOT_FOUND, Json(json!({"error": format!("Model {} is not cached", name)}))).into_response(),
+    }
+}
+
+fn header(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers.get(name).and_then(|value| value.to_str().ok()).map(|value| value.to_string())
+}
+
+async fn transcribe_raw(
+    State(mock): State<Mock>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    mock.uploads.lock().unwrap().push(ReceivedUpload {
+        content_type: header(&headers, "content-type").unwrap_or_default(),
+        sample_rate: header(&headers, "x-sample-rate"),
+        channels: header(&headers, "x-channels"),
+        query,
+        bytes: body.len(),
+    });
+    
+    match mock.behavior {
+        Behavior::Transcribe => Json(json!({
+            "text": mock.transcript,
+            "language": "en",
+            "model_used": "base",
+            "processing_ms": 120,
+            "segments": [{"id": 0, "start": 0.0, "end": 1.0, "text": mock.transcript}],
+        }))
+        .into_response(),
+        Behavior::ServerError => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "Failed to load Whisper model"}))).into_response(),
+        Behavior::MalformedJson => (StatusCode::OK, "{\"text\": ").into_response(),
+        Behavior::Slow => {
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            Json(json!({"text": "too late"})).into_response()
+        }
+    }
+}
+
+// OpenAI-style verbose_json: language by name, words outside the segments
+async fn openai_transcriptions(State(mock): State<Mock>, headers: HeaderMap, body: Bytes) -> Response {
+    let content_type = header(&headers, "content-type").unwrap_or_default();
+    if !content_type.starts_with("multipart/form-data") || header(&headers, "authorization").as_deref() != Some("Bearer test-key") {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    mock.uploads.lock().unwrap().push(ReceivedUpload {
+        content_type,
+        sample_rate: None,
+        channels: None,
+        query: HashMap::new(),
+        bytes: body.len(),
+    });
+    
+    Json(json!({
+        "text": "Hello there. General Kenobi.",
+        "language": "english",
+        "segments": [
+            {"id": 0, "start": 0.0, "end": 1.0, "text": "Hello there."},
+            {"id": 1, "start": 1.0, "end": 2.5, "text": "General Kenobi."},
+        ],
+        "words": [
+            {"word": "Hello", "start": 0.1, "end": 0.4},
+            {"word": "there", "start": 0.5, "end": 0.9},
+            {"word": "General", "start": 1.2, "end": 1.7},
+            {"word": "Kenobi", "start": 1.8, "end": 2.4},
+        ],
+    }))
+    .into_response()
+}
+
+async fn start_mock(behavior: Behavior, transcript: &'static str) -> MockBackend {
+    let uploads = Arc::new(Mutex::new(Vec::new()));
+    let mock = Mock {
+        behavior,
+        transcript,
+        uploads: uploads.clone(),
+    };
+    let app = Router::new()
+        .route("/health", get(health))
+        .route("/models", get(models))
+        .route("/models/cache", get(cached_models))
+        .route("/models/cache/:name", delete(delete_cached))
+        .route("/transcribe_raw", post(transcribe_raw))
+        .route("/v1/audio/transcriptions", post(openai_transcriptions))
+        .with_state(mock);
+    
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    
+    MockBackend { url, uploads }
+}
+
+fn provider(name: &str, kind: ProviderKind, url: &str) -> BackendProvider {
+    BackendProvider {
+        name: name.to_string(),
+        kind,
+        url: url.to_string(),
+        api_key: None,
+        model: None,
+        cloud: false,
+        timeout_secs: default_timeout_secs(),
+        max_audio_seconds_per_day: None,
+        max_cost_per_day: None,
+        cost_per_minute: None,
+        max_clip_seconds: None,
+    }
+}
+
+// Stereo 440 Hz tone in the left channel, surrounded by silence
+fn synthetic_recording(sample_rate: u32) -> RecordedAudio {
+    let silence = vec![0.0f32; sample_rate as usize / 2 * 2];
+    let tone: Vec<f32> = (0..sample_rate as usize)
+        .flat_map(|frame| {
+            let t = frame as f32 / sample_rate as f32;
+            [0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin(), 0.0]
+        })
+        .collect();
+    
+    let mut samples = silence.clone();
+    samples.extend(tone);
+    samples.extend(silence);
+    RecordedAudio {
+        samples,
+        sample_rate,
+        channels: 2,
+        duration: Duration::from_secs(2),
+        dropped_samples: 0,
+    }
+}
+
+#[tokio::test]
+async fn health_and_models_are_parsed() {
+    let backend = start_mock(Behavior::Transcribe, "").await;
+    let client = reqwest::Client::new();
+    
+    let health = check_health(&client, &backend.url).await.unwrap();
+    assert!(health.model_loaded);
+    assert!(health.audio_formats.iter().any(|format| format == PCM_FORMAT));
+    
+    let models = fetch_models(&client, &backend.url).await.unwrap();
+    assert_eq!(models, vec!["tiny", "base"]);
+}
+
+#[tokio::test]
+async fn cached_models_are_listed_and_deleted() {
+    let backend = start_mock(Behavior::Transcribe, "").await;
+    let client = reqwest::Client::new();
+    
+    let cached = fetch_cached_models(&client, &backend.url).await.unwrap();
+    assert_eq!(cached.len(), 2);
+    assert_eq!(cached[1].name, "large-v3");
+    assert_eq!(cached[1].size_bytes, 3_087_371_615);
+    assert!(cached[0].loaded);
+    
+    delete_cached_model(&client, &backend.url, "large-v3").await.unwrap();
+    let error = delete_cached_model(&client, &backend.url, "base").await.unwrap_err();
+    assert!(error.contains("in use"), "{}", error);
+}
+
+#[tokio::test]
+async fn synthetic_audio_is_transcribed_post_processed_and_inserted_in_order() {
+    let backend = start_mock(Behavior::Transcribe, "I owe you twenty five dollars, damn").await;
+    let client = reqwest::Client::new();
+    
+    // Capture processing as in the app: fade in, pick the mic channel, trim silence
+    let recorded = synthetic_recording(16000);
+    let recorded = gate_key_click(recorded, 150);
+    let recorded = map_channels(recorded, InputChannelMode::Channel(1));
+    let recorded = trim_silence(recorded, 0.01, 200).expect("tone should count as speech");
+    assert_eq!(recorded.channels, 1);
+    assert!(recorded.samples.len() < 32000);
+    
+    let upload = UploadAudio {
+        data: convert_to_pcm(&recorded.samples),
+        pcm: Some((recorded.sample_rate, recorded.channels)),
+        opus: false,
+        control: UploadControl::default(),
+    };
+    let local = provider("Local", ProviderKind::Cursper, &backend.url);
+    let decoding = DecodingOptions {
+        temperature: Some(0.0),
+        beam_size: Some(5),
+        best_of: None,
+        no_speech_threshold: Some(0.6),
+    };
+    let response = transcribe_with_provider(&client, &local, upload, Some("en"), true, &decoding, Some("Earlier sentence."))
+        .await
+        .unwrap();
+    
+    let uploads = backend.uploads();
+    assert_eq!(uploads.len(), 1);
+    assert_eq!(uploads[0].content_type, "audio/pcm");
+    assert_eq!(uploads[0].sample_rate.as_deref(), Some("16000"));
+    assert_eq!(uploads[0].channels.as_deref(), Some("1"));
+    assert_eq!(uploads[0].query.get("language").map(String::as_str), Some("en"));
+    assert_eq!(uploads[0].query.get("word_timestamps").map(String::as_str), Some("1"));
+    assert_eq!(uploads[0].query.get("temperature").map(String::as_str), Some("0"));
+    assert_eq!(uploads[0].query.get("beam_size").map(String::as_str), Some("5"));
+    assert_eq!(uploads[0].query.get("no_speech_threshold").map(String::as_str), Some("0.6"));
+    assert!(!uploads[0].query.contains_key("best_of"));
+    assert_eq!(uploads[0].query.get("prompt").map(String::as_str), Some("Earlier sentence."));
+    assert_eq!(uploads[0].bytes, recorded.samples.len() * 2);
+    
+    let parsed = parse_backend_response(&response);
+    assert_eq!(parsed.processing_ms, Some(120));
+    let options = NormalizationOptions {
+        numbers: true,
+        currency: true,
+        dates: true,
+        ..NormalizationOptions::default()
+    };
+    let text = normalize_transcription(&parsed.text, parsed.language.as_deref(), &options);
+    let text = filter_profanity(&text, ProfanityFilter::Mask, &[]);
+    
+    // A second, faster dictation must still be typed after this one
+    let mut sequencer = InsertionSequencer::default();
+    let first = sequencer.issue_ticket();
+    let second = sequencer.issue_ticket();
+    let mut typed: Vec<String> = Vec::new();
+    typed.extend(sequencer.complete(second, Some("Next one.".to_string())));
+    assert!(typed.is_empty());
+    typed.extend(sequencer.complete(first, Some(text)));
+    assert_eq!(typed, vec!["I owe you $25, d***", "Next one."]);
+}
+
+#[tokio::test]
+async fn wav_and_opus_uploads_are_labelled() {
+    let backend = start_mock(Behavior::Transcribe, "hello").await;
+    let recorded = synthetic_recording(8000);
+    let wav = convert_to_wav(&recorded.samples, recorded.sample_rate, recorded.channels).unwrap();
+    
+    let local = provider("Local", ProviderKind::Cursper, &backend.url);
+    let upload = UploadAudio { data: wav, pcm: None, opus: false, control: UploadControl::default() };
+    transcribe_with_provider(&reqwest::Client::new(), &local, upload, None, false, &DecodingOptions::default(), None)
+        .await
+        .unwrap();
+    
+    // Compressed uploads on slow connections
+    let upload = UploadAudio { data: b"OggS".repeat(64), pcm: None, opus: true, control: UploadControl::default() };
+    transcribe_with_provider(&reqwest::Client::new(), &local, upload, None, false, &DecodingOptions::default(), None)
+        .await
+        .unwrap();
+    
+    let uploads = backend.uploads();
+    assert_eq!(uploads[0].content_type, "audio/wav");
+    assert!(uploads[0].sample_rate.is_none());
+    assert!(uploads[0].query.is_empty());
+    assert_eq!(uploads[1].content_type, "audio/ogg");
+}
+
+#[tokio::test]
+async fn long_uploads_report_progress_and_can_be_cancelled() {
+    let backend = start_mock(Behavior::Transcribe, "a long meeting").await;
+    let local = provider("Local", ProviderKind::Cursper, &backend.url);
+    let client = reqwest::Client::new();
+    // A minute of 16 kHz mono, several upload chunks long
+    let pcm = convert_to_pcm(&vec![0.1; 16000 * 60]);
+    
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let sink = reported.clone();
+    let control = UploadControl {
+        progress: Some(Arc::new(move |sent, total| sink.lock().unwrap().push((sent, total)))),
+        cancel: Some(Arc::new(AtomicBool::new(false))),
+    };
+    let upload = UploadAudio { data: pcm.clone(), pcm: Some((16000, 1)), opus: false, control };
+    transcribe_with_provider(&client, &local, upload, None, false, &DecodingOptions::default(), None)
+        .await
+        .unwrap();
+    let reported = reported.lock().unwrap().clone();
+    assert!(reported.len() > 1);
+    assert!(reported.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert_eq!(reported.last(), Some(&(pcm.len() as u64, pcm.len() as u64)));
+    assert_eq!(backend.uploads()[0].bytes, pcm.len());
+    
+    // Cancelling after the first chunk aborts the request instead of falling through to a
+    // network error
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = cancel.clone();
+    let control = UploadControl {
+        progress: Some(Arc::new(move |_, _| flag.store(true, Ordering::SeqCst))),
+        cancel: Some(cancel),
+    };
+    let upload = UploadAudio { data: pcm, pcm: Some((16000, 1)), opus: false, control };
+    let error = transcribe_with_provider(&client, &local, upload, None, false, &DecodingOptions::default(), None)
+        .await
+        .unwrap_err();
+    assert_eq!(error, UPLOAD_CANCELLED_ERROR);
+    assert_eq!(backend.uploads().len(), 1);
+}
+
+#[tokio::test]
+async fn backend_failures_surface_as_errors() {
+    let client = reqwest::Client::new();
+    let wav = || UploadAudio {
+        data: convert_to_wav(&[0.1; 800], 8000, 1).unwrap(),
+        pcm: None,
+        opus: false,
+        control: UploadControl::default(),
+    };
+    
+    let failing = start_mock(Behavior::ServerError, "").await;
+    let error = transcribe_with_provider(&client, &provider("Local", ProviderKind::Cursper, &failing.url), wav(), None, false, &DecodingOptions::default(), None)
+        .await
+        .unwrap_err();
+    assert!(error.contains("returned error 500"), "{}", error);
+    assert!(error.contains("Failed to load Whisper model"), "{}", error);
+    
+    let malformed = start_mock(Behavior::MalformedJson, "").await;
+    let error = transcribe_with_provider(&client, &provider("Local", ProviderKind::Cursper, &malformed.url), wav(), None, false, &DecodingOptions::default(), None)
+        .await
+        .unwrap_err();
+    assert!(error.contains("Failed to parse response"), "{}", error);
+    
+    let slow = start_mock(Behavior::Slow, "").await;
+    let mut impatient = provider("Local", ProviderKind::Cursper, &slow.url);
+    impatient.timeout_secs = 1;
+    let error = transcribe_with_provider(&client, &impatient, wav(), None, false, &DecodingOptions::default(), None)
+        .await
+        .unwrap_err();
+    assert!(error.contains("Failed to send audio"), "{}", error);
+    
+    // Nothing listens on port 9 (discard) on test machines
+    let offline = "http://127.0.0.1:9";
+    assert!(check_health(&client, offline).await.unwrap_err().starts_with("Backend not available"));
+    let error = transcribe_with_provider(&client, &provider("Local", ProviderKind::Cursper, offline), wav(), None, false, &DecodingOptions::default(), None)
+        .await
+        .unwrap_err();
+    assert!(error.contains("Failed to send audio"), "{}", error);
+}
+
+#[tokio::test]
+async fn failover_reaches_an_openai_compatible_provider() {
+    let failing = start_mock(Behavior::ServerError, "").await;
+    let openai = start_mock(Behavior::Transcribe, "").await;
+    let client = reqwest::Client::new();
+    
+    let mut cloud = provider("Cloud", ProviderKind::OpenAi, &openai.url);
+    cloud.api_key = Some("test-key".to_string());
+    cloud.cloud = true;
+    let chain = provider_chain(&failing.url, &[cloud], true);
+    
+    let recorded = synthetic_recording(8000);
+    let wav = convert_to_wav(&recorded.samples, recorded.sample_rate, recorded.channels).unwrap();
+    let mut outcome = None;
+    let mut errors = Vec::new();
+    for provider in &chain {
+        let upload = UploadAudio { data: wav.clone(), pcm: None, opus: false, control: UploadControl::default() };
+        match transcribe_with_provider(&client, provider, upload, None, true, &DecodingOptions::default(), None).await {
+            Ok(response) => {
+                outcome = Some((provider.name.clone(), response));
+                break;
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    
+    let (name, response) = outcome.expect("the cloud fallback should answer");
+    assert_eq!(name, "Cloud");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(openai.uploads().len(), 1);
+    
+    // Language names become codes and words are moved into their segments
+    let parsed = parse_backend_response(&response);
+    assert_eq!(parsed.language.as_deref(), Some("en"));
+    assert_eq!(parsed.model_used.as_deref(), Some("whisper-1"));
+    let words: Vec<usize> = parsed.segments.iter().map(|segment| segment.words.len()).collect();
+    assert_eq!(words, vec![2, 2]);
+}
+
+#[tokio::test]
+async fn openai_providers_refuse_raw_pcm() {
+    let openai = start_mock(Behavior::Transcribe, "").await;
+    let upload = UploadAudio {
+        data: convert_to_pcm(&[0.0; 100]),
+        pcm: Some((16000, 1)),
+        opus: false,
+        control: UploadControl::default(),
+    };
+    let error = transcribe_with_provider(&reqwest::Client::new(), &provider("Cloud", ProviderKind::OpenAi, &openai.url), upload, None, false, &DecodingOptions::default(), None)
+        .await
+        .unwrap_err();
+    assert!(error.contains("only accepts WAV"), "{}", error);
+    assert!(openai.uploads().is_empty());
+}
@@ -31,6 +31,18 @@ current_model: Optional[Any] = None
 current_model_size: str = "base"
 model_lock = threading.Lock()
 
+# Models loaded for requests naming another size (e.g. adaptive model selection), kept
+# warm alongside the current model
+extra_models: Dict[str, Any] = {}
+
+# Upload formats accepted by /transcribe_raw, advertised via /health
+AUDIO_FORMATS = ["wav", "pcm_s16le", "opus"]
+WHISPER_SAMPLE_RATE = 16000
+
+# Where whisper.load_model downloads checkpoints, and when each was last loaded
+MODEL_CACHE_DIR = Path(os.getenv("XDG_CACHE_HOME", Path.home() / ".cache")) / "whisper"
+MODEL_USAGE_FILE = MODEL_CACHE_DIR / "cursper-usage.json"
+
 # Available model sizes
 AVAILABLE_MODELS = {
     "tiny": "Fastest, lowest quality (39 MB)",
@@ -56,6 +68,7 @@ def load_model(model_size):
                 print(f"⏳ Calling whisper.load_model('{model_size}')...")
                 current_model = whisper.load_model(model_size)
                 current_model_size = model_size
+                record_model_usage(model_size)
                 print(f"✅ Model {model_size} loaded successfully")
                 print(f"📋 Model type: {type(current_model)}")
                 return True
@@ -67,6 +80,29 @@ def load_model(model_size):
             print(f"♻️  Model {model_size} already loaded, skipping")
             return True
 
+def read_model_usage():
+    """Last-load times by model name; mtimes stand in for models never loaded by Cursper"""
+    try:
+        return json.loads(MODEL_USAGE_FILE.read_text())
+    except (OSError, ValueError):
+        return {}
+
+def record_model_usage(model_size):
+    """Remember when a model was last loaded"""
+    usage = read_model_usage()
+    usage[model_size] = int(time.time())
+    try:
+        MODEL_CACHE_DIR.mkdir(parents=True, exist_ok=True)
+        MODEL_USAGE_FILE.write_text(json.dumps(usage))
+    except OSError as e:
+        print(f"⚠️ Failed to record model usage: {e}")
+
+def cached_model_files():
+    """Downloaded checkpoints by model name, e.g. {"base": .../base.pt}"""
+    if not MODEL_CACHE_DIR.is_dir():
+        return {}
+    return {path.stem: path for path in MODEL_CACHE_DIR.glob("*.pt") if path.is_file()}
+
 @app.route('/health', methods=['GET'])
 def health_check():
     """Health check endpoint"""
@@ -83,6 +119,7 @@ def health_check():
         "current_model": current_model_size,
         "model_loaded": current_model is not None,
         "available_models": AVAILABLE_MODELS,
+        "audio_formats": AUDIO_FORMATS,
         "timestamp": time.strftime('%Y-%m-%d %H:%M:%S')
     }
     print(f"✅ Health check response: {response}")
@@ -100,6 +137,60 @@ def get_models():
     print(f"✅ Models response: {response}")
     return jsonify(response)
 
+@app.route('/models/cache', methods=['GET'])
+def list_cached_models():
+    """Downloaded models with their size on disk and when they were last used"""
+    print("📦 Cached models requested")
+    usage = read_model_usage()
+    cached = []
+    for name, path in sorted(cached_model_files().items()):
+        stat = path.stat()
+        cached.append({
+            "name": name,
+            "size_bytes": stat.st_size,
+            "last_used": usage.get(name, int(stat.st_mtime)),
+            "loaded": (current_model is not None and name == current_model_size) or name in extra_models,
+        })
+    return jsonify({"cache_dir": str(MODEL_CACHE_DIR), "cached_models": cached})
+
+@app.route('/models/cache/<name>', methods=['DELETE'])
+def delete_cached_model(name):
+    """Delete a downloaded model; it is downloaded again when next selected"""
+    print(f"🗑️ Delete cached model request received for: {name}")
+    # Only names listed in the cache are accepted, so paths can't escape it
+    path = cached_model_files().get(name)
+    if path is None:
+        return jsonify({"error": f"Model {name} is not cached"}), 404
+    
+    with model_lock:
+        if (current_model is not None and name == current_model_size) or name in extra_models:
+            return jsonify({"error": f"Model {name} is in use"}), 409
+        size = path.stat().st_size
+        path.unlink()
+    
+    usage = read_model_usage()
+    if usage.pop(name, None) is not None:
+        try:
+            MODEL_USAGE_FILE.write_text(json.dumps(usage))
+        except OSError as e:
+            print(f"⚠️ Failed to update model usage: {e}")
+    print(f"✅ Deleted cached model {name}, freed {size} bytes")
+    return jsonify({"message": f"Deleted {name}", "freed_bytes": size})
+
+def model_for_request(model_size):
+    """Model to transcribe with: the current one, or the requested size loaded alongside it"""
+    if not model_size or model_size == current_model_size:
+        return current_model, current_model_size
+    
+    with model_lock:
+        if model_size not in extra_models:
+            print(f"🚀 Loading additional Whisper model: {model_size}")
+            extra_models[model_size] = whisper.load_model(model_size)
+            record_model_usage(model_size)
+            print(f"✅ Additional model {model_size} loaded")
+        return extra_models[model_size], model_size
+
+
 @app.route('/set_model', methods=['POST'])
 def set_model():
     """Set the active model"""
@@ -127,6 +218,60 @@ def set_model():
         print(f"❌ {error_msg}")
         return jsonify({"error": error_msg}), 500
 
+@app.route('/load_model', methods=['POST'])
+def load_current_model():
+    """Reload the current model after it was unloaded for being idle"""
+    print(f"⏰ Load model request received for: {current_model_size}")
+    if load_model(current_model_size):
+        return jsonify({"message": f"Model {current_model_size} loaded", "current_model": current_model_size})
+    error_msg = f"Failed to load model {current_model_size}"
+    print(f"❌ {error_msg}")
+    return jsonify({"error": error_msg}), 500
+
+@app.route('/standby_model', methods=['POST'])
+def standby_model():
+    """Keep a second model loaded next to the current one for requests that ask for it"""
+    data = request.get_json() or {}
+    model_size = data.get('model_size')
+    print(f"🔧 Standby model request received: {model_size}")
+    
+    if model_size not in AVAILABLE_MODELS:
+        error_msg = f"Invalid model size. Available: {list(AVAILABLE_MODELS.keys())}"
+        print(f"❌ {error_msg}")
+        return jsonify({"error": error_msg}), 400
+    
+    try:
+        model_for_request(model_size)
+    except Exception as e:
+        error_msg = f"Failed to load standby model {model_size}: {e}"
+        print(f"❌ {error_msg}")
+        return jsonify({"error": error_msg}), 500
+    return jsonify({
+        "message": f"Model {model_size} on standby",
+        "current_model": current_model_size,
+        "standby_models": sorted(extra_models.keys()),
+    })
+
+@app.route('/unload_model', methods=['POST'])
+def unload_model():
+    """Drop the loaded model to free memory while idle"""
+    global current_model
+    print("💤 Unload model request received")
+    
+    with model_lock:
+        if current_model is None and not extra_models:
+            print("♻️  Model already unloaded, skipping")
+        else:
+            current_model = None
+            extra_models.clear()
+            import gc
+            gc.collect()
+            if torch.cuda.is_available():
+                torch.cuda.empty_cache()
+            print(f"✅ Model {current_model_size} unloaded")
+    
+    return jsonify({"message": "Model unloaded", "current_model": current_model_size})
+
 @app.route('/transcribe', methods=['POST'])
 def transcribe_audio():
     """Transcribe audio file to text"""
@@ -179,6 +324,35 @@ def transcribe_audio():
     except Exception as e:
         return jsonify({"error": str(e)}), 500
 
+def decode_pcm(audio_data, sample_rate, channels):
+    """Turn 16-bit little-endian PCM into the 16 kHz mono float32 array Whisper expects"""
+    import numpy as np
+    
+    samples = np.frombuffer(audio_data, dtype='<i2').astype(np.float32) / 32768.0
+    if channels > 1:
+        samples = samples[:len(samples) - len(samples) % channels].reshape(-1, channels).mean(axis=1)
+    if sample_rate != WHISPER_SAMPLE_RATE and len(samples) > 0:
+        target_length = int(len(samples) * WHISPER_SAMPLE_RATE / sample_rate)
+        samples = np.interp(
+            np.linspace(0, len(samples) - 1, target_length),
+            np.arange(len(samples)),
+            samples,
+        ).astype(np.float32)
+    return samples
+
+def decoding_options(args):
+    """Advanced Whisper decoding settings passed as query parameters; absent ones keep Whisper's defaults"""
+    options = {}
+    for name, parse in (('temperature', float), ('beam_size', int), ('best_of', int), ('no_speech_threshold', float)):
+        value = args.get(name)
+        if value is None or value == '':
+            continue
+        try:
+            options[name] = parse(value)
+        except ValueError:
+            print(f"⚠️  Ignoring invalid {name}: {value}")
+    return options
+
 @app.route('/transcribe_raw', methods=['POST'])
 def transcribe_raw_audio():
     """Transcribe raw audio bytes"""
@@ -224,6 +398,24 @@ def transcribe_raw_audio():
             print(f"🔍 Stack trace: {traceback.format_exc()}")
             return jsonify({"error": error_msg}), 400
         
+        # Optional dictation language, auto-detect when omitted
+        language = request.args.get('language') or None
+        print(f"🌍 Requested language: {language or 'auto'}")
+        word_timestamps = request.args.get('word_timestamps') == '1'
+        decode_options = decoding_options(request.args)
+        # Text that came just before this audio, e.g. the previous chunk of a long dictation
+        initial_prompt = request.args.get('prompt') or None
+        # Size to use instead of the current model, e.g. a faster one for a short clip
+        requested_model = request.args.get('model') or None
+        if requested_model and requested_model not in AVAILABLE_MODELS:
+            error_msg = f"Invalid model size. Available: {list(AVAILABLE_MODELS.keys())}"
+            print(f"❌ {error_msg}")
+            return jsonify({"error": error_msg}), 400
+        if initial_prompt:
+            decode_options['initial_prompt'] = initial_prompt
+        if decode_options:
+            print(f"🎛️  Decoding options: {decode_options}")
+        
         if not audio_data:
             error_msg = "No audio data provided in request body"
             print(f"❌ {error_msg}")
@@ -249,44 +441,63 @@ def transcribe_raw_audio():
                 print(f"🎉 Returning test response: {response}")
                 return jsonify(response)
         
-        # Save raw data to temporary file
-        print("💾 Saving audio data to temporary file...")
-        try:
-            with tempfile.NamedTemporaryFile(delete=False, suffix='.wav') as temp_file:
-                temp_file.write(audio_data)
-                temp_path = temp_file.name
-                print(f"📁 Temporary file created: {temp_path}")
+        # Raw PCM is decoded in memory; WAV and Ogg Opus go through a temporary file that
+        # Whisper decodes with ffmpeg
+        temp_path = None
+        audio_input = None
+        if request.content_type == 'audio/pcm':
+            try:
+                sample_rate = int(request.headers.get('X-Sample-Rate', WHISPER_SAMPLE_RATE))
+                channels = int(request.headers.get('X-Channels', 1))
+                audio_input = decode_pcm(audio_data, sample_rate, channels)
+                print(f"🎵 Decoded raw PCM: {sample_rate} Hz, {channels} channels, {len(audio_input)} samples")
+            except Exception as e:
+                error_msg = f"Failed to decode raw PCM: {str(e)}"
+                print(f"❌ {error_msg}")
+                return jsonify({"error": error_msg}), 400
+        else:
+            # Save raw data to temporary file
+            print("💾 Saving audio data to temporary file...")
+            suffix = '.ogg' if request.content_type == 'audio/ogg' else '.wav'
+            try:
+                with tempfile.NamedTemporaryFile(delete=False, suffix=suffix) as temp_file:
+                    temp_file.write(audio_data)
+                    temp_path = temp_file.name
+                    print(f"📁 Temporary file created: {temp_path}")
                 
-            # Verify the file was written correctly
-            import os
-            file_size = os.path.getsize(temp_path)
-            print(f"📊 Temporary file size: {file_size} bytes")
+                # Verify the file was written correctly
+                import os
+                file_size = os.path.getsize(temp_path)
+                print(f"📊 Temporary file size: {file_size} bytes")
             
-            if file_size != len(audio_data):
-                print(f"⚠️  File size mismatch! Expected {len(audio_data)}, got {file_size}")
+                if file_size != len(audio_data):
+                    print(f"⚠️  File size mismatch! Expected {len(audio_data)}, got {file_size}")
             
-        except Exception as e:
-            error_msg = f"Failed to save audio data to temporary file: {str(e)}"
-            print(f"❌ {error_msg}")
-            import traceback
-            print(f"🔍 Stack trace: {traceback.format_exc()}")
-            return jsonify({"error": error_msg}), 500
+            except Exception as e:
+                error_msg = f"Failed to save audio data to temporary file: {str(e)}"
+                print(f"❌ {error_msg}")
+                import traceback
+                print(f"🔍 Stack trace: {traceback.format_exc()}")
+                return jsonify({"error": error_msg}), 500
         
         try:
             # Transcribe with Whisper
+            model, model_size = model_for_request(requested_model)
             print("🧠 Starting Whisper transcription...")
-            print(f"🔍 Using model: {current_model_size}")
-            print(f"🔍 Model object: {current_model}")
+            print(f"🔍 Using model: {model_size}")
+            print(f"🔍 Model object: {model}")
             print(f"🔍 Temp file path: {temp_path}")
             
             with model_lock:
                 print("🔒 Acquired model lock")
-                if current_model is not None:
-                    print(f"🔄 Calling current_model.transcribe('{temp_path}')")
+                if model is not None:
+                    print(f"🔄 Calling model.transcribe('{temp_path}')")
                     
                     # Call Whisper transcription with more detailed error handling
                     try:
-                        result = current_model.transcribe(temp_path)
+                        transcribe_start = time.perf_counter()
+                        result = model.transcribe(audio_input if audio_input is not None else temp_path, language=language, word_timestamps=word_timestamps, **decode_options)
+                        processing_ms = int((time.perf_counter() - transcribe_start) * 1000)
                         print(f"✅ Whisper transcription raw result: {result}")
                         print(f"📝 Result type: {type(result)}")
                         if isinstance(result, dict):
@@ -306,19 +517,39 @@ def transcribe_raw_audio():
             print("🔓 Released model lock")
             
             # Clean up temp file
-            print(f"🗑️  Cleaning up temporary file: {temp_path}")
-            try:
-                os.unlink(temp_path)
-                print("✅ Temporary file cleaned up")
-            except Exception as cleanup_error:
-                print(f"⚠️  Failed to clean up temp file: {cleanup_error}")
+            if temp_path:
+                print(f"🗑️  Cleaning up temporary file: {temp_path}")
+                try:
+                    os.unlink(temp_path)
+                    print("✅ Temporary file cleaned up")
+                except Exception as cleanup_error:
+                    print(f"⚠️  Failed to clean up temp file: {cleanup_error}")
             
             # Process the result
             try:
                 response = {
                     "text": result["text"].strip() if "text" in result else "No text found",
                     "language": result.get("language", "unknown"),
-                    "model_used": current_model_size
+                    "model_used": model_size,
+                    "processing_ms": processing_ms,
+                    "segments": [
+                        {
+                            "id": segment.get("id", index),
+                            "start": segment.get("start", 0.0),
+                            "end": segment.get("end", 0.0),
+                            "text": segment.get("text", "").strip(),
+                            "words": [
+                                {
+                                    "word": word.get("word", "").strip(),
+                                    "start": word.get("start", 0.0),
+                                    "end": word.get("end", 0.0),
+                                    "probability": word.get("probability")
+                                }
+                                for word in segment.get("words", [])
+                            ]
+                        }
+                        for index, segment in enumerate(result.get("segments", []))
+                    ]
                 }
                 print(f"🎉 Transcription successful: {response}")
                 return jsonify(response)
@@ -332,7 +563,7 @@ def transcribe_raw_audio():
             
         except Exception as transcription_error:
             # Clean up temp file on error
-            if os.path.exists(temp_path):
+            if temp_path and os.path.exists(temp_path):
                 print(f"🗑️  Cleaning up temporary file after error: {temp_path}")
                 try:
                     os.unlink(temp_path)
@@ -0,0 +1,232 @@
+use cursper_core::normalize::NormalizationOptions;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use crate::obsidian::OutputSink;
+use crate::text_input::{InsertionMethod, PostInsertAction};
+use crate::text_input::remember_insertion_target;
+use crate::types::AppStateType;
+
+// Preferences applied while dictating into a particular application
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppProfile {
+    // Application name as reported by get_focused_application, matched case-insensitively
+    pub app: String,
+    // Dictation language pinned for this application, overriding the global language
+    #[serde(default)]
+    pub language: Option<String>,
+    // Where dictations into this application go, overriding the global output sink
+    #[serde(default)]
+    pub sink: Option<OutputSink>,
+    // Input device to record from, e.g. a loopback device for meeting apps and the headset
+    // everywhere else, overriding the global input device
+    #[serde(default)]
+    pub input_device: Option<String>,
+    // Whether to type or paste into this application, e.g. paste for one that always runs
+    // with an input method on or remote_desktop for an RDP/VNC client, overriding the
+    // global insertion method
+    #[serde(default)]
+    pub insertion_method: Option<InsertionMethod>,
+    // Pause before typing into this application, e.g. for Electron apps that lose the first
+    // characters typed right after they regain focus, overriding the global delay
+    #[serde(default)]
+    pub pre_insert_delay_ms: Option<u64>,
+    // Keys pressed after inserting into this application, e.g. Enter to send in a chat app
+    // but nothing in the editor, overriding the global actions
+    #[serde(default)]
+    pub post_insert_actions: Option<Vec<PostInsertAction>>,
+    // Number, currency and date rewriting for this application, e.g. none in a code editor,
+    // overriding the global normalization settings
+    #[serde(default)]
+    pub normalization: Option<NormalizationOverride>,
+}
+
+// Normalizations for one application; unset fields keep the global settings
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NormalizationOverride {
+    #[serde(default)]
+    pub numbers: Option<bool>,
+    #[serde(default)]
+    pub currency: Option<bool>,
+    #[serde(default)]
+    pub dates: Option<bool>,
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+// The profile matching an application, if any
+pub fn profile_for_app<'a>(profiles: &'a [AppProfile], app: &str) -> Option<&'a AppProfile> {
+    profiles
+        .iter()
+        .find(|profile| profile.app.trim().eq_ignore_ascii_case(app.trim()))
+}
+
+// Dictation language for the application focused when recording started, falling back
+// to the global setting
+pub fn dictation_language(state: &AppStateType) -> Option<String> {
+    let app_state = match state.lock() {
+        Ok(app_state) => app_state,
+        Err(_) => return None,
+    };
+    let pinned = app_state
+        .focused_app
+        .as_deref()
+        .and_then(|app| profile_for_app(&app_state.settings.app_profiles, app))
+        .and_then(|profile| profile.language.clone());
+    match pinned {
+        Some(language) => {
+            println!("🌍 Using {} for {}", language, app_state.focused_app.as_deref().unwrap_or_default());
+            Some(language)
+        }
+        None => app_state.settings.language.clone(),
+    }
+}
+
+// Normalizations for the application focused when recording started, falling back to
+// the global settings
+pub fn normalization_options(state: &AppStateType) -> NormalizationOptions {
+    let app_state = match state.lock() {
+        Ok(app_state) => app_state,
+        Err(_) => return NormalizationOptions::default(),
+    };
+    let overrides = app_state
+        .focused_app
+        .as_deref()
+        .and_then(|app| profile_for_app(&app_state.settings.app_profiles, app))
+        .and_then(|profile| profile.normalization.as_ref());
+    app_state.settings.normalization_options(overrides)
+}
+
+// Input device for the application focused when recording started, falling back to the
+// global setting
+pub fn recording_device(state: &AppStateType) -> Option<String> {
+    let app_state = state.lock().ok()?;
+    let pinned = app_state
+        .focused_app
+        .as_deref()
+        .and_then(|app| profile_for_app(&app_state.settings.app_profiles, app))
+        .and_then(|profile| profile.input_device.clone());
+    match pinned {
+        Some(device) => {
+            println!("🎙️ Recording from {} for {}", device, app_state.focused_app.as_deref().unwrap_or_default());
+            Some(device)
+        }
+        None => app_state.settings.input_device.clone(),
+    }
+}
+
+// Remember which application is focused as a recording starts, before the overlay shows,
+// along with how to insert into it
+pub async fn remember_focused_app(app_handle: &AppHandle, state: &AppStateType) {
+    let focused_app = tokio::task::spawn_blocking(focused_application)
+        .await
+        .ok()
+        .flatten();
+    println!("🪟 Focused application: {}", focused_app.as_deref().unwrap_or("unknown"));
+    remember_insertion_target(app_handle, focused_app.clone()).await;
+    if let Ok(mut app_state) = state.lock() {
+        app_state.focused_app = focused_app;
+    }
+}
+
+// Name of the frontmost application's process
+#[cfg(target_os = "macos")]
+pub fn focused_application() -> Option<String> {
+    let output = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg("tell application \"System Events\" to get name of first application process whose frontmost is true")
+        .output()
+        .ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !name.is_empty()).then_some(name)
+}
+
+// Executable name (without extension) of the foreground window's process
+#[cfg(target_os = "windows")]
+pub fn focused_application() -> Option<String> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+    
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return None;
+        }
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(window, &mut process_id);
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process_id);
+        if process.is_null() {
+            return None;
+        }
+        
+        let mut path = [0u16; 1024];
+        let mut length = path.len() as u32;
+        let ok = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, path.as_mut_ptr(), &mut length);
+        CloseHandle(process);
+        if ok == 0 {
+            return None;
+        }
+        
+        let path = String::from_utf16_lossy(&path[..length as usize]);
+        std::path::Path::new(&path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+    }
+}
+
+// Window class of the active window, from the compositor on Hyprland or from X11
+#[cfg(target_os = "linux")]
+pub fn focused_application() -> Option<String> {
+    if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+        let output = std::process::Command::new("hyprctl")
+            .args(["activewindow", "-j"])
+            .output()
+            .ok()?;
+        let window: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+        return window["class"].as_str().filter(|class| !class.is_empty()).map(|class| class.to_string());
+    }
+    
+    x11_active_window_class()
+}
+
+#[cfg(target_os = "linux")]
+fn x11_active_window_class() -> Option<String> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+    
+    let (conn, screen_num) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots[screen_num].root;
+    let active_atom = conn.intern_atom(false, b"_NET_ACTIVE_WINDOW").ok()?.reply().ok()?.atom;
+    let window = conn
+        .get_property(false, root, active_atom, AtomEnum::WINDOW, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?
+        .value32()?
+        .next()?;
+    
+    // WM_CLASS holds "instance\0class\0"; the class is the stable application name
+    let class = conn
+        .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)
+        .ok()?
+        .reply()
+        .ok()?
+        .value;
+    class
+        .split(|byte| *byte == 0)
+        .rfind(|part| !part.is_empty())
+        .map(|part| String::from_utf8_lossy(part).to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn focused_application() -> Option<String> {
+    None
+}
+
+// Name of the currently focused application, for creating a profile for it
+#[tauri::command]
+pub async fn get_focused_application() -> Result<Option<String>, String> {
+    tokio::task::spawn_blocking(focused_application)
+        .await
+        .map_err(|e| format!("Failed to read focused application: {}", e))
+}
@@ -1,7 +1,28 @@
-use crate::types::{AppStateType, get_recording_control};
-use std::sync::mpsc;
-use std::thread;
-use std::time::Duration;
+use crate::types::{AppStateType, BackendStatusEvent, RecordingCancelledEvent, SamplesDroppedEvent, SpeechRateEvent, StageTimings, TranscriptionResult, WordTimestampsEvent, TranscriptionAttemptEvent, UploadProgressEvent, AUDIO_SAMPLES_DROPPED_EVENT, BACKEND_STATUS_EVENT, RECORDING_CANCELLED_EVENT, SPEECH_RATE_EVENT, TRANSCRIPTION_ATTEMPT_EVENT, UPLOAD_PROGRESS_EVENT, WORD_TIMESTAMPS_EVENT, recorder};
+use crate::privacy::loggable;
+use crate::recordings::{recording_name, save_recording, should_retain_audio};
+use crate::jobs::{carried_context, carry_over_context, finish_upload, next_job_id, start_upload, store_job_result, FinalizePipeline};
+use crate::providers::{check_budget, compress_uploads, provider_chain, record_upload_throughput, record_usage};
+use crate::settings::Settings;
+use crate::http::{http_client, state_http_client};
+use cursper_core::backend::check_health;
+use cursper_core::audio::{convert_to_pcm, convert_to_wav, gate_key_click, map_channels, pcm_to_wav, trim_silence, EncodedAudio, InputChannelMode, UploadFormat, OPUS_FORMAT, PCM_FORMAT};
+use crate::app_profiles::{dictation_language, recording_device};
+use crate::screen_share::sharing_paused;
+use cursper_core::capture::{input_device_names, CaptureOptions, is_capturing_audio as capture_active};
+use cursper_core::recorder::{RecorderState, SampleSink};
+use cursper_core::providers::{route_adaptive_model, route_model, transcribe_with_provider, DecodingOptions, UploadAudio, UploadControl, UploadProgress, AUTO_MODEL, UPLOAD_CANCELLED_ERROR};
+use cursper_core::opus::encode_ogg_opus;
+use cursper_core::transcript::{compute_speech_rate, parse_backend_response, BackendResponse};
+use tauri::{AppHandle, Emitter, Manager};
+use std::sync::Arc;
+use std::time::Instant;
+
+// Returned when a recording is shorter than min_recording_ms; callers cancel silently
+pub const RECORDING_TOO_SHORT_ERROR: &str = "Recording too short";
+
+// Uploads at least this large (about half a minute of 16 kHz audio) emit progress events
+const UPLOAD_PROGRESS_MIN_BYTES: usize = 1024 * 1024;
 
 // Start recording audio with platform-specific tools
 #[tauri::command]
@@ -20,9 +41,44 @@ pub async fn start_recording(
     Ok(())
 }
 
+// Stop collecting audio without ending the recording, e.g. while taking a phone call
+#[tauri::command]
+pub async fn pause_recording() -> Result<(), String> {
+    if recorder().state() != RecorderState::Recording {
+        return Err("Not recording".to_string());
+    }
+    println!("⏸️ Pausing recording");
+    recorder().pause()
+}
+
+// Continue a recording paused with pause_recording
+#[tauri::command]
+pub async fn resume_recording() -> Result<(), String> {
+    if recorder().state() != RecorderState::Paused {
+        return Err("Recording is not paused".to_string());
+    }
+    println!("▶️ Resuming recording");
+    recorder().resume()
+}
+
+// Whether the microphone is open right now, which is what the OS recording indicator reflects
+#[tauri::command]
+pub fn is_capturing_audio() -> Result<bool, String> {
+    Ok(capture_active())
+}
+
+// Input devices that can be chosen globally or per app profile
+#[tauri::command]
+pub async fn list_input_devices() -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(input_device_names)
+        .await
+        .map_err(|e| format!("Failed to list input devices: {}", e))?
+}
+
 // Stop recording and transcribe with REAL Python backend
 #[tauri::command]
 pub async fn stop_recording_and_transcribe(
+    app_handle: AppHandle,
     state: tauri::State<'_, AppStateType>
 ) -> Result<String, String> {
     println!("🛑 Stopping audio recording...");
@@ -30,221 +86,475 @@ pub async fn stop_recording_and_transcribe(
     let backend_url = {
         let mut app_state = state.lock().map_err(|e| e.to_string())?;
         app_state.is_recording = false;
-        app_state.backend_url.clone()
+        app_state.settings.backend_url.clone()
     };
     
-    stop_recording_and_transcribe_internal(backend_url).await
+    let result = stop_recording_and_transcribe_internal(&app_handle, backend_url).await?;
+    Ok(result.text)
+}
+
+// Emit backend reachability so the UI can show online/offline state
+fn emit_backend_status(app_handle: &AppHandle, backend_url: &str, online: bool, message: Option<String>) {
+    let event = BackendStatusEvent {
+        online,
+        backend_url: backend_url.to_string(),
+        message,
+    };
+    if let Err(e) = app_handle.emit(BACKEND_STATUS_EVENT, event) {
+        println!("⚠️ Failed to emit backend status: {}", e);
+    }
 }
 
 // Internal function for transcription that can be called from shortcut handler
-pub async fn stop_recording_and_transcribe_internal(backend_url: String) -> Result<String, String> {
+pub async fn stop_recording_and_transcribe_internal(app_handle: &AppHandle, backend_url: String) -> Result<TranscriptionResult, String> {
     println!("🎤 stop_recording_and_transcribe_internal called");
     println!("🌐 Backend URL: {}", backend_url);
     
+    // Primary backend followed by configured fallbacks
+    let mut providers = match app_handle.try_state::<AppStateType>() {
+        Some(state) => {
+            let app_state = state.lock().map_err(|e| e.to_string())?;
+            provider_chain(&app_state.settings, &backend_url)
+        }
+        None => provider_chain(&Settings::default(), &backend_url),
+    };
+    let has_fallbacks = providers.len() > 1;
+    
     // Test backend connection first
     println!("🧪 Testing backend connection...");
-    let client = reqwest::Client::new();
-    match client.get(&format!("{}/health", backend_url)).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                println!("✅ Backend is responding");
-                match response.text().await {
-                    Ok(health_text) => println!("📋 Backend health: {}", health_text),
-                    Err(e) => println!("⚠️ Could not read health response: {}", e)
-                }
-            } else {
-                println!("⚠️ Backend responded with status: {}", response.status());
-                return Err(format!("Backend unhealthy: {}", response.status()));
+    let client = match app_handle.try_state::<AppStateType>() {
+        Some(state) => state_http_client(state.inner()),
+        None => http_client(&Settings::default()),
+    };
+    // Older backends don't advertise formats and only take WAV
+    let backend_formats = match check_health(&client, &backend_url).await {
+        Ok(health) => {
+            println!("✅ Backend is responding");
+            emit_backend_status(app_handle, &backend_url, true, None);
+            health.audio_formats
+        }
+        Err(error) => {
+            println!("❌ {}", error);
+            emit_backend_status(app_handle, &backend_url, false, Some(error.clone()));
+            if !has_fallbacks {
+                return Err(error);
             }
-        },
-        Err(e) => {
-            println!("❌ Backend connection failed: {}", e);
-            return Err(format!("Backend not available: {}", e));
+            Vec::new()
         }
-    }
-    
-    // Record audio using CPAL
-    println!("🎙️ Starting audio recording with CPAL...");
-    let audio_data = record_audio_cpal().await?;
+    };
     
-    println!("📤 Sending {} bytes to Python backend...", audio_data.len());
+    // Pick or mix input channels and fade in over the hotkey's key click; with pipelined
+    // finalize this already happens while capturing
+    let (channel_mode, key_click_gate_ms, pipelined_finalize) = match app_handle.try_state::<AppStateType>() {
+        Some(state) => {
+            let app_state = state.lock().map_err(|e| e.to_string())?;
+            (
+                app_state.settings.input_channel,
+                app_state.settings.key_click_gate_ms,
+                app_state.settings.pipelined_finalize,
+            )
+        }
+        None => (InputChannelMode::default(), 0, false),
+    };
+    let pipeline = pipelined_finalize.then(|| FinalizePipeline::start(channel_mode, key_click_gate_ms));
+    
+    // The device is resolved now, so a profile change applies from the next recording
+    let capture_options = match app_handle.try_state::<AppStateType>() {
+        Some(state) => {
+            let monitor_volume = {
+                let app_state = state.lock().map_err(|e| e.to_string())?;
+                app_state.settings.monitor_input.then_some(app_state.settings.monitor_volume)
+            };
+            CaptureOptions {
+                device: recording_device(state.inner()),
+                monitor_volume,
+            }
+        }
+        None => CaptureOptions::default(),
+    };
     
-    // Send to Python backend
-    let response = client
-        .post(&format!("{}/transcribe_raw", backend_url))
-        .header("Content-Type", "application/octet-stream")
-        .body(audio_data)
-        .send()
+    // Record until the recorder is told to stop or cancel
+    println!("🎙️ Starting audio recording with CPAL...");
+    let on_samples: SampleSink = match &pipeline {
+        Some(pipeline) => pipeline.sample_sink(),
+        None => Box::new(|_, _, _| {}),
+    };
+    let recorded = tokio::task::spawn_blocking(move || recorder().record(capture_options, on_samples))
         .await
-        .map_err(|e| format!("Failed to send audio to backend: {}", e))?;
-    
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Backend returned error {}: {}", status, error_text));
+        .map_err(|e| format!("Recording task failed: {}", e))??;
+    let capture_ms = recorded.duration.as_millis() as u64;
+    
+    // Tell the user the clip has gaps, e.g. the machine was too busy to keep up
+    if recorded.dropped_samples > 0 {
+        let samples_per_second = (recorded.sample_rate as u64 * recorded.channels as u64).max(1);
+        let event = SamplesDroppedEvent {
+            dropped_samples: recorded.dropped_samples,
+            dropped_ms: recorded.dropped_samples * 1000 / samples_per_second,
+        };
+        if let Err(e) = app_handle.emit(AUDIO_SAMPLES_DROPPED_EVENT, event) {
+            println!("⚠️ Failed to emit dropped samples: {}", e);
+        }
     }
     
-    // Parse the response
-    let transcription_result: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse backend response: {}", e))?;
+    // A double-tapped shortcut yields a near-empty clip; never send it to the backend
+    let (min_recording_ms, short_recording_toast) = match app_handle.try_state::<AppStateType>() {
+        Some(state) => {
+            let app_state = state.lock().map_err(|e| e.to_string())?;
+            (app_state.settings.min_recording_ms, app_state.settings.short_recording_toast)
+        }
+        None => (0, false),
+    };
+    if capture_ms < min_recording_ms {
+        println!("⏭️ Recording of {} ms is shorter than {} ms, cancelling", capture_ms, min_recording_ms);
+        if short_recording_toast && !sharing_paused(app_handle) {
+            let event = RecordingCancelledEvent {
+                reason: "Recording too short".to_string(),
+                duration_ms: capture_ms,
+            };
+            if let Err(e) = app_handle.emit(RECORDING_CANCELLED_EVENT, event) {
+                println!("⚠️ Failed to emit recording cancellation: {}", e);
+            }
+        }
+        return Err(RECORDING_TOO_SHORT_ERROR.to_string());
+    }
     
-    let transcribed_text = transcription_result
-        .get("text")
-        .and_then(|t| t.as_str())
-        .unwrap_or("No text returned")
-        .to_string();
+    // Cut silent ends so Whisper doesn't hallucinate on them
+    let trim = match app_handle.try_state::<AppStateType>() {
+        Some(state) => {
+            let app_state = state.lock().map_err(|e| e.to_string())?;
+            app_state
+                .settings
+                .trim_silence
+                .then_some((app_state.settings.silence_threshold, app_state.settings.silence_padding_ms))
+        }
+        None => None,
+    };
+    let encoder = match pipeline {
+        Some(pipeline) => pipeline.finish().await,
+        None => None,
+    };
+    let (recorded, pre_encoded) = match encoder {
+        Some(encoder) => {
+            let EncodedAudio { recorded, pcm } = encoder
+                .finish(&recorded, trim)
+                .ok_or_else(|| "No speech detected in recording".to_string())?;
+            (recorded, Some(pcm))
+        }
+        None => {
+            let recorded = gate_key_click(recorded, key_click_gate_ms);
+            let recorded = map_channels(recorded, channel_mode);
+            let recorded = match trim {
+                Some((threshold, padding_ms)) => trim_silence(recorded, threshold, padding_ms)
+                    .ok_or_else(|| "No speech detected in recording".to_string())?,
+                None => recorded,
+            };
+            (recorded, None)
+        }
+    };
     
-    println!("✅ Transcription received: '{}'", transcribed_text);
-    Ok(transcribed_text)
-}
-
-// Record audio using CPAL (Cross-Platform Audio Library)
-async fn record_audio_cpal() -> Result<Vec<u8>, String> {
-    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-    use std::sync::{Arc, Mutex};
-    
-    println!("🎤 Initializing CPAL audio recording...");
-    
-    // Get the default audio host and input device
-    let host = cpal::default_host();
-    let device = host.default_input_device()
-        .ok_or("No input device available")?;
-    
-    println!("🎤 Using audio device: {}", device.name().unwrap_or("Unknown".to_string()));
-    
-    let config = device.default_input_config()
-        .map_err(|e| format!("Failed to get default input config: {}", e))?;
-    
-    let sample_rate = config.sample_rate().0;
-    let channels = config.channels();
-    let sample_format = config.sample_format();
-    
-    println!("🎤 Audio config: {} Hz, {} channels", sample_rate, channels);
-    
-    // Create a channel to collect audio data
-    let (tx, rx) = mpsc::channel::<Vec<f32>>();
-    let tx = Arc::new(Mutex::new(tx));
-    
-    // Create the audio stream
-    let stream = match sample_format {
-        cpal::SampleFormat::F32 => {
-            let tx_clone = tx.clone();
-            device.build_input_stream(
-                &config.into(),
-                move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    if let Ok(sender) = tx_clone.lock() {
-                        let _ = sender.send(data.to_vec());
-                    }
-                },
-                |err| eprintln!("❌ Audio stream error: {}", err),
-                None,
-            )
-        },
-        cpal::SampleFormat::I16 => {
-            let tx_clone = tx.clone();
-            device.build_input_stream(
-                &config.into(),
-                move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                    let f32_data: Vec<f32> = data.iter().map(|&sample| sample as f32 / i16::MAX as f32).collect();
-                    if let Ok(sender) = tx_clone.lock() {
-                        let _ = sender.send(f32_data);
-                    }
-                },
-                |err| eprintln!("❌ Audio stream error: {}", err),
-                None,
+    // Negotiate the upload format with the backend
+    let (upload_format, low_bandwidth_kbps, opus_bitrate_kbps) = match app_handle.try_state::<AppStateType>() {
+        Some(state) => {
+            let app_state = state.lock().map_err(|e| e.to_string())?;
+            (
+                app_state.settings.upload_format,
+                app_state.settings.low_bandwidth_kbps,
+                app_state.settings.low_bandwidth_bitrate_kbps,
             )
-        },
-        _ => return Err("Unsupported sample format".to_string()),
-    }.map_err(|e| format!("Failed to build input stream: {}", e))?;
+        }
+        None => (UploadFormat::Auto, None, 0),
+    };
     
-    // Start recording
-    println!("🎤 Starting audio recording... (will record until stopped or max 30 seconds)");
-    stream.play().map_err(|e| format!("Failed to start audio stream: {}", e))?;
+    // Encode the upload body; on a slow connection it's compressed when the backend can
+    // decode Opus
+    let encode_start = Instant::now();
+    let compress = low_bandwidth_kbps.is_some()
+        && compress_uploads()
+        && backend_formats.iter().any(|format| format == OPUS_FORMAT);
+    let opus_data = if compress {
+        match encode_ogg_opus(&recorded.samples, recorded.sample_rate, recorded.channels, opus_bitrate_kbps * 1000) {
+            Ok(data) => Some(data),
+            Err(e) => {
+                println!("⚠️ {}, uploading uncompressed", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let use_opus = opus_data.is_some();
+    let use_pcm = !use_opus
+        && match upload_format {
+            UploadFormat::Auto => backend_formats.iter().any(|format| format == PCM_FORMAT),
+            UploadFormat::Wav => false,
+            UploadFormat::Pcm => true,
+        };
+    let audio_data = match (opus_data, pre_encoded, use_pcm) {
+        (Some(opus), _, _) => opus,
+        (None, Some(pcm), true) => pcm,
+        (None, Some(pcm), false) => pcm_to_wav(&pcm, recorded.sample_rate, recorded.channels),
+        (None, None, true) => convert_to_pcm(&recorded.samples),
+        (None, None, false) => convert_to_wav(&recorded.samples, recorded.sample_rate, recorded.channels)?,
+    };
+    let encode_ms = encode_start.elapsed().as_millis() as u64;
+    let format_name = if use_opus { "Ogg Opus" } else if use_pcm { "raw PCM" } else { "WAV" };
+    println!("🎵 Encoded as {}: {} bytes", format_name, audio_data.len());
+    
+    // Keep a copy on disk when audio retention is enabled
+    let mut audio_file = None;
+    if should_retain_audio(app_handle) {
+        let wav_data = if use_pcm || use_opus {
+            convert_to_wav(&recorded.samples, recorded.sample_rate, recorded.channels)?
+        } else {
+            audio_data.clone()
+        };
+        match save_recording(app_handle, &wav_data) {
+            Ok(path) => audio_file = path.and_then(|path| recording_name(app_handle, &path)),
+            Err(e) => println!("⚠️ Failed to retain recording: {}", e),
+        }
+    }
     
-    // Collect audio data until recording is stopped or max duration reached
-    let mut all_audio_data = Vec::new();
-    let start_time = std::time::Instant::now();
-    let max_recording_duration = Duration::from_secs(30); // Maximum 30 seconds to prevent infinite recording
+    println!("📤 Sending {} bytes to Python backend...", audio_data.len());
     
-    // Get the global recording control
-    let recording_control = get_recording_control();
+    // Dictation language (omitted for auto-detect), word timing, decoding and live WPM preferences
+    let (requested_language, word_timestamps, decoding, show_live_wpm) = match app_handle.try_state::<AppStateType>() {
+        Some(state) => {
+            let requested_language = dictation_language(state.inner());
+            let app_state = state.lock().map_err(|e| e.to_string())?;
+            (
+                requested_language,
+                app_state.settings.word_timestamps,
+                app_state.settings.decoding.clone(),
+                app_state.settings.show_live_wpm,
+            )
+        }
+        None => (None, false, DecodingOptions::default(), false),
+    };
     
-    // Set recording state to true at the start
-    {
-        let mut should_record = recording_control.lock().unwrap();
-        *should_record = true;
+    // Continue a long dictation split over several recordings from where the last one ended,
+    // so sentences and casing carry across the boundary
+    let (carry_over, focused_app) = match app_handle.try_state::<AppStateType>() {
+        Some(state) => {
+            let app_state = state.lock().map_err(|e| e.to_string())?;
+            (app_state.settings.carry_over_context, app_state.focused_app.clone())
+        }
+        None => (false, None),
+    };
+    let prompt = if carry_over { carried_context(focused_app.as_deref()) } else { None };
+    if let Some(prompt) = &prompt {
+        println!("🧵 Carrying over context: '{}'", loggable(prompt));
     }
     
-    let recording_check_interval = Duration::from_millis(50); // Check more frequently
+    // Try each provider in order until one succeeds
+    let mut attempt_result = None;
+    let mut last_error = String::new();
+    let mut wav_fallback: Option<Vec<u8>> = None;
+    let clip_seconds = recorded.samples.len() as f64 / (recorded.sample_rate as f64 * recorded.channels.max(1) as f64);
+    
+    // A model picked for this dictation wins; otherwise the "auto" model sends short clips
+    // to a fast model and long ones to an accurate one
+    let (dictation_model, adaptive) = match app_handle.try_state::<AppStateType>() {
+        Some(state) => {
+            let app_state = state.lock().map_err(|e| e.to_string())?;
+            (
+                app_state.dictation_model.clone(),
+                (app_state.settings.model == AUTO_MODEL).then(|| app_state.settings.adaptive_model.clone()),
+            )
+        }
+        None => (None, None),
+    };
+    if let Some(model) = dictation_model {
+        println!("🎯 Using {} for this dictation", model);
+        route_model(&mut providers, &model);
+    } else if let Some(adaptive) = adaptive {
+        println!("🧠 Auto model: {} for a {:.1}s clip", adaptive.model_for_clip(clip_seconds), clip_seconds);
+        route_adaptive_model(&mut providers, &adaptive, clip_seconds);
+    }
     
-    while start_time.elapsed() < max_recording_duration {
-        // Check if we should stop recording
-        {
-            let should_record = recording_control.lock().unwrap();
-            if !*should_record {
-                println!("🛑 Recording stopped by user input");
-                break;
+    // The job id is known up front so a long upload can be cancelled with cancel_upload
+    let job_id = next_job_id();
+    let cancel = start_upload(job_id);
+    
+    for (index, provider) in providers.iter().enumerate() {
+        // Only the primary negotiated PCM or Opus; everything else gets WAV
+        let (data, pcm, opus) = if index == 0 {
+            (audio_data.clone(), use_pcm.then_some((recorded.sample_rate, recorded.channels)), use_opus)
+        } else {
+            let wav = match (&wav_fallback, use_pcm || use_opus) {
+                (Some(wav), _) => wav.clone(),
+                (None, true) => match convert_to_wav(&recorded.samples, recorded.sample_rate, recorded.channels) {
+                    Ok(wav) => {
+                        wav_fallback = Some(wav.clone());
+                        wav
+                    }
+                    Err(e) => {
+                        finish_upload(job_id);
+                        return Err(e);
+                    }
+                },
+                (None, false) => audio_data.clone(),
+            };
+            (wav, None, false)
+        };
+        let progress: Option<UploadProgress> = (data.len() >= UPLOAD_PROGRESS_MIN_BYTES).then(|| {
+            let app_handle = app_handle.clone();
+            let provider = provider.name.clone();
+            Arc::new(move |bytes_sent: u64, total_bytes: u64| {
+                let event = UploadProgressEvent {
+                    job_id,
+                    provider: provider.clone(),
+                    bytes_sent,
+                    total_bytes,
+                };
+                if let Err(e) = app_handle.emit(UPLOAD_PROGRESS_EVENT, event) {
+                    println!("⚠️ Failed to emit upload progress: {}", e);
+                }
+            }) as UploadProgress
+        });
+        let uploaded_bytes = data.len();
+        let upload = UploadAudio {
+            data,
+            pcm,
+            opus,
+            control: UploadControl {
+                progress,
+                cancel: Some(cancel.clone()),
+            },
+        };
+        
+        println!("📤 Attempt {}: sending to {} ({})", index + 1, provider.name, provider.url);
+        if let Some(language) = &requested_language {
+            println!("🌍 Requesting language: {}", language);
+        }
+        let request_start = Instant::now();
+        let outcome = match check_budget(app_handle, provider, clip_seconds) {
+            Ok(_) => transcribe_with_provider(&client, provider, upload, requested_language.as_deref(), word_timestamps, &decoding, prompt.as_deref()).await,
+            Err(e) => Err(e),
+        };
+        if outcome.is_ok() {
+            if let Err(e) = record_usage(app_handle, provider, clip_seconds) {
+                println!("⚠️ Failed to record provider usage: {}", e);
             }
         }
+        let next_provider = providers.get(index + 1).map(|next| next.name.clone());
         
-        match rx.try_recv() {
-            Ok(data) => {
-                all_audio_data.extend(data);
-            },
-            Err(mpsc::TryRecvError::Empty) => {
-                thread::sleep(recording_check_interval);
-            },
-            Err(mpsc::TryRecvError::Disconnected) => {
+        let event = TranscriptionAttemptEvent {
+            provider: provider.name.clone(),
+            attempt: index as u32 + 1,
+            success: outcome.is_ok(),
+            error: outcome.as_ref().err().cloned(),
+            next_provider: if outcome.is_ok() { None } else { next_provider.clone() },
+        };
+        if let Err(e) = app_handle.emit(TRANSCRIPTION_ATTEMPT_EVENT, event) {
+            println!("⚠️ Failed to emit transcription attempt: {}", e);
+        }
+        
+        match outcome {
+            Ok(response) => {
+                attempt_result = Some((response, request_start, index == 0, uploaded_bytes));
+                break;
+            }
+            // A cancelled upload isn't retried with the fallbacks
+            Err(e) if e == UPLOAD_CANCELLED_ERROR => {
+                println!("🚫 Upload to {} cancelled", provider.name);
+                last_error = e;
                 break;
             }
+            Err(e) => {
+                match &next_provider {
+                    Some(next) => println!("⚠️ {} failed ({}), falling back to {}", provider.name, e, next),
+                    None => println!("❌ {} failed: {}", provider.name, e),
+                }
+                last_error = e;
+            }
         }
     }
     
-    // Stop the stream
-    drop(stream);
+    finish_upload(job_id);
     
-    let recording_time = start_time.elapsed();
-    println!("🎤 Audio recording completed. Recorded for {:.2} seconds, collected {} samples", 
-             recording_time.as_secs_f64(), all_audio_data.len());
+    let (transcription_result, request_start, from_primary, uploaded_bytes) = match attempt_result {
+        Some(result) => result,
+        None => return Err(last_error),
+    };
+    let request_ms = request_start.elapsed().as_millis() as u64;
+    
+    let BackendResponse {
+        text: transcribed_text,
+        language,
+        model_used,
+        segments,
+        processing_ms: backend_ms,
+    } = parse_backend_response(&transcription_result);
+    
+    // Split round-trip time into backend processing and transfer overhead
+    let upload_ms = request_ms.saturating_sub(backend_ms.unwrap_or(0));
+    
+    // Throughput is only known when the backend reports its processing time
+    if let (true, Some(_), Some(threshold_kbps)) = (from_primary, backend_ms, low_bandwidth_kbps) {
+        record_upload_throughput(app_handle, uploaded_bytes, upload_ms, threshold_kbps);
+    }
     
-    if all_audio_data.is_empty() {
-        return Err("No audio data recorded".to_string());
+    // Publish word timings for karaoke-style highlighting in the overlay
+    if word_timestamps {
+        let words = segments
+            .iter()
+            .flat_map(|segment| segment.words.iter().cloned())
+            .collect::<Vec<_>>();
+        println!("🕒 Received {} word timestamps", words.len());
+        if let Err(e) = app_handle.emit(WORD_TIMESTAMPS_EVENT, WordTimestampsEvent { words }) {
+            println!("⚠️ Failed to emit word timestamps: {}", e);
+        }
     }
     
-    // Convert to WAV format
-    let wav_data = convert_to_wav(&all_audio_data, sample_rate, channels)?;
-    println!("🎵 Converted to WAV format: {} bytes", wav_data.len());
+    println!("✅ Transcription received: '{}'", loggable(&transcribed_text));
+    let timings = StageTimings {
+        capture_ms,
+        encode_ms,
+        upload_ms,
+        backend_ms,
+        post_process_ms: None,
+        insert_ms: None,
+    };
+    println!(
+        "⏱️ Timings: capture {}ms, encode {}ms, upload {}ms, backend {}ms",
+        timings.capture_ms,
+        timings.encode_ms,
+        timings.upload_ms,
+        timings.backend_ms.map(|ms| ms.to_string()).unwrap_or_else(|| "?".to_string())
+    );
+    
+    let speech_rate = compute_speech_rate(&transcribed_text, &segments, capture_ms);
+    println!(
+        "🗣️ Speech rate: {:.0} wpm, {:.0}% speaking",
+        speech_rate.words_per_minute,
+        speech_rate.speaking_ratio * 100.0
+    );
+    
+    let result = TranscriptionResult {
+        job_id,
+        text: transcribed_text,
+        language,
+        model_used,
+        segments,
+        timings,
+        speech_rate,
+        audio_file,
+    };
     
-    Ok(wav_data)
-}
-
-// Convert audio samples to WAV format
-fn convert_to_wav(samples: &[f32], sample_rate: u32, channels: u16) -> Result<Vec<u8>, String> {
-    use std::io::Cursor;
-    use hound::{WavWriter, WavSpec};
-    
-    let spec = WavSpec {
-        channels,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
-    };
-    
-    let mut cursor = Cursor::new(Vec::new());
-    let mut writer = WavWriter::new(&mut cursor, spec)
-        .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
-    
-    // Convert f32 samples to i16 and write
-    for &sample in samples {
-        let sample_i16 = (sample * i16::MAX as f32) as i16;
-        writer.write_sample(sample_i16)
-            .map_err(|e| format!("Failed to write sample: {}", e))?;
+    if show_live_wpm {
+        let payload = SpeechRateEvent {
+            job_id: result.job_id,
+            speech_rate: result.speech_rate.clone(),
+        };
+        if let Err(e) = app_handle.emit(SPEECH_RATE_EVENT, payload) {
+            println!("⚠️ Failed to emit speech rate: {}", e);
+        }
     }
     
-    writer.finalize()
-        .map_err(|e| format!("Failed to finalize WAV: {}", e))?;
-    
-    Ok(cursor.into_inner())
-} 
\ No newline at end of file
+    // Keep the result around so segments can be re-inserted selectively
+    store_job_result(&result);
+    if carry_over {
+        carry_over_context(focused_app, &result.text);
+    }
+    Ok(result)
+}
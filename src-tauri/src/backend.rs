@@ -1,23 +1,121 @@
-use crate::types::AppStateType;
+use crate::types::{AppStateType, BackendSetupEvent, LanguageChangedEvent, ModelStatusEvent, BACKEND_SETUP_EVENT, LANGUAGE_CHANGED_EVENT, MODEL_LOADING_EVENT, MODEL_READY_EVENT, MODEL_UNLOADED_EVENT};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::settings::save_settings;
+use crate::http::state_http_client;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use cursper_core::providers::AUTO_MODEL;
+use cursper_core::backend::{check_health, delete_cached_model as delete_from_backend_cache, fetch_cached_models, fetch_models, CachedModel};
+use serde::Serialize;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use crate::portable::app_data_dir;
+
+pub use cursper_core::languages::DICTATION_LANGUAGES;
+
+// How often the idle monitor checks whether the model should be unloaded
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+// Whisper model sizes offered by the Python backend
+pub const WHISPER_MODELS: &[&str] = &["tiny", "base", "small", "medium", "large", AUTO_MODEL];
+
+// How many recently used languages are pinned in the tray
+const MAX_RECENT_LANGUAGES: usize = 3;
+
+// Interpreter names tried in order when looking for Python
+const PYTHON_CANDIDATES: &[&str] = &["python3", "py", "python"];
+
+// Backend entry point, bundled as a resource and relative to the repository root in
+// development
+const BACKEND_SCRIPT: &str = "python/app.py";
+
+// Modules the backend imports, with the pip package providing each
+const REQUIRED_PACKAGES: &[(&str, &str)] = &[
+    ("flask", "flask"),
+    ("flask_cors", "flask-cors"),
+    ("whisper", "openai-whisper"),
+    ("torch", "torch"),
+    ("numpy", "numpy"),
+];
+
+// Pinned backend requirements installed into the managed virtualenv
+const PINNED_REQUIREMENTS: &str = include_str!("../../python/requirements-lock.txt");
+
+// Set while setup_backend_environment runs so a second click doesn't race it
+static BACKEND_SETUP_RUNNING: AtomicBool = AtomicBool::new(false);
+
+// A Python interpreter found on PATH
+#[derive(Clone, Serialize)]
+pub struct PythonInfo {
+    pub command: String,
+    pub version: String,
+    // CPU architecture the interpreter was built for, e.g. "x86_64" under Rosetta
+    pub architecture: String,
+}
+
+// Whether a backend dependency can be imported
+#[derive(Clone, Serialize)]
+pub struct PackageStatus {
+    pub module: String,
+    pub package: String,
+    pub installed: bool,
+    pub version: Option<String>,
+    pub error: Option<String>,
+}
+
+// What the backend needs to start, and what to do about anything missing
+#[derive(Clone, Serialize)]
+pub struct BackendEnvironment {
+    pub os: String,
+    pub architecture: String,
+    pub python: Option<PythonInfo>,
+    pub packages: Vec<PackageStatus>,
+    pub script_found: bool,
+    pub backend_url: String,
+    pub port: Option<u16>,
+    pub port_in_use: bool,
+    pub backend_running: bool,
+    pub suggestions: Vec<String>,
+}
 
 // Set Whisper model
 #[tauri::command]
-pub async fn set_whisper_model(model: String, state: tauri::State<'_, AppStateType>) -> Result<(), String> {
+pub async fn set_whisper_model(app_handle: AppHandle, model: String, state: tauri::State<'_, AppStateType>) -> Result<(), String> {
+    set_model_internal(&app_handle, state.inner(), model).await
+}
+
+// Persist the model choice and switch the backend to it
+pub async fn set_model_internal(app_handle: &AppHandle, state: &AppStateType, model: String) -> Result<(), String> {
     println!("🔄 Setting Whisper model to: {}", model);
     
     // Update app state
-    {
+    let settings = {
         let mut app_state = state.lock().map_err(|e| e.to_string())?;
-        app_state.current_model = model.clone();
+        app_state.settings.model = model.clone();
+        app_state.settings.clone()
+    };
+    
+    if let Err(e) = save_settings(app_handle, &settings) {
+        println!("⚠️ Failed to persist model choice: {}", e);
     }
     
-    // Send model change to backend
-    let backend_url = {
+    send_model_to_backend(app_handle, state, model).await
+}
+
+// Ask the backend to switch to the given model
+pub async fn send_model_to_backend(app_handle: &AppHandle, state: &AppStateType, model: String) -> Result<(), String> {
+    let (backend_url, adaptive) = {
         let app_state = state.lock().map_err(|e| e.to_string())?;
-        app_state.backend_url.clone()
+        (app_state.settings.backend_url.clone(), app_state.settings.adaptive_model.clone())
     };
     
-    let client = reqwest::Client::new();
+    // "auto" keeps the fast model for short clips loaded; the backend loads the long one
+    // alongside it on first use
+    let model = if model == AUTO_MODEL { adaptive.short_model } else { model };
+    
+    let client = state_http_client(state);
     let response = client
         .post(&format!("{}/set_model", backend_url))
         .json(&serde_json::json!({ "model": model }))
@@ -27,6 +125,15 @@ pub async fn set_whisper_model(model: String, state: tauri::State<'_, AppStateTy
     
     if response.status().is_success() {
         println!("✅ Model set successfully");
+        {
+            let mut app_state = state.lock().map_err(|e| e.to_string())?;
+            app_state.model_sleeping = false;
+            app_state.last_model_activity = Instant::now();
+        }
+        emit_model_status(app_handle, MODEL_READY_EVENT, &model);
+        if let Err(e) = load_standby_model(state).await {
+            println!("⚠️ {}", e);
+        }
         Ok(())
     } else {
         let error = format!("Backend returned error: {}", response.status());
@@ -35,6 +142,78 @@ pub async fn set_whisper_model(model: String, state: tauri::State<'_, AppStateTy
     }
 }
 
+// Keep accurate_model loaded next to the current model so dictations started with the
+// accurate shortcut don't wait for it to load
+pub async fn load_standby_model(state: &AppStateType) -> Result<(), String> {
+    let (backend_url, model, standby) = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        (
+            app_state.settings.backend_url.clone(),
+            app_state.settings.model.clone(),
+            app_state.settings.accurate_model.clone(),
+        )
+    };
+    let standby = match standby {
+        Some(standby) if !standby.trim().is_empty() && standby != model => standby,
+        _ => return Ok(()),
+    };
+    
+    println!("🔧 Loading standby model: {}", standby);
+    let client = state_http_client(state);
+    let response = client
+        .post(&format!("{}/standby_model", backend_url))
+        .json(&serde_json::json!({ "model_size": standby }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to load standby model: {}", e))?;
+    
+    if !response.status().is_success() {
+        return Err(format!("Backend failed to load standby model {}: {}", standby, response.status()));
+    }
+    println!("✅ Standby model {} loaded", standby);
+    Ok(())
+}
+
+// Set dictation language (None = auto-detect)
+#[tauri::command]
+pub async fn set_language(app_handle: AppHandle, language: Option<String>, state: tauri::State<'_, AppStateType>) -> Result<(), String> {
+    set_language_internal(&app_handle, state.inner(), language)
+}
+
+// Persist the dictation language and remember it as recently used
+pub fn set_language_internal(app_handle: &AppHandle, state: &AppStateType, language: Option<String>) -> Result<(), String> {
+    println!("🌍 Setting dictation language to: {}", language.as_deref().unwrap_or("auto"));
+    
+    let settings = {
+        let mut app_state = state.lock().map_err(|e| e.to_string())?;
+        if let Some(code) = &language {
+            app_state.settings.recent_languages.retain(|recent| recent != code);
+            app_state.settings.recent_languages.insert(0, code.clone());
+            app_state.settings.recent_languages.truncate(MAX_RECENT_LANGUAGES);
+        }
+        app_state.settings.language = language.clone();
+        app_state.settings.clone()
+    };
+    
+    save_settings(app_handle, &settings)?;
+    
+    if let Err(e) = app_handle.emit(LANGUAGE_CHANGED_EVENT, LanguageChangedEvent { language }) {
+        println!("⚠️ Failed to emit language change: {}", e);
+    }
+    
+    println!("✅ Language set successfully");
+    Ok(())
+}
+
+// Display name for a language code
+pub fn language_name(code: &str) -> String {
+    DICTATION_LANGUAGES
+        .iter()
+        .find(|(language_code, _)| *language_code == code)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| code.to_string())
+}
+
 // Get available models
 #[tauri::command]
 pub async fn get_available_models(state: tauri::State<'_, AppStateType>) -> Result<Vec<String>, String> {
@@ -42,40 +221,84 @@ pub async fn get_available_models(state: tauri::State<'_, AppStateType>) -> Resu
     
     let backend_url = {
         let app_state = state.lock().map_err(|e| e.to_string())?;
-        app_state.backend_url.clone()
+        app_state.settings.backend_url.clone()
     };
     
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&format!("{}/models", backend_url))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to get models from backend: {}", e))?;
+    let client = state_http_client(&state);
+    match fetch_models(&client, &backend_url).await {
+        Ok(models) => {
+            println!("✅ Available models: {:?}", models);
+            Ok(models)
+        }
+        Err(error) => {
+            println!("❌ {}", error);
+            Err(error)
+        }
+    }
+}
+
+// Downloaded models with their size on disk and last-use time, so unused ones can be removed
+#[tauri::command]
+pub async fn list_cached_models(state: tauri::State<'_, AppStateType>) -> Result<Vec<CachedModel>, String> {
+    let backend_url = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.backend_url.clone()
+    };
     
-    if response.status().is_success() {
-        let models: Vec<String> = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse models response: {}", e))?;
-        
-        println!("✅ Available models: {:?}", models);
-        Ok(models)
-    } else {
-        let error = format!("Backend returned error: {}", response.status());
-        println!("❌ {}", error);
-        Err(error)
+    let client = state_http_client(&state);
+    fetch_cached_models(&client, &backend_url).await
+}
+
+// Delete a downloaded model to reclaim disk space; the backend refuses the loaded one
+#[tauri::command]
+pub async fn delete_cached_model(name: String, state: tauri::State<'_, AppStateType>) -> Result<(), String> {
+    println!("🗑️ Deleting cached model: {}", name);
+    
+    let backend_url = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.backend_url.clone()
+    };
+    
+    let client = state_http_client(&state);
+    match delete_from_backend_cache(&client, &backend_url, &name).await {
+        Ok(_) => {
+            println!("✅ Deleted cached model {}", name);
+            Ok(())
+        }
+        Err(error) => {
+            println!("❌ {}", error);
+            Err(error)
+        }
     }
 }
 
 // Start backend server
 #[tauri::command]
-pub async fn start_backend() -> Result<(), String> {
+pub async fn start_backend(app_handle: AppHandle) -> Result<(), String> {
     println!("🐍 Starting Python backend...");
     
-    let output = std::process::Command::new("python3")
-        .arg("python/app.py")
+    let script = backend_script(&app_handle)?;
+    let python = match configured_python(&app_handle) {
+        Some(python) => python,
+        None => find_python()
+            .await
+            .map(|python| python.command)
+            .ok_or_else(|| format!("Failed to start backend: no Python interpreter found (tried {})", PYTHON_CANDIDATES.join(", ")))?,
+    };
+    
+    let mut command = std::process::Command::new(&python);
+    command.arg(&script);
+    if let Some(dir) = script.parent() {
+        command.current_dir(dir);
+    }
+    // Listen where the settings point, so each user on a shared machine can run their own
+    // backend on their own port
+    if let Some(port) = backend_port(&app_handle) {
+        command.arg("--port").arg(port.to_string());
+    }
+    let output = command
         .spawn()
-        .map_err(|e| format!("Failed to start backend: {}", e))?;
+        .map_err(|e| format!("Failed to start backend with {}: {}", python, e))?;
     
     println!("✅ Backend started with PID: {}", output.id());
     
@@ -83,4 +306,443 @@ pub async fn start_backend() -> Result<(), String> {
     tokio::time::sleep(std::time::Duration::from_secs(2)).await;
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+// Map the names Python and Rust use for the same CPU architecture onto one
+fn normalize_architecture(architecture: &str) -> String {
+    match architecture.trim().to_lowercase().as_str() {
+        "amd64" | "x64" => "x86_64".to_string(),
+        "arm64" => "aarch64".to_string(),
+        other => other.to_string(),
+    }
+}
+
+// Run an interpreter with a Python snippet, returning trimmed stdout or the last stderr line
+async fn run_python(command: &str, code: &str) -> Result<String, String> {
+    let output = tokio::process::Command::new(command)
+        .arg("-c")
+        .arg(code)
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(stderr.lines().last().unwrap_or("exited with an error").trim().to_string())
+    }
+}
+
+// Version and architecture of an interpreter, if it runs and is Python 3
+async fn probe_python(command: &str) -> Option<PythonInfo> {
+    let probe = "import sys, platform; print(sys.version.split()[0]); print(platform.machine())";
+    match run_python(command, probe).await {
+        Ok(output) => {
+            let mut lines = output.lines();
+            let version = lines.next().unwrap_or_default().to_string();
+            // The Windows "py" launcher can resolve to Python 2
+            if !version.starts_with('3') {
+                println!("⚠️ Skipping {}: Python {} is too old", command, version);
+                return None;
+            }
+            Some(PythonInfo {
+                command: command.to_string(),
+                version,
+                architecture: normalize_architecture(lines.next().unwrap_or_default()),
+            })
+        }
+        Err(e) => {
+            println!("🔍 {} not usable: {}", command, e);
+            None
+        }
+    }
+}
+
+// First interpreter on PATH that runs Python 3
+async fn find_python() -> Option<PythonInfo> {
+    for command in PYTHON_CANDIDATES {
+        if let Some(python) = probe_python(command).await {
+            return Some(python);
+        }
+    }
+    None
+}
+
+// Locate app.py regardless of the working directory: the bundled copy in installed builds,
+// the source tree in development
+fn backend_script(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let bundled = app_handle.path().resolve(BACKEND_SCRIPT, BaseDirectory::Resource).ok();
+    let source_tree = Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join(BACKEND_SCRIPT);
+    bundled
+        .into_iter()
+        .chain([source_tree])
+        .find(|path| path.exists())
+        .ok_or_else(|| format!("{} is missing from this installation", BACKEND_SCRIPT))
+}
+
+// Port of the configured backend URL
+fn backend_port(app_handle: &AppHandle) -> Option<u16> {
+    let backend_url = app_handle
+        .try_state::<AppStateType>()?
+        .lock()
+        .ok()?
+        .settings
+        .backend_url
+        .clone();
+    reqwest::Url::parse(&backend_url).ok()?.port_or_known_default()
+}
+
+// Interpreter chosen in settings, if it still exists
+fn configured_python(app_handle: &AppHandle) -> Option<String> {
+    let configured = match app_handle.try_state::<AppStateType>() {
+        Some(state) => state
+            .lock()
+            .map(|app_state| app_state.settings.backend_python.clone())
+            .unwrap_or_default(),
+        None => None,
+    };
+    configured.filter(|python| Path::new(python).exists())
+}
+
+// Try importing a backend dependency and read its version
+async fn check_package(python: &str, module: &str, package: &str) -> PackageStatus {
+    let code = format!("import {0}; print(getattr({0}, '__version__', ''))", module);
+    let result = run_python(python, &code).await;
+    PackageStatus {
+        module: module.to_string(),
+        package: package.to_string(),
+        installed: result.is_ok(),
+        version: result.as_ref().ok().filter(|version| !version.is_empty()).cloned(),
+        error: result.err(),
+    }
+}
+
+// Probe for Python, the backend's packages and its port, and suggest fixes for whatever
+// would stop start_backend from working
+#[tauri::command]
+pub async fn detect_backend_environment(app_handle: AppHandle, state: tauri::State<'_, AppStateType>) -> Result<BackendEnvironment, String> {
+    println!("🔍 Detecting backend environment...");
+    
+    let backend_url = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.backend_url.clone()
+    };
+    let architecture = normalize_architecture(std::env::consts::ARCH);
+    let mut suggestions = Vec::new();
+    
+    let python = match configured_python(&app_handle) {
+        Some(configured) => match probe_python(&configured).await {
+            Some(python) => Some(python),
+            None => find_python().await,
+        },
+        None => find_python().await,
+    };
+    let mut packages = Vec::new();
+    match &python {
+        Some(python) => {
+            println!("🐍 Found {} (Python {}, {})", python.command, python.version, python.architecture);
+            for (module, package) in REQUIRED_PACKAGES {
+                packages.push(check_package(&python.command, module, package).await);
+            }
+            
+            let missing: Vec<&str> = packages
+                .iter()
+                .filter(|status| !status.installed)
+                .map(|status| status.package.as_str())
+                .collect();
+            if !missing.is_empty() {
+                suggestions.push(format!(
+                    "Install the missing packages: {} -m pip install {} (or -r python/requirements.txt)",
+                    python.command,
+                    missing.join(" ")
+                ));
+            }
+            
+            if python.architecture != architecture {
+                suggestions.push(format!(
+                    "Python is built for {} but this machine runs {}; install a native {} Python so torch uses the right wheels",
+                    python.architecture, architecture, architecture
+                ));
+            }
+        }
+        None => suggestions.push(format!(
+            "No Python 3 interpreter found (tried {}); install Python 3 and make sure it is on PATH",
+            PYTHON_CANDIDATES.join(", ")
+        )),
+    }
+    
+    let script_found = match backend_script(&app_handle) {
+        Ok(_) => true,
+        Err(e) => {
+            suggestions.push(format!("{}; reinstall Cursper", e));
+            false
+        }
+    };
+    
+    let port = reqwest::Url::parse(&backend_url)
+        .ok()
+        .and_then(|url| url.port_or_known_default());
+    let client = state_http_client(&state);
+    let backend_running = check_health(&client, &backend_url).await.is_ok();
+    let port_in_use = match port {
+        Some(port) => std::net::TcpListener::bind(("127.0.0.1", port)).is_err(),
+        None => false,
+    };
+    match port {
+        Some(port) if port_in_use && !backend_running => suggestions.push(format!(
+            "Port {} is taken by another program; stop it or point the backend URL at a free port",
+            port
+        )),
+        Some(_) => {}
+        None => suggestions.push(format!("Backend URL '{}' has no usable port", backend_url)),
+    }
+    
+    println!("✅ Backend environment checked ({} suggestions)", suggestions.len());
+    Ok(BackendEnvironment {
+        os: std::env::consts::OS.to_string(),
+        architecture,
+        python,
+        packages,
+        script_found,
+        backend_url,
+        port,
+        port_in_use,
+        backend_running,
+        suggestions,
+    })
+}
+
+// Where the managed backend virtualenv lives
+fn backend_venv_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app_data_dir(app_handle)?;
+    Ok(data_dir.join("backend-venv"))
+}
+
+// Interpreter inside a virtualenv
+fn venv_python(venv_dir: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv_dir.join("Scripts").join("python.exe")
+    } else {
+        venv_dir.join("bin").join("python")
+    }
+}
+
+fn emit_setup_progress(app_handle: &AppHandle, stage: &str, message: &str, progress: f32) {
+    let payload = BackendSetupEvent {
+        stage: stage.to_string(),
+        message: message.to_string(),
+        progress,
+    };
+    if let Err(e) = app_handle.emit(BACKEND_SETUP_EVENT, payload) {
+        println!("⚠️ Failed to emit backend setup progress: {}", e);
+    }
+}
+
+// Run a setup step, forwarding each line it prints as a progress event
+async fn run_setup_step(app_handle: &AppHandle, stage: &str, progress: f32, program: &Path, args: &[&str]) -> Result<(), String> {
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program.display(), e))?;
+    
+    // Drain stderr alongside stdout so neither pipe fills up and blocks the child
+    let stderr = child.stderr.take();
+    let stderr_task = tokio::spawn(async move {
+        let mut text = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_string(&mut text).await;
+        }
+        text
+    });
+    
+    if let Some(stdout) = child.stdout.take() {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            emit_setup_progress(app_handle, stage, &line, progress);
+        }
+    }
+    
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("Failed to wait for {}: {}", program.display(), e))?;
+    let stderr = stderr_task.await.unwrap_or_default();
+    if status.success() {
+        Ok(())
+    } else {
+        let reason = stderr.lines().last().unwrap_or("exited with an error").trim().to_string();
+        Err(format!("{} failed: {}", stage, reason))
+    }
+}
+
+async fn setup_backend_environment_internal(app_handle: &AppHandle, state: &AppStateType) -> Result<String, String> {
+    let python = find_python()
+        .await
+        .ok_or_else(|| format!("No Python interpreter found (tried {})", PYTHON_CANDIDATES.join(", ")))?;
+    let venv_dir = backend_venv_dir(app_handle)?;
+    let python_path = venv_python(&venv_dir);
+    
+    if !python_path.exists() {
+        println!("🐍 Creating backend virtualenv in {}", venv_dir.display());
+        emit_setup_progress(app_handle, "venv", "Creating virtual environment", 0.05);
+        let venv_arg = venv_dir.to_string_lossy().to_string();
+        run_setup_step(app_handle, "venv", 0.05, Path::new(&python.command), &["-m", "venv", &venv_arg]).await?;
+    }
+    
+    let requirements_path = venv_dir.join("requirements-lock.txt");
+    std::fs::write(&requirements_path, PINNED_REQUIREMENTS)
+        .map_err(|e| format!("Failed to write backend requirements: {}", e))?;
+    
+    println!("📦 Installing pinned backend requirements");
+    emit_setup_progress(app_handle, "install", "Installing backend packages (this can take a while)", 0.2);
+    let requirements_arg = requirements_path.to_string_lossy().to_string();
+    run_setup_step(
+        app_handle,
+        "install",
+        0.2,
+        &python_path,
+        &["-m", "pip", "install", "--disable-pip-version-check", "-r", &requirements_arg],
+    )
+    .await?;
+    
+    let python_path = python_path.to_string_lossy().to_string();
+    let settings = {
+        let mut app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.backend_python = Some(python_path.clone());
+        app_state.settings.clone()
+    };
+    save_settings(app_handle, &settings)?;
+    Ok(python_path)
+}
+
+// Create a virtualenv in the app data dir, install the pinned backend requirements into
+// it and make start_backend use it; returns the interpreter path
+#[tauri::command]
+pub async fn setup_backend_environment(app_handle: AppHandle, state: tauri::State<'_, AppStateType>) -> Result<String, String> {
+    if BACKEND_SETUP_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("Backend setup is already running".to_string());
+    }
+    
+    println!("🛠️ Setting up backend environment...");
+    let result = setup_backend_environment_internal(&app_handle, state.inner()).await;
+    BACKEND_SETUP_RUNNING.store(false, Ordering::SeqCst);
+    
+    match &result {
+        Ok(python) => {
+            println!("✅ Backend environment ready: {}", python);
+            emit_setup_progress(&app_handle, "done", "Backend environment ready", 1.0);
+        }
+        Err(e) => {
+            println!("❌ Backend setup failed: {}", e);
+            emit_setup_progress(&app_handle, "failed", e, 1.0);
+        }
+    }
+    result
+}
+
+// Emit a model lifecycle event so the UI can show "model sleeping" / loading states
+fn emit_model_status(app_handle: &AppHandle, event: &str, model: &str) {
+    let payload = ModelStatusEvent { model: model.to_string() };
+    if let Err(e) = app_handle.emit(event, payload) {
+        println!("⚠️ Failed to emit {}: {}", event, e);
+    }
+}
+
+// Record that the model was just used, postponing idle unload
+pub fn touch_model_activity(state: &AppStateType) {
+    if let Ok(mut app_state) = state.lock() {
+        app_state.last_model_activity = Instant::now();
+    }
+}
+
+// Reload the model if it was unloaded for being idle
+pub async fn wake_model_if_sleeping(app_handle: &AppHandle, state: &AppStateType) -> Result<(), String> {
+    let (sleeping, model, backend_url) = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        (app_state.model_sleeping, app_state.settings.model.clone(), app_state.settings.backend_url.clone())
+    };
+    
+    touch_model_activity(state);
+    
+    if !sleeping {
+        return Ok(());
+    }
+    
+    println!("⏰ Waking sleeping model: {}", model);
+    emit_model_status(app_handle, MODEL_LOADING_EVENT, &model);
+    
+    let client = state_http_client(state);
+    let response = client
+        .post(&format!("{}/load_model", backend_url))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to ask backend to reload model: {}", e))?;
+    
+    if !response.status().is_success() {
+        let error = format!("Backend failed to reload model: {}", response.status());
+        println!("❌ {}", error);
+        return Err(error);
+    }
+    
+    {
+        let mut app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.model_sleeping = false;
+        app_state.last_model_activity = Instant::now();
+    }
+    
+    println!("✅ Model reloaded");
+    emit_model_status(app_handle, MODEL_READY_EVENT, &model);
+    if let Err(e) = load_standby_model(state).await {
+        println!("⚠️ {}", e);
+    }
+    Ok(())
+}
+
+// Periodically unload the backend model after the configured idle time
+pub fn spawn_idle_unload_monitor(app_handle: AppHandle, state: AppStateType) {
+    tauri::async_runtime::spawn(async move {
+        println!("💤 Idle model unload monitor started");
+        
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            
+            let (should_unload, model, backend_url) = {
+                let app_state = match state.lock() {
+                    Ok(app_state) => app_state,
+                    Err(e) => {
+                        println!("❌ Idle monitor failed to lock app state: {}", e);
+                        continue;
+                    }
+                };
+                let idle_minutes = app_state.settings.model_idle_unload_minutes;
+                let idle_for = app_state.last_model_activity.elapsed();
+                let should_unload = idle_minutes > 0
+                    && !app_state.model_sleeping
+                    && !app_state.is_recording
+                    && idle_for >= Duration::from_secs(idle_minutes * 60);
+                (should_unload, app_state.settings.model.clone(), app_state.settings.backend_url.clone())
+            };
+            
+            if !should_unload {
+                continue;
+            }
+            
+            println!("💤 Model idle, asking backend to unload: {}", model);
+            let client = state_http_client(&state);
+            match client.post(&format!("{}/unload_model", backend_url)).send().await {
+                Ok(response) if response.status().is_success() => {
+                    if let Ok(mut app_state) = state.lock() {
+                        app_state.model_sleeping = true;
+                    }
+                    println!("✅ Model unloaded");
+                    emit_model_status(&app_handle, MODEL_UNLOADED_EVENT, &model);
+                }
+                Ok(response) => println!("⚠️ Backend refused to unload model: {}", response.status()),
+                Err(e) => println!("⚠️ Failed to reach backend for model unload: {}", e),
+            }
+        }
+    });
+}
\ No newline at end of file
@@ -0,0 +1,266 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+use crate::privacy::loggable;
+use crate::types::AppStateType;
+
+// Only browser extensions may connect; web pages would otherwise be able to read dictations
+const EXTENSION_ORIGINS: &[&str] = &["chrome-extension://", "moz-extension://", "safari-web-extension://"];
+
+// How long the extension gets to confirm an insertion before we type it instead
+const INSERT_ACK_TIMEOUT: Duration = Duration::from_secs(1);
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+// Connected extensions, one per browser profile
+static CONNECTIONS: Mutex<Vec<ExtensionConnection>> = Mutex::new(Vec::new());
+
+// Insertions waiting for the extension's confirmation, by insertion ID
+static PENDING_INSERTS: Mutex<Option<HashMap<u64, oneshot::Sender<Result<(), String>>>>> = Mutex::new(None);
+
+// Stops the running server when the port changes or the bridge is disabled
+static SHUTDOWN: Mutex<Option<oneshot::Sender<()>>> = Mutex::new(None);
+
+struct ExtensionConnection {
+    id: u64,
+    outgoing: mpsc::UnboundedSender<String>,
+    // When the extension last reported an editable field gaining focus; None once it blurs
+    focused_at: Option<Instant>,
+    url: Option<String>,
+}
+
+// Messages sent by the extension
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ExtensionMessage {
+    // An editable field in the active tab gained focus
+    Focus { url: Option<String> },
+    // The field lost focus, or the browser window did
+    Blur,
+    // Outcome of an insert request
+    Inserted { id: u64, ok: bool, error: Option<String> },
+}
+
+// Messages sent to the extension
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BridgeMessage<'a> {
+    Hello { version: &'a str },
+    Insert { id: u64, text: &'a str },
+}
+
+fn is_extension_origin(request: &Request) -> bool {
+    request
+        .headers()
+        .get("Origin")
+        .and_then(|origin| origin.to_str().ok())
+        .is_some_and(|origin| EXTENSION_ORIGINS.iter().any(|prefix| origin.starts_with(prefix)))
+}
+
+fn update_connection(id: u64, update: impl FnOnce(&mut ExtensionConnection)) {
+    if let Ok(mut connections) = CONNECTIONS.lock() {
+        if let Some(connection) = connections.iter_mut().find(|connection| connection.id == id) {
+            update(connection);
+        }
+    }
+}
+
+fn resolve_insert(id: u64, result: Result<(), String>) {
+    let waiter = PENDING_INSERTS
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.as_mut().and_then(|pending| pending.remove(&id)));
+    if let Some(waiter) = waiter {
+        let _ = waiter.send(result);
+    }
+}
+
+fn handle_extension_message(connection_id: u64, text: &str) {
+    let message = match serde_json::from_str::<ExtensionMessage>(text) {
+        Ok(message) => message,
+        Err(e) => {
+            println!("⚠️ Ignoring malformed browser extension message: {}", e);
+            return;
+        }
+    };
+    match message {
+        ExtensionMessage::Focus { url } => update_connection(connection_id, |connection| {
+            connection.focused_at = Some(Instant::now());
+            connection.url = url;
+        }),
+        ExtensionMessage::Blur => update_connection(connection_id, |connection| {
+            connection.focused_at = None;
+        }),
+        ExtensionMessage::Inserted { id, ok, error } => {
+            let result = if ok {
+                Ok(())
+            } else {
+                Err(error.unwrap_or_else(|| "Extension could not insert text".to_string()))
+            };
+            resolve_insert(id, result);
+        }
+    }
+}
+
+async fn serve_extension(stream: TcpStream) -> Result<(), String> {
+    let callback = |request: &Request, response: Response| {
+        if is_extension_origin(request) {
+            Ok(response)
+        } else {
+            let mut rejection = ErrorResponse::new(Some("Only browser extensions may connect".to_string()));
+            *rejection.status_mut() = StatusCode::FORBIDDEN;
+            Err(rejection)
+        }
+    };
+    let socket = tokio_tungstenite::accept_hdr_async(stream, callback)
+        .await
+        .map_err(|e| format!("Browser extension handshake failed: {}", e))?;
+    let (mut sink, mut source) = socket.split();
+    
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<String>();
+    if let Ok(hello) = serde_json::to_string(&BridgeMessage::Hello { version: env!("CARGO_PKG_VERSION") }) {
+        let _ = outgoing.send(hello);
+    }
+    if let Ok(mut connections) = CONNECTIONS.lock() {
+        connections.push(ExtensionConnection {
+            id,
+            outgoing,
+            focused_at: None,
+            url: None,
+        });
+    }
+    println!("🧩 Browser extension connected ({})", id);
+    
+    loop {
+        tokio::select! {
+            incoming = source.next() => match incoming {
+                Some(Ok(Message::Text(text))) => handle_extension_message(id, &text),
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    println!("⚠️ Browser extension connection error: {}", e);
+                    break;
+                }
+            },
+            outgoing = outgoing_rx.recv() => match outgoing {
+                Some(text) => {
+                    if let Err(e) = sink.send(Message::Text(text)).await {
+                        println!("⚠️ Failed to send to browser extension: {}", e);
+                        break;
+                    }
+                }
+                // The bridge was stopped
+                None => break,
+            },
+        }
+    }
+    
+    if let Ok(mut connections) = CONNECTIONS.lock() {
+        connections.retain(|connection| connection.id != id);
+    }
+    println!("🧩 Browser extension disconnected ({})", id);
+    Ok(())
+}
+
+// Insert text through the extension that most recently reported a focused field.
+// Returns None when no extension has one, so the caller types the text instead.
+pub async fn insert_via_extension(text: &str) -> Option<Result<(), String>> {
+    let (outgoing, url) = {
+        let connections = CONNECTIONS.lock().ok()?;
+        let connection = connections
+            .iter()
+            .filter(|connection| connection.focused_at.is_some())
+            .max_by_key(|connection| connection.focused_at)?;
+        (connection.outgoing.clone(), connection.url.clone())
+    };
+    
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let (waiter, confirmation) = oneshot::channel();
+    if let Ok(mut pending) = PENDING_INSERTS.lock() {
+        pending.get_or_insert_with(HashMap::new).insert(id, waiter);
+    }
+    
+    let message = match serde_json::to_string(&BridgeMessage::Insert { id, text }) {
+        Ok(message) => message,
+        Err(e) => return Some(Err(format!("Failed to serialize insertion: {}", e))),
+    };
+    println!("🧩 Inserting via browser extension into {}: '{}'", url.as_deref().unwrap_or("focused field"), loggable(text));
+    if outgoing.send(message).is_err() {
+        resolve_insert(id, Err("Browser extension disconnected".to_string()));
+    }
+    
+    let result = match tokio::time::timeout(INSERT_ACK_TIMEOUT, confirmation).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err("Browser extension disconnected".to_string()),
+        Err(_) => {
+            let error = "Browser extension did not confirm the insertion".to_string();
+            resolve_insert(id, Err(error.clone()));
+            Err(error)
+        }
+    };
+    Some(result)
+}
+
+// Stop any running bridge and listen on the configured localhost port
+pub fn restart_browser_bridge(state: &AppStateType) {
+    if let Ok(mut shutdown) = SHUTDOWN.lock() {
+        if let Some(shutdown) = shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+    // Dropping their senders closes the connected extensions
+    if let Ok(mut connections) = CONNECTIONS.lock() {
+        connections.clear();
+    }
+    
+    let port = state
+        .lock()
+        .map(|app_state| app_state.settings.browser_bridge_port)
+        .unwrap_or_default();
+    let port = match port {
+        Some(port) => port,
+        None => return,
+    };
+    
+    let (shutdown, mut stopped) = oneshot::channel();
+    if let Ok(mut current) = SHUTDOWN.lock() {
+        *current = Some(shutdown);
+    }
+    
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                println!("❌ Failed to start browser extension bridge on port {}: {}", port, e);
+                return;
+            }
+        };
+        println!("🧩 Browser extension bridge listening on ws://127.0.0.1:{}", port);
+        
+        loop {
+            tokio::select! {
+                _ = &mut stopped => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = serve_extension(stream).await {
+                                println!("⚠️ {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => println!("⚠️ Browser extension bridge accept failed: {}", e),
+                },
+            }
+        }
+        println!("🧩 Browser extension bridge stopped");
+    });
+}
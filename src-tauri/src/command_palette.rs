@@ -0,0 +1,181 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use tauri_plugin_opener::OpenerExt;
+use crate::backend::{language_name, set_language_internal, set_model_internal, start_backend, DICTATION_LANGUAGES, WHISPER_MODELS};
+use crate::history::show_history_window;
+use crate::jobs::{cancel_uploads, has_active_uploads};
+use crate::privacy::{is_incognito, toggle_incognito_internal};
+use crate::recordings::recordings_dir;
+use crate::shortcuts::{cancel_recording, handle_recording_shortcut};
+use crate::system_tray::show_settings_window;
+use crate::types::AppStateType;
+use crate::window_manager::pin_overlay_at_cursor;
+
+// Label of the command palette window, created on first use
+const PALETTE_LABEL: &str = "palette";
+
+// Size of the palette in logical pixels
+const PALETTE_WIDTH: f64 = 520.0;
+const PALETTE_HEIGHT: f64 = 360.0;
+
+// One entry of the palette; `id` is passed back to run_action
+#[derive(Clone, Serialize)]
+pub struct PaletteAction {
+    pub id: String,
+    pub title: String,
+    pub group: String,
+    // Currently selected model or language, or incognito when on
+    pub active: bool,
+}
+
+impl PaletteAction {
+    fn new(id: impl Into<String>, title: impl Into<String>, group: &str, active: bool) -> Self {
+        PaletteAction {
+            id: id.into(),
+            title: title.into(),
+            group: group.to_string(),
+            active,
+        }
+    }
+}
+
+// Every action the palette offers, in display order
+#[tauri::command]
+pub async fn list_actions(state: tauri::State<'_, AppStateType>) -> Result<Vec<PaletteAction>, String> {
+    let (is_recording, model, language) = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        (app_state.is_recording, app_state.settings.model.clone(), app_state.settings.language.clone())
+    };
+    
+    let mut actions = vec![PaletteAction::new(
+        "dictation:toggle",
+        if is_recording { "Stop dictation" } else { "Start dictation" },
+        "Dictation",
+        is_recording,
+    )];
+    if is_recording {
+        actions.push(PaletteAction::new("dictation:cancel", "Cancel dictation", "Dictation", false));
+    }
+    if has_active_uploads() {
+        actions.push(PaletteAction::new("upload:cancel", "Cancel upload", "Dictation", false));
+    }
+    actions.push(PaletteAction::new("incognito:toggle", "Incognito dictation", "Dictation", is_incognito()));
+    
+    for candidate in WHISPER_MODELS {
+        actions.push(PaletteAction::new(format!("model:{}", candidate), format!("Model: {}", candidate), "Model", *candidate == model));
+    }
+    
+    actions.push(PaletteAction::new("language:auto", "Language: Auto-detect", "Language", language.is_none()));
+    for (code, _) in DICTATION_LANGUAGES {
+        let active = language.as_deref() == Some(*code);
+        actions.push(PaletteAction::new(format!("language:{}", code), format!("Language: {}", language_name(code)), "Language", active));
+    }
+    
+    actions.push(PaletteAction::new("overlay:pin", "Pin overlay at cursor", "App", false));
+    actions.push(PaletteAction::new("settings:open", "Open settings", "App", false));
+    actions.push(PaletteAction::new("history:open", "Open history", "App", false));
+    actions.push(PaletteAction::new("recordings:open", "Open recordings folder", "App", false));
+    actions.push(PaletteAction::new("backend:start", "Start backend", "App", false));
+    Ok(actions)
+}
+
+// Run the action with the given ID, hiding the palette first so focus returns to the
+// app dictation should type into
+#[tauri::command]
+pub async fn run_action(app_handle: AppHandle, state: tauri::State<'_, AppStateType>, id: String) -> Result<(), String> {
+    println!("🎛️ Command palette action: {}", id);
+    hide_palette(&app_handle);
+    let state = state.inner().clone();
+    
+    match id.as_str() {
+        "dictation:toggle" => {
+            tauri::async_runtime::spawn(handle_recording_shortcut(app_handle, state));
+        }
+        "dictation:cancel" => {
+            cancel_recording(&app_handle, &state).await;
+        }
+        "upload:cancel" => {
+            cancel_uploads(None);
+        }
+        "incognito:toggle" => {
+            toggle_incognito_internal(&app_handle);
+        }
+        "overlay:pin" => pin_overlay_at_cursor(&app_handle, &state).await?,
+        "settings:open" => show_settings_window(&app_handle)?,
+        "history:open" => show_history_window(&app_handle)?,
+        "recordings:open" => open_path(&app_handle, recordings_dir(&app_handle)?)?,
+        "backend:start" => start_backend(app_handle).await?,
+        "language:auto" => set_language_internal(&app_handle, &state, None)?,
+        id if id.starts_with("language:") => {
+            let code = id.trim_start_matches("language:").to_string();
+            set_language_internal(&app_handle, &state, Some(code))?;
+        }
+        id if id.starts_with("model:") => {
+            let model = id.trim_start_matches("model:").to_string();
+            set_model_internal(&app_handle, &state, model).await?;
+        }
+        _ => return Err(format!("Unknown action: {}", id)),
+    }
+    Ok(())
+}
+
+fn open_path(app_handle: &AppHandle, path: std::path::PathBuf) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("{} does not exist yet", path.display()));
+    }
+    app_handle
+        .opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+fn hide_palette(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(PALETTE_LABEL) {
+        if let Err(e) = window.hide() {
+            println!("⚠️ Failed to hide command palette: {}", e);
+        }
+    }
+}
+
+// Hide the palette, e.g. when Escape is pressed
+#[tauri::command]
+pub async fn hide_command_palette(app_handle: AppHandle) -> Result<(), String> {
+    hide_palette(&app_handle);
+    Ok(())
+}
+
+// Show the palette centered on screen, or hide it when already visible
+pub fn toggle_command_palette(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(PALETTE_LABEL) {
+        if window.is_visible().unwrap_or(false) {
+            hide_palette(app);
+            return Ok(());
+        }
+        window.center().map_err(|e| format!("Failed to center command palette: {}", e))?;
+        window.show().map_err(|e| format!("Failed to show command palette: {}", e))?;
+        window.set_focus().map_err(|e| format!("Failed to focus command palette: {}", e))?;
+        return Ok(());
+    }
+    
+    let window = WebviewWindowBuilder::new(app, PALETTE_LABEL, WebviewUrl::App("/palette".into()))
+        .title("Cursper Commands")
+        .inner_size(PALETTE_WIDTH, PALETTE_HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .focused(true)
+        .build()
+        .map_err(|e| format!("Failed to create command palette window: {}", e))?;
+    
+    // Like other launchers, the palette goes away once the user clicks elsewhere
+    let app_handle = app.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            hide_palette(&app_handle);
+        }
+    });
+    println!("🪟 Created command palette window");
+    Ok(())
+}
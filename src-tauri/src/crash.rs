@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use crate::http::state_http_client;
+use crate::types::AppStateType;
+use crate::portable::app_log_dir;
+use crate::private_files::{create_private_dir, write_private};
+
+// How many breadcrumbs are kept for the next crash report
+const MAX_BREADCRUMBS: usize = 50;
+
+static BREADCRUMBS: OnceLock<Mutex<VecDeque<String>>> = OnceLock::new();
+
+// Anonymous crash report: no transcriptions, usernames or paths beyond the panic location
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub timestamp: u64,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub breadcrumbs: Vec<String>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Record a short, text-free note about what the app was doing
+pub fn breadcrumb(message: &str) {
+    let breadcrumbs = BREADCRUMBS.get_or_init(|| Mutex::new(VecDeque::new()));
+    if let Ok(mut breadcrumbs) = breadcrumbs.lock() {
+        breadcrumbs.push_back(format!("{} {}", unix_now(), message));
+        while breadcrumbs.len() > MAX_BREADCRUMBS {
+            breadcrumbs.pop_front();
+        }
+    }
+}
+
+fn recent_breadcrumbs() -> Vec<String> {
+    BREADCRUMBS
+        .get()
+        .and_then(|breadcrumbs| breadcrumbs.lock().ok().map(|b| b.iter().cloned().collect()))
+        .unwrap_or_default()
+}
+
+// Resolve the directory crash reports are written to
+pub fn crash_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let log_dir = app_log_dir(app)?;
+    Ok(log_dir.join("crashes"))
+}
+
+// Write a local crash report for every panic, then run the default hook
+pub fn install_panic_hook(app: &AppHandle) {
+    let dir = match crash_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            println!("⚠️ Crash reports disabled: {}", e);
+            return;
+        }
+    };
+    let app_version = app.package_info().version.to_string();
+    let default_hook = std::panic::take_hook();
+    
+    std::panic::set_hook(Box::new(move |info| {
+        let message = if let Some(message) = info.payload().downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = info.payload().downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "Unknown panic".to_string()
+        };
+        
+        let report = CrashReport {
+            timestamp: unix_now(),
+            app_version: app_version.clone(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            message,
+            location: info.location().map(|l| format!("{}:{}", l.file(), l.line())),
+            thread: std::thread::current().name().map(|name| name.to_string()),
+            breadcrumbs: recent_breadcrumbs(),
+        };
+        
+        let path = dir.join(format!("crash-{}.json", report.timestamp));
+        let written = create_private_dir(&dir).is_ok()
+            && serde_json::to_string_pretty(&report)
+                .map(|contents| write_private(&path, contents).is_ok())
+                .unwrap_or(false);
+        if written {
+            eprintln!("💥 Crash report written to {}", path.display());
+        }
+        
+        default_hook(info);
+    }));
+    
+    println!("✅ Panic hook installed");
+}
+
+// Upload crash reports left by previous runs when the user opted in
+pub fn spawn_pending_crash_upload(app_handle: AppHandle, state: AppStateType) {
+    let (enabled, url) = match state.lock() {
+        Ok(app_state) => (
+            app_state.settings.crash_reporting_enabled,
+            app_state.settings.crash_report_url.clone(),
+        ),
+        Err(_) => return,
+    };
+    
+    let url = match url {
+        Some(url) if enabled && !url.trim().is_empty() => url,
+        _ => return,
+    };
+    
+    let client = state_http_client(&state);
+    tauri::async_runtime::spawn(async move {
+        let dir = match crash_dir(&app_handle) {
+            Ok(dir) => dir,
+            Err(_) => return,
+        };
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            
+            let contents = match std::fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(_) => continue,
+            };
+            
+            let response = client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .body(contents)
+                .send()
+                .await;
+            
+            match response {
+                Ok(response) if response.status().is_success() => {
+                    println!("📤 Uploaded crash report {}", path.display());
+                    // Keep the local copy but mark it as sent
+                    let _ = std::fs::rename(&path, path.with_extension("sent"));
+                }
+                Ok(response) => println!("⚠️ Crash report upload rejected: {}", response.status()),
+                Err(e) => {
+                    println!("⚠️ Crash report upload failed: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+}
+
+// List locally stored crash reports
+#[tauri::command]
+pub async fn get_crash_reports(app_handle: AppHandle) -> Result<Vec<CrashReport>, String> {
+    let dir = crash_dir(&app_handle)?;
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(Vec::new()),
+    };
+    
+    let mut reports: Vec<CrashReport> = entries
+        .flatten()
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|contents| serde_json::from_str(&contents).ok())
+        .collect();
+    reports.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(reports)
+}
@@ -0,0 +1,118 @@
+use tauri::AppHandle;
+use crate::types::AppStateType;
+
+// Session bus service so Linux users can bind dictation to compositor keybindings and
+// script it, e.g. `busctl --user call org.cursper.Dictation /org/cursper/Dictation
+// org.cursper.Dictation Toggle`
+pub const DBUS_NAME: &str = "org.cursper.Dictation";
+pub const DBUS_PATH: &str = "/org/cursper/Dictation";
+
+// Claim the bus name and serve the Dictation interface
+pub fn start_dbus_service(app_handle: &AppHandle, state: &AppStateType) {
+    platform::start(app_handle.clone(), state.clone());
+}
+
+// Broadcast a StateChanged signal ("recording" or "idle")
+pub fn emit_dbus_state(is_recording: bool) {
+    platform::emit_state(if is_recording { "recording" } else { "idle" });
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::sync::OnceLock;
+    use tauri::AppHandle;
+    use zbus::object_server::SignalEmitter;
+    use crate::jobs::latest_job_result;
+    use crate::privacy::is_incognito;
+    use crate::shortcuts::{cancel_recording, handle_recording_shortcut};
+    use crate::types::AppStateType;
+    use super::{DBUS_NAME, DBUS_PATH};
+    
+    static CONNECTION: OnceLock<zbus::Connection> = OnceLock::new();
+    
+    struct DictationService {
+        app_handle: AppHandle,
+        state: AppStateType,
+    }
+    
+    impl DictationService {
+        fn is_recording(&self) -> bool {
+            self.state.lock().map(|app_state| app_state.is_recording).unwrap_or(false)
+        }
+    }
+    
+    #[zbus::interface(name = "org.cursper.Dictation")]
+    impl DictationService {
+        // Start recording, or stop and transcribe
+        async fn toggle(&self) {
+            tauri::async_runtime::spawn(handle_recording_shortcut(self.app_handle.clone(), self.state.clone()));
+        }
+        
+        // Stop recording without inserting anything; false when not recording
+        async fn cancel(&self) -> bool {
+            cancel_recording(&self.app_handle, &self.state).await
+        }
+        
+        // "recording" or "idle"
+        fn get_status(&self) -> String {
+            if self.is_recording() { "recording" } else { "idle" }.to_string()
+        }
+        
+        // Text of the latest transcription, empty if none (or incognito)
+        fn last_transcription(&self) -> String {
+            latest_job_result()
+                .filter(|_| !is_incognito())
+                .map(|result| result.text)
+                .unwrap_or_default()
+        }
+        
+        #[zbus(signal)]
+        async fn state_changed(emitter: &SignalEmitter<'_>, state: &str) -> zbus::Result<()>;
+    }
+    
+    pub fn start(app_handle: AppHandle, state: AppStateType) {
+        tauri::async_runtime::spawn(async move {
+            let service = DictationService { app_handle, state };
+            let connection = zbus::connection::Builder::session()
+                .and_then(|builder| builder.name(DBUS_NAME))
+                .and_then(|builder| builder.serve_at(DBUS_PATH, service));
+            let connection = match connection {
+                Ok(builder) => builder.build().await,
+                Err(e) => Err(e),
+            };
+            match connection {
+                Ok(connection) => {
+                    println!("🚌 DBus service {} ready", DBUS_NAME);
+                    let _ = CONNECTION.set(connection);
+                }
+                Err(e) => println!("⚠️ Failed to start DBus service: {}", e),
+            }
+        });
+    }
+    
+    pub fn emit_state(state: &'static str) {
+        let connection = match CONNECTION.get() {
+            Some(connection) => connection.clone(),
+            None => return,
+        };
+        tauri::async_runtime::spawn(async move {
+            let result = match SignalEmitter::new(&connection, DBUS_PATH) {
+                Ok(emitter) => DictationService::state_changed(&emitter, state).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                println!("⚠️ Failed to emit DBus state: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use tauri::AppHandle;
+    use crate::types::AppStateType;
+    
+    pub fn start(_app_handle: AppHandle, _state: AppStateType) {}
+    
+    pub fn emit_state(_state: &'static str) {}
+}
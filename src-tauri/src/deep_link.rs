@@ -0,0 +1,223 @@
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_opener::OpenerExt;
+use tokio::sync::oneshot;
+use url::Url;
+use crate::jobs::latest_job_result;
+use crate::privacy::is_incognito;
+use crate::settings::{apply_settings, save_settings};
+use crate::shortcuts::handle_recording_shortcut;
+use crate::types::AppStateType;
+
+// Automation URLs follow the x-callback-url convention so macOS Shortcuts can chain them:
+//   cursper://x-callback-url/dictate?x-success=shortcuts://x-callback-url/...
+// Actions: dictate (record until the shortcut is pressed again, then return the text),
+// toggle (start or stop recording) and last-transcription.
+pub const URL_SCHEME: &str = "cursper";
+
+// Callbacks that would hand dictations to a web page or run script; only app schemes
+// like shortcuts:// or things:// are opened
+const BLOCKED_CALLBACK_SCHEMES: &[&str] = &["http", "https", "file", "javascript", "data"];
+
+// Callbacks of a dictate request, answered once its transcription finishes
+static PENDING_DICTATION: Mutex<Option<XCallback>> = Mutex::new(None);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DeepLinkAction {
+    Dictate,
+    Toggle,
+    LastTranscription,
+}
+
+#[derive(Clone, Default)]
+struct XCallback {
+    success: Option<Url>,
+    error: Option<Url>,
+    cancel: Option<Url>,
+}
+
+// How a pending dictation ended
+pub enum DictationOutcome<'a> {
+    Success(&'a str),
+    Error(&'a str),
+    Cancelled,
+}
+
+impl XCallback {
+    fn from_url(url: &Url) -> Self {
+        let mut callback = XCallback::default();
+        for (key, value) in url.query_pairs() {
+            let target = match key.as_ref() {
+                "x-success" => &mut callback.success,
+                "x-error" => &mut callback.error,
+                "x-cancel" => &mut callback.cancel,
+                _ => continue,
+            };
+            *target = Url::parse(&value).ok();
+        }
+        callback
+    }
+}
+
+// Both cursper://x-callback-url/dictate and cursper://dictate are accepted
+fn parse_action(url: &Url) -> Option<DeepLinkAction> {
+    let name = match url.host_str() {
+        Some("x-callback-url") => url.path().trim_matches('/').to_string(),
+        Some(host) => host.to_string(),
+        None => return None,
+    };
+    match name.to_ascii_lowercase().as_str() {
+        "dictate" => Some(DeepLinkAction::Dictate),
+        "toggle" => Some(DeepLinkAction::Toggle),
+        "last-transcription" => Some(DeepLinkAction::LastTranscription),
+        _ => None,
+    }
+}
+
+// The callback URL with its parameters, or None when there is none or it isn't an app scheme
+fn callback_url(base: Option<&Url>, params: &[(&str, &str)]) -> Option<Url> {
+    let mut url = base?.clone();
+    if BLOCKED_CALLBACK_SCHEMES.contains(&url.scheme()) {
+        println!("⚠️ Refusing {} x-callback URL", url.scheme());
+        return None;
+    }
+    url.query_pairs_mut().extend_pairs(params);
+    Some(url)
+}
+
+fn open_callback(app_handle: &AppHandle, base: Option<&Url>, params: &[(&str, &str)]) {
+    let url = match callback_url(base, params) {
+        Some(url) => url,
+        None => return,
+    };
+    if let Err(e) = app_handle.opener().open_url(url.as_str(), None::<&str>) {
+        println!("⚠️ Failed to open x-callback URL: {}", e);
+    }
+}
+
+fn open_error(app_handle: &AppHandle, callback: &XCallback, message: &str) {
+    println!("❌ Deep link failed: {}", message);
+    open_callback(app_handle, callback.error.as_ref(), &[("errorCode", "1"), ("errorMessage", message)]);
+}
+
+// Every action starts the microphone or hands out a dictation, and any web page can open
+// a cursper:// URL, so the user has to allow automation URLs once
+async fn automation_allowed(app_handle: &AppHandle, state: &AppStateType) -> bool {
+    let mut settings = match state.lock() {
+        Ok(app_state) if app_state.settings.automation_urls_enabled => return true,
+        Ok(app_state) => app_state.settings.clone(),
+        Err(_) => return false,
+    };
+    
+    let (answer, allowed) = oneshot::channel();
+    app_handle
+        .dialog()
+        .message("An automation URL wants to start recording or read your dictations. Only allow this if you set up a Shortcut or script that uses cursper:// URLs.")
+        .title("Allow automation URLs?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Allow".to_string(), "Don't Allow".to_string()))
+        .show(move |allowed| {
+            let _ = answer.send(allowed);
+        });
+    if !allowed.await.unwrap_or(false) {
+        return false;
+    }
+    
+    settings.automation_urls_enabled = true;
+    let result = match save_settings(app_handle, &settings) {
+        Ok(_) => apply_settings(app_handle, state, settings).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        println!("⚠️ Failed to remember allowing automation URLs: {}", e);
+    }
+    true
+}
+
+// Run an automation URL opened from Shortcuts, a script or the browser
+pub fn handle_deep_link(app_handle: &AppHandle, url: &Url) {
+    if url.scheme() != URL_SCHEME {
+        return;
+    }
+    println!("🔗 Deep link: {}", url.path());
+    
+    let callback = XCallback::from_url(url);
+    let action = match parse_action(url) {
+        Some(action) => action,
+        None => return open_error(app_handle, &callback, "Unknown action"),
+    };
+    let state = match app_handle.try_state::<AppStateType>() {
+        Some(state) => state.inner().clone(),
+        None => return open_error(app_handle, &callback, "App is not ready"),
+    };
+    
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if automation_allowed(&app_handle, &state).await {
+            run_action(&app_handle, state, action, callback);
+        } else {
+            open_error(&app_handle, &callback, "Automation URLs are not allowed");
+        }
+    });
+}
+
+// Carry out an automation URL the user has allowed
+fn run_action(app_handle: &AppHandle, state: AppStateType, action: DeepLinkAction, callback: XCallback) {
+    let is_recording = state.lock().map(|app_state| app_state.is_recording).unwrap_or(false);
+    
+    match action {
+        DeepLinkAction::Dictate => {
+            if is_recording {
+                return open_error(app_handle, &callback, "Already recording");
+            }
+            if let Ok(mut pending) = PENDING_DICTATION.lock() {
+                // A newer request supersedes one that never finished
+                if let Some(previous) = pending.replace(callback) {
+                    open_callback(app_handle, previous.cancel.as_ref(), &[]);
+                }
+            }
+            tauri::async_runtime::spawn(handle_recording_shortcut(app_handle.clone(), state));
+        }
+        DeepLinkAction::Toggle => {
+            tauri::async_runtime::spawn(handle_recording_shortcut(app_handle.clone(), state));
+            let recording = if is_recording { "false" } else { "true" };
+            open_callback(app_handle, callback.success.as_ref(), &[("recording", recording)]);
+        }
+        DeepLinkAction::LastTranscription => {
+            // Incognito dictations must not leak to other apps
+            match latest_job_result().filter(|_| !is_incognito()) {
+                Some(result) => open_callback(app_handle, callback.success.as_ref(), &[("result", result.text.as_str())]),
+                None => open_error(app_handle, &callback, "No transcription yet"),
+            }
+        }
+    }
+}
+
+// Answer the pending dictate request, if any, with the finished dictation
+pub fn complete_pending_dictation(app_handle: &AppHandle, outcome: DictationOutcome) {
+    let callback = match PENDING_DICTATION.lock().ok().and_then(|mut pending| pending.take()) {
+        Some(callback) => callback,
+        None => return,
+    };
+    match outcome {
+        DictationOutcome::Success(text) => open_callback(app_handle, callback.success.as_ref(), &[("result", text)]),
+        DictationOutcome::Error(message) => open_error(app_handle, &callback, message),
+        DictationOutcome::Cancelled => open_callback(app_handle, callback.cancel.as_ref(), &[]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn web_callbacks_are_refused() {
+        let web = Url::parse("https://evil.example/").unwrap();
+        assert!(callback_url(Some(&web), &[("result", "secret")]).is_none());
+        
+        let shortcut = Url::parse("shortcuts://x-callback-url/run-shortcut?name=Send").unwrap();
+        let url = callback_url(Some(&shortcut), &[("result", "hello")]).unwrap();
+        assert_eq!(url.as_str(), "shortcuts://x-callback-url/run-shortcut?name=Send&result=hello");
+    }
+}
@@ -0,0 +1,401 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use crate::privacy::is_incognito;
+use crate::screen_share::sharing_paused;
+use crate::sequencer::{insert_in_order, issue_insertion_ticket};
+use crate::tray_menu::{TrayEntry, TraySection};
+use crate::types::{AppStateType, TranscriptionResult};
+use crate::recordings::recording_path;
+use crate::history_index::{clear_history_index, index_history_entry};
+use crate::vault::{decrypt_line, encrypt_line, history_encryption, ENCRYPTED_LINE_PREFIX, HISTORY_LOCKED_ERROR};
+use cursper_core::redact::redact_pii;
+use crate::portable::app_data_dir;
+use crate::private_files::{append_private, create_private_dir, write_private};
+
+// Label of the history window, created on first use
+const HISTORY_LABEL: &str = "history";
+
+// Dictations listed under "Recent Dictations" in the tray
+const TRAY_RECENT_ENTRIES: usize = 5;
+
+// Characters of a dictation shown in its tray entry
+const TRAY_LABEL_CHARS: usize = 40;
+
+// Held while appending to or rewriting history.jsonl, so an entry appended during a
+// rewrite can't be dropped by the rename that follows
+static HISTORY_FILE_LOCK: Mutex<()> = Mutex::new(());
+
+// Recent dictations in the tray, clicked to type one again
+pub const HISTORY_TRAY_SECTION: TraySection = TraySection {
+    order: 30,
+    entries: recent_tray_entries,
+    on_click: on_recent_tray_click,
+};
+
+// File formats history can be exported to
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryExportFormat {
+    Csv,
+    // One JSON entry per line, like history.jsonl itself
+    Jsonl,
+    // Digest grouped by day, for keeping the log as a work journal
+    Markdown,
+}
+
+// Time span to export, in Unix seconds (inclusive); open ends export everything
+#[derive(Clone, Default, Deserialize)]
+pub struct HistoryRange {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+// A single persisted transcription
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub timestamp: u64,
+    pub text: String,
+    pub language: Option<String>,
+    pub model_used: Option<String>,
+    // Application the text was dictated into, when known
+    #[serde(default)]
+    pub app: Option<String>,
+    // Retained recording, relative to the recordings dir; None when retention was off
+    #[serde(default)]
+    pub audio_file: Option<String>,
+}
+
+// Resolve the history file location (one JSON entry per line)
+pub fn history_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app_data_dir(app)?;
+    Ok(data_dir.join("history.jsonl"))
+}
+
+// Read all history entries, oldest first
+pub fn load_history(app: &AppHandle) -> Result<Vec<HistoryEntry>, String> {
+    let path = history_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read history: {}", e))?;
+    
+    let mut entries = Vec::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let line = match decrypt_line(line) {
+            Ok(line) => line,
+            Err(e) if e == HISTORY_LOCKED_ERROR => return Err(e),
+            Err(e) => {
+                println!("⚠️ Skipping unreadable history line: {}", e);
+                continue;
+            }
+        };
+        match serde_json::from_str::<HistoryEntry>(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => println!("⚠️ Skipping malformed history line: {}", e),
+        }
+    }
+    
+    Ok(entries)
+}
+
+// Append a transcription to history unless incognito or history is disabled
+pub fn record_transcription(app: &AppHandle, state: &AppStateType, result: &TranscriptionResult) -> Result<(), String> {
+    if is_incognito() {
+        println!("🕶️ Incognito active, not writing transcription to history");
+        return Ok(());
+    }
+    
+    let (history_enabled, redact, extra_patterns, focused_app) = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        (
+            app_state.settings.history_enabled,
+            app_state.settings.history_redact_pii,
+            app_state.settings.history_redaction_patterns.clone(),
+            app_state.focused_app.clone(),
+        )
+    };
+    if !history_enabled {
+        return Ok(());
+    }
+    
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("System clock error: {}", e))?;
+    
+    let entry = HistoryEntry {
+        id: now.as_millis() as u64,
+        timestamp: now.as_secs(),
+        text: if redact { redact_pii(&result.text, &extra_patterns) } else { result.text.clone() },
+        language: result.language.clone(),
+        model_used: result.model_used.clone(),
+        app: focused_app,
+        audio_file: result.audio_file.clone(),
+    };
+    
+    let path = history_path(app)?;
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    }
+    
+    let line = serde_json::to_string(&entry)
+        .map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+    let line = if history_encryption() { encrypt_line(&line)? } else { line };
+    let guard = HISTORY_FILE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut file = append_private(&path).map_err(|e| format!("Failed to open history file: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write history entry: {}", e))?;
+    drop(guard);
+    
+    println!("📝 Transcription saved to history (id {})", entry.id);
+    
+    // A stale index is rebuilt on the next search, so this only costs time there
+    if let Err(e) = index_history_entry(app, &entry) {
+        println!("⚠️ {}", e);
+    }
+    Ok(())
+}
+
+// Get the most recent history entries, newest first
+#[tauri::command]
+pub async fn get_history(app_handle: AppHandle, limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
+    let mut entries = load_history(&app_handle)?;
+    entries.reverse();
+    if let Some(limit) = limit {
+        entries.truncate(limit);
+    }
+    Ok(entries)
+}
+
+// Delete all history entries
+#[tauri::command]
+pub async fn clear_history(app_handle: AppHandle) -> Result<(), String> {
+    let path = history_path(&app_handle)?;
+    let _guard = HISTORY_FILE_LOCK.lock().map_err(|e| e.to_string())?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to clear history: {}", e))?;
+    }
+    clear_history_index(&app_handle)?;
+    println!("🗑️ History cleared");
+    Ok(())
+}
+
+// Rewrite history written before encryption was turned on with every line encrypted
+pub fn encrypt_history_file(app: &AppHandle) -> Result<(), String> {
+    let path = history_path(app)?;
+    let _guard = HISTORY_FILE_LOCK.lock().map_err(|e| e.to_string())?;
+    if !path.exists() {
+        return Ok(());
+    }
+    
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read history: {}", e))?;
+    let mut encrypted = String::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        if line.starts_with(ENCRYPTED_LINE_PREFIX) {
+            encrypted.push_str(line);
+        } else {
+            encrypted.push_str(&encrypt_line(line)?);
+        }
+        encrypted.push('\n');
+    }
+    
+    // Write aside and rename so a crash can't leave history half plaintext, half lost
+    let temp_path = path.with_extension("jsonl.tmp");
+    write_private(&temp_path, encrypted).map_err(|e| format!("Failed to write encrypted history: {}", e))?;
+    std::fs::rename(&temp_path, &path).map_err(|e| format!("Failed to replace history: {}", e))?;
+    Ok(())
+}
+
+// Path of the retained recording behind a history entry
+pub fn history_audio_path(app: &AppHandle, id: u64) -> Result<PathBuf, String> {
+    let entry = load_history(app)?
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| format!("No history entry {}", id))?;
+    let name = entry
+        .audio_file
+        .ok_or_else(|| "No audio was retained for this transcription".to_string())?;
+    let path = recording_path(app, &name)?;
+    if !path.exists() {
+        return Err("The recording was removed by retention cleanup".to_string());
+    }
+    Ok(path)
+}
+
+// Show the history window, creating it the first time
+pub fn show_history_window(app: &AppHandle) -> Result<(), String> {
+    let window = match app.get_webview_window(HISTORY_LABEL) {
+        Some(window) => window,
+        None => WebviewWindowBuilder::new(app, HISTORY_LABEL, WebviewUrl::App("/history".into()))
+            .title("Cursper History")
+            .inner_size(640.0, 520.0)
+            .build()
+            .map_err(|e| format!("Failed to create history window: {}", e))?,
+    };
+    window.show().map_err(|e| format!("Failed to show history window: {}", e))?;
+    window.set_focus().map_err(|e| format!("Failed to focus history window: {}", e))?;
+    Ok(())
+}
+
+// One line of a dictation, shortened to fit a menu entry
+fn tray_label(text: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > TRAY_LABEL_CHARS {
+        format!("{}…", line.chars().take(TRAY_LABEL_CHARS).collect::<String>())
+    } else {
+        line
+    }
+}
+
+// Left out while incognito, so the tray doesn't show dictations to onlookers
+fn recent_tray_entries(app: &AppHandle) -> Vec<TrayEntry> {
+    if is_incognito() || sharing_paused(app) {
+        return Vec::new();
+    }
+    let recent: Vec<TrayEntry> = match load_history(app) {
+        Ok(entries) => entries
+            .iter()
+            .rev()
+            .take(TRAY_RECENT_ENTRIES)
+            .map(|entry| TrayEntry::item(format!("recent:{}", entry.id), tray_label(&entry.text)))
+            .collect(),
+        Err(e) if e == HISTORY_LOCKED_ERROR => vec![TrayEntry::item("recent:locked", "History is locked").disabled()],
+        Err(e) => {
+            println!("⚠️ Failed to list recent dictations: {}", e);
+            Vec::new()
+        }
+    };
+    if recent.is_empty() {
+        return Vec::new();
+    }
+    vec![TrayEntry::submenu("Recent Dictations", recent)]
+}
+
+// Type a recent dictation again into the focused app
+fn on_recent_tray_click(app: &AppHandle, id: &str) -> bool {
+    let id = match id.strip_prefix("recent:").and_then(|id| id.parse::<u64>().ok()) {
+        Some(id) => id,
+        None => return false,
+    };
+    println!("📜 Typing history entry {} again from tray menu", id);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = retype_history_entry(&app, id).await {
+            println!("❌ {}", e);
+        }
+    });
+    true
+}
+
+// Type a history entry again at the cursor, after any dictations still being inserted
+pub async fn retype_history_entry(app: &AppHandle, id: u64) -> Result<(), String> {
+    let text = load_history(app)?
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .map(|entry| entry.text)
+        .ok_or_else(|| format!("History entry {} not found", id))?;
+    insert_in_order(issue_insertion_ticket(), Some(text), &[])
+        .await
+        .map_err(|e| format!("Failed to type history entry: {}", e))
+}
+
+// Open the history window from the frontend
+#[tauri::command]
+pub async fn open_history_window(app_handle: AppHandle) -> Result<(), String> {
+    show_history_window(&app_handle)
+}
+
+// Local date and time of a history timestamp, for exports
+fn local_time(timestamp: u64) -> chrono::DateTime<chrono::Local> {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .unwrap_or_default()
+        .with_timezone(&chrono::Local)
+}
+
+// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn history_csv(entries: &[HistoryEntry]) -> String {
+    let mut csv = String::from("id,time,text,language,model,app,audio_file\n");
+    for entry in entries {
+        let fields = [
+            entry.id.to_string(),
+            local_time(entry.timestamp).to_rfc3339(),
+            entry.text.clone(),
+            entry.language.clone().unwrap_or_default(),
+            entry.model_used.clone().unwrap_or_default(),
+            entry.app.clone().unwrap_or_default(),
+            entry.audio_file.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn history_jsonl(entries: &[HistoryEntry]) -> Result<String, String> {
+    let mut jsonl = String::new();
+    for entry in entries {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+        jsonl.push_str(&line);
+        jsonl.push('\n');
+    }
+    Ok(jsonl)
+}
+
+// "## Monday, 2026-10-12" headings with one bullet per dictation, oldest first
+fn history_markdown(entries: &[HistoryEntry]) -> String {
+    let mut markdown = String::from("# Dictation Journal\n");
+    let mut current_day = None;
+    for entry in entries {
+        let time = local_time(entry.timestamp);
+        let day = time.date_naive();
+        if current_day != Some(day) {
+            markdown.push_str(&format!("\n## {}\n\n", time.format("%A, %Y-%m-%d")));
+            current_day = Some(day);
+        }
+        let app = entry.app.as_deref().map(|app| format!(" ({})", app)).unwrap_or_default();
+        // Continuation lines are indented so multi-paragraph dictations stay in their bullet
+        let text = entry.text.trim().replace('\n', "\n  ");
+        markdown.push_str(&format!("- **{}**{} {}\n", time.format("%H:%M"), app, text));
+    }
+    markdown
+}
+
+// Write history within the range to a file; returns how many entries were exported
+#[tauri::command]
+pub async fn export_history(app_handle: AppHandle, format: HistoryExportFormat, path: String, range: Option<HistoryRange>) -> Result<usize, String> {
+    println!("📤 Exporting history as {:?} to {}", format, path);
+    
+    let range = range.unwrap_or_default();
+    let entries: Vec<HistoryEntry> = load_history(&app_handle)?
+        .into_iter()
+        .filter(|entry| range.from.map_or(true, |from| entry.timestamp >= from))
+        .filter(|entry| range.to.map_or(true, |to| entry.timestamp <= to))
+        .collect();
+    
+    let contents = match format {
+        HistoryExportFormat::Csv => history_csv(&entries),
+        HistoryExportFormat::Jsonl => history_jsonl(&entries)?,
+        HistoryExportFormat::Markdown => history_markdown(&entries),
+    };
+    write_private(Path::new(&path), contents).map_err(|e| format!("Failed to write history export: {}", e))?;
+    
+    println!("✅ Exported {} history entries", entries.len());
+    Ok(entries.len())
+}
@@ -0,0 +1,143 @@
+use rusqlite::{params, Connection};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use crate::history::{load_history, HistoryEntry};
+use crate::vault::history_encryption;
+use cursper_core::search::fts_query;
+use crate::portable::app_data_dir;
+use crate::private_files::{create_private_dir, restrict_file};
+
+// Results returned when the search doesn't ask for a limit
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+// Narrow a history search; all fields are optional
+#[derive(Clone, Default, Deserialize)]
+pub struct HistorySearchFilters {
+    // Unix seconds, inclusive
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    // Application the text was dictated into, matched case-insensitively
+    pub app: Option<String>,
+    pub limit: Option<usize>,
+}
+
+// The index lives next to history.jsonl, which stays the source of truth; it can be
+// deleted at any time and is rebuilt on the next search. With encrypted history it
+// would leak the text, so it's then kept in memory for the duration of a search.
+fn index_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app_data_dir(app)?;
+    Ok(data_dir.join("history-index.sqlite"))
+}
+
+fn open_index(app: &AppHandle) -> Result<Connection, String> {
+    let path = index_path(app)?;
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    }
+    let connection = if history_encryption() { Connection::open_in_memory() } else { Connection::open(&path) }
+        .map_err(|e| format!("Failed to open history index: {}", e))?;
+    // SQLite gives its journal files the permissions of the database
+    if !history_encryption() {
+        restrict_file(&path).map_err(|e| format!("Failed to protect history index: {}", e))?;
+    }
+    connection
+        .execute_batch(
+            "CREATE TABLE IF NOT EXISTS entries (id INTEGER PRIMARY KEY, timestamp INTEGER NOT NULL, app TEXT);
+             CREATE INDEX IF NOT EXISTS entries_timestamp ON entries (timestamp);
+             CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5(text, tokenize = 'unicode61 remove_diacritics 2');",
+        )
+        .map_err(|e| format!("Failed to create history index: {}", e))?;
+    Ok(connection)
+}
+
+fn insert_entry(connection: &Connection, entry: &HistoryEntry) -> rusqlite::Result<()> {
+    connection.execute(
+        "INSERT OR REPLACE INTO entries (id, timestamp, app) VALUES (?1, ?2, ?3)",
+        params![entry.id as i64, entry.timestamp as i64, entry.app],
+    )?;
+    connection.execute("DELETE FROM history_fts WHERE rowid = ?1", params![entry.id as i64])?;
+    connection.execute(
+        "INSERT INTO history_fts (rowid, text) VALUES (?1, ?2)",
+        params![entry.id as i64, entry.text],
+    )?;
+    Ok(())
+}
+
+// Add a newly recorded entry to the index
+pub fn index_history_entry(app: &AppHandle, entry: &HistoryEntry) -> Result<(), String> {
+    if history_encryption() {
+        return Ok(());
+    }
+    let connection = open_index(app)?;
+    insert_entry(&connection, entry).map_err(|e| format!("Failed to index history entry: {}", e))
+}
+
+// Re-index everything when the index has drifted from history.jsonl, e.g. after an
+// upgrade, a failed write or the file being edited by hand
+fn sync_index(connection: &mut Connection, entries: &[HistoryEntry]) -> Result<(), String> {
+    let (indexed, newest) = connection
+        .query_row("SELECT COUNT(*), MAX(id) FROM entries", [], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?))
+        })
+        .map_err(|e| format!("Failed to read history index: {}", e))?;
+    if indexed as usize == entries.len() && newest == entries.last().map(|entry| entry.id as i64) {
+        return Ok(());
+    }
+    
+    println!("🔎 Rebuilding history search index ({} entries)", entries.len());
+    let transaction = connection.transaction().map_err(|e| format!("Failed to rebuild history index: {}", e))?;
+    let rebuilt = transaction
+        .execute_batch("DELETE FROM entries; DELETE FROM history_fts;")
+        .and_then(|_| entries.iter().try_for_each(|entry| insert_entry(&transaction, entry)));
+    rebuilt
+        .and_then(|_| transaction.commit())
+        .map_err(|e| format!("Failed to rebuild history index: {}", e))
+}
+
+// Delete the index along with the history it was built from
+pub fn clear_history_index(app: &AppHandle) -> Result<(), String> {
+    let path = index_path(app)?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to clear history index: {}", e))?;
+    }
+    Ok(())
+}
+
+// Full-text search over history, newest first. An empty query lists the entries
+// matching the filters.
+#[tauri::command]
+pub async fn search_history(app_handle: AppHandle, query: String, filters: Option<HistorySearchFilters>) -> Result<Vec<HistoryEntry>, String> {
+    let filters = filters.unwrap_or_default();
+    let entries = load_history(&app_handle)?;
+    let mut connection = open_index(&app_handle)?;
+    sync_index(&mut connection, &entries)?;
+    
+    // MATCH can't be made optional with OR, so the join is left out for an empty query;
+    // ?1 is then simply unused
+    let matcher = fts_query(&query);
+    let sql = format!(
+        "SELECT entries.id FROM entries {} \
+         WHERE (?2 IS NULL OR entries.timestamp >= ?2) \
+           AND (?3 IS NULL OR entries.timestamp <= ?3) \
+           AND (?4 IS NULL OR entries.app = ?4 COLLATE NOCASE) \
+         ORDER BY entries.timestamp DESC, entries.id DESC LIMIT ?5",
+        if matcher.is_some() { "JOIN history_fts ON history_fts.rowid = entries.id AND history_fts MATCH ?1" } else { "" }
+    );
+    let limit = filters.limit.unwrap_or(DEFAULT_SEARCH_LIMIT) as i64;
+    let mut statement = connection.prepare(&sql).map_err(|e| format!("Failed to search history: {}", e))?;
+    let ids = statement
+        .query_map(
+            params![matcher, filters.from.map(|from| from as i64), filters.to.map(|to| to as i64), filters.app, limit],
+            |row| row.get::<_, i64>(0),
+        )
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<i64>>>())
+        .map_err(|e| format!("Failed to search history: {}", e))?;
+    
+    let mut by_id: HashMap<i64, HistoryEntry> = entries.into_iter().map(|entry| (entry.id as i64, entry)).collect();
+    let results: Vec<HistoryEntry> = ids.iter().filter_map(|id| by_id.remove(id)).collect();
+    println!("🔎 History search matched {} entries", results.len());
+    Ok(results)
+}
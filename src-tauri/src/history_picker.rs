@@ -0,0 +1,89 @@
+use std::time::Duration;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+use crate::history::{load_history, retype_history_entry, HistoryEntry};
+use crate::privacy::is_incognito;
+
+// Label of the history picker window, created on first use
+const PICKER_LABEL: &str = "history-picker";
+
+// Size of the picker in logical pixels
+const PICKER_WIDTH: f64 = 520.0;
+const PICKER_HEIGHT: f64 = 320.0;
+
+// Dictations offered by the picker, newest first
+const PICKER_ENTRIES: usize = 20;
+
+// Time for focus to go back to the previous app before typing into it
+const FOCUS_RETURN_DELAY: Duration = Duration::from_millis(150);
+
+// The most recent dictations; none while incognito, like the tray's recent dictations
+#[tauri::command]
+pub async fn list_recent_dictations(app_handle: AppHandle) -> Result<Vec<HistoryEntry>, String> {
+    if is_incognito() {
+        return Ok(Vec::new());
+    }
+    let mut entries = load_history(&app_handle)?;
+    entries.reverse();
+    entries.truncate(PICKER_ENTRIES);
+    Ok(entries)
+}
+
+// Hide the picker and type the chosen dictation into the app that was focused before
+#[tauri::command]
+pub async fn insert_recent_dictation(app_handle: AppHandle, id: u64) -> Result<(), String> {
+    println!("📜 Typing history entry {} again from history picker", id);
+    hide_picker(&app_handle);
+    tokio::time::sleep(FOCUS_RETURN_DELAY).await;
+    retype_history_entry(&app_handle, id).await
+}
+
+fn hide_picker(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(PICKER_LABEL) {
+        if let Err(e) = window.hide() {
+            println!("⚠️ Failed to hide history picker: {}", e);
+        }
+    }
+}
+
+// Hide the picker, e.g. when Escape is pressed
+#[tauri::command]
+pub async fn hide_history_picker(app_handle: AppHandle) -> Result<(), String> {
+    hide_picker(&app_handle);
+    Ok(())
+}
+
+// Show the picker centered on screen, or hide it when already visible
+pub fn toggle_history_picker(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(PICKER_LABEL) {
+        if window.is_visible().unwrap_or(false) {
+            hide_picker(app);
+            return Ok(());
+        }
+        window.center().map_err(|e| format!("Failed to center history picker: {}", e))?;
+        window.show().map_err(|e| format!("Failed to show history picker: {}", e))?;
+        window.set_focus().map_err(|e| format!("Failed to focus history picker: {}", e))?;
+        return Ok(());
+    }
+    
+    let window = WebviewWindowBuilder::new(app, PICKER_LABEL, WebviewUrl::App("/history-picker".into()))
+        .title("Cursper Recent Dictations")
+        .inner_size(PICKER_WIDTH, PICKER_HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .focused(true)
+        .build()
+        .map_err(|e| format!("Failed to create history picker window: {}", e))?;
+    
+    // Clicking elsewhere dismisses the picker, as with the command palette
+    let app_handle = app.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            hide_picker(&app_handle);
+        }
+    });
+    println!("🪟 Created history picker window");
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use crate::privacy::{is_incognito, loggable};
+use crate::text_input::type_text;
+use crate::types::TranscriptionResult;
+use cursper_core::audio::{IncrementalEncoder, InputChannelMode};
+use cursper_core::recorder::SampleSink;
+use cursper_core::transcript::context_tail;
+
+// How many finished transcriptions are kept in memory for follow-up actions
+const MAX_RECENT_JOBS: usize = 20;
+
+// Dictations into the same app closer together than this continue one long-form text
+const CONTEXT_CARRY_OVER_WINDOW: Duration = Duration::from_secs(90);
+
+// Characters of the previous chunk handed to the next as conditioning context
+const CONTEXT_CARRY_OVER_CHARS: usize = 200;
+
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+static RECENT_JOBS: OnceLock<Mutex<VecDeque<TranscriptionResult>>> = OnceLock::new();
+
+// Cancellation tokens of uploads in flight, by job id
+static ACTIVE_UPLOADS: Mutex<Vec<(u64, Arc<AtomicBool>)>> = Mutex::new(Vec::new());
+
+// Tail of the latest chunk: when it finished, the app it was dictated into and its text
+static CARRIED_CONTEXT: Mutex<Option<(Instant, Option<String>, String)>> = Mutex::new(None);
+
+fn recent_jobs() -> &'static Mutex<VecDeque<TranscriptionResult>> {
+    RECENT_JOBS.get_or_init(|| Mutex::new(VecDeque::new()))
+}
+
+// Allocate an id for a new transcription job
+pub fn next_job_id() -> u64 {
+    NEXT_JOB_ID.fetch_add(1, Ordering::SeqCst)
+}
+
+// Remember a finished transcription (memory only, never persisted)
+pub fn store_job_result(result: &TranscriptionResult) {
+    if let Ok(mut jobs) = recent_jobs().lock() {
+        jobs.push_back(result.clone());
+        while jobs.len() > MAX_RECENT_JOBS {
+            jobs.pop_front();
+        }
+    }
+}
+
+// Register a job's upload; setting the returned token aborts it
+pub fn start_upload(job_id: u64) -> Arc<AtomicBool> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    if let Ok(mut uploads) = ACTIVE_UPLOADS.lock() {
+        uploads.push((job_id, cancel.clone()));
+    }
+    cancel
+}
+
+// Forget a job's upload once all providers were tried
+pub fn finish_upload(job_id: u64) {
+    if let Ok(mut uploads) = ACTIVE_UPLOADS.lock() {
+        uploads.retain(|(id, _)| *id != job_id);
+    }
+}
+
+pub fn has_active_uploads() -> bool {
+    ACTIVE_UPLOADS.lock().map(|uploads| !uploads.is_empty()).unwrap_or(false)
+}
+
+// Abort one job's upload, or all of them; returns how many were cancelled
+pub fn cancel_uploads(job_id: Option<u64>) -> usize {
+    let uploads = match ACTIVE_UPLOADS.lock() {
+        Ok(uploads) => uploads,
+        Err(_) => return 0,
+    };
+    let mut cancelled = 0;
+    for (id, cancel) in uploads.iter().filter(|(id, _)| job_id.map_or(true, |job_id| job_id == *id)) {
+        println!("🚫 Cancelling upload of job {}", id);
+        cancel.store(true, Ordering::SeqCst);
+        cancelled += 1;
+    }
+    cancelled
+}
+
+// Keep the end of a finished chunk so the next one into the same app can continue it
+pub fn carry_over_context(app: Option<String>, text: &str) {
+    if let Ok(mut context) = CARRIED_CONTEXT.lock() {
+        *context = if is_incognito() || text.trim().is_empty() {
+            None
+        } else {
+            Some((Instant::now(), app, context_tail(text, CONTEXT_CARRY_OVER_CHARS)))
+        };
+    }
+}
+
+// Context for the next chunk into this app, if the previous one just finished there
+pub fn carried_context(app: Option<&str>) -> Option<String> {
+    let context = CARRIED_CONTEXT.lock().ok()?;
+    let (finished_at, context_app, text) = context.as_ref()?;
+    if finished_at.elapsed() > CONTEXT_CARRY_OVER_WINDOW || context_app.as_deref() != app {
+        return None;
+    }
+    Some(text.clone())
+}
+
+// Look up a recent transcription by job id
+pub fn get_job_result(job_id: u64) -> Option<TranscriptionResult> {
+    recent_jobs()
+        .lock()
+        .ok()
+        .and_then(|jobs| jobs.iter().find(|job| job.job_id == job_id).cloned())
+}
+
+// The most recent finished transcription
+pub fn latest_job_result() -> Option<TranscriptionResult> {
+    recent_jobs().lock().ok().and_then(|jobs| jobs.back().cloned())
+}
+
+// A captured chunk with the source's sample rate and channel count
+type CapturedChunk = (Vec<f32>, u32, u16);
+
+// Encodes a recording on a worker while it is still being captured, so finishing a
+// dictation only trims the already-encoded audio instead of processing it from scratch
+pub struct FinalizePipeline {
+    chunks: std::sync::mpsc::Sender<CapturedChunk>,
+    worker: tokio::task::JoinHandle<Option<IncrementalEncoder>>,
+}
+
+impl FinalizePipeline {
+    pub fn start(mode: InputChannelMode, gate_ms: u64) -> Self {
+        let (chunks, received) = std::sync::mpsc::channel::<CapturedChunk>();
+        let worker = tokio::task::spawn_blocking(move || {
+            let mut encoder: Option<IncrementalEncoder> = None;
+            let mut supported = true;
+            for (samples, sample_rate, channels) in received {
+                if encoder.is_none() && supported {
+                    encoder = IncrementalEncoder::new(sample_rate, channels, mode, gate_ms);
+                    supported = encoder.is_some();
+                }
+                if let Some(encoder) = encoder.as_mut() {
+                    encoder.push(&samples);
+                }
+            }
+            encoder
+        });
+        Self { chunks, worker }
+    }
+    
+    // Sink handing captured chunks to the worker, for the recorder to call as it captures
+    pub fn sample_sink(&self) -> SampleSink {
+        let chunks = self.chunks.clone();
+        Box::new(move |samples, sample_rate, channels| {
+            let _ = chunks.send((samples.to_vec(), sample_rate, channels));
+        })
+    }
+    
+    // Wait for the worker to encode the last chunks; None when the channel mode can't be
+    // processed incrementally, in which case the recording is processed as a whole
+    pub async fn finish(self) -> Option<IncrementalEncoder> {
+        drop(self.chunks);
+        self.worker.await.ok().flatten()
+    }
+}
+
+// Abort an upload in flight, e.g. a long recording sent over a slow connection; without a
+// job id every upload is cancelled
+#[tauri::command]
+pub async fn cancel_upload(job_id: Option<u64>) -> Result<(), String> {
+    if cancel_uploads(job_id) == 0 {
+        return Err("No upload in progress".to_string());
+    }
+    Ok(())
+}
+
+// Type only the selected segments of a finished transcription, in spoken order
+#[tauri::command]
+pub async fn insert_segments(job_id: u64, segment_ids: Vec<u32>) -> Result<(), String> {
+    println!("✂️ insert_segments called for job {} with segments {:?}", job_id, segment_ids);
+    
+    let result = get_job_result(job_id)
+        .ok_or_else(|| format!("Transcription job {} not found", job_id))?;
+    
+    let text = result
+        .segments
+        .iter()
+        .filter(|segment| segment_ids.contains(&segment.id))
+        .map(|segment| segment.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    
+    if text.is_empty() {
+        let error = "No matching segments selected".to_string();
+        println!("⚠️ {}", error);
+        return Err(error);
+    }
+    
+    println!("⌨️  Inserting selected segments: '{}'", loggable(&text));
+    type_text(text).await
+}
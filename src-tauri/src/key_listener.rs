@@ -0,0 +1,502 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+// Longest gap between the two presses of a double-press shortcut
+const DOUBLE_PRESS_WINDOW: Duration = Duration::from_millis(400);
+
+// Keys the global shortcut plugin can't register, caught by a low-level listener instead
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LowLevelKey {
+    // Fn, or the Globe key on newer Mac keyboards
+    Fn,
+    MediaPlayPause,
+    MediaNextTrack,
+    MediaPreviousTrack,
+    VolumeMute,
+    // F13 to F24
+    F(u8),
+    // Win+H, which opens Windows voice typing unless intercepted
+    WinH,
+}
+
+// A low-level key, optionally pressed twice in quick succession
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LowLevelShortcut {
+    pub key: LowLevelKey,
+    pub double_press: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyEdge {
+    Pressed,
+    Released,
+}
+
+pub type KeyHandler = Arc<dyn Fn(&AppHandle, KeyEdge) + Send + Sync>;
+
+// Handlers bound to low-level keys, cleared and rebuilt with the plugin's shortcuts
+static BINDINGS: Mutex<Vec<(LowLevelShortcut, KeyHandler)>> = Mutex::new(Vec::new());
+
+// Keys currently held, so auto-repeat doesn't fire a binding again
+static HELD_KEYS: Mutex<Vec<LowLevelKey>> = Mutex::new(Vec::new());
+
+// When each key was last pressed, for double-press detection
+static LAST_PRESSES: Mutex<Vec<(LowLevelKey, Instant)>> = Mutex::new(Vec::new());
+
+static LISTENER_APP: OnceLock<AppHandle> = OnceLock::new();
+static LISTENER_STARTED: AtomicBool = AtomicBool::new(false);
+
+// Consume bound keys instead of letting them reach the focused app as well
+static SUPPRESS_BOUND_KEYS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_suppress_shortcut_keys(enabled: bool) {
+    let was_enabled = SUPPRESS_BOUND_KEYS.swap(enabled, Ordering::SeqCst);
+    if enabled && !was_enabled && cfg!(target_os = "macos") && LISTENER_STARTED.load(Ordering::SeqCst) {
+        println!("⚠️ Suppressing shortcut keys takes effect after restarting Cursper");
+    }
+}
+
+// Parse a low-level shortcut: a single key such as "Fn", "Globe" or "MediaPlayPause", the
+// same key pressed twice ("DoubleFn"), "Win+H", or "Dictation" for the platform's own
+// dictation trigger
+pub fn parse_low_level_shortcut(shortcut_str: &str) -> Option<LowLevelShortcut> {
+    let shortcut_str = shortcut_str.trim();
+    if shortcut_str == "Dictation" {
+        return os_dictation_shortcut();
+    }
+    if shortcut_str.replace(' ', "") == "Win+H" {
+        return Some(LowLevelShortcut { key: LowLevelKey::WinH, double_press: false });
+    }
+    
+    let (name, double_press) = match shortcut_str.strip_prefix("Double") {
+        Some(name) => (name.trim(), true),
+        None => (shortcut_str, false),
+    };
+    parse_low_level_key(name).map(|key| LowLevelShortcut { key, double_press })
+}
+
+// The shortcut the OS itself uses to start dictation
+fn os_dictation_shortcut() -> Option<LowLevelShortcut> {
+    if cfg!(target_os = "macos") {
+        Some(LowLevelShortcut { key: LowLevelKey::Fn, double_press: true })
+    } else if cfg!(target_os = "windows") {
+        Some(LowLevelShortcut { key: LowLevelKey::WinH, double_press: false })
+    } else {
+        None
+    }
+}
+
+fn parse_low_level_key(name: &str) -> Option<LowLevelKey> {
+    let key = match name {
+        "Fn" | "Globe" => LowLevelKey::Fn,
+        "MediaPlayPause" => LowLevelKey::MediaPlayPause,
+        "MediaNextTrack" => LowLevelKey::MediaNextTrack,
+        "MediaPreviousTrack" => LowLevelKey::MediaPreviousTrack,
+        "VolumeMute" => LowLevelKey::VolumeMute,
+        other => {
+            let number = other.strip_prefix('F')?.parse::<u8>().ok()?;
+            if !(13..=24).contains(&number) {
+                return None;
+            }
+            LowLevelKey::F(number)
+        }
+    };
+    Some(key)
+}
+
+// Bind a handler to a low-level shortcut, starting the platform listener on first use
+pub fn register_low_level_shortcut(app: &AppHandle, shortcut: LowLevelShortcut, handler: KeyHandler) -> Result<(), String> {
+    start_listener(app)?;
+    if shortcut.key == LowLevelKey::Fn && shortcut.double_press {
+        platform::warn_if_os_dictation_enabled();
+    }
+    BINDINGS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .push((shortcut, handler));
+    println!("✅ Low-level shortcut {:?} registered", shortcut);
+    Ok(())
+}
+
+// Drop every low-level binding; the listener thread keeps running but ignores all keys
+pub fn unregister_all_low_level_shortcuts() {
+    if let Ok(mut bindings) = BINDINGS.lock() {
+        bindings.clear();
+    }
+}
+
+// Whether any binding uses this key, so platforms can swallow keys only when bound
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn is_bound(key: LowLevelKey) -> bool {
+    BINDINGS
+        .lock()
+        .map(|bindings| bindings.iter().any(|(shortcut, _)| shortcut.key == key))
+        .unwrap_or(false)
+}
+
+// Whether the key's events, press, repeat and release alike, should be consumed
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn should_swallow(key: LowLevelKey) -> bool {
+    SUPPRESS_BOUND_KEYS.load(Ordering::SeqCst) && is_bound(key)
+}
+
+// Whether this press completes a double press of the key
+fn completes_double_press(key: LowLevelKey) -> bool {
+    let mut last_presses = match LAST_PRESSES.lock() {
+        Ok(last_presses) => last_presses,
+        Err(_) => return false,
+    };
+    let now = Instant::now();
+    let previous = last_presses.iter().position(|(pressed, _)| *pressed == key);
+    match previous {
+        Some(index) if now.duration_since(last_presses[index].1) <= DOUBLE_PRESS_WINDOW => {
+            // A third press starts a new pair instead of firing again
+            last_presses.remove(index);
+            true
+        }
+        Some(index) => {
+            last_presses[index].1 = now;
+            false
+        }
+        None => {
+            last_presses.push((key, now));
+            false
+        }
+    }
+}
+
+// Called from the platform listener for every key it recognizes
+fn dispatch(key: LowLevelKey, edge: KeyEdge) {
+    {
+        let mut held = match HELD_KEYS.lock() {
+            Ok(held) => held,
+            Err(_) => return,
+        };
+        match edge {
+            KeyEdge::Pressed if held.contains(&key) => return,
+            KeyEdge::Pressed => held.push(key),
+            KeyEdge::Released => held.retain(|held_key| *held_key != key),
+        }
+    }
+    
+    let app = match LISTENER_APP.get() {
+        Some(app) => app,
+        None => return,
+    };
+    let double_press = edge == KeyEdge::Pressed && completes_double_press(key);
+    let handlers: Vec<KeyHandler> = match BINDINGS.lock() {
+        Ok(bindings) => bindings
+            .iter()
+            .filter(|(shortcut, _)| shortcut.key == key && (!shortcut.double_press || double_press))
+            .map(|(_, handler)| handler.clone())
+            .collect(),
+        Err(_) => return,
+    };
+    for handler in handlers {
+        handler(app, edge);
+    }
+}
+
+fn start_listener(app: &AppHandle) -> Result<(), String> {
+    let _ = LISTENER_APP.set(app.clone());
+    if LISTENER_STARTED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    
+    println!("⌨️  Starting low-level keyboard listener...");
+    if let Err(e) = platform::spawn_listener() {
+        LISTENER_STARTED.store(false, Ordering::SeqCst);
+        return Err(e);
+    }
+    Ok(())
+}
+
+// Keyboard event tap; needs the Input Monitoring permission
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{dispatch, should_swallow, KeyEdge, LowLevelKey, SUPPRESS_BOUND_KEYS};
+    use core_foundation::runloop::{kCFRunLoopCommonModes, CFRunLoop};
+    use std::sync::atomic::Ordering;
+    use core_graphics::event::{CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement, CGEventType, EventField};
+    
+    // Virtual key codes from HIToolbox/Events.h
+    const KEY_CODE_FN: i64 = 63;
+    const KEY_CODE_GLOBE: i64 = 179;
+    
+    fn key_for_code(code: i64) -> Option<LowLevelKey> {
+        match code {
+            KEY_CODE_FN | KEY_CODE_GLOBE => Some(LowLevelKey::Fn),
+            105 => Some(LowLevelKey::F(13)),
+            107 => Some(LowLevelKey::F(14)),
+            113 => Some(LowLevelKey::F(15)),
+            106 => Some(LowLevelKey::F(16)),
+            64 => Some(LowLevelKey::F(17)),
+            79 => Some(LowLevelKey::F(18)),
+            80 => Some(LowLevelKey::F(19)),
+            90 => Some(LowLevelKey::F(20)),
+            _ => None,
+        }
+    }
+    
+    pub fn spawn_listener() -> Result<(), String> {
+        // Only an active tap may consume events, and it needs Accessibility permission too
+        let options = if SUPPRESS_BOUND_KEYS.load(Ordering::SeqCst) {
+            CGEventTapOptions::Default
+        } else {
+            CGEventTapOptions::ListenOnly
+        };
+        std::thread::Builder::new()
+            .name("cursper-key-tap".to_string())
+            .spawn(move || {
+                let tap = CGEventTap::new(
+                    CGEventTapLocation::HID,
+                    CGEventTapPlacement::HeadInsertEventTap,
+                    options,
+                    vec![CGEventType::KeyDown, CGEventType::KeyUp, CGEventType::FlagsChanged],
+                    |_proxy, event_type, event| {
+                        let code = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE);
+                        if let Some(key) = key_for_code(code) {
+                            let edge = match event_type {
+                                CGEventType::KeyDown => KeyEdge::Pressed,
+                                CGEventType::KeyUp => KeyEdge::Released,
+                                // Modifier-like keys only report a flag change
+                                _ if event.get_flags().contains(CGEventFlags::CGEventFlagSecondaryFn) => KeyEdge::Pressed,
+                                _ => KeyEdge::Released,
+                            };
+                            dispatch(key, edge);
+                            // A null event is dropped instead of delivered
+                            if should_swallow(key) {
+                                event.set_type(CGEventType::Null);
+                            }
+                        }
+                        None
+                    },
+                );
+                
+                match tap {
+                    Ok(tap) => unsafe {
+                        let source = match tap.mach_port.create_runloop_source(0) {
+                            Ok(source) => source,
+                            Err(_) => {
+                                println!("❌ Failed to create run loop source for keyboard tap");
+                                return;
+                            }
+                        };
+                        CFRunLoop::get_current().add_source(&source, kCFRunLoopCommonModes);
+                        tap.enable();
+                        println!("✅ Keyboard event tap running");
+                        CFRunLoop::run_current();
+                    },
+                    Err(_) => println!("❌ Failed to create keyboard event tap (grant Input Monitoring permission)"),
+                }
+            })
+            .map(|_| ())
+            .map_err(|e| format!("Failed to start keyboard listener thread: {}", e))
+    }
+    
+    // The tap only listens, so macOS still starts its own dictation from the Fn key
+    // unless the key is set to do something else
+    pub fn warn_if_os_dictation_enabled() {
+        // AppleFnUsageType 3 = "Press 🌐 key to: Start Dictation"
+        let output = std::process::Command::new("defaults")
+            .args(["read", "com.apple.HIToolbox", "AppleFnUsageType"])
+            .output();
+        if let Ok(output) = output {
+            if String::from_utf8_lossy(&output.stdout).trim() == "3" {
+                println!("⚠️ The Fn/Globe key also starts macOS Dictation; change \"Press 🌐 key to\" in System Settings › Keyboard so only Cursper listens");
+            }
+        }
+    }
+}
+
+// Low-level keyboard hook; the Fn key never reaches Windows, so only media and F13-F24 keys apply
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{dispatch, is_bound, should_swallow, KeyEdge, LowLevelKey};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use windows_sys::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, GetMessageW, SetWindowsHookExW, KBDLLHOOKSTRUCT, MSG, WH_KEYBOARD_LL, WM_KEYDOWN, WM_KEYUP,
+        WM_SYSKEYDOWN, WM_SYSKEYUP,
+    };
+    
+    // Virtual key codes from WinUser.h
+    const VK_VOLUME_MUTE: u32 = 0xAD;
+    const VK_MEDIA_NEXT_TRACK: u32 = 0xB0;
+    const VK_MEDIA_PREV_TRACK: u32 = 0xB1;
+    const VK_MEDIA_PLAY_PAUSE: u32 = 0xB3;
+    const VK_F13: u32 = 0x7C;
+    const VK_F24: u32 = 0x87;
+    const VK_LWIN: u32 = 0x5B;
+    const VK_RWIN: u32 = 0x5C;
+    const VK_H: u32 = 0x48;
+    // Unassigned key tapped so releasing Win after a swallowed Win+H doesn't open Start
+    const VK_MASK: u16 = 0xE8;
+    
+    static WIN_HELD: AtomicBool = AtomicBool::new(false);
+    
+    fn key_for_code(code: u32) -> Option<LowLevelKey> {
+        match code {
+            VK_VOLUME_MUTE => Some(LowLevelKey::VolumeMute),
+            VK_MEDIA_NEXT_TRACK => Some(LowLevelKey::MediaNextTrack),
+            VK_MEDIA_PREV_TRACK => Some(LowLevelKey::MediaPreviousTrack),
+            VK_MEDIA_PLAY_PAUSE => Some(LowLevelKey::MediaPlayPause),
+            VK_F13..=VK_F24 => Some(LowLevelKey::F((code - VK_F13) as u8 + 13)),
+            _ => None,
+        }
+    }
+    
+    unsafe fn tap_mask_key() {
+        let input = |flags| INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: VK_MASK,
+                    wScan: 0,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        };
+        let inputs = [input(0), input(KEYEVENTF_KEYUP)];
+        SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<INPUT>() as i32);
+    }
+    
+    unsafe extern "system" fn keyboard_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0 {
+            let info = &*(lparam as *const KBDLLHOOKSTRUCT);
+            let edge = match wparam as u32 {
+                WM_KEYDOWN | WM_SYSKEYDOWN => Some(KeyEdge::Pressed),
+                WM_KEYUP | WM_SYSKEYUP => Some(KeyEdge::Released),
+                _ => None,
+            };
+            
+            if info.vkCode == VK_LWIN || info.vkCode == VK_RWIN {
+                WIN_HELD.store(edge == Some(KeyEdge::Pressed), Ordering::SeqCst);
+            }
+            
+            // Swallow a bound Win+H so Windows voice typing doesn't open as well
+            if info.vkCode == VK_H && WIN_HELD.load(Ordering::SeqCst) && is_bound(LowLevelKey::WinH) {
+                if let Some(edge) = edge {
+                    if edge == KeyEdge::Pressed {
+                        tap_mask_key();
+                    }
+                    dispatch(LowLevelKey::WinH, edge);
+                }
+                return 1;
+            }
+            
+            if let (Some(key), Some(edge)) = (key_for_code(info.vkCode), edge) {
+                dispatch(key, edge);
+                if should_swallow(key) {
+                    return 1;
+                }
+            }
+        }
+        CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam)
+    }
+    
+    pub fn spawn_listener() -> Result<(), String> {
+        std::thread::Builder::new()
+            .name("cursper-key-hook".to_string())
+            .spawn(|| unsafe {
+                let hook = SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook), std::ptr::null_mut(), 0);
+                if hook.is_null() {
+                    println!("❌ Failed to install low-level keyboard hook");
+                    return;
+                }
+                println!("✅ Low-level keyboard hook installed");
+                
+                // The hook is called from this thread's message loop
+                let mut message: MSG = std::mem::zeroed();
+                while GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) > 0 {}
+            })
+            .map(|_| ())
+            .map_err(|e| format!("Failed to start keyboard listener thread: {}", e))
+    }
+    
+    pub fn warn_if_os_dictation_enabled() {}
+}
+
+// Reads keyboards straight from /dev/input; works on X11 and Wayland but needs the
+// user in the "input" group. Keys can't be consumed without grabbing the whole keyboard,
+// so suppression doesn't apply here.
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{dispatch, KeyEdge, LowLevelKey};
+    use evdev::{InputEventKind, Key};
+    
+    fn key_for_code(key: Key) -> Option<LowLevelKey> {
+        match key {
+            Key::KEY_FN => Some(LowLevelKey::Fn),
+            Key::KEY_PLAYPAUSE => Some(LowLevelKey::MediaPlayPause),
+            Key::KEY_NEXTSONG => Some(LowLevelKey::MediaNextTrack),
+            Key::KEY_PREVIOUSSONG => Some(LowLevelKey::MediaPreviousTrack),
+            Key::KEY_MUTE => Some(LowLevelKey::VolumeMute),
+            _ if (Key::KEY_F13.code()..=Key::KEY_F24.code()).contains(&key.code()) => {
+                Some(LowLevelKey::F((key.code() - Key::KEY_F13.code()) as u8 + 13))
+            }
+            _ => None,
+        }
+    }
+    
+    pub fn spawn_listener() -> Result<(), String> {
+        let keyboards: Vec<(std::path::PathBuf, evdev::Device)> = evdev::enumerate()
+            .filter(|(_, device)| {
+                device
+                    .supported_keys()
+                    .map(|keys| keys.contains(Key::KEY_FN) || keys.contains(Key::KEY_PLAYPAUSE) || keys.contains(Key::KEY_ENTER))
+                    .unwrap_or(false)
+            })
+            .collect();
+        if keyboards.is_empty() {
+            return Err("No readable keyboard devices in /dev/input (is the user in the \"input\" group?)".to_string());
+        }
+        
+        // fetch_events blocks, so every device gets its own thread
+        for (path, mut device) in keyboards {
+            println!("⌨️  Listening to {} ({})", path.display(), device.name().unwrap_or("unnamed"));
+            std::thread::Builder::new()
+                .name("cursper-evdev".to_string())
+                .spawn(move || loop {
+                    let events = match device.fetch_events() {
+                        Ok(events) => events,
+                        Err(e) => {
+                            println!("⚠️ Stopped reading {}: {}", path.display(), e);
+                            return;
+                        }
+                    };
+                    for event in events {
+                        if let InputEventKind::Key(key) = event.kind() {
+                            // 0 = release, 1 = press, 2 = auto-repeat
+                            let edge = match event.value() {
+                                0 => KeyEdge::Released,
+                                1 => KeyEdge::Pressed,
+                                _ => continue,
+                            };
+                            if let Some(key) = key_for_code(key) {
+                                dispatch(key, edge);
+                            }
+                        }
+                    }
+                })
+                .map_err(|e| format!("Failed to start keyboard listener thread: {}", e))?;
+        }
+        Ok(())
+    }
+    
+    pub fn warn_if_os_dictation_enabled() {}
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+mod platform {
+    pub fn spawn_listener() -> Result<(), String> {
+        Err("Low-level shortcuts are not supported on this platform".to_string())
+    }
+    
+    pub fn warn_if_os_dictation_enabled() {}
+}
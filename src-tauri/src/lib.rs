@@ -8,17 +8,67 @@ mod text_input;
 mod shortcuts;
 mod backend;
 mod system_tray;
+mod tray_menu;
+mod settings;
+mod history;
+mod history_index;
+mod privacy;
+mod recordings;
+mod screen_share;
+mod sequencer;
+mod jobs;
+mod statistics;
+mod crash;
+mod watchdog;
+mod templates;
+mod providers;
+mod http;
+mod snippets;
+mod key_listener;
+mod pedal;
+mod app_profiles;
+mod webhook;
+mod mqtt;
+mod obsidian;
+mod browser_bridge;
+mod deep_link;
+mod dbus;
+mod media_control;
+mod command_palette;
+mod history_picker;
+mod playback;
+mod vault;
+mod quit;
+mod updater;
+mod portable;
+mod private_files;
 
 // Re-export commonly used items
 use types::AppState;
-use shortcuts::setup_shortcuts;
+use shortcuts::{setup_accurate_shortcut, setup_correction_shortcut, setup_history_picker_shortcut, setup_incognito_shortcut, setup_palette_shortcut, setup_shortcuts};
+use snippets::setup_snippet_shortcuts;
 use system_tray::setup_system_tray;
+use settings::{load_settings, watch_settings_file};
+use backend::spawn_idle_unload_monitor;
+use recordings::spawn_retention_cleanup;
+use screen_share::spawn_screen_share_monitor;
+use updater::spawn_update_checker;
+use crash::{install_panic_hook, spawn_pending_crash_upload};
+use watchdog::spawn_state_watchdog;
+use window_manager::{setup_overlay_pool, setup_settings_window_geometry};
+use pedal::restart_foot_pedal_listener;
+use mqtt::restart_mqtt_client;
+use browser_bridge::restart_browser_bridge;
+use deep_link::handle_deep_link;
+use dbus::start_dbus_service;
+use tauri_plugin_deep_link::DeepLinkExt;
 
 // Import required traits and types
 use std::sync::{Arc, Mutex};
 
 pub fn run() {
     println!("🚀 CURSPER TAURI APP STARTING");
+    portable::prepare_portable_mode();
     println!("🔧 Creating app state...");
     let state = Arc::new(Mutex::new(AppState::default()));
     
@@ -28,25 +78,103 @@ pub fn run() {
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_os::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
         .manage(state.clone())
         .invoke_handler(tauri::generate_handler![
             window_manager::get_cursor_position,
             window_manager::show_overlay,
+            window_manager::test_overlay_focus,
             audio::start_recording,
+            audio::pause_recording,
+            audio::resume_recording,
             audio::stop_recording_and_transcribe,
+            audio::is_capturing_audio,
+            audio::list_input_devices,
             text_input::type_text,
             backend::set_whisper_model,
+            backend::set_language,
             backend::get_available_models,
+            backend::list_cached_models,
+            backend::delete_cached_model,
             backend::start_backend,
+            backend::detect_backend_environment,
+            backend::setup_backend_environment,
             shortcuts::toggle_recording,
             shortcuts::update_global_shortcut,
             shortcuts::emit_recording_state,
             system_tray::test_global_shortcut_system,
-            system_tray::open_settings
+            system_tray::open_settings,
+            types::get_event_schemas,
+            settings::get_settings,
+            settings::update_settings,
+            settings::export_settings,
+            settings::import_settings,
+            history::get_history,
+            history::clear_history,
+            history::open_history_window,
+            history::export_history,
+            history_index::search_history,
+            playback::play_history_audio,
+            playback::stop_playback,
+            vault::unlock_history,
+            vault::lock_history,
+            vault::get_history_locked,
+            privacy::set_incognito,
+            privacy::get_incognito,
+            recordings::purge_recordings,
+            jobs::insert_segments,
+            jobs::cancel_upload,
+            statistics::get_statistics,
+            statistics::get_statistics_by_app,
+            statistics::reset_statistics,
+            crash::get_crash_reports,
+            templates::list_templates,
+            templates::save_template,
+            templates::delete_template,
+            templates::select_template,
+            snippets::list_snippets,
+            snippets::save_snippet,
+            snippets::delete_snippet,
+            snippets::insert_snippet,
+            providers::get_provider_usage,
+            pedal::list_hid_devices,
+            pedal::set_foot_pedal,
+            pedal::learn_foot_pedal_button,
+            app_profiles::get_focused_application,
+            webhook::test_webhook,
+            command_palette::list_actions,
+            command_palette::run_action,
+            command_palette::hide_command_palette,
+            history_picker::list_recent_dictations,
+            history_picker::insert_recent_dictation,
+            history_picker::hide_history_picker,
+            updater::check_for_updates,
+            updater::install_update
         ])
         .setup(move |app| {
             println!("🔧 Setting up application...");
             
+            // Write a crash report instead of dying silently
+            install_panic_hook(app.handle());
+            
+            // Load persisted settings
+            println!("🔄 Loading settings...");
+            let loaded_settings = load_settings(app.handle());
+            text_input::set_use_helper_processes(loaded_settings.use_helper_processes);
+            text_input::set_smart_spacing(loaded_settings.smart_spacing);
+            text_input::set_paste_rtl_text(loaded_settings.paste_rtl_text);
+            text_input::set_insertion_options(loaded_settings.insertion_method, loaded_settings.pre_insert_delay_ms, &loaded_settings.app_profiles);
+            text_input::set_remote_desktop_delay_ms(loaded_settings.remote_desktop_delay_ms);
+            text_input::set_xdotool_options(loaded_settings.xdotool.clone());
+            key_listener::set_suppress_shortcut_keys(loaded_settings.suppress_shortcut_keys);
+            vault::set_history_encryption(loaded_settings.encrypt_history);
+            match state.lock() {
+                Ok(mut app_state) => app_state.settings = loaded_settings,
+                Err(e) => println!("❌ Failed to apply settings: {}", e),
+            }
+            
             // Setup system tray
             println!("🔄 Setting up system tray...");
             if let Err(e) = setup_system_tray(app.handle()) {
@@ -55,6 +183,12 @@ pub fn run() {
                 println!("✅ System tray setup completed");
             }
             
+            // One overlay window per monitor
+            setup_overlay_pool(app.handle());
+            
+            // Reopen the settings window where it was left
+            setup_settings_window_geometry(app.handle());
+            
             // Setup global shortcuts
             println!("🔄 Setting up global shortcuts...");
             if let Err(e) = setup_shortcuts(app.handle(), state.clone()) {
@@ -63,6 +197,73 @@ pub fn run() {
                 println!("✅ Global shortcuts setup completed");
             }
             
+            if let Err(e) = setup_incognito_shortcut(app.handle(), state.clone()) {
+                println!("❌ Failed to setup incognito shortcut: {}", e);
+            }
+            
+            if let Err(e) = setup_correction_shortcut(app.handle(), state.clone()) {
+                println!("❌ Failed to setup correction shortcut: {}", e);
+            }
+            
+            if let Err(e) = setup_palette_shortcut(app.handle(), state.clone()) {
+                println!("❌ Failed to setup command palette shortcut: {}", e);
+            }
+            
+            if let Err(e) = setup_history_picker_shortcut(app.handle(), state.clone()) {
+                println!("❌ Failed to setup history picker shortcut: {}", e);
+            }
+            
+            if let Err(e) = setup_accurate_shortcut(app.handle(), state.clone()) {
+                println!("❌ Failed to setup accurate dictation shortcut: {}", e);
+            }
+            
+            if let Err(e) = setup_snippet_shortcuts(app.handle()) {
+                println!("❌ Failed to setup snippet shortcuts: {}", e);
+            }
+            
+            // Push-to-talk from a USB foot pedal, if one is configured
+            restart_foot_pedal_listener(app.handle(), &state);
+            
+            // Publish dictation events for home automation, if a broker is configured
+            restart_mqtt_client(&state);
+            
+            // Let the companion browser extension insert into web apps
+            restart_browser_bridge(&state);
+            
+            // Scriptable org.cursper.Dictation service on the Linux session bus
+            start_dbus_service(app.handle(), &state);
+            
+            // Automation URLs (cursper://x-callback-url/...) from Shortcuts and scripts
+            let deep_link_handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    handle_deep_link(&deep_link_handle, &url);
+                }
+            });
+            
+            // Apply external edits to the settings file live
+            if let Err(e) = watch_settings_file(app.handle().clone(), state.clone()) {
+                println!("❌ Failed to watch settings file: {}", e);
+            }
+            
+            // Unload the model when dictation has been idle for a while
+            spawn_idle_unload_monitor(app.handle().clone(), state.clone());
+            
+            // Enforce retention limits on kept recordings
+            spawn_retention_cleanup(app.handle().clone(), state.clone());
+            
+            // Send reports from earlier crashes if the user opted in
+            spawn_pending_crash_upload(app.handle().clone(), state.clone());
+            
+            // Recover from a poisoned state mutex after a panic
+            spawn_state_watchdog(app.handle().clone(), state.clone());
+            
+            // Hide the overlay automatically while screen sharing
+            spawn_screen_share_monitor(app.handle().clone(), state.clone());
+            
+            // Look for new releases on the configured channel
+            spawn_update_checker(app.handle().clone(), state.clone());
+            
             println!("🎉 Application setup completed successfully");
             Ok(())
         })
@@ -0,0 +1,316 @@
+use tokio::sync::Mutex;
+use crate::types::AppStateType;
+
+// Players paused when the current recording started, resumed when it stops. Held across
+// the whole pause or resume so a quick stop can't overtake the pause it undoes.
+static PAUSED_PLAYERS: Mutex<Vec<String>> = Mutex::const_new(Vec::new());
+
+// Output volume before it was muted or ducked for the current recording
+static SAVED_OUTPUT: Mutex<Option<platform::OutputLevel>> = Mutex::const_new(None);
+
+// Pause background audio and mute or duck system output while the microphone is live,
+// as configured, and undo it once recording stops
+pub fn on_recording_state(state: &AppStateType, is_recording: bool) {
+    let (pause, resume, mute, duck_percent) = match state.lock() {
+        Ok(app_state) => (
+            app_state.settings.pause_media_while_recording,
+            app_state.settings.resume_media_after_recording,
+            app_state.settings.mute_output_while_recording,
+            app_state.settings.duck_output_percent,
+        ),
+        Err(_) => return,
+    };
+    
+    if pause {
+        tauri::async_runtime::spawn(async move {
+            let mut paused = PAUSED_PLAYERS.lock().await;
+            if is_recording {
+                if paused.is_empty() {
+                    *paused = platform::pause_playing().await;
+                    if !paused.is_empty() {
+                        println!("⏸️ Paused media while recording: {}", paused.join(", "));
+                    }
+                }
+            } else {
+                let players = std::mem::take(&mut *paused);
+                if resume && !players.is_empty() {
+                    println!("▶️ Resuming media: {}", players.join(", "));
+                    platform::resume(&players).await;
+                }
+            }
+        });
+    }
+    
+    // Restoring doesn't depend on the setting, so turning it off mid-recording is safe
+    if mute || !is_recording {
+        tauri::async_runtime::spawn(async move {
+            let mut saved = SAVED_OUTPUT.lock().await;
+            if is_recording {
+                if saved.is_none() {
+                    *saved = platform::lower_output(duck_percent.map(|percent| percent.min(100))).await;
+                }
+            } else if let Some(level) = saved.take() {
+                platform::restore_output(level).await;
+            }
+        });
+    }
+}
+
+// MPRIS players on the session bus; only those actually playing are paused
+#[cfg(target_os = "linux")]
+mod platform {
+    const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+    const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+    const MPRIS_PLAYER: &str = "org.mpris.MediaPlayer2.Player";
+    
+    // Default PulseAudio/PipeWire sink before it was lowered
+    pub struct OutputLevel {
+        volume: Option<String>,
+        muted: bool,
+    }
+    
+    fn pactl(args: &[&str]) -> Option<String> {
+        let output = std::process::Command::new("pactl").args(args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+    
+    async fn player(connection: &zbus::Connection, name: &str) -> zbus::Result<zbus::Proxy<'static>> {
+        zbus::Proxy::new(connection, name.to_string(), MPRIS_PATH, MPRIS_PLAYER).await
+    }
+    
+    async fn pause_all(connection: &zbus::Connection) -> zbus::Result<Vec<String>> {
+        let names = zbus::fdo::DBusProxy::new(connection).await?.list_names().await?;
+        let mut paused = Vec::new();
+        for name in names.iter().map(|name| name.as_str()).filter(|name| name.starts_with(MPRIS_PREFIX)) {
+            let player = player(connection, name).await?;
+            if player.get_property::<String>("PlaybackStatus").await.as_deref() != Ok("Playing") {
+                continue;
+            }
+            match player.call_method("Pause", &()).await {
+                Ok(_) => paused.push(name.to_string()),
+                Err(e) => println!("⚠️ Failed to pause {}: {}", name, e),
+            }
+        }
+        Ok(paused)
+    }
+    
+    pub async fn pause_playing() -> Vec<String> {
+        let result = match zbus::Connection::session().await {
+            Ok(connection) => pause_all(&connection).await,
+            Err(e) => Err(e),
+        };
+        result.unwrap_or_else(|e| {
+            println!("⚠️ Failed to pause media players: {}", e);
+            Vec::new()
+        })
+    }
+    
+    pub async fn resume(players: &[String]) {
+        let connection = match zbus::Connection::session().await {
+            Ok(connection) => connection,
+            Err(e) => return println!("⚠️ Failed to resume media players: {}", e),
+        };
+        for name in players {
+            let result = match player(&connection, name).await {
+                Ok(player) => player.call_method("Play", &()).await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                println!("⚠️ Failed to resume {}: {}", name, e);
+            }
+        }
+    }
+    
+    // Mute the default sink, or set it to duck_percent when given
+    pub async fn lower_output(duck_percent: Option<u8>) -> Option<OutputLevel> {
+        tauri::async_runtime::spawn_blocking(move || {
+            // "Mute: yes" / "Volume: front-left: 42000 /  64% / ..."
+            let muted = pactl(&["get-sink-mute", "@DEFAULT_SINK@"])?.ends_with("yes");
+            let volume = pactl(&["get-sink-volume", "@DEFAULT_SINK@"])
+                .and_then(|volume| volume.split('/').nth(1).map(|percent| percent.trim().to_string()));
+            let lowered = match duck_percent {
+                Some(percent) => pactl(&["set-sink-volume", "@DEFAULT_SINK@", &format!("{}%", percent)]),
+                None => pactl(&["set-sink-mute", "@DEFAULT_SINK@", "1"]),
+            };
+            if lowered.is_none() {
+                println!("⚠️ Failed to lower output volume with pactl");
+                return None;
+            }
+            println!("🔇 Lowered output volume while recording");
+            Some(OutputLevel { volume, muted })
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+    
+    pub async fn restore_output(level: OutputLevel) {
+        let _ = tauri::async_runtime::spawn_blocking(move || {
+            if let Some(volume) = level.volume {
+                pactl(&["set-sink-volume", "@DEFAULT_SINK@", &volume]);
+            }
+            pactl(&["set-sink-mute", "@DEFAULT_SINK@", if level.muted { "1" } else { "0" }]);
+            println!("🔊 Restored output volume");
+        })
+        .await;
+    }
+}
+
+// Music and Spotify are asked directly, so a player that wasn't playing is never started
+#[cfg(target_os = "macos")]
+mod platform {
+    const PLAYERS: &[&str] = &["Music", "Spotify"];
+    
+    // System output volume (0-100) before it was lowered
+    pub struct OutputLevel {
+        volume: u8,
+        muted: bool,
+    }
+    
+    fn run_script(script: &str) -> Option<String> {
+        let output = std::process::Command::new("osascript").args(["-e", script]).output().ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+    
+    pub async fn pause_playing() -> Vec<String> {
+        tauri::async_runtime::spawn_blocking(|| {
+            PLAYERS
+                .iter()
+                .filter(|player| {
+                    // Checking "running" first keeps the script from launching the player
+                    let script = format!(
+                        "if application \"{0}\" is running then\n\
+                             tell application \"{0}\"\n\
+                                 if player state is playing then\n\
+                                     pause\n\
+                                     return \"paused\"\n\
+                                 end if\n\
+                             end tell\n\
+                         end if",
+                        player
+                    );
+                    run_script(&script).as_deref() == Some("paused")
+                })
+                .map(|player| player.to_string())
+                .collect()
+        })
+        .await
+        .unwrap_or_default()
+    }
+    
+    pub async fn resume(players: &[String]) {
+        let players = players.to_vec();
+        let _ = tauri::async_runtime::spawn_blocking(move || {
+            for player in players {
+                run_script(&format!("tell application \"{}\" to play", player));
+            }
+        })
+        .await;
+    }
+    
+    // Mute system output, or set it to duck_percent when given
+    pub async fn lower_output(duck_percent: Option<u8>) -> Option<OutputLevel> {
+        tauri::async_runtime::spawn_blocking(move || {
+            // "42,false"
+            let settings = run_script("set levels to get volume settings\n\
+                                       return ((output volume of levels) as text) & \",\" & ((output muted of levels) as text)")?;
+            let (volume, muted) = settings.split_once(',')?;
+            let level = OutputLevel {
+                volume: volume.trim().parse().ok()?,
+                muted: muted.trim() == "true",
+            };
+            match duck_percent {
+                Some(percent) => run_script(&format!("set volume output volume {}", percent)),
+                None => run_script("set volume with output muted"),
+            }?;
+            println!("🔇 Lowered output volume while recording");
+            Some(level)
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+    
+    pub async fn restore_output(level: OutputLevel) {
+        let _ = tauri::async_runtime::spawn_blocking(move || {
+            let muted = if level.muted { "with" } else { "without" };
+            run_script(&format!("set volume output volume {} {} output muted", level.volume, muted));
+            println!("🔊 Restored output volume");
+        })
+        .await;
+    }
+}
+
+// Windows can't tell whether anything is playing without WinRT, so the play/pause media
+// key is sent and sent again to resume. Likewise the volume can't be read without COM, so
+// output is muted with the mute key (also when ducking was asked for) and unmuted with it.
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VIRTUAL_KEY, VK_MEDIA_PLAY_PAUSE, VK_VOLUME_MUTE,
+    };
+    
+    const MEDIA_KEY: &str = "media key";
+    
+    // Output was muted by toggling the mute key
+    pub struct OutputLevel;
+    
+    fn tap_key(key: VIRTUAL_KEY) {
+        let input = |flags| INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: key,
+                    wScan: 0,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        };
+        let inputs = [input(0), input(KEYEVENTF_KEYUP)];
+        unsafe {
+            SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<INPUT>() as i32);
+        }
+    }
+    
+    pub async fn pause_playing() -> Vec<String> {
+        tap_key(VK_MEDIA_PLAY_PAUSE);
+        vec![MEDIA_KEY.to_string()]
+    }
+    
+    pub async fn resume(_players: &[String]) {
+        tap_key(VK_MEDIA_PLAY_PAUSE);
+    }
+    
+    pub async fn lower_output(_duck_percent: Option<u8>) -> Option<OutputLevel> {
+        tap_key(VK_VOLUME_MUTE);
+        println!("🔇 Muted output while recording");
+        Some(OutputLevel)
+    }
+    
+    pub async fn restore_output(_level: OutputLevel) {
+        tap_key(VK_VOLUME_MUTE);
+        println!("🔊 Restored output volume");
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    pub struct OutputLevel;
+    
+    pub async fn pause_playing() -> Vec<String> {
+        Vec::new()
+    }
+    
+    pub async fn resume(_players: &[String]) {}
+    
+    pub async fn lower_output(_duck_percent: Option<u8>) -> Option<OutputLevel> {
+        None
+    }
+    
+    pub async fn restore_output(_level: OutputLevel) {}
+}
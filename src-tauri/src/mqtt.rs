@@ -0,0 +1,165 @@
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Outgoing, QoS};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::privacy::is_incognito;
+use crate::types::{AppStateType, TranscriptionResult};
+
+// Delay before polling again after the broker connection dropped
+const MQTT_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+// Requests queued while the broker is unreachable
+const MQTT_QUEUE_CAPACITY: usize = 32;
+
+// Bumped whenever the connection is replaced, e.g. the broker settings changed
+static MQTT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// Client of the running connection, if MQTT is enabled
+static MQTT_CLIENT: Mutex<Option<(AsyncClient, MqttSettings)>> = Mutex::new(None);
+
+// Broker connection and topics for publishing dictation events
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttSettings {
+    pub host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    // Topics are published under this prefix: <prefix>/status, <prefix>/state and
+    // <prefix>/transcription
+    #[serde(default = "default_topic_prefix")]
+    pub topic_prefix: String,
+    // Also publish transcription text; off publishes only state changes
+    #[serde(default)]
+    pub publish_transcriptions: bool,
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_topic_prefix() -> String {
+    "cursper".to_string()
+}
+
+// Body published on <prefix>/transcription
+#[derive(Serialize)]
+struct TranscriptionMessage<'a> {
+    text: &'a str,
+    duration_ms: u64,
+    language: Option<&'a str>,
+    timestamp: u64,
+}
+
+fn topic(settings: &MqttSettings, name: &str) -> String {
+    format!("{}/{}", settings.topic_prefix.trim_end_matches('/'), name)
+}
+
+fn publish(name: &str, retain: bool, payload: Vec<u8>) {
+    let client = match MQTT_CLIENT.lock() {
+        Ok(client) => client,
+        Err(_) => return,
+    };
+    if let Some((client, settings)) = client.as_ref() {
+        if let Err(e) = client.try_publish(topic(settings, name), QoS::AtLeastOnce, retain, payload) {
+            println!("⚠️ Failed to queue MQTT message: {}", e);
+        }
+    }
+}
+
+// Publish "recording" or "idle" on <prefix>/state, retained so new subscribers see it
+pub fn publish_recording_state(is_recording: bool) {
+    let state = if is_recording { "recording" } else { "idle" };
+    publish("state", true, state.as_bytes().to_vec());
+}
+
+// Publish a final transcription when enabled; incognito dictations are never published
+pub fn publish_transcription(result: &TranscriptionResult) {
+    let enabled = MQTT_CLIENT
+        .lock()
+        .map(|client| client.as_ref().is_some_and(|(_, settings)| settings.publish_transcriptions))
+        .unwrap_or(false);
+    if !enabled || is_incognito() {
+        return;
+    }
+    
+    let message = TranscriptionMessage {
+        text: &result.text,
+        duration_ms: result.timings.capture_ms,
+        language: result.language.as_deref(),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or(0),
+    };
+    match serde_json::to_vec(&message) {
+        Ok(payload) => publish("transcription", false, payload),
+        Err(e) => println!("⚠️ Failed to serialize MQTT transcription: {}", e),
+    }
+}
+
+// Drop any running connection and connect to the configured broker
+pub fn restart_mqtt_client(state: &AppStateType) {
+    let generation = MQTT_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    if let Ok(mut client) = MQTT_CLIENT.lock() {
+        if let Some((client, settings)) = client.take() {
+            let _ = client.try_publish(topic(&settings, "status"), QoS::AtLeastOnce, true, "offline");
+            let _ = client.try_disconnect();
+        }
+    }
+    
+    let settings = state
+        .lock()
+        .map(|app_state| app_state.settings.mqtt.clone())
+        .unwrap_or_default();
+    let settings = match settings.filter(|settings| !settings.host.trim().is_empty()) {
+        Some(settings) => settings,
+        None => return,
+    };
+    
+    let mut options = MqttOptions::new(format!("cursper-{}", std::process::id()), settings.host.trim(), settings.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some(username) = settings.username.clone().filter(|username| !username.is_empty()) {
+        options.set_credentials(username, settings.password.clone().unwrap_or_default());
+    }
+    // The broker marks us offline if the app dies without disconnecting
+    options.set_last_will(LastWill::new(topic(&settings, "status"), "offline", QoS::AtLeastOnce, true));
+    
+    let (client, mut event_loop) = AsyncClient::new(options, MQTT_QUEUE_CAPACITY);
+    let _ = client.try_publish(topic(&settings, "status"), QoS::AtLeastOnce, true, "online");
+    let _ = client.try_publish(topic(&settings, "state"), QoS::AtLeastOnce, true, "idle");
+    println!("📡 Connecting to MQTT broker {}:{}", settings.host, settings.port);
+    if let Ok(mut current) = MQTT_CLIENT.lock() {
+        *current = Some((client, settings));
+    }
+    
+    // The event loop drives the connection; polling again after an error reconnects.
+    // A replaced connection keeps polling until its "offline" message and disconnect
+    // are sent, unless the broker is unreachable anyway.
+    tauri::async_runtime::spawn(async move {
+        let mut connected = false;
+        loop {
+            match event_loop.poll().await {
+                Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                Ok(_) => {
+                    if !connected {
+                        println!("✅ Connected to MQTT broker");
+                        connected = true;
+                    }
+                }
+                Err(e) => {
+                    if MQTT_GENERATION.load(Ordering::SeqCst) != generation {
+                        break;
+                    }
+                    println!("⚠️ MQTT connection error: {}", e);
+                    connected = false;
+                    tokio::time::sleep(MQTT_RECONNECT_DELAY).await;
+                }
+            }
+        }
+        println!("📡 MQTT client stopped");
+    });
+}
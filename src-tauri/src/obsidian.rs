@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use crate::app_profiles::profile_for_app;
+use crate::types::AppStateType;
+
+// Marker in the header template replaced by the note's date
+const DATE_PLACEHOLDER: &str = "{date}";
+
+// Where a finished dictation goes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputSink {
+    // Type it at the cursor
+    #[default]
+    Type,
+    // Append it to today's daily note in the Obsidian vault
+    Obsidian,
+}
+
+// Daily-note location and format in an Obsidian (or any Markdown) vault
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObsidianSettings {
+    pub vault_path: String,
+    // Folder inside the vault holding daily notes, e.g. "Daily"
+    #[serde(default)]
+    pub daily_folder: String,
+    // chrono format of the note's file name, without ".md"
+    #[serde(default = "default_note_format")]
+    pub note_format: String,
+    // Written to a note when it is created; {date} is the note's date
+    #[serde(default = "default_header_template")]
+    pub header_template: String,
+}
+
+fn default_note_format() -> String {
+    "%Y-%m-%d".to_string()
+}
+
+fn default_header_template() -> String {
+    format!("# {}\n\n", DATE_PLACEHOLDER)
+}
+
+// Today's daily note path in the vault
+fn daily_note_path(settings: &ObsidianSettings, now: &chrono::DateTime<chrono::Local>) -> PathBuf {
+    PathBuf::from(&settings.vault_path)
+        .join(&settings.daily_folder)
+        .join(format!("{}.md", now.format(&settings.note_format)))
+}
+
+// Append a timestamped entry to today's note, creating it with the header if missing
+pub fn append_to_daily_note(settings: &ObsidianSettings, text: &str) -> Result<PathBuf, String> {
+    if settings.vault_path.trim().is_empty() {
+        return Err("No Obsidian vault configured".to_string());
+    }
+    
+    let now = chrono::Local::now();
+    let path = daily_note_path(settings, &now);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create daily note folder: {}", e))?;
+    }
+    
+    let is_new = !path.exists();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open daily note: {}", e))?;
+    if is_new {
+        let header = settings
+            .header_template
+            .replace(DATE_PLACEHOLDER, &now.format("%Y-%m-%d").to_string());
+        file.write_all(header.as_bytes())
+            .map_err(|e| format!("Failed to write daily note header: {}", e))?;
+    }
+    
+    writeln!(file, "- {} {}", now.format("%H:%M"), text.trim())
+        .map_err(|e| format!("Failed to append to daily note: {}", e))?;
+    Ok(path)
+}
+
+// Sink for the application dictated into: its profile's choice, else the global one
+pub fn output_sink(state: &AppStateType) -> OutputSink {
+    state
+        .lock()
+        .map(|app_state| {
+            app_state
+                .focused_app
+                .as_deref()
+                .and_then(|app| profile_for_app(&app_state.settings.app_profiles, app))
+                .and_then(|profile| profile.sink)
+                .unwrap_or(app_state.settings.output_sink)
+        })
+        .unwrap_or_default()
+}
+
+// Send a dictation to the Obsidian vault
+pub fn send_to_obsidian(state: &AppStateType, text: &str) -> Result<(), String> {
+    let settings = state
+        .lock()
+        .map_err(|e| e.to_string())?
+        .settings
+        .obsidian
+        .clone()
+        .ok_or("No Obsidian vault configured")?;
+    let path = append_to_daily_note(&settings, text)?;
+    println!("📓 Appended dictation to {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use crate::settings::save_settings;
+use crate::shortcuts::handle_recording_shortcut;
+use crate::types::AppStateType;
+
+// How long a read waits for a report before checking whether the listener was replaced
+const PEDAL_READ_TIMEOUT_MS: i32 = 200;
+
+// How long to wait for a pedal press while learning its button
+const LEARN_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Delay before reopening a pedal that was unplugged
+const PEDAL_RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+// Bumped whenever the listener should stop, e.g. the pedal was changed or is being learned
+static PEDAL_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// USB foot pedal used for push-to-talk
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct FootPedal {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    // Bit of the HID report that is set while the push-to-talk pedal is held
+    pub button: u16,
+}
+
+// A HID device offered when choosing a pedal
+#[derive(Clone, Serialize)]
+pub struct HidDeviceSummary {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+}
+
+// Bits set in a report, numbered from the first byte's lowest bit
+fn pressed_buttons(report: &[u8]) -> Vec<u16> {
+    report
+        .iter()
+        .enumerate()
+        .flat_map(|(byte, value)| (0..8).filter(move |bit| value & (1 << bit) != 0).map(move |bit| (byte * 8 + bit) as u16))
+        .collect()
+}
+
+fn open_pedal(vendor_id: u16, product_id: u16) -> Result<hidapi::HidDevice, String> {
+    let api = hidapi::HidApi::new().map_err(|e| format!("Failed to initialize HID: {}", e))?;
+    api.open(vendor_id, product_id)
+        .map_err(|e| format!("Failed to open pedal {:04x}:{:04x}: {}", vendor_id, product_id, e))
+}
+
+// Press the pedal to start recording, release it to stop and transcribe
+fn on_pedal_edge(app_handle: &AppHandle, state: &AppStateType, pressed: bool) {
+    let is_recording = state.lock().map(|app_state| app_state.is_recording).unwrap_or(false);
+    if pressed == is_recording {
+        return;
+    }
+    
+    println!("🦶 Foot pedal {}", if pressed { "pressed" } else { "released" });
+    tauri::async_runtime::spawn(handle_recording_shortcut(app_handle.clone(), state.clone()));
+}
+
+// Read the pedal until the listener is replaced, reopening it when unplugged
+fn run_pedal_listener(app_handle: AppHandle, state: AppStateType, pedal: FootPedal, generation: u64) {
+    let mut buffer = [0u8; 64];
+    
+    while PEDAL_GENERATION.load(Ordering::SeqCst) == generation {
+        let device = match open_pedal(pedal.vendor_id, pedal.product_id) {
+            Ok(device) => device,
+            Err(e) => {
+                println!("⚠️ {}", e);
+                std::thread::sleep(PEDAL_RECONNECT_DELAY);
+                continue;
+            }
+        };
+        println!("🦶 Listening to foot pedal {:04x}:{:04x}, button {}", pedal.vendor_id, pedal.product_id, pedal.button);
+        
+        let mut held = false;
+        while PEDAL_GENERATION.load(Ordering::SeqCst) == generation {
+            let length = match device.read_timeout(&mut buffer, PEDAL_READ_TIMEOUT_MS) {
+                Ok(0) => continue,
+                Ok(length) => length,
+                Err(e) => {
+                    println!("⚠️ Foot pedal disconnected: {}", e);
+                    break;
+                }
+            };
+            
+            let pressed = pressed_buttons(&buffer[..length]).contains(&pedal.button);
+            if pressed != held {
+                held = pressed;
+                on_pedal_edge(&app_handle, &state, pressed);
+            }
+        }
+        
+        // Don't leave a recording running because the pedal went away mid-press
+        if held {
+            on_pedal_edge(&app_handle, &state, false);
+        }
+    }
+    
+    println!("🦶 Foot pedal listener stopped");
+}
+
+// Stop any running pedal listener and start one for the configured pedal
+pub fn restart_foot_pedal_listener(app_handle: &AppHandle, state: &AppStateType) {
+    let generation = PEDAL_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let pedal = state
+        .lock()
+        .map(|app_state| app_state.settings.foot_pedal.clone())
+        .unwrap_or_default();
+    let pedal = match pedal {
+        Some(pedal) => pedal,
+        None => return,
+    };
+    
+    let app_handle = app_handle.clone();
+    let state = state.clone();
+    let result = std::thread::Builder::new()
+        .name("cursper-foot-pedal".to_string())
+        .spawn(move || run_pedal_listener(app_handle, state, pedal, generation));
+    if let Err(e) = result {
+        println!("❌ Failed to start foot pedal listener: {}", e);
+    }
+}
+
+// List connected HID devices so the user can pick their pedal
+#[tauri::command]
+pub async fn list_hid_devices() -> Result<Vec<HidDeviceSummary>, String> {
+    let api = hidapi::HidApi::new().map_err(|e| format!("Failed to initialize HID: {}", e))?;
+    let mut devices: Vec<HidDeviceSummary> = Vec::new();
+    for info in api.device_list() {
+        // A device shows up once per interface; list it once
+        if devices
+            .iter()
+            .any(|device| device.vendor_id == info.vendor_id() && device.product_id == info.product_id())
+        {
+            continue;
+        }
+        devices.push(HidDeviceSummary {
+            vendor_id: info.vendor_id(),
+            product_id: info.product_id(),
+            manufacturer: info.manufacturer_string().map(|name| name.to_string()),
+            product: info.product_string().map(|name| name.to_string()),
+        });
+    }
+    Ok(devices)
+}
+
+// Use a pedal for push-to-talk, or none
+#[tauri::command]
+pub async fn set_foot_pedal(app_handle: AppHandle, pedal: Option<FootPedal>, state: tauri::State<'_, AppStateType>) -> Result<(), String> {
+    let settings = {
+        let mut app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.foot_pedal = pedal;
+        app_state.settings.clone()
+    };
+    save_settings(&app_handle, &settings)?;
+    
+    restart_foot_pedal_listener(&app_handle, state.inner());
+    Ok(())
+}
+
+// Wait for the user to press a pedal on the device and return the button it maps to
+#[tauri::command]
+pub async fn learn_foot_pedal_button(
+    app_handle: AppHandle,
+    vendor_id: u16,
+    product_id: u16,
+    state: tauri::State<'_, AppStateType>,
+) -> Result<u16, String> {
+    println!("🦶 Press the pedal to map...");
+    
+    // The running listener would hold the device and start recordings meanwhile
+    PEDAL_GENERATION.fetch_add(1, Ordering::SeqCst);
+    
+    let result = tokio::task::spawn_blocking(move || {
+        let device = open_pedal(vendor_id, product_id)?;
+        let mut buffer = [0u8; 64];
+        let mut first: Option<Vec<u16>> = None;
+        let started = Instant::now();
+        
+        while started.elapsed() < LEARN_TIMEOUT {
+            let length = device
+                .read_timeout(&mut buffer, PEDAL_READ_TIMEOUT_MS)
+                .map_err(|e| format!("Failed to read pedal: {}", e))?;
+            if length == 0 {
+                continue;
+            }
+            
+            // Compare with the first report so bits that are always set (like a report ID)
+            // are ignored; pedals that only report changes send the press first, so a bit
+            // that clears afterwards is the pedal too
+            let buttons = pressed_buttons(&buffer[..length]);
+            match &first {
+                None => first = Some(buttons),
+                Some(first) => {
+                    if let Some(button) = buttons.iter().find(|button| !first.contains(button)) {
+                        return Ok(*button);
+                    }
+                    if let Some(button) = first.iter().find(|button| !buttons.contains(button)) {
+                        return Ok(*button);
+                    }
+                }
+            }
+        }
+        Err("No pedal press detected".to_string())
+    })
+    .await
+    .map_err(|e| format!("Pedal learning task failed: {}", e))?;
+    
+    restart_foot_pedal_listener(&app_handle, state.inner());
+    
+    if let Ok(button) = &result {
+        println!("✅ Pedal mapped to button {}", button);
+    }
+    result
+}
@@ -0,0 +1,91 @@
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use crate::history::history_audio_path;
+use crate::types::{PlaybackEvent, PLAYBACK_EVENT};
+use crate::vault::decrypt;
+
+// How often the playback thread checks for the end of the clip or a stop request
+const PLAYBACK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// Stop flag of the clip currently playing; a new clip stops the previous one
+static CURRENT_PLAYBACK: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+
+fn emit_playback(app_handle: &AppHandle, id: u64, playing: bool) {
+    if let Err(e) = app_handle.emit(PLAYBACK_EVENT, PlaybackEvent { id, playing }) {
+        println!("⚠️ Failed to emit playback state: {}", e);
+    }
+}
+
+fn stop_current() {
+    if let Ok(mut current) = CURRENT_PLAYBACK.lock() {
+        if let Some(stop) = current.take() {
+            stop.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+// rodio's output stream isn't Send, so each clip plays on its own thread
+fn play_file(app_handle: AppHandle, id: u64, path: PathBuf, stop: Arc<AtomicBool>) -> Result<(), String> {
+    // Recordings may be encrypted at rest, so they are decoded from memory
+    let data = std::fs::read(&path).map_err(|e| format!("Failed to open recording: {}", e))?;
+    let source = rodio::Decoder::new(Cursor::new(decrypt(&data)?)).map_err(|e| format!("Failed to decode recording: {}", e))?;
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+    
+    std::thread::spawn(move || {
+        let output = rodio::OutputStream::try_default()
+            .map_err(|e| format!("Failed to open audio output: {}", e))
+            .and_then(|(stream, handle)| {
+                let sink = rodio::Sink::try_new(&handle).map_err(|e| format!("Failed to start playback: {}", e))?;
+                Ok((stream, sink))
+            });
+        let (_stream, sink) = match output {
+            Ok(output) => {
+                let _ = ready_tx.send(Ok(()));
+                output
+            }
+            Err(e) => {
+                let _ = ready_tx.send(Err(e));
+                return;
+            }
+        };
+        
+        println!("▶️ Playing recording for history entry {}", id);
+        emit_playback(&app_handle, id, true);
+        sink.append(source);
+        while !sink.empty() && !stop.load(Ordering::SeqCst) {
+            std::thread::sleep(PLAYBACK_POLL_INTERVAL);
+        }
+        sink.stop();
+        emit_playback(&app_handle, id, false);
+    });
+    
+    ready_rx
+        .recv()
+        .map_err(|_| "Playback thread exited unexpectedly".to_string())?
+}
+
+// Play the retained recording of a history entry, stopping anything already playing
+#[tauri::command]
+pub async fn play_history_audio(app_handle: AppHandle, id: u64) -> Result<(), String> {
+    let path = history_audio_path(&app_handle, id)?;
+    stop_current();
+    
+    let stop = Arc::new(AtomicBool::new(false));
+    if let Ok(mut current) = CURRENT_PLAYBACK.lock() {
+        *current = Some(stop.clone());
+    }
+    tauri::async_runtime::spawn_blocking(move || play_file(app_handle, id, path, stop))
+        .await
+        .map_err(|e| format!("Playback task failed: {}", e))?
+}
+
+// Stop playback started by play_history_audio
+#[tauri::command]
+pub async fn stop_playback() -> Result<(), String> {
+    stop_current();
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+// A file with this name next to the executable (or next to Cursper.app on macOS), or
+// launching with PORTABLE_FLAG, keeps everything in PORTABLE_DIR beside it
+const PORTABLE_MARKER: &str = "cursper.portable";
+const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_DIR: &str = "cursper-data";
+
+// Root of the portable data directory, or None when using the OS directories
+static PORTABLE_ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+// Where the portable directory goes: next to the binary, or next to the app bundle since
+// the bundle itself is signed and often read-only
+fn install_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?;
+    if cfg!(target_os = "macos") && dir.ends_with("Contents/MacOS") {
+        return dir.ancestors().nth(3).map(Path::to_path_buf);
+    }
+    Some(dir.to_path_buf())
+}
+
+fn portable_root() -> Option<&'static Path> {
+    PORTABLE_ROOT
+        .get_or_init(|| {
+            let dir = install_dir()?;
+            let requested = std::env::args().any(|arg| arg == PORTABLE_FLAG) || dir.join(PORTABLE_MARKER).exists();
+            requested.then(|| dir.join(PORTABLE_DIR))
+        })
+        .as_deref()
+}
+
+// Announce portable mode and point the webview's own data there; call before the app is built
+pub fn prepare_portable_mode() {
+    let root = match portable_root() {
+        Some(root) => root,
+        None => return,
+    };
+    println!("🧳 Portable mode: keeping settings and data in {}", root.display());
+    
+    // WebView2 otherwise keeps its cache under %LOCALAPPDATA%
+    #[cfg(target_os = "windows")]
+    std::env::set_var("WEBVIEW2_USER_DATA_FOLDER", root.join("webview"));
+}
+
+// Settings, templates and snippets
+pub fn app_config_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match portable_root() {
+        Some(root) => Ok(root.join("config")),
+        None => app
+            .path()
+            .app_config_dir()
+            .map_err(|e| format!("Failed to resolve config directory: {}", e)),
+    }
+}
+
+// History, recordings, statistics and the backend environment
+pub fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match portable_root() {
+        Some(root) => Ok(root.join("data")),
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve data directory: {}", e)),
+    }
+}
+
+// Crash reports
+pub fn app_log_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match portable_root() {
+        Some(root) => Ok(root.join("logs")),
+        None => app
+            .path()
+            .app_log_dir()
+            .map_err(|e| format!("Failed to resolve log directory: {}", e)),
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter};
+use crate::types::{IncognitoEvent, INCOGNITO_EVENT};
+
+// Session-only incognito flag, never persisted to settings
+static INCOGNITO: AtomicBool = AtomicBool::new(false);
+
+pub fn is_incognito() -> bool {
+    INCOGNITO.load(Ordering::SeqCst)
+}
+
+// Text safe to print to logs: redacted while incognito
+pub fn loggable(text: &str) -> String {
+    if is_incognito() {
+        format!("[redacted {} chars]", text.chars().count())
+    } else {
+        text.to_string()
+    }
+}
+
+// Switch incognito on/off and notify the tray and frontend
+pub fn set_incognito_internal(app_handle: &AppHandle, enabled: bool) {
+    INCOGNITO.store(enabled, Ordering::SeqCst);
+    println!("🕶️ Incognito dictation {}", if enabled { "enabled" } else { "disabled" });
+    
+    if let Err(e) = app_handle.emit(INCOGNITO_EVENT, IncognitoEvent { enabled }) {
+        println!("⚠️ Failed to emit incognito state: {}", e);
+    }
+}
+
+pub fn toggle_incognito_internal(app_handle: &AppHandle) -> bool {
+    let enabled = !is_incognito();
+    set_incognito_internal(app_handle, enabled);
+    enabled
+}
+
+// Enable or disable incognito dictation
+#[tauri::command]
+pub async fn set_incognito(app_handle: AppHandle, enabled: bool) -> Result<(), String> {
+    set_incognito_internal(&app_handle, enabled);
+    Ok(())
+}
+
+// Get whether incognito dictation is active
+#[tauri::command]
+pub async fn get_incognito() -> Result<bool, String> {
+    Ok(is_incognito())
+}
@@ -0,0 +1,60 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+// Owner-only permissions for Cursper's directories and for files holding dictated text,
+// audio or secrets, so other accounts on a shared machine can't read them. On Windows the
+// per-user AppData directories already carry owner-only ACLs.
+#[cfg(unix)]
+const PRIVATE_DIR_MODE: u32 = 0o700;
+#[cfg(unix)]
+const PRIVATE_FILE_MODE: u32 = 0o600;
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+fn private_options() -> OpenOptions {
+    #[allow(unused_mut)]
+    let mut options = OpenOptions::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(PRIVATE_FILE_MODE);
+    }
+    options
+}
+
+// Create a directory (and missing parents) only the current user can enter
+pub fn create_private_dir(dir: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    set_mode(dir, PRIVATE_DIR_MODE)?;
+    Ok(())
+}
+
+// Tighten a file created elsewhere, e.g. by SQLite or by an older version of Cursper
+pub fn restrict_file(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    set_mode(path, PRIVATE_FILE_MODE)?;
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+// Like std::fs::write, but the file is only readable by the current user
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut file = private_options().write(true).create(true).truncate(true).open(path)?;
+    // The mode only applies to new files
+    restrict_file(path)?;
+    file.write_all(contents.as_ref())
+}
+
+// Open a file for appending, creating it readable by the current user only
+pub fn append_private(path: &Path) -> io::Result<File> {
+    let file = private_options().create(true).append(true).open(path)?;
+    restrict_file(path)?;
+    Ok(file)
+}
@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use cursper_core::bandwidth::{throughput_kbps, BandwidthGovernor, QualityChange};
+use cursper_core::providers::{check_clip_budget, clip_cost, has_budget, provider_chain as core_provider_chain, BackendProvider, DailyUsage, ProviderUsage};
+use crate::settings::Settings;
+use crate::types::{BudgetWarningEvent, UploadQualityEvent, BUDGET_WARNING_EVENT, UPLOAD_QUALITY_EVENT};
+use crate::portable::app_data_dir;
+use crate::private_files::{create_private_dir, write_private};
+
+// Share of a daily budget at which a warning is emitted
+const BUDGET_WARNING_RATIO: f64 = 0.8;
+
+// Throughput of recent uploads to the primary backend
+static BANDWIDTH: Mutex<BandwidthGovernor> = Mutex::new(BandwidthGovernor::new());
+
+// The primary backend followed by the configured fallbacks, in order
+pub fn provider_chain(settings: &Settings, primary_url: &str) -> Vec<BackendProvider> {
+    core_provider_chain(primary_url, &settings.fallback_providers, settings.allow_cloud_fallback)
+}
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / (24 * 60 * 60))
+        .unwrap_or(0)
+}
+
+fn usage_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app_data_dir(app)?;
+    Ok(data_dir.join("provider_usage.json"))
+}
+
+// Load today's usage; a file from an earlier day counts as empty
+pub fn load_usage(app: &AppHandle) -> Result<DailyUsage, String> {
+    let path = usage_path(app)?;
+    let usage = if path.exists() {
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read provider usage: {}", e))?;
+        serde_json::from_str::<DailyUsage>(&contents)
+            .map_err(|e| format!("Failed to parse provider usage: {}", e))?
+    } else {
+        DailyUsage::default()
+    };
+    
+    if usage.day == today() {
+        Ok(usage)
+    } else {
+        Ok(DailyUsage { day: today(), ..DailyUsage::default() })
+    }
+}
+
+fn save_usage(app: &AppHandle, usage: &DailyUsage) -> Result<(), String> {
+    let path = usage_path(app)?;
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    }
+    
+    let contents = serde_json::to_string_pretty(usage)
+        .map_err(|e| format!("Failed to serialize provider usage: {}", e))?;
+    write_private(&path, contents).map_err(|e| format!("Failed to write provider usage: {}", e))
+}
+
+// Refuse an upload that would exceed the provider's clip length or daily budget
+pub fn check_budget(app: &AppHandle, provider: &BackendProvider, clip_seconds: f64) -> Result<(), String> {
+    // Only read the usage file for providers that have a daily budget
+    let used = if has_budget(provider) {
+        let usage = load_usage(app)?;
+        usage.providers.get(&provider.name).cloned().unwrap_or_default()
+    } else {
+        ProviderUsage::default()
+    };
+    check_clip_budget(provider, &used, clip_seconds)
+}
+
+// Add an upload to today's usage and warn when a budget is nearly used up
+pub fn record_usage(app: &AppHandle, provider: &BackendProvider, clip_seconds: f64) -> Result<(), String> {
+    if !has_budget(provider) && provider.cost_per_minute.is_none() {
+        return Ok(());
+    }
+    
+    let mut usage = load_usage(app)?;
+    let entry = usage.providers.entry(provider.name.clone()).or_default();
+    let before = entry.clone();
+    entry.audio_seconds += clip_seconds;
+    entry.cost += clip_cost(provider, clip_seconds);
+    let after = entry.clone();
+    save_usage(app, &usage)?;
+    
+    // Warn once, when crossing the threshold
+    let limits = [
+        ("audio_seconds", provider.max_audio_seconds_per_day.map(|max| max as f64), before.audio_seconds, after.audio_seconds),
+        ("cost", provider.max_cost_per_day, before.cost, after.cost),
+    ];
+    for (budget, limit, used_before, used_after) in limits {
+        let limit = match limit {
+            Some(limit) if limit > 0.0 => limit,
+            _ => continue,
+        };
+        if used_before < limit * BUDGET_WARNING_RATIO && used_after >= limit * BUDGET_WARNING_RATIO {
+            println!("💸 {} has used {:.0}% of its daily {} budget", provider.name, used_after / limit * 100.0, budget);
+            let event = BudgetWarningEvent {
+                provider: provider.name.clone(),
+                budget: budget.to_string(),
+                used: used_after,
+                limit,
+            };
+            if let Err(e) = app.emit(BUDGET_WARNING_EVENT, event) {
+                println!("⚠️ Failed to emit budget warning: {}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Whether uploads should be compressed because recent ones were slow
+pub fn compress_uploads() -> bool {
+    BANDWIDTH
+        .lock()
+        .map(|governor| governor.should_compress(Instant::now()))
+        .unwrap_or(false)
+}
+
+// Measure a finished upload and tell the user when uploads switch to or from compressed audio
+pub fn record_upload_throughput(app: &AppHandle, bytes: usize, upload_ms: u64, threshold_kbps: f64) {
+    let change = match BANDWIDTH.lock() {
+        Ok(mut governor) => governor.record(bytes, upload_ms, threshold_kbps, Instant::now()),
+        Err(_) => return,
+    };
+    let kbps = throughput_kbps(bytes, upload_ms);
+    let compressed = match change {
+        Some(QualityChange::Degraded) => {
+            println!("🐢 Uploads are slow ({:.0} kbit/s), switching to compressed audio", kbps);
+            true
+        }
+        Some(QualityChange::Restored) => {
+            println!("🚀 Uploads are fast again ({:.0} kbit/s), switching back to lossless audio", kbps);
+            false
+        }
+        None => return,
+    };
+    let event = UploadQualityEvent {
+        compressed,
+        throughput_kbps: kbps,
+    };
+    if let Err(e) = app.emit(UPLOAD_QUALITY_EVENT, event) {
+        println!("⚠️ Failed to emit upload quality change: {}", e);
+    }
+}
+
+// Today's usage per provider
+#[tauri::command]
+pub async fn get_provider_usage(app_handle: AppHandle) -> Result<DailyUsage, String> {
+    load_usage(&app_handle)
+}
@@ -0,0 +1,112 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tokio::sync::oneshot;
+use crate::jobs::{cancel_uploads, has_active_uploads};
+use crate::sequencer::outstanding_insertions;
+use crate::tray_menu::rebuild_tray_menu;
+use crate::types::recorder;
+use cursper_core::recorder::RecorderState;
+
+// How long quitting waits for transcriptions in flight to type their text
+const QUIT_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+// How often the quit path checks whether the transcriptions finished
+const QUIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Set while a quit is waiting for jobs or for the user's confirmation
+static QUITTING: AtomicBool = AtomicBool::new(false);
+
+pub fn is_quitting() -> bool {
+    QUITTING.load(Ordering::SeqCst)
+}
+
+fn set_quitting(app: &AppHandle, quitting: bool) {
+    QUITTING.store(quitting, Ordering::SeqCst);
+    rebuild_tray_menu(app);
+}
+
+fn has_jobs_in_flight() -> bool {
+    outstanding_insertions() > 0 || has_active_uploads()
+}
+
+// Wait until every transcription has inserted its text; false when the grace period ran out
+async fn wait_for_jobs() -> bool {
+    let started = Instant::now();
+    if has_jobs_in_flight() {
+        println!("⏳ Waiting up to {}s for transcriptions to finish before quitting...", QUIT_GRACE_PERIOD.as_secs());
+    }
+    while has_jobs_in_flight() {
+        if started.elapsed() >= QUIT_GRACE_PERIOD {
+            return false;
+        }
+        tokio::time::sleep(QUIT_POLL_INTERVAL).await;
+    }
+    true
+}
+
+// Ask before quitting would lose a dictation; closing the dialog keeps the app running
+async fn confirm_quit(app: &AppHandle, message: &str) -> bool {
+    let (answer, confirmed) = oneshot::channel();
+    app.dialog()
+        .message(message)
+        .title("Quit cursper?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Quit".to_string(), "Keep Running".to_string()))
+        .show(move |confirmed| {
+            let _ = answer.send(confirmed);
+        });
+    confirmed.await.unwrap_or(false)
+}
+
+// Drop the recording and uploads still running so nothing is left half done
+fn discard_jobs() {
+    if recorder().state() != RecorderState::Idle {
+        println!("🗑️ Discarding the recording in progress");
+        if let Err(e) = recorder().cancel() {
+            println!("⚠️ Failed to cancel recording: {}", e);
+        }
+    }
+    let cancelled = cancel_uploads(None);
+    if cancelled > 0 {
+        println!("🗑️ Discarded {} uploads in flight", cancelled);
+    }
+}
+
+// Let in-flight transcriptions type their text before the app exits. A recording, or a
+// transcription still running after the grace period, is only discarded once the user
+// confirms; false when they chose to keep the app running.
+pub async fn prepare_exit(app: &AppHandle) -> bool {
+    if QUITTING.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    rebuild_tray_menu(app);
+    
+    let exit = if recorder().state() != RecorderState::Idle {
+        confirm_quit(app, "A dictation is still being recorded. Quitting now discards its audio.").await
+    } else {
+        wait_for_jobs().await
+            || confirm_quit(app, "A transcription is still running. Quitting now discards its text.").await
+    };
+    if !exit {
+        println!("↩️ Quit cancelled, keeping the dictation");
+        set_quitting(app, false);
+        return false;
+    }
+    
+    discard_jobs();
+    true
+}
+
+// Back out of a prepared exit that couldn't go ahead, e.g. a failed update install
+pub fn cancel_exit(app: &AppHandle) {
+    set_quitting(app, false);
+}
+
+pub async fn quit_gracefully(app: AppHandle) {
+    if prepare_exit(&app).await {
+        println!("🚪 Quitting");
+        app.exit(0);
+    }
+}
@@ -0,0 +1,240 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use crate::privacy::is_incognito;
+use crate::settings::Settings;
+use crate::types::AppStateType;
+use crate::vault::{encrypt, history_encryption, is_encrypted};
+use crate::portable::app_data_dir;
+use crate::private_files::{create_private_dir, write_private};
+
+// How often the background task enforces retention limits
+const RETENTION_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+struct RecordingFile {
+    path: PathBuf,
+    modified: SystemTime,
+    size: u64,
+}
+
+// Resolve the directory holding retained recordings
+pub fn recordings_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app_data_dir(app)?;
+    Ok(data_dir.join("recordings"))
+}
+
+// Path of a retained recording relative to the recordings dir, as stored in history
+pub fn recording_name(app: &AppHandle, path: &Path) -> Option<String> {
+    let dir = recordings_dir(app).ok()?;
+    path.strip_prefix(&dir).ok().map(|name| name.to_string_lossy().to_string())
+}
+
+// Resolve a name from recording_name, refusing anything outside the recordings dir
+pub fn recording_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(name);
+    if relative.components().any(|component| !matches!(component, std::path::Component::Normal(_))) {
+        return Err(format!("Invalid recording name: {}", name));
+    }
+    Ok(recordings_dir(app)?.join(relative))
+}
+
+// Whether captured audio should be kept: retention enabled and incognito off
+pub fn should_retain_audio(app: &AppHandle) -> bool {
+    if is_incognito() {
+        return false;
+    }
+    
+    match app.try_state::<AppStateType>() {
+        Some(state) => state
+            .lock()
+            .map(|app_state| app_state.settings.retain_audio)
+            .unwrap_or(false),
+        None => false,
+    }
+}
+
+// Persist a captured WAV if audio retention is enabled and incognito is off
+pub fn save_recording(app: &AppHandle, wav_data: &[u8]) -> Result<Option<PathBuf>, String> {
+    if is_incognito() {
+        println!("🕶️ Incognito active, not persisting audio");
+        return Ok(None);
+    }
+    if !should_retain_audio(app) {
+        return Ok(None);
+    }
+    
+    let dir = recordings_dir(app)?;
+    create_private_dir(&dir)
+        .map_err(|e| format!("Failed to create recordings directory: {}", e))?;
+    
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("System clock error: {}", e))?
+        .as_millis();
+    let path = dir.join(format!("recording-{}.wav", millis));
+    
+    // Encrypted recordings keep their name; the contents identify them
+    let data = if history_encryption() { encrypt(wav_data)? } else { wav_data.to_vec() };
+    write_private(&path, data)
+        .map_err(|e| format!("Failed to write recording: {}", e))?;
+    
+    println!("💾 Recording saved to {}", path.display());
+    Ok(Some(path))
+}
+
+// Collect all WAV files under the recordings dir (including subfolders)
+fn collect_recordings(dir: &Path, files: &mut Vec<RecordingFile>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_recordings(&path, files);
+            continue;
+        }
+        if path.extension().and_then(|e| e.to_str()) != Some("wav") {
+            continue;
+        }
+        if let Ok(metadata) = entry.metadata() {
+            files.push(RecordingFile {
+                path,
+                modified: metadata.modified().unwrap_or(UNIX_EPOCH),
+                size: metadata.len(),
+            });
+        }
+    }
+}
+
+// Delete recordings exceeding the keep-last, max-age and max-size limits
+pub fn apply_retention(app: &AppHandle, settings: &Settings) -> Result<usize, String> {
+    let dir = recordings_dir(app)?;
+    let mut files = Vec::new();
+    collect_recordings(&dir, &mut files);
+    
+    // Newest first
+    files.sort_by(|a, b| b.modified.cmp(&a.modified));
+    
+    let now = SystemTime::now();
+    let mut kept = Vec::new();
+    let mut to_delete = Vec::new();
+    
+    for (index, file) in files.into_iter().enumerate() {
+        let too_many = settings
+            .retention_keep_last
+            .map(|keep_last| index >= keep_last)
+            .unwrap_or(false);
+        let too_old = settings
+            .retention_max_days
+            .map(|days| {
+                now.duration_since(file.modified).unwrap_or_default() > Duration::from_secs(days * 24 * 60 * 60)
+            })
+            .unwrap_or(false);
+        
+        if too_many || too_old {
+            to_delete.push(file);
+        } else {
+            kept.push(file);
+        }
+    }
+    
+    // Drop the oldest remaining files until under the size cap
+    if let Some(max_mb) = settings.retention_max_total_mb {
+        let max_bytes = max_mb * 1024 * 1024;
+        let mut total: u64 = kept.iter().map(|f| f.size).sum();
+        while total > max_bytes {
+            match kept.pop() {
+                Some(file) => {
+                    total -= file.size;
+                    to_delete.push(file);
+                }
+                None => break,
+            }
+        }
+    }
+    
+    let mut removed = 0;
+    for file in to_delete {
+        match std::fs::remove_file(&file.path) {
+            Ok(_) => removed += 1,
+            Err(e) => println!("⚠️ Failed to delete {}: {}", file.path.display(), e),
+        }
+    }
+    
+    if removed > 0 {
+        println!("🧹 Retention cleanup removed {} recordings", removed);
+    }
+    Ok(removed)
+}
+
+// Periodically enforce retention limits
+pub fn spawn_retention_cleanup(app_handle: AppHandle, state: AppStateType) {
+    tauri::async_runtime::spawn(async move {
+        println!("🧹 Recording retention task started");
+        
+        loop {
+            let settings = match state.lock() {
+                Ok(app_state) => Some(app_state.settings.clone()),
+                Err(e) => {
+                    println!("❌ Retention task failed to lock app state: {}", e);
+                    None
+                }
+            };
+            
+            if let Some(settings) = settings {
+                if let Err(e) = apply_retention(&app_handle, &settings) {
+                    println!("⚠️ Retention cleanup failed: {}", e);
+                }
+            }
+            
+            tokio::time::sleep(RETENTION_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+// Encrypt recordings retained before encryption was turned on; returns how many
+pub fn encrypt_recordings(app: &AppHandle) -> Result<usize, String> {
+    let dir = recordings_dir(app)?;
+    let mut files = Vec::new();
+    collect_recordings(&dir, &mut files);
+    
+    let mut encrypted = 0;
+    for file in files {
+        let data = std::fs::read(&file.path)
+            .map_err(|e| format!("Failed to read {}: {}", file.path.display(), e))?;
+        if is_encrypted(&data) {
+            continue;
+        }
+        // Write aside and rename so a crash mid-write can't destroy the recording
+        let temp_path = file.path.with_extension("wav.tmp");
+        write_private(&temp_path, encrypt(&data)?)
+            .map_err(|e| format!("Failed to encrypt {}: {}", file.path.display(), e))?;
+        std::fs::rename(&temp_path, &file.path)
+            .map_err(|e| format!("Failed to replace {}: {}", file.path.display(), e))?;
+        encrypted += 1;
+    }
+    Ok(encrypted)
+}
+
+// Delete all retained recordings
+#[tauri::command]
+pub async fn purge_recordings(app_handle: AppHandle) -> Result<usize, String> {
+    println!("🗑️ Purging all recordings...");
+    
+    let dir = recordings_dir(&app_handle)?;
+    let mut files = Vec::new();
+    collect_recordings(&dir, &mut files);
+    
+    let mut removed = 0;
+    for file in files {
+        match std::fs::remove_file(&file.path) {
+            Ok(_) => removed += 1,
+            Err(e) => println!("⚠️ Failed to delete {}: {}", file.path.display(), e),
+        }
+    }
+    
+    println!("✅ Purged {} recordings", removed);
+    Ok(removed)
+}
@@ -0,0 +1,242 @@
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use crate::types::{AppStateType, ScreenSharingEvent, SCREEN_SHARING_EVENT};
+use crate::window_manager::show_overlay;
+
+// How often the capture state is checked for active screen sharing
+const SCREEN_SHARE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+// Helper processes that only run while a meeting app is sharing the screen
+#[cfg(target_os = "macos")]
+const SCREEN_SHARE_PROCESSES: &[&str] = &["CptHost", "caphost", "ScreensharingAgent"];
+#[cfg(target_os = "windows")]
+const SCREEN_SHARE_PROCESSES: &[&str] = &["CptHost.exe", "caphost.exe"];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const SCREEN_SHARE_PROCESSES: &[&str] = &[];
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::{c_int, c_void};
+    
+    // macOS has no public API reporting capture by other apps, so only processes are checked
+    pub fn is_capturing() -> bool {
+        false
+    }
+    
+    // Names of running processes, from libproc
+    pub fn running_processes() -> Result<Vec<String>, String> {
+        let count = unsafe { libc::proc_listallpids(std::ptr::null_mut(), 0) };
+        if count <= 0 {
+            return Err("Failed to list processes".to_string());
+        }
+        // Leave room for processes started in between
+        let mut pids = vec![0 as c_int; count as usize + 32];
+        let size = (pids.len() * std::mem::size_of::<c_int>()) as c_int;
+        let count = unsafe { libc::proc_listallpids(pids.as_mut_ptr() as *mut c_void, size) };
+        if count <= 0 {
+            return Err("Failed to list processes".to_string());
+        }
+        Ok(pids[..(count as usize).min(pids.len())]
+            .iter()
+            .filter_map(|pid| {
+                let mut name = [0u8; 256];
+                let length = unsafe { libc::proc_name(*pid, name.as_mut_ptr() as *mut c_void, name.len() as u32) };
+                (length > 0).then(|| String::from_utf8_lossy(&name[..length as usize]).into_owned())
+            })
+            .collect())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::ptr::{null, null_mut};
+    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_SUCCESS, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+    use windows_sys::Win32::System::Registry::{
+        RegCloseKey, RegEnumKeyExW, RegOpenKeyExW, RegQueryValueExW, HKEY, HKEY_CURRENT_USER, KEY_READ,
+    };
+    // Windows records apps using Windows.Graphics.Capture like camera and microphone use;
+    // an entry that has started but not stopped is capturing right now
+    const CAPTURE_CONSENT_KEYS: &[&str] = &[
+        r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\graphicsCaptureProgrammatic",
+        r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\graphicsCaptureWithoutBorder",
+    ];
+    
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+    
+    unsafe fn open_key(parent: HKEY, path: &[u16]) -> Option<HKEY> {
+        let mut key: HKEY = null_mut();
+        (RegOpenKeyExW(parent, path.as_ptr(), 0, KEY_READ, &mut key) == ERROR_SUCCESS).then_some(key)
+    }
+    
+    unsafe fn subkey_names(key: HKEY) -> Vec<Vec<u16>> {
+        let mut names = Vec::new();
+        for index in 0.. {
+            let mut name = [0u16; 512];
+            let mut length = name.len() as u32;
+            let result = RegEnumKeyExW(key, index, name.as_mut_ptr(), &mut length, null(), null_mut(), null_mut(), null_mut());
+            if result != ERROR_SUCCESS {
+                break;
+            }
+            names.push(name[..length as usize].iter().copied().chain(Some(0)).collect());
+        }
+        names
+    }
+    
+    unsafe fn read_qword(key: HKEY, name: &str) -> Option<u64> {
+        let name = wide(name);
+        let mut value = 0u64;
+        let mut size = std::mem::size_of::<u64>() as u32;
+        let result = RegQueryValueExW(key, name.as_ptr(), null(), null_mut(), &mut value as *mut u64 as *mut u8, &mut size);
+        (result == ERROR_SUCCESS).then_some(value)
+    }
+    
+    // Check an app entry and the ones below it (desktop apps are grouped under NonPackaged)
+    unsafe fn entry_capturing(key: HKEY, depth: u32) -> bool {
+        let started = read_qword(key, "LastUsedTimeStart").is_some_and(|start| start > 0);
+        if started && read_qword(key, "LastUsedTimeStop") == Some(0) {
+            return true;
+        }
+        depth > 0 && subkey_names(key).iter().any(|name| match open_key(key, name) {
+            Some(child) => {
+                let capturing = entry_capturing(child, depth - 1);
+                RegCloseKey(child);
+                capturing
+            }
+            None => false,
+        })
+    }
+    
+    pub fn is_capturing() -> bool {
+        CAPTURE_CONSENT_KEYS.iter().any(|path| unsafe {
+            match open_key(HKEY_CURRENT_USER, &wide(path)) {
+                Some(key) => {
+                    let capturing = entry_capturing(key, 2);
+                    RegCloseKey(key);
+                    capturing
+                }
+                None => false,
+            }
+        })
+    }
+    
+    // Names of running processes, from a Toolhelp snapshot
+    pub fn running_processes() -> Result<Vec<String>, String> {
+        let mut names = Vec::new();
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return Err("Failed to list processes".to_string());
+            }
+            let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+            entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+            let mut found = Process32FirstW(snapshot, &mut entry);
+            while found != 0 {
+                let length = entry.szExeFile.iter().position(|c| *c == 0).unwrap_or(entry.szExeFile.len());
+                names.push(String::from_utf16_lossy(&entry.szExeFile[..length]));
+                found = Process32NextW(snapshot, &mut entry);
+            }
+            CloseHandle(snapshot);
+        }
+        Ok(names)
+    }
+}
+
+// Portals don't expose other apps' ScreenCast sessions, so Linux only checks processes
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    pub fn is_capturing() -> bool {
+        false
+    }
+    
+    // Names of running processes, read from /proc
+    pub fn running_processes() -> Result<Vec<String>, String> {
+        let entries = std::fs::read_dir("/proc").map_err(|e| format!("Failed to list processes: {}", e))?;
+        Ok(entries
+            .flatten()
+            .filter_map(|entry| std::fs::read_to_string(entry.path().join("comm")).ok())
+            .map(|name| name.trim().to_string())
+            .collect())
+    }
+}
+
+// Check whether the screen is being captured, or a known (or user-configured)
+// screen-sharing process is running
+pub fn is_screen_sharing(extra_processes: &[String]) -> Result<bool, String> {
+    if platform::is_capturing() {
+        return Ok(true);
+    }
+    let processes = platform::running_processes()?;
+    Ok(processes.iter().any(|process| {
+        SCREEN_SHARE_PROCESSES.iter().any(|known| process.eq_ignore_ascii_case(known))
+            || extra_processes.iter().any(|extra| process.eq_ignore_ascii_case(extra))
+    }))
+}
+
+// Whether toasts and dictation snippets should stay off-screen because it is being shared
+pub fn sharing_paused(app_handle: &AppHandle) -> bool {
+    app_handle
+        .try_state::<AppStateType>()
+        .and_then(|state| state.lock().ok().map(|app_state| {
+            app_state.settings.pause_overlay_while_sharing && app_state.screen_sharing
+        }))
+        .unwrap_or(false)
+}
+
+// Poll for screen sharing while auto-pause is enabled and publish changes
+pub fn spawn_screen_share_monitor(app_handle: AppHandle, state: AppStateType) {
+    tauri::async_runtime::spawn(async move {
+        println!("🖥️ Screen sharing monitor started");
+        
+        loop {
+            tokio::time::sleep(SCREEN_SHARE_CHECK_INTERVAL).await;
+            
+            let (enabled, extra_processes, was_sharing) = match state.lock() {
+                Ok(app_state) => (
+                    app_state.settings.pause_overlay_while_sharing,
+                    app_state.settings.screen_share_processes.clone(),
+                    app_state.screen_sharing,
+                ),
+                Err(e) => {
+                    println!("❌ Screen sharing monitor failed to lock app state: {}", e);
+                    continue;
+                }
+            };
+            
+            let sharing = if enabled {
+                match is_screen_sharing(&extra_processes) {
+                    Ok(sharing) => sharing,
+                    Err(e) => {
+                        println!("⚠️ Screen sharing check failed: {}", e);
+                        continue;
+                    }
+                }
+            } else {
+                false
+            };
+            
+            if sharing == was_sharing {
+                continue;
+            }
+            
+            if let Ok(mut app_state) = state.lock() {
+                app_state.screen_sharing = sharing;
+            }
+            
+            println!("🖥️ Screen sharing {}", if sharing { "detected, hiding overlay" } else { "ended" });
+            // An overlay shown before sharing began would otherwise stay on screen
+            if sharing {
+                if let Err(e) = show_overlay(app_handle.clone(), false).await {
+                    println!("⚠️ Failed to hide overlay for screen sharing: {}", e);
+                }
+            }
+            if let Err(e) = app_handle.emit(SCREEN_SHARING_EVENT, ScreenSharingEvent { sharing }) {
+                println!("⚠️ Failed to emit screen sharing state: {}", e);
+            }
+        }
+    });
+}
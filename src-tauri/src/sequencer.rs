@@ -0,0 +1,155 @@
+use std::sync::{Mutex, OnceLock};
+use crate::browser_bridge::insert_via_extension;
+use crate::privacy::loggable;
+use crate::text_input::{fit_to_caret, press_backspaces, run_post_insert_actions, type_text, PostInsertAction};
+use cursper_core::correction::correct_last_sentence;
+use cursper_core::sequencer::InsertionSequencer;
+
+// What a finished job does to the focused field once it is its turn
+enum Insertion {
+    Text(String),
+    // Replace the last sentence of the previous insertion ("fix that")
+    Correction(String),
+}
+
+static SEQUENCER: OnceLock<Mutex<InsertionSequencer<Insertion>>> = OnceLock::new();
+
+// Held while typing so drained batches never interleave
+static INSERTION_LOCK: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+
+// Text the latest insertion left before the caret, for corrections; cleared when a
+// post-insertion action moves focus away
+static LAST_INSERTION: Mutex<Option<String>> = Mutex::new(None);
+
+fn sequencer() -> &'static Mutex<InsertionSequencer<Insertion>> {
+    SEQUENCER.get_or_init(|| Mutex::new(InsertionSequencer::default()))
+}
+
+// Take a ticket for a transcription job that may insert text later
+pub fn issue_insertion_ticket() -> u64 {
+    let ticket = sequencer()
+        .lock()
+        .map(|mut sequencer| sequencer.issue_ticket())
+        .unwrap_or(0);
+    println!("🎫 Issued insertion ticket {}", ticket);
+    ticket
+}
+
+// Transcription jobs that haven't inserted their text (or skipped it) yet
+pub fn outstanding_insertions() -> u64 {
+    sequencer().lock().map(|sequencer| sequencer.outstanding()).unwrap_or(0)
+}
+
+// Complete a job and type all text that is now in order; pass None to skip this job.
+// The post-insertion actions run after each inserted text.
+pub async fn insert_in_order(ticket: u64, text: Option<String>, actions: &[PostInsertAction]) -> Result<(), String> {
+    complete_in_order(ticket, text.map(Insertion::Text), actions).await
+}
+
+// Complete a job by correcting the last sentence inserted before it
+pub async fn correct_in_order(ticket: u64, replacement: String) -> Result<(), String> {
+    complete_in_order(ticket, Some(Insertion::Correction(replacement)), &[]).await
+}
+
+async fn complete_in_order(ticket: u64, insertion: Option<Insertion>, actions: &[PostInsertAction]) -> Result<(), String> {
+    let _insertion_guard = INSERTION_LOCK
+        .get_or_init(|| tokio::sync::Mutex::new(()))
+        .lock()
+        .await;
+    
+    let ready = {
+        let mut sequencer = sequencer().lock().map_err(|e| e.to_string())?;
+        sequencer.complete(ticket, insertion)
+    };
+    
+    if ready.is_empty() {
+        println!("⏳ Ticket {} waiting for earlier transcriptions", ticket);
+    }
+    
+    // The batch has left the sequencer, so a failed insertion must not drop the ones after it
+    let mut first_error = None;
+    for insertion in ready {
+        let text = match insertion {
+            Insertion::Text(text) => text,
+            Insertion::Correction(replacement) => {
+                // A failed correction must not hold back the insertions queued after it
+                if let Err(e) = apply_correction(&replacement).await {
+                    println!("❌ Failed to correct last sentence: {}", e);
+                }
+                continue;
+            }
+        };
+        println!("⌨️  Inserting in order: '{}'", loggable(&text));
+        match insert_text(text, actions).await {
+            Ok(inserted) => remember_insertion(inserted, actions),
+            Err(e) => {
+                println!("❌ Failed to insert in order: {}", e);
+                // What is before the caret is unknown now, so don't correct it later
+                if let Ok(mut last) = LAST_INSERTION.lock() {
+                    *last = None;
+                }
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    
+    first_error.map_or(Ok(()), Err)
+}
+
+// Insert one text and press the keys after it; returns the text that was inserted
+async fn insert_text(text: String, actions: &[PostInsertAction]) -> Result<String, String> {
+    // Web apps get the text through the browser extension when it has a focused field
+    let inserted = match insert_via_extension(&text).await {
+        Some(Ok(_)) => text,
+        Some(Err(e)) => {
+            println!("⚠️ {}, typing instead", e);
+            let text = fit_to_caret(text).await;
+            type_text(text.clone()).await?;
+            text
+        }
+        None => {
+            let text = fit_to_caret(text).await;
+            type_text(text.clone()).await?;
+            text
+        }
+    };
+    run_post_insert_actions(actions).await?;
+    Ok(inserted)
+}
+
+// Track what an insertion left before the caret, including keys pressed after it
+fn remember_insertion(mut text: String, actions: &[PostInsertAction]) {
+    let mut still_focused = true;
+    for action in actions {
+        match action {
+            PostInsertAction::AppendSpace => text.push(' '),
+            PostInsertAction::PressEnter => text.push('\n'),
+            PostInsertAction::PressTab => still_focused = false,
+        }
+    }
+    if let Ok(mut last) = LAST_INSERTION.lock() {
+        *last = still_focused.then_some(text);
+    }
+}
+
+// Backspace over the changed part of the last sentence and type the replacement
+async fn apply_correction(replacement: &str) -> Result<(), String> {
+    let inserted = LAST_INSERTION
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("Nothing to correct")?;
+    let correction = correct_last_sentence(&inserted, replacement);
+    println!("✏️ Correcting last sentence: {} backspaces, then '{}'", correction.backspaces, loggable(&correction.insert));
+    
+    press_backspaces(correction.backspaces).await?;
+    type_text(correction.insert.clone()).await?;
+    
+    // A further correction replaces the corrected sentence
+    let kept = inserted.chars().count() - correction.backspaces;
+    let corrected = inserted.chars().take(kept).chain(correction.insert.chars()).collect();
+    if let Ok(mut last) = LAST_INSERTION.lock() {
+        *last = Some(corrected);
+    }
+    Ok(())
+}
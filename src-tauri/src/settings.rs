@@ -0,0 +1,983 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use notify::{RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use crate::backend::{load_standby_model, send_model_to_backend};
+use crate::shortcuts::{parse_shortcut, reregister_shortcuts};
+use crate::key_listener::{parse_low_level_shortcut, set_suppress_shortcut_keys};
+use crate::vault::{encrypt_existing_data, set_history_encryption};
+use crate::text_input::{set_insertion_options, set_paste_rtl_text, set_remote_desktop_delay_ms, set_smart_spacing, set_use_helper_processes, set_xdotool_options, InsertionMethod, PostInsertAction, XdotoolOptions};
+use crate::window_manager::{OverlayPosition, WindowGeometry, WindowPosition};
+use crate::tray_menu::rebuild_tray_menu;
+use crate::browser_bridge::restart_browser_bridge;
+use crate::mqtt::{restart_mqtt_client, MqttSettings};
+use crate::obsidian::{ObsidianSettings, OutputSink};
+use crate::pedal::{restart_foot_pedal_listener, FootPedal};
+use crate::updater::{forget_pending_update, UpdateChannel};
+use crate::app_profiles::{AppProfile, NormalizationOverride};
+use cursper_core::audio::{InputChannelMode, UploadFormat};
+use cursper_core::bidi::DirectionalMarks;
+use cursper_core::locale::locale_format;
+use cursper_core::normalize::NormalizationOptions;
+use cursper_core::profanity::ProfanityFilter;
+use cursper_core::providers::{AdaptiveModel, BackendProvider, DecodingOptions, AUTO_MODEL};
+use crate::http::strip_proxy_credentials;
+use crate::types::{AppStateType, SettingsReloadedEvent, SETTINGS_RELOADED_EVENT};
+use crate::portable::app_config_dir;
+use crate::private_files::{create_private_dir, restrict_file, write_private};
+
+// Wait for editors to finish writing before re-reading the settings file
+const SETTINGS_RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+// Version of the export bundle format, bumped on incompatible changes
+pub const SETTINGS_BUNDLE_VERSION: u32 = 1;
+
+// Version of the settings format; files from before versioning count as version 1.
+// Bump it together with a new entry in SETTINGS_MIGRATIONS when stored settings need
+// rewriting to keep their meaning.
+pub const SETTINGS_VERSION: u32 = 2;
+
+// Migrations in order; entry i upgrades raw settings from version i + 1 to i + 2
+const SETTINGS_MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[migrate_v1_to_v2];
+
+// Shared values this machine's hosts section replaced at load (None = absent), restored
+// when saving so the overrides don't leak to other machines
+static HOST_SHARED_VALUES: Mutex<Vec<(String, Option<Value>)>> = Mutex::new(Vec::new());
+
+// Portable settings export shared between machines or team members
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    pub exported_at: u64,
+    pub settings: Settings,
+}
+
+// User-configurable settings persisted to settings.json in the app config dir
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Format version, see SETTINGS_VERSION
+    pub version: u32,
+    // Per-machine overrides by hostname, merged over the rest at load time, for config
+    // dirs synced between machines, e.g. {"laptop": {"backend_url": "...", "input_device": "..."}}
+    pub hosts: HashMap<String, Value>,
+    // Whisper model size, or "auto" to choose per clip with adaptive_model
+    pub model: String,
+    // Short/long models and the clip length (seconds) dividing them, for the "auto" model
+    pub adaptive_model: AdaptiveModel,
+    // Model kept loaded next to `model` and used for dictations started with accurate_shortcut
+    pub accurate_model: Option<String>,
+    pub shortcut: String,
+    pub backend_url: String,
+    // Interpreter start_backend runs (None = first Python 3 on PATH); set by setup_backend_environment
+    pub backend_python: Option<String>,
+    // Dictation language passed to the backend (None = auto-detect)
+    pub language: Option<String>,
+    // Per-application overrides, e.g. German in Mail and English in Slack
+    pub app_profiles: Vec<AppProfile>,
+    // Most recently used languages, newest first
+    pub recent_languages: Vec<String>,
+    // Ask the backend for per-word timings
+    pub word_timestamps: bool,
+    // Advanced Whisper decoding (temperature, beam size, best-of, no-speech threshold)
+    pub decoding: DecodingOptions,
+    // Condition each dictation on the end of the previous one into the same app, so a long
+    // text dictated in several recordings keeps its sentence flow and casing
+    pub carry_over_context: bool,
+    // Show the words-per-minute of each dictation in the overlay
+    pub show_live_wpm: bool,
+    // Minutes without dictation before the backend unloads the model (0 = never)
+    pub model_idle_unload_minutes: u64,
+    // Show the cursor overlay while recording (false = headless, tray only)
+    pub show_overlay: bool,
+    // Follow the cursor, stay at a pinned spot or dock a slim status bar to the top or
+    // bottom screen edge
+    pub overlay_position: OverlayPosition,
+    // Where the overlay stays with overlay_position "fixed" (physical pixels)
+    pub overlay_fixed_position: Option<WindowPosition>,
+    // Where the settings window was last left, restored on launch
+    pub settings_window: Option<WindowGeometry>,
+    // Persist transcriptions to history (always skipped while incognito)
+    pub history_enabled: bool,
+    // Optional shortcut toggling incognito dictation
+    pub incognito_shortcut: Option<String>,
+    // Optional keys that stop-and-insert or cancel a recording, e.g. "Enter" and "Escape";
+    // registered only while recording so they don't swallow normal typing
+    pub stop_shortcut: Option<String>,
+    pub cancel_shortcut: Option<String>,
+    // Optional "fix that" shortcut: re-dictate, and replace, the last inserted sentence
+    pub correction_shortcut: Option<String>,
+    // Optional shortcut showing the command palette, for actions without their own hotkey
+    pub palette_shortcut: Option<String>,
+    // Optional shortcut showing recent dictations to pick one and type it again
+    pub history_picker_shortcut: Option<String>,
+    // Optional variant of `shortcut` that dictates with accurate_model instead of `model`
+    pub accurate_shortcut: Option<String>,
+    // Bind keys the shortcut plugin can't (Fn/Globe, "DoubleFn", Win+H, "Dictation", media keys,
+    // F13-F24) through a low-level keyboard listener; needs Input Monitoring on macOS or the
+    // "input" group on Linux
+    pub low_level_shortcuts: bool,
+    // Keep the dictation shortcut's keys from reaching the focused app: its release no
+    // longer toggles recording, and low-level keys are consumed on macOS (needs
+    // Accessibility too) and Windows
+    pub suppress_shortcut_keys: bool,
+    // Ignore recording hotkeys firing within this many ms of the previous trigger (0 = off)
+    pub shortcut_debounce_ms: u64,
+    // USB foot pedal for push-to-talk (None = no pedal)
+    pub foot_pedal: Option<FootPedal>,
+    // Replace emails, phone numbers and card numbers in stored history (typed text is unaffected)
+    pub history_redact_pii: bool,
+    // Extra regex patterns redacted from history
+    pub history_redaction_patterns: Vec<String>,
+    // Keep captured audio on disk (never while incognito)
+    pub retain_audio: bool,
+    // Encrypt history and retained audio at rest with a key kept in the OS keychain
+    pub encrypt_history: bool,
+    // Retention limits for kept recordings (None = unlimited)
+    pub retention_keep_last: Option<usize>,
+    pub retention_max_days: Option<u64>,
+    pub retention_max_total_mb: Option<u64>,
+    // Hide the overlay, toasts and recent dictations while the screen is being shared;
+    // dictation keeps working
+    pub pause_overlay_while_sharing: bool,
+    // Additional process names that indicate active screen sharing
+    pub screen_share_processes: Vec<String>,
+    // Opt-in upload of crash reports (always kept locally)
+    pub crash_reporting_enabled: bool,
+    pub crash_report_url: Option<String>,
+    // Keys pressed after each insertion, e.g. Enter to send in chat apps
+    pub post_insert_actions: Vec<PostInsertAction>,
+    // Rewrite spoken numbers, currencies and dates as digits / ISO dates (English only)
+    pub normalize_numbers: bool,
+    pub normalize_currency: bool,
+    pub normalize_dates: bool,
+    // Locale whose decimal separator, currency placement and quotation marks the
+    // post-processed text uses, e.g. "de-CH" (None = the system locale)
+    pub locale: Option<String>,
+    // Mask or drop profanity before insertion, plus extra words to treat as profane
+    pub profanity_filter: ProfanityFilter,
+    pub profanity_words: Vec<String>,
+    // Share of characters that may differ when matching snippet trigger phrases (0 = exact)
+    pub snippet_trigger_tolerance: f64,
+    // Type and read the cursor via osascript/PowerShell/xdotool instead of in-process APIs
+    pub use_helper_processes: bool,
+    // Match spacing and capitalization to the text around the caret (macOS accessibility)
+    pub smart_spacing: bool,
+    // Type or paste dictations; auto pastes CJK and right-to-left text and whenever an input method is on
+    pub insertion_method: InsertionMethod,
+    // Pause after each character with the remote_desktop insertion method (at most 1000)
+    pub remote_desktop_delay_ms: u64,
+    // Pause between focus returning to the target app and typing starting (at most 5000)
+    pub pre_insert_delay_ms: u64,
+    // Insert right-to-left dictations (Hebrew, Arabic, ...) by pasting instead of typing in auto mode
+    pub paste_rtl_text: bool,
+    // Unicode directional marks added to right-to-left dictations before they are typed
+    pub directional_marks: DirectionalMarks,
+    // Keystroke delay, modifier clearing and keysym fallback for typing via xdotool on Linux
+    pub xdotool: XdotoolOptions,
+    // Which input channel(s) to record from multi-channel interfaces
+    pub input_channel: InputChannelMode,
+    // Input device to record from, by name as list_input_devices reports it (None = system default)
+    pub input_device: Option<String>,
+    // Play the microphone on the default output while recording (sidetone), at monitor_volume (0-1)
+    pub monitor_input: bool,
+    pub monitor_volume: f32,
+    // Trim leading/trailing silence below an RMS threshold, keeping some padding
+    pub trim_silence: bool,
+    pub silence_threshold: f32,
+    pub silence_padding_ms: u64,
+    // Recordings shorter than this are cancelled without calling the backend
+    pub min_recording_ms: u64,
+    // Show a toast when a short recording is cancelled
+    pub short_recording_toast: bool,
+    // Grace period with an overlay countdown before capture starts (0-1000 ms)
+    pub recording_countdown_ms: u64,
+    // Fade in the first milliseconds of a capture to suppress the hotkey's key click (0 = off)
+    pub key_click_gate_ms: u64,
+    // Upload as WAV or raw PCM; auto picks PCM when the backend supports it
+    pub upload_format: UploadFormat,
+    // Send Ogg Opus instead when uploads run slower than this (kbit/s); None never compresses
+    pub low_bandwidth_kbps: Option<f64>,
+    // Opus bitrate used on slow connections (6-128 kbit/s)
+    pub low_bandwidth_bitrate_kbps: u32,
+    // Providers tried in order when the primary backend fails or times out
+    pub fallback_providers: Vec<BackendProvider>,
+    // Allow falling back to providers marked as cloud
+    pub allow_cloud_fallback: bool,
+    // Explicit proxy for all outbound requests; credentials may be embedded in the URL
+    pub proxy_url: Option<String>,
+    // Honour system/environment proxy settings when no explicit proxy is set
+    pub use_system_proxy: bool,
+    // Client certificate for mutual TLS: a PEM certificate (with client_key_path)
+    // or a PKCS#12 bundle (.p12/.pfx, with client_cert_password)
+    pub client_cert_path: Option<String>,
+    // PKCS#8 PEM private key for a PEM client certificate
+    pub client_key_path: Option<String>,
+    // Password for a PKCS#12 client certificate bundle
+    pub client_cert_password: Option<String>,
+    // Extra PEM CA bundle trusted for self-hosted backends
+    pub ca_bundle_path: Option<String>,
+    // POST each final transcription as JSON to this URL
+    pub webhook_url: Option<String>,
+    // Key for the X-Cursper-Signature HMAC-SHA256 header; unsigned when unset
+    pub webhook_secret: Option<String>,
+    // Publish recording state and transcriptions to an MQTT broker
+    pub mqtt: Option<MqttSettings>,
+    // Where dictations go by default; app profiles can override it
+    pub output_sink: OutputSink,
+    // Daily-note vault for the Obsidian output sink
+    pub obsidian: Option<ObsidianSettings>,
+    // Localhost port for the browser extension bridge; disabled when unset
+    pub browser_bridge_port: Option<u16>,
+    // Let cursper:// automation URLs record and read dictations; asked once on first use
+    pub automation_urls_enabled: bool,
+    // Process and encode audio while it is captured, so stopping goes straight to upload
+    pub pipelined_finalize: bool,
+    // Pause playing music and video while recording, and resume them afterwards unless
+    // resume_media_after_recording is off
+    pub pause_media_while_recording: bool,
+    pub resume_media_after_recording: bool,
+    // Mute system output while recording, or lower it to duck_output_percent when set,
+    // and restore it afterwards
+    pub mute_output_while_recording: bool,
+    pub duck_output_percent: Option<u8>,
+    // Release channel updates come from
+    pub update_channel: UpdateChannel,
+    // Hours between automatic update checks (0 = only when asked)
+    pub update_check_interval_hours: u64,
+}
+
+impl Settings {
+    // Normalizations to apply, with an app profile's overrides taking precedence
+    pub fn normalization_options(&self, overrides: Option<&NormalizationOverride>) -> NormalizationOptions {
+        let overrides = overrides.cloned().unwrap_or_default();
+        NormalizationOptions {
+            numbers: overrides.numbers.unwrap_or(self.normalize_numbers),
+            currency: overrides.currency.unwrap_or(self.normalize_currency),
+            dates: overrides.dates.unwrap_or(self.normalize_dates),
+            format: locale_format(&overrides.locale.unwrap_or_else(|| self.effective_locale())),
+        }
+    }
+    
+    fn effective_locale(&self) -> String {
+        self.locale
+            .clone()
+            .or_else(tauri_plugin_os::locale)
+            .unwrap_or_else(|| "en".to_string())
+    }
+    
+    // Check URLs, shortcuts and numeric ranges, listing every problem found
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+        
+        if self.version > SETTINGS_VERSION {
+            problems.push(format!("version {} is newer than supported version {}", self.version, SETTINGS_VERSION));
+        }
+        
+        if let Err(e) = validate_url(&self.backend_url, &["http", "https"]) {
+            problems.push(format!("backend_url {}", e));
+        }
+        let optional_urls = [
+            ("webhook_url", &self.webhook_url, &["http", "https"][..]),
+            ("crash_report_url", &self.crash_report_url, &["http", "https"][..]),
+            ("proxy_url", &self.proxy_url, &["http", "https", "socks5", "socks5h"][..]),
+        ];
+        for (name, url, schemes) in optional_urls {
+            if let Some(Err(e)) = url.as_deref().map(|url| validate_url(url, schemes)) {
+                problems.push(format!("{} {}", name, e));
+            }
+        }
+        
+        let shortcuts = [
+            ("shortcut", Some(&self.shortcut)),
+            ("incognito_shortcut", self.incognito_shortcut.as_ref()),
+            ("stop_shortcut", self.stop_shortcut.as_ref()),
+            ("cancel_shortcut", self.cancel_shortcut.as_ref()),
+            ("correction_shortcut", self.correction_shortcut.as_ref()),
+            ("palette_shortcut", self.palette_shortcut.as_ref()),
+            ("history_picker_shortcut", self.history_picker_shortcut.as_ref()),
+            ("accurate_shortcut", self.accurate_shortcut.as_ref()),
+        ];
+        for (name, shortcut) in shortcuts {
+            let shortcut = match shortcut {
+                Some(shortcut) if !shortcut.trim().is_empty() => shortcut,
+                _ => continue,
+            };
+            let low_level = self.low_level_shortcuts && parse_low_level_shortcut(shortcut).is_some();
+            if let (false, Err(e)) = (low_level, parse_shortcut(shortcut)) {
+                problems.push(format!("{} '{}' is not a valid shortcut: {}", name, shortcut, e));
+            }
+        }
+        
+        let fractions = [
+            ("monitor_volume", Some(self.monitor_volume)),
+            ("silence_threshold", Some(self.silence_threshold)),
+            ("decoding.no_speech_threshold", self.decoding.no_speech_threshold),
+        ];
+        for (name, value) in fractions {
+            if let Some(value) = value.filter(|value| !(0.0..=1.0).contains(value)) {
+                problems.push(format!("{} must be between 0 and 1, got {}", name, value));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.snippet_trigger_tolerance) {
+            problems.push(format!("snippet_trigger_tolerance must be between 0 and 1, got {}", self.snippet_trigger_tolerance));
+        }
+        if let Some(temperature) = self.decoding.temperature.filter(|temperature| *temperature < 0.0) {
+            problems.push(format!("decoding.temperature must not be negative, got {}", temperature));
+        }
+        if let Some(geometry) = self.settings_window {
+            if geometry.width == 0 || geometry.height == 0 {
+                problems.push(format!("settings_window must have a size, got {}x{}", geometry.width, geometry.height));
+            }
+        }
+        if self.shortcut_debounce_ms > 2000 {
+            problems.push(format!("shortcut_debounce_ms must be at most 2000, got {}", self.shortcut_debounce_ms));
+        }
+        if self.remote_desktop_delay_ms > 1000 {
+            problems.push(format!("remote_desktop_delay_ms must be at most 1000, got {}", self.remote_desktop_delay_ms));
+        }
+        if self.pre_insert_delay_ms > 5000 {
+            problems.push(format!("pre_insert_delay_ms must be at most 5000, got {}", self.pre_insert_delay_ms));
+        }
+        for profile in &self.app_profiles {
+            if let Some(delay_ms) = profile.pre_insert_delay_ms.filter(|delay_ms| *delay_ms > 5000) {
+                problems.push(format!("pre_insert_delay_ms for {} must be at most 5000, got {}", profile.app, delay_ms));
+            }
+            let locale = profile.normalization.as_ref().and_then(|normalization| normalization.locale.as_deref());
+            if let Some(locale) = locale.filter(|locale| !is_language_tag(locale)) {
+                problems.push(format!("locale for {} must be a language tag like \"de-DE\", got '{}'", profile.app, locale));
+            }
+        }
+        if self.recording_countdown_ms > 1000 {
+            problems.push(format!("recording_countdown_ms must be at most 1000, got {}", self.recording_countdown_ms));
+        }
+        if let Some(model) = self.accurate_model.as_deref().filter(|model| *model == AUTO_MODEL) {
+            problems.push(format!("accurate_model must name a model, got '{}'", model));
+        }
+        if self.adaptive_model.threshold_seconds <= 0.0 {
+            problems.push(format!("adaptive_model.threshold_seconds must be positive, got {}", self.adaptive_model.threshold_seconds));
+        }
+        if let Some(kbps) = self.low_bandwidth_kbps.filter(|kbps| *kbps <= 0.0) {
+            problems.push(format!("low_bandwidth_kbps must be positive, got {}", kbps));
+        }
+        if !(6..=128).contains(&self.low_bandwidth_bitrate_kbps) {
+            problems.push(format!("low_bandwidth_bitrate_kbps must be between 6 and 128, got {}", self.low_bandwidth_bitrate_kbps));
+        }
+        if let Some(percent) = self.duck_output_percent.filter(|percent| *percent > 100) {
+            problems.push(format!("duck_output_percent must be at most 100, got {}", percent));
+        }
+        if let Some(locale) = self.locale.as_deref().filter(|locale| !is_language_tag(locale)) {
+            problems.push(format!("locale must be a language tag like \"de-DE\", got '{}'", locale));
+        }
+        if self.update_check_interval_hours > 24 * 30 {
+            problems.push(format!("update_check_interval_hours must be at most 720, got {}", self.update_check_interval_hours));
+        }
+        
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Invalid settings: {}", problems.join("; ")))
+        }
+    }
+}
+
+fn validate_url(url: &str, schemes: &[&str]) -> Result<(), String> {
+    let parsed = url::Url::parse(url).map_err(|e| format!("'{}' is not a valid URL: {}", url, e))?;
+    if !schemes.contains(&parsed.scheme()) {
+        return Err(format!("'{}' must use {}", url, schemes.join(" or ")));
+    }
+    Ok(())
+}
+
+// A BCP 47-style tag such as "de" or "de-CH"
+fn is_language_tag(locale: &str) -> bool {
+    locale.len() >= 2
+        && locale.chars().take(2).all(|c| c.is_ascii_alphabetic())
+        && locale.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// Version 2 introduced versioning. Backend URLs saved with a trailing slash produced
+// request paths like "//health", which some proxies reject.
+fn migrate_v1_to_v2(settings: &mut Map<String, Value>) {
+    if let Some(Value::String(url)) = settings.get_mut("backend_url") {
+        *url = url.trim_end_matches('/').to_string();
+    }
+}
+
+// Overlay JSON onto a target, merging objects key by key and replacing anything else
+fn merge_json(target: &mut Value, overlay: Value) {
+    match (target, overlay) {
+        (Value::Object(target), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge_json(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, overlay) => *target = overlay,
+    }
+}
+
+// Merge this machine's section of "hosts" over the shared settings, remembering the
+// shared values it replaced
+fn apply_host_overrides(raw: &mut Map<String, Value>) {
+    let host = tauri_plugin_os::hostname();
+    let overrides = raw
+        .get("hosts")
+        .and_then(|hosts| hosts.get(&host))
+        .and_then(|overrides| overrides.as_object())
+        .cloned()
+        .unwrap_or_default();
+    
+    let mut shared = Vec::new();
+    for (key, value) in overrides {
+        if key == "hosts" || key == "version" {
+            continue;
+        }
+        shared.push((key.clone(), raw.get(&key).cloned()));
+        merge_json(raw.entry(key).or_insert(Value::Null), value);
+    }
+    if !shared.is_empty() {
+        println!("🖥️ Applied settings overrides for {}", host);
+    }
+    if let Ok(mut current) = HOST_SHARED_VALUES.lock() {
+        *current = shared;
+    }
+}
+
+// Settings as written to disk: values this machine overrides go back into its hosts
+// section, and the shared values they replaced are restored
+fn stored_settings_json(settings: &Settings) -> Result<Value, String> {
+    let mut value = serde_json::to_value(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let shared = HOST_SHARED_VALUES.lock().map(|shared| shared.clone()).unwrap_or_default();
+    let host = tauri_plugin_os::hostname();
+    let raw = match value.as_object_mut() {
+        Some(raw) if !shared.is_empty() && settings.hosts.contains_key(&host) => raw,
+        _ => return Ok(value),
+    };
+    
+    let mut overrides = Map::new();
+    for (key, shared_value) in shared {
+        if let Some(current) = raw.remove(&key) {
+            overrides.insert(key.clone(), current);
+        }
+        if let Some(shared_value) = shared_value {
+            raw.insert(key, shared_value);
+        }
+    }
+    if let Some(Value::Object(hosts)) = raw.get_mut("hosts") {
+        hosts.insert(host, Value::Object(overrides));
+    }
+    Ok(value)
+}
+
+// Upgrade raw settings JSON, host sections included, to the current version
+fn migrate_settings(raw: &mut Map<String, Value>) -> Result<(), String> {
+    let version = match raw.get("version") {
+        Some(version) => version
+            .as_u64()
+            .filter(|version| *version >= 1)
+            .ok_or_else(|| format!("Invalid settings version: {}", version))? as u32,
+        None => 1,
+    };
+    
+    for (index, migrate) in SETTINGS_MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        println!("🔀 Migrating settings from version {} to {}", index + 1, index + 2);
+        migrate(raw);
+        // Host sections are stored in the same format as the rest
+        if let Some(Value::Object(hosts)) = raw.get_mut("hosts") {
+            for overrides in hosts.values_mut() {
+                if let Value::Object(overrides) = overrides {
+                    migrate(overrides);
+                }
+            }
+        }
+    }
+    if version < SETTINGS_VERSION {
+        raw.insert("version".to_string(), SETTINGS_VERSION.into());
+    }
+    Ok(())
+}
+
+// Upgrade raw settings JSON to the current version, apply this machine's overrides, then
+// parse and validate it
+pub fn parse_settings(mut value: Value) -> Result<Settings, String> {
+    let raw = value
+        .as_object_mut()
+        .ok_or("Settings must be a JSON object")?;
+    migrate_settings(raw)?;
+    apply_host_overrides(raw);
+    
+    let settings = serde_json::from_value::<Settings>(value)
+        .map_err(|e| format!("Failed to parse settings: {}", e))?;
+    settings.validate()?;
+    Ok(settings)
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            hosts: HashMap::new(),
+            model: "base".to_string(),
+            adaptive_model: AdaptiveModel::default(),
+            accurate_model: None,
+            shortcut: "Option+Space".to_string(),
+            backend_url: "http://127.0.0.1:8788".to_string(),
+            backend_python: None,
+            language: None,
+            app_profiles: Vec::new(),
+            recent_languages: Vec::new(),
+            word_timestamps: false,
+            decoding: DecodingOptions::default(),
+            carry_over_context: false,
+            show_live_wpm: false,
+            model_idle_unload_minutes: 0,
+            show_overlay: true,
+            overlay_position: OverlayPosition::Cursor,
+            overlay_fixed_position: None,
+            settings_window: None,
+            history_enabled: true,
+            incognito_shortcut: None,
+            stop_shortcut: None,
+            cancel_shortcut: None,
+            correction_shortcut: None,
+            palette_shortcut: None,
+            history_picker_shortcut: None,
+            accurate_shortcut: None,
+            low_level_shortcuts: false,
+            suppress_shortcut_keys: false,
+            shortcut_debounce_ms: 250,
+            foot_pedal: None,
+            history_redact_pii: false,
+            history_redaction_patterns: Vec::new(),
+            retain_audio: false,
+            encrypt_history: false,
+            retention_keep_last: Some(100),
+            retention_max_days: Some(30),
+            retention_max_total_mb: Some(500),
+            pause_overlay_while_sharing: false,
+            screen_share_processes: Vec::new(),
+            crash_reporting_enabled: false,
+            crash_report_url: None,
+            post_insert_actions: Vec::new(),
+            normalize_numbers: false,
+            normalize_currency: false,
+            normalize_dates: false,
+            locale: None,
+            profanity_filter: ProfanityFilter::Off,
+            profanity_words: Vec::new(),
+            snippet_trigger_tolerance: 0.2,
+            use_helper_processes: false,
+            smart_spacing: false,
+            insertion_method: InsertionMethod::Auto,
+            remote_desktop_delay_ms: 40,
+            pre_insert_delay_ms: 0,
+            paste_rtl_text: true,
+            directional_marks: DirectionalMarks::Off,
+            xdotool: XdotoolOptions::default(),
+            input_channel: InputChannelMode::All,
+            input_device: None,
+            monitor_input: false,
+            monitor_volume: 0.2,
+            trim_silence: true,
+            silence_threshold: 0.01,
+            silence_padding_ms: 200,
+            min_recording_ms: 300,
+            short_recording_toast: true,
+            recording_countdown_ms: 0,
+            key_click_gate_ms: 150,
+            upload_format: UploadFormat::Auto,
+            low_bandwidth_kbps: Some(500.0),
+            low_bandwidth_bitrate_kbps: 24,
+            fallback_providers: Vec::new(),
+            allow_cloud_fallback: false,
+            proxy_url: None,
+            use_system_proxy: true,
+            client_cert_path: None,
+            client_key_path: None,
+            client_cert_password: None,
+            ca_bundle_path: None,
+            webhook_url: None,
+            webhook_secret: None,
+            mqtt: None,
+            output_sink: OutputSink::Type,
+            obsidian: None,
+            browser_bridge_port: None,
+            automation_urls_enabled: false,
+            pipelined_finalize: false,
+            pause_media_while_recording: false,
+            resume_media_after_recording: true,
+            mute_output_while_recording: false,
+            duck_output_percent: None,
+            update_channel: UpdateChannel::Stable,
+            update_check_interval_hours: 24,
+        }
+    }
+}
+
+// Resolve the settings file location
+pub fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app_config_dir(app)?;
+    Ok(config_dir.join("settings.json"))
+}
+
+// Load settings from disk, falling back to defaults
+pub fn load_settings(app: &AppHandle) -> Settings {
+    let path = match settings_path(app) {
+        Ok(path) => path,
+        Err(e) => {
+            println!("⚠️ {}, using default settings", e);
+            return Settings::default();
+        }
+    };
+    
+    if !path.exists() {
+        println!("📋 No settings file at {}, using defaults", path.display());
+        return Settings::default();
+    }
+    
+    match read_settings_file(&path) {
+        Ok(settings) => {
+            println!("✅ Settings loaded from {}", path.display());
+            settings
+        }
+        Err(e) => {
+            // Keep the rejected file so the next save doesn't lose what was in it
+            let backup = path.with_extension("invalid.json");
+            match std::fs::copy(&path, &backup).and_then(|_| restrict_file(&backup)) {
+                Ok(_) => println!("❌ {}, using defaults (kept a copy at {})", e, backup.display()),
+                Err(_) => println!("❌ {}, using defaults", e),
+            }
+            Settings::default()
+        }
+    }
+}
+
+// Read, migrate and validate a settings file
+pub fn read_settings_file(path: &Path) -> Result<Settings, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+    let value = serde_json::from_str::<Value>(&contents)
+        .map_err(|e| format!("Failed to parse settings file: {}", e))?;
+    parse_settings(value)
+}
+
+// Write settings to disk
+pub fn save_settings(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    
+    // Settings can hold secrets such as webhook_secret and the MQTT password
+    let contents = serde_json::to_string_pretty(&stored_settings_json(settings)?)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    write_private(&path, contents)
+        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+    
+    println!("💾 Settings saved to {}", path.display());
+    Ok(())
+}
+
+// Get current settings
+#[tauri::command]
+pub async fn get_settings(state: tauri::State<'_, AppStateType>) -> Result<Settings, String> {
+    let app_state = state.lock().map_err(|e| e.to_string())?;
+    Ok(app_state.settings.clone())
+}
+
+// Replace and persist settings
+#[tauri::command]
+pub async fn update_settings(
+    app_handle: AppHandle,
+    settings: Settings,
+    state: tauri::State<'_, AppStateType>
+) -> Result<(), String> {
+    println!("🔧 Updating settings...");
+    
+    settings.validate()?;
+    save_settings(&app_handle, &settings)?;
+    apply_settings(&app_handle, state.inner(), settings).await
+}
+
+// Swap in new settings, re-registering shortcuts and switching model when they changed
+pub async fn apply_settings(app_handle: &AppHandle, state: &AppStateType, settings: Settings) -> Result<(), String> {
+    let previous = {
+        let mut app_state = state.lock().map_err(|e| e.to_string())?;
+        std::mem::replace(&mut app_state.settings, settings.clone())
+    };
+    
+    set_use_helper_processes(settings.use_helper_processes);
+    set_smart_spacing(settings.smart_spacing);
+    set_paste_rtl_text(settings.paste_rtl_text);
+    set_insertion_options(settings.insertion_method, settings.pre_insert_delay_ms, &settings.app_profiles);
+    set_remote_desktop_delay_ms(settings.remote_desktop_delay_ms);
+    set_xdotool_options(settings.xdotool.clone());
+    set_suppress_shortcut_keys(settings.suppress_shortcut_keys);
+    set_history_encryption(settings.encrypt_history);
+    
+    if settings.encrypt_history && !previous.encrypt_history {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Err(e) = encrypt_existing_data(&app_handle) {
+                println!("❌ Failed to encrypt existing history: {}", e);
+            }
+        });
+    }
+    
+    if previous.shortcut != settings.shortcut
+        || previous.incognito_shortcut != settings.incognito_shortcut
+        || previous.stop_shortcut != settings.stop_shortcut
+        || previous.cancel_shortcut != settings.cancel_shortcut
+        || previous.correction_shortcut != settings.correction_shortcut
+        || previous.palette_shortcut != settings.palette_shortcut
+        || previous.history_picker_shortcut != settings.history_picker_shortcut
+        || previous.accurate_shortcut != settings.accurate_shortcut
+        || previous.low_level_shortcuts != settings.low_level_shortcuts
+    {
+        reregister_shortcuts(app_handle, state.clone())?;
+    }
+    
+    if previous.foot_pedal != settings.foot_pedal {
+        restart_foot_pedal_listener(app_handle, state);
+    }
+    
+    if previous.mqtt != settings.mqtt {
+        restart_mqtt_client(state);
+    }
+    
+    if previous.browser_bridge_port != settings.browser_bridge_port {
+        restart_browser_bridge(state);
+    }
+    
+    if previous.model != settings.model
+        || previous.backend_url != settings.backend_url
+        || (settings.model == AUTO_MODEL && previous.adaptive_model != settings.adaptive_model)
+    {
+        if let Err(e) = send_model_to_backend(app_handle, state, settings.model.clone()).await {
+            println!("⚠️ Failed to switch backend model: {}", e);
+        }
+    } else if previous.accurate_model != settings.accurate_model {
+        if let Err(e) = load_standby_model(state).await {
+            println!("⚠️ {}", e);
+        }
+    }
+    
+    // An update found on the old channel isn't offered on the new one
+    if previous.update_channel != settings.update_channel {
+        forget_pending_update();
+    }
+    
+    // Checkboxes, model and language in the tray follow the new settings
+    rebuild_tray_menu(app_handle);
+    
+    println!("✅ Settings applied");
+    Ok(())
+}
+
+// Write the current settings to a versioned JSON bundle
+#[tauri::command]
+pub async fn export_settings(path: String, state: tauri::State<'_, AppStateType>) -> Result<(), String> {
+    println!("📤 Exporting settings to {}", path);
+    
+    let mut settings = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.clone()
+    };
+    
+    // Bundles carry this machine's effective settings, not overrides for other hostnames
+    settings.hosts.clear();
+    
+    strip_secrets(&mut settings);
+    
+    let exported_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    
+    let bundle = SettingsBundle {
+        version: SETTINGS_BUNDLE_VERSION,
+        exported_at,
+        settings,
+    };
+    
+    let contents = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("Failed to serialize settings bundle: {}", e))?;
+    // The bundle holds the same private settings as settings.json
+    write_private(Path::new(&path), contents)
+        .map_err(|e| format!("Failed to write settings bundle: {}", e))?;
+    
+    println!("✅ Settings exported");
+    Ok(())
+}
+
+// Never share proxy credentials or secrets in a bundle
+fn strip_secrets(settings: &mut Settings) {
+    settings.proxy_url = settings.proxy_url.as_deref().map(strip_proxy_credentials);
+    settings.client_cert_password = None;
+    settings.webhook_secret = None;
+    if let Some(mqtt) = settings.mqtt.as_mut() {
+        mqtt.password = None;
+    }
+    for provider in &mut settings.fallback_providers {
+        provider.api_key = None;
+    }
+}
+
+// Load a settings bundle from disk, persist it and apply it
+#[tauri::command]
+pub async fn import_settings(
+    app_handle: AppHandle,
+    path: String,
+    state: tauri::State<'_, AppStateType>
+) -> Result<(), String> {
+    println!("📥 Importing settings from {}", path);
+    
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read settings bundle: {}", e))?;
+    let mut bundle: Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Invalid settings bundle: {}", e))?;
+    
+    let version = bundle.get("version").and_then(|version| version.as_u64()).unwrap_or(0);
+    if version > SETTINGS_BUNDLE_VERSION as u64 {
+        return Err(format!(
+            "Settings bundle version {} is newer than supported version {}",
+            version, SETTINGS_BUNDLE_VERSION
+        ));
+    }
+    
+    let mut imported = match bundle.get_mut("settings").map(Value::take) {
+        Some(Value::Object(settings)) => settings,
+        _ => return Err("Invalid settings bundle: missing settings".to_string()),
+    };
+    // Bundles exported by older versions carry settings in their format of the time
+    migrate_settings(&mut imported)?;
+    
+    // Bundles carry no hosts section; keep this file's, so per-hostname overrides are
+    // applied over the imported settings and written back on save instead of dropped
+    let hosts = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        serde_json::to_value(&app_state.settings.hosts)
+            .map_err(|e| format!("Failed to serialize host overrides: {}", e))?
+    };
+    imported.insert("hosts".to_string(), hosts);
+    
+    let settings = parse_settings(Value::Object(imported))?;
+    save_settings(&app_handle, &settings)?;
+    apply_settings(&app_handle, state.inner(), settings).await?;
+    
+    println!("✅ Settings imported");
+    Ok(())
+}
+
+// Watch the settings file and apply external edits live
+pub fn watch_settings_file(app_handle: AppHandle, state: AppStateType) -> Result<(), String> {
+    let path = settings_path(&app_handle)?;
+    let dir = path
+        .parent()
+        .ok_or("Settings path has no parent directory")?
+        .to_path_buf();
+    create_private_dir(&dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| format!("Failed to create settings watcher: {}", e))?;
+    
+    // Watch the directory so atomic replace-on-save by editors is still seen
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch config directory: {}", e))?;
+    
+    println!("👀 Watching settings file: {}", path.display());
+    
+    std::thread::spawn(move || {
+        // Keep the watcher alive for as long as this thread runs
+        let _watcher = watcher;
+        
+        while let Ok(event) = rx.recv() {
+            let touches_settings = match event {
+                Ok(event) => event.paths.iter().any(|p| p == &path),
+                Err(e) => {
+                    println!("⚠️ Settings watcher error: {}", e);
+                    false
+                }
+            };
+            if !touches_settings {
+                continue;
+            }
+            
+            // Coalesce the burst of events a single save produces
+            std::thread::sleep(SETTINGS_RELOAD_DEBOUNCE);
+            while rx.try_recv().is_ok() {}
+            
+            let settings = match read_settings_file(&path) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    println!("⚠️ Ignoring settings change: {}", e);
+                    continue;
+                }
+            };
+            
+            let unchanged = match state.lock() {
+                Ok(app_state) => app_state.settings == settings,
+                Err(e) => {
+                    println!("❌ Settings watcher failed to lock app state: {}", e);
+                    continue;
+                }
+            };
+            if unchanged {
+                // Our own save, nothing to apply
+                continue;
+            }
+            
+            println!("🔄 Settings file changed on disk, reloading...");
+            let result = tauri::async_runtime::block_on(apply_settings(&app_handle, &state, settings));
+            match result {
+                Ok(_) => {
+                    let event = SettingsReloadedEvent { path: path.display().to_string() };
+                    if let Err(e) = app_handle.emit(SETTINGS_RELOADED_EVENT, event) {
+                        println!("⚠️ Failed to emit settings reload: {}", e);
+                    }
+                }
+                Err(e) => println!("❌ Failed to apply reloaded settings: {}", e),
+            }
+        }
+        
+        println!("👀 Settings watcher stopped");
+    });
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn exported_bundles_leave_out_provider_keys() {
+        let provider: BackendProvider = serde_json::from_value(serde_json::json!({
+            "name": "OpenAI",
+            "kind": "open_ai",
+            "url": "https://api.openai.com/v1/audio/transcriptions",
+            "api_key": "sk-secret",
+            "cloud": true,
+        }))
+        .unwrap();
+        let mut settings = Settings {
+            fallback_providers: vec![provider],
+            ..Settings::default()
+        };
+        strip_secrets(&mut settings);
+        
+        assert_eq!(settings.fallback_providers[0].api_key, None);
+        let bundle = serde_json::to_string(&settings).unwrap();
+        assert!(!bundle.contains("sk-secret"));
+    }
+}
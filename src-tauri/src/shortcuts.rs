@@ -1,24 +1,91 @@
-use tauri::{AppHandle, Emitter};
-use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut, GlobalShortcutExt};
-use crate::types::{AppStateType, get_recording_control};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut, ShortcutState, GlobalShortcutExt};
+use crate::types::{AppStateType, RecordingCancelledEvent, RecordingCountdownEvent, RecordingStateEvent, TranscriptionEvent, RECORDING_CANCELLED_EVENT, RECORDING_COUNTDOWN_EVENT, RECORDING_STATE_EVENT, TRANSCRIPTION_EVENT, recorder};
 use crate::window_manager::show_overlay;
-use crate::audio::stop_recording_and_transcribe_internal;
-use crate::text_input::type_text;
-use std::time::Duration;
+use crate::audio::{stop_recording_and_transcribe_internal, RECORDING_TOO_SHORT_ERROR};
+use crate::sequencer::{correct_in_order, insert_in_order, issue_insertion_ticket};
+use crate::backend::{touch_model_activity, wake_model_if_sleeping};
+use crate::history::record_transcription;
+use crate::statistics::record_dictation;
+use crate::webhook::send_transcription_webhook;
+use crate::mqtt::{publish_recording_state, publish_transcription};
+use crate::obsidian::{output_sink, send_to_obsidian, OutputSink};
+use crate::crash::breadcrumb;
+use crate::dbus::emit_dbus_state;
+use crate::media_control::on_recording_state;
+use crate::deep_link::{complete_pending_dictation, DictationOutcome};
+use crate::templates::apply_template;
+use crate::snippets::{expand_spoken_snippet, setup_snippet_shortcuts};
+use cursper_core::normalize::normalize_transcription;
+use cursper_core::capture::RECORDING_CANCELLED_ERROR;
+use cursper_core::providers::UPLOAD_CANCELLED_ERROR;
+use cursper_core::profanity::filter_profanity;
+use cursper_core::bidi::add_directional_marks;
+use crate::privacy::{loggable, toggle_incognito_internal};
+use crate::command_palette::toggle_command_palette;
+use crate::history_picker::toggle_history_picker;
+use crate::settings::save_settings;
+use crate::text_input::PostInsertAction;
+use crate::app_profiles::{normalization_options, profile_for_app, remember_focused_app};
+use crate::screen_share::sharing_paused;
+use crate::key_listener::{parse_low_level_shortcut, register_low_level_shortcut, unregister_all_low_level_shortcuts, KeyEdge, LowLevelShortcut};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use tokio;
+use tokio::sync::Semaphore;
+
+// Stop and cancel shortcuts registered for the current recording
+static RECORDING_SHORTCUTS: Mutex<Vec<Shortcut>> = Mutex::new(Vec::new());
+
+// The current recording was started by the correction shortcut, so its text replaces the
+// last inserted sentence instead of being inserted
+static CORRECTION_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+// The next recording was started by the accurate shortcut and uses accurate_model
+static ACCURATE_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+// When a recording hotkey last fired, accepted or not, for debouncing
+static LAST_HOTKEY_TRIGGER: Mutex<Option<Instant>> = Mutex::new(None);
+
+// The dictation shortcut is held down; presses before its release are key repeats
+static SHORTCUT_HELD: AtomicBool = AtomicBool::new(false);
+
+// Single permit taken by each shortcut handler while it flips the recording state, so
+// handlers spawned by rapid presses run their start/stop one after another. It is given
+// back before transcribing, so a new dictation can start while the last one finishes.
+static TOGGLE_PERMIT: OnceLock<Semaphore> = OnceLock::new();
 
 // Add a new command to emit recording state changes
 #[tauri::command]
 pub async fn emit_recording_state(app_handle: AppHandle, is_recording: bool) -> Result<(), String> {
     println!("📡 Emitting recording state: {}", is_recording);
+    publish_recording_state(is_recording);
+    emit_dbus_state(is_recording);
+    if let Some(state) = app_handle.try_state::<AppStateType>() {
+        on_recording_state(state.inner(), is_recording);
+        if is_recording {
+            register_recording_shortcuts(&app_handle, state.inner().clone());
+        }
+    }
+    if !is_recording {
+        unregister_recording_shortcuts(&app_handle);
+    }
     
     app_handle
-        .emit("recording-state-changed", is_recording)
+        .emit(RECORDING_STATE_EVENT, RecordingStateEvent { is_recording })
         .map_err(|e| format!("Failed to emit recording state: {}", e))?;
     
     Ok(())
 }
 
+// Emit the outcome of a transcription to the frontend
+pub fn emit_transcription(app_handle: &AppHandle, event: TranscriptionEvent) {
+    if let Err(e) = app_handle.emit(TRANSCRIPTION_EVENT, event) {
+        println!("⚠️ Failed to emit transcription event: {}", e);
+    }
+}
+
 // Toggle recording state
 #[tauri::command]
 pub async fn toggle_recording(
@@ -32,7 +99,8 @@ pub async fn toggle_recording(
     
     if is_recording {
         // Stop recording and transcribe
-        match crate::audio::stop_recording_and_transcribe(state.clone()).await {
+        let ticket = issue_insertion_ticket();
+        match crate::audio::stop_recording_and_transcribe(app_handle.clone(), state.clone()).await {
             Ok(text) => {
                 // Emit recording state change
                 let _ = emit_recording_state(app_handle.clone(), false).await;
@@ -41,45 +109,195 @@ pub async fn toggle_recording(
                 let _ = show_overlay(app_handle.clone(), false).await;
                 
                 // Type the transcribed text
-                let _ = type_text(text).await;
+                let _ = deliver_dictation(state.inner(), ticket, text).await;
+            }
+            Err(e) => {
+                println!("Transcription error: {}", e);
+                let _ = insert_in_order(ticket, None, &[]).await;
             }
-            Err(e) => println!("Transcription error: {}", e),
         }
     } else {
         // Start recording
+        remember_focused_app(&app_handle, state.inner()).await;
         let _ = crate::audio::start_recording(state.clone()).await;
         
         // Emit recording state change
         let _ = emit_recording_state(app_handle.clone(), true).await;
         
-        // Show overlay
-        let _ = show_overlay(app_handle.clone(), true).await;
+        // Show overlay unless running headless
+        if overlay_enabled(state.inner()) {
+            let _ = show_overlay(app_handle.clone(), true).await;
+        }
     }
     
     Ok(())
 }
 
+// Whether the overlay should be shown while recording ("stealth mode" when false)
+fn overlay_enabled(state: &AppStateType) -> bool {
+    state
+        .lock()
+        .map(|app_state| app_state.settings.show_overlay)
+        .unwrap_or(true)
+}
+
+// Keys to press after inserting a transcription into the application focused when
+// recording started, falling back to the global actions
+pub fn post_insert_actions(state: &AppStateType) -> Vec<PostInsertAction> {
+    let app_state = match state.lock() {
+        Ok(app_state) => app_state,
+        Err(_) => return Vec::new(),
+    };
+    app_state
+        .focused_app
+        .as_deref()
+        .and_then(|app| profile_for_app(&app_state.settings.app_profiles, app))
+        .and_then(|profile| profile.post_insert_actions.clone())
+        .unwrap_or_else(|| app_state.settings.post_insert_actions.clone())
+}
+
+// Type a dictation in order, or hand it to the output sink chosen for the focused app
+pub async fn deliver_dictation(state: &AppStateType, ticket: u64, text: String) -> Result<(), String> {
+    match output_sink(state) {
+        OutputSink::Type => {
+            let marks = state
+                .lock()
+                .map(|app_state| app_state.settings.directional_marks)
+                .unwrap_or_default();
+            let text = add_directional_marks(&text, marks);
+            insert_in_order(ticket, Some(text), &post_insert_actions(state)).await
+        }
+        OutputSink::Obsidian => {
+            // Nothing is typed, but later dictations still wait for this one's turn
+            insert_in_order(ticket, None, &[]).await?;
+            send_to_obsidian(state, &text)
+        }
+    }
+}
+
+// Longest allowed grace period before capture
+const MAX_RECORDING_COUNTDOWN_MS: u64 = 1000;
+const COUNTDOWN_TICK_MS: u64 = 100;
+
+// Count down before capture starts; returns false if recording was stopped meanwhile
+async fn run_recording_countdown(app_handle: &AppHandle, state: &AppStateType) -> bool {
+    let countdown_ms = state
+        .lock()
+        .map(|app_state| app_state.settings.recording_countdown_ms)
+        .unwrap_or(0)
+        .min(MAX_RECORDING_COUNTDOWN_MS);
+    
+    let mut remaining_ms = countdown_ms;
+    while remaining_ms > 0 {
+        let still_recording = state.lock().map(|app_state| app_state.is_recording).unwrap_or(false);
+        if !still_recording {
+            println!("⏹️ Recording stopped during countdown");
+            return false;
+        }
+        
+        if let Err(e) = app_handle.emit(RECORDING_COUNTDOWN_EVENT, RecordingCountdownEvent { remaining_ms }) {
+            println!("⚠️ Failed to emit countdown: {}", e);
+        }
+        let tick = remaining_ms.min(COUNTDOWN_TICK_MS);
+        tokio::time::sleep(Duration::from_millis(tick)).await;
+        remaining_ms -= tick;
+    }
+    
+    if countdown_ms > 0 {
+        let _ = app_handle.emit(RECORDING_COUNTDOWN_EVENT, RecordingCountdownEvent { remaining_ms: 0 });
+    }
+    true
+}
+
 // Update global shortcut
 #[tauri::command]
 pub async fn update_global_shortcut(app_handle: AppHandle, shortcut: String, state: tauri::State<'_, AppStateType>) -> Result<(), String> {
     println!("🔄 Updating global shortcut to: {}", shortcut);
     
-    // First unregister existing shortcuts (we'll need to track this properly)
-    // For now, we'll just try to register the new one
-    
     // Update state
-    {
+    let settings = {
         let mut app_state = state.lock().map_err(|e| e.to_string())?;
-        app_state.current_shortcut = shortcut.clone();
+        app_state.settings.shortcut = shortcut.clone();
+        app_state.settings.clone()
+    };
+    
+    if let Err(e) = save_settings(&app_handle, &settings) {
+        println!("⚠️ Failed to persist shortcut: {}", e);
     }
     
     // Re-setup shortcuts with new shortcut
-    setup_shortcuts(&app_handle, state.inner().clone())?;
+    reregister_shortcuts(&app_handle, state.inner().clone())?;
     
     println!("✅ Global shortcut updated successfully");
     Ok(())
 }
 
+// Drop all registered shortcuts and register them again from current settings
+pub fn reregister_shortcuts(app: &AppHandle, state: AppStateType) -> Result<(), String> {
+    println!("🔄 Re-registering global shortcuts...");
+    
+    app.global_shortcut().unregister_all().map_err(|e| {
+        let error = format!("Failed to unregister shortcuts: {}", e);
+        println!("❌ {}", error);
+        error
+    })?;
+    unregister_all_low_level_shortcuts();
+    if let Ok(mut registered) = RECORDING_SHORTCUTS.lock() {
+        registered.clear();
+    }
+    // A release missed while unregistered would otherwise make every later press a repeat
+    SHORTCUT_HELD.store(false, Ordering::SeqCst);
+    
+    setup_shortcuts(app, state.clone())?;
+    setup_incognito_shortcut(app, state.clone())?;
+    setup_correction_shortcut(app, state.clone())?;
+    setup_palette_shortcut(app, state.clone())?;
+    setup_history_picker_shortcut(app, state.clone())?;
+    setup_accurate_shortcut(app, state.clone())?;
+    setup_snippet_shortcuts(app)?;
+    
+    // Settings changed mid-recording; bring back the stop and cancel keys
+    if state.lock().map(|app_state| app_state.is_recording).unwrap_or(false) {
+        register_recording_shortcuts(app, state);
+    }
+    Ok(())
+}
+
+// Whether a recording hotkey should act. Triggers within shortcut_debounce_ms of the
+// previous one are dropped, and each one restarts the window, so a bouncing key or a
+// stream of repeats can't flap the recording state.
+fn accept_hotkey_trigger(state: &AppStateType) -> bool {
+    let debounce_ms = state
+        .lock()
+        .map(|app_state| app_state.settings.shortcut_debounce_ms)
+        .unwrap_or(0);
+    let now = Instant::now();
+    let previous = match LAST_HOTKEY_TRIGGER.lock() {
+        Ok(mut last) => last.replace(now),
+        Err(_) => return true,
+    };
+    match previous {
+        Some(previous) if now.duration_since(previous) < Duration::from_millis(debounce_ms) => {
+            println!("⏱️ Ignoring hotkey trigger within {} ms of the previous one", debounce_ms);
+            false
+        }
+        _ => true,
+    }
+}
+
+// The low-level shortcut a shortcut string names, when low-level shortcuts are enabled
+fn low_level_shortcut(state: &AppStateType, shortcut_str: &str) -> Option<LowLevelShortcut> {
+    let enabled = state
+        .lock()
+        .map(|app_state| app_state.settings.low_level_shortcuts)
+        .unwrap_or(false);
+    if enabled {
+        parse_low_level_shortcut(shortcut_str)
+    } else {
+        None
+    }
+}
+
 // Parse shortcut string into Shortcut struct
 pub fn parse_shortcut(shortcut_str: &str) -> Result<Shortcut, String> {
     println!("🔍 Parsing shortcut: '{}'", shortcut_str);
@@ -156,6 +374,268 @@ pub fn parse_shortcut(shortcut_str: &str) -> Result<Shortcut, String> {
     }
 }
 
+// Start recording, or stop it and transcribe, in response to the dictation shortcut
+pub async fn handle_recording_shortcut(app_handle: AppHandle, state: AppStateType) {
+    println!("🔄 Starting async shortcut handler...");
+    
+    let permit = match TOGGLE_PERMIT.get_or_init(|| Semaphore::new(1)).acquire().await {
+        Ok(permit) => permit,
+        Err(e) => {
+            println!("❌ Failed to serialize shortcut handler: {}", e);
+            return;
+        }
+    };
+    
+    // Handle the recording toggle directly without the State wrapper
+    let is_recording = {
+        let app_state = state.lock().map_err(|e| e.to_string());
+        match app_state {
+            Ok(state) => {
+                println!("📊 Current recording state: {}", state.is_recording);
+                state.is_recording
+            },
+            Err(e) => {
+                println!("❌ Failed to lock app state: {}", e);
+                return;
+            }
+        }
+    };
+    
+    if is_recording {
+        println!("🛑 STOPPING RECORDING...");
+        breadcrumb("shortcut: stop recording");
+        
+        // Signal the recording to stop
+        match recorder().stop() {
+            Ok(_) => println!("✅ Recorder told to stop"),
+            Err(e) => println!("❌ Failed to stop recorder: {}", e),
+        }
+        
+        // Update app state
+        let backend_url = {
+            let mut app_state = state.lock().unwrap();
+            app_state.is_recording = false;
+            println!("✅ App recording state set to false");
+            app_state.settings.backend_url.clone()
+        };
+        
+        // Emit recording state change
+        let _ = emit_recording_state(app_handle.clone(), false).await;
+        
+        // Give a moment for the recording to stop gracefully
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        
+        // Reserve this dictation's place in the insertion order
+        let ticket = issue_insertion_ticket();
+        let is_correction = CORRECTION_REQUESTED.swap(false, Ordering::SeqCst);
+        drop(permit);
+        
+        // Call actual transcription function
+        println!("🎤 Starting transcription process...");
+        let transcription_result = stop_recording_and_transcribe_internal(&app_handle, backend_url).await;
+        touch_model_activity(&state);
+        
+        let mut completed_result = None;
+        let transcribed_text = match transcription_result {
+            Ok(mut result) => {
+                println!("✅ Transcription successful: '{}'", loggable(&result.text));
+                
+                // Normalize numbers, currencies and dates before anything sees the text
+                let post_process_start = Instant::now();
+                let options = normalization_options(&state);
+                if options.any() {
+                    result.text = normalize_transcription(&result.text, result.language.as_deref(), &options);
+                    result.timings.post_process_ms = Some(post_process_start.elapsed().as_millis() as u64);
+                }
+                
+                if let Err(e) = record_transcription(&app_handle, &state, &result) {
+                    println!("⚠️ Failed to save transcription to history: {}", e);
+                }
+                send_transcription_webhook(&state, &result);
+                publish_transcription(&result);
+                // "Insert <snippet>" replaces the dictation with the saved text as-is
+                let text = match expand_spoken_snippet(&app_handle, &result.text) {
+                    Some(snippet) => snippet,
+                    None => {
+                        let text = apply_template(&app_handle, &result.text);
+                        let (filter_mode, extra_words) = state
+                            .lock()
+                            .map(|app_state| (app_state.settings.profanity_filter, app_state.settings.profanity_words.clone()))
+                            .unwrap_or_default();
+                        filter_profanity(&text, filter_mode, &extra_words)
+                    }
+                };
+                emit_transcription(&app_handle, TranscriptionEvent {
+                    success: true,
+                    result: Some(result.clone()),
+                    error: None,
+                });
+                complete_pending_dictation(&app_handle, DictationOutcome::Success(&text));
+                completed_result = Some(result);
+                text
+            },
+            Err(e) if e == RECORDING_TOO_SHORT_ERROR => {
+                println!("⏭️ Recording too short, nothing to insert");
+                complete_pending_dictation(&app_handle, DictationOutcome::Cancelled);
+                String::new()
+            }
+            Err(e) if e == RECORDING_CANCELLED_ERROR => {
+                println!("⏭️ Recording cancelled, nothing to insert");
+                complete_pending_dictation(&app_handle, DictationOutcome::Cancelled);
+                String::new()
+            }
+            Err(e) if e == UPLOAD_CANCELLED_ERROR => {
+                println!("⏭️ Upload cancelled, nothing to insert");
+                complete_pending_dictation(&app_handle, DictationOutcome::Cancelled);
+                String::new()
+            }
+            Err(e) => {
+                println!("❌ Transcription failed: {}", e);
+                breadcrumb("transcription failed");
+                emit_transcription(&app_handle, TranscriptionEvent {
+                    success: false,
+                    result: None,
+                    error: Some(e.clone()),
+                });
+                complete_pending_dictation(&app_handle, DictationOutcome::Error(&e));
+                println!("🔄 Using fallback text");
+                "Transcription failed".to_string()
+            }
+        };
+        
+        // Hide overlay and type text
+        println!("🔒 Hiding overlay...");
+        match show_overlay(app_handle.clone(), false).await {
+            Ok(_) => println!("✅ Overlay hidden successfully"),
+            Err(e) => println!("❌ Failed to hide overlay: {}", e),
+        }
+        
+        // Only type text if it's not empty and not an error message
+        if !transcribed_text.trim().is_empty() && !transcribed_text.contains("failed") {
+            println!("⌨️  Starting to type text...");
+            let insert_start = Instant::now();
+            let delivered = if is_correction {
+                correct_in_order(ticket, transcribed_text.clone()).await
+            } else {
+                deliver_dictation(&state, ticket, transcribed_text.clone()).await
+            };
+            match delivered {
+                Ok(_) => println!("✅ Text typed successfully: '{}'", loggable(&transcribed_text)),
+                Err(e) => println!("❌ Failed to type text: {}", e),
+            }
+            
+            // Aggregate per-stage latency into the statistics store
+            if let Some(mut result) = completed_result {
+                result.timings.insert_ms = Some(insert_start.elapsed().as_millis() as u64);
+                let target_app = state.lock().ok().and_then(|app_state| app_state.focused_app.clone());
+                if let Err(e) = record_dictation(&app_handle, &result, target_app.as_deref()) {
+                    println!("⚠️ Failed to update statistics: {}", e);
+                }
+            }
+        } else {
+            println!("⚠️ Skipping text typing due to empty or error transcription");
+            // Release the ticket so later dictations are not held back
+            if let Err(e) = insert_in_order(ticket, None, &[]).await {
+                println!("❌ Failed to flush pending insertions: {}", e);
+            }
+        }
+    } else {
+        println!("🎙️ STARTING RECORDING...");
+        breadcrumb("shortcut: start recording");
+        
+        // Per-app profiles are resolved from the app being dictated into
+        remember_focused_app(&app_handle, &state).await;
+        
+        // Start recording
+        {
+            let mut app_state = state.lock().unwrap();
+            app_state.is_recording = true;
+            app_state.dictation_model = if ACCURATE_REQUESTED.swap(false, Ordering::SeqCst) {
+                app_state.settings.accurate_model.clone()
+            } else {
+                None
+            };
+            println!("✅ App recording state set to true");
+        }
+        
+        // Emit recording state change
+        let _ = emit_recording_state(app_handle.clone(), true).await;
+        
+        // Show overlay unless running headless
+        if overlay_enabled(&state) {
+            println!("👁️ Showing overlay...");
+            match show_overlay(app_handle.clone(), true).await {
+                Ok(_) => println!("✅ Overlay shown successfully"),
+                Err(e) => println!("❌ Failed to show overlay: {}", e),
+            }
+        } else {
+            println!("🥷 Overlay disabled, recording headless");
+        }
+        
+        // Reload the model in the background if it was unloaded while idle
+        let wake_app_handle = app_handle.clone();
+        let wake_state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = wake_model_if_sleeping(&wake_app_handle, &wake_state).await {
+                println!("⚠️ Failed to wake model: {}", e);
+            }
+        });
+        
+        // Start the actual recording process in a separate task
+        let backend_url = {
+            let app_state = state.lock().unwrap();
+            app_state.settings.backend_url.clone()
+        };
+        
+        let recording_app_handle = app_handle.clone();
+        let recording_state = state.clone();
+        tokio::spawn(async move {
+            // Optional grace period, which also keeps the hotkey's click out of the capture
+            if !run_recording_countdown(&recording_app_handle, &recording_state).await {
+                return;
+            }
+            
+            println!("🎤 Starting background recording task...");
+            // This will run until the recording control is set to false
+            let _result = stop_recording_and_transcribe_internal(&recording_app_handle, backend_url).await;
+            println!("🎤 Background recording task completed");
+        });
+    }
+    
+    println!("🎉 Shortcut handler completed successfully");
+}
+
+// Stop recording without inserting anything; returns false when not recording
+pub async fn cancel_recording(app_handle: &AppHandle, state: &AppStateType) -> bool {
+    let was_recording = match state.lock() {
+        Ok(mut app_state) => std::mem::replace(&mut app_state.is_recording, false),
+        Err(_) => false,
+    };
+    if !was_recording {
+        return false;
+    }
+    
+    println!("🚫 Cancelling recording");
+    breadcrumb("recording cancelled");
+    CORRECTION_REQUESTED.store(false, Ordering::SeqCst);
+    if let Err(e) = recorder().cancel() {
+        println!("❌ Failed to cancel recorder: {}", e);
+    }
+    let _ = emit_recording_state(app_handle.clone(), false).await;
+    let _ = show_overlay(app_handle.clone(), false).await;
+    
+    if !sharing_paused(app_handle) {
+        let event = RecordingCancelledEvent {
+            reason: "Recording cancelled".to_string(),
+            duration_ms: 0,
+        };
+        if let Err(e) = app_handle.emit(RECORDING_CANCELLED_EVENT, event) {
+            println!("⚠️ Failed to emit recording cancellation: {}", e);
+        }
+    }
+    true
+}
+
 // Register global shortcuts with proper event handling
 pub fn setup_shortcuts(app: &AppHandle, state: AppStateType) -> Result<(), String> {
     println!("🎛️  setup_shortcuts called");
@@ -167,11 +647,21 @@ pub fn setup_shortcuts(app: &AppHandle, state: AppStateType) -> Result<(), Strin
             println!("❌ {}", error);
             error
         })?;
-        app_state.current_shortcut.clone()
+        app_state.settings.shortcut.clone()
     };
     
     println!("⌨️  Setting up global shortcut: {}", shortcut_str);
     
+    // Keys like Fn/Globe never reach the plugin, so they go to the low-level listener
+    if let Some(low_level) = low_level_shortcut(&state, &shortcut_str) {
+        return register_low_level_shortcut(app, low_level, Arc::new(move |app, edge| {
+            if edge == KeyEdge::Pressed && accept_hotkey_trigger(&state) {
+                println!("🎯 Low-level shortcut triggered: {:?}", low_level);
+                tauri::async_runtime::spawn(handle_recording_shortcut(app.clone(), state.clone()));
+            }
+        }));
+    }
+    
     // Parse and register the shortcut
     let shortcut = parse_shortcut(&shortcut_str)?;
     println!("✅ Shortcut parsed successfully");
@@ -181,133 +671,27 @@ pub fn setup_shortcuts(app: &AppHandle, state: AppStateType) -> Result<(), Strin
     let state_clone = state.clone();
     
     println!("🔗 Registering shortcut event handler...");
-    app.global_shortcut().on_shortcut(shortcut, move |_app, _event, _monitor| {
+    app.global_shortcut().on_shortcut(shortcut, move |_app, _shortcut, event| {
         let app_handle_clone = app_handle.clone();
         let state_clone = state_clone.clone();
         
+        // Only a fresh press toggles: the release would flip recording straight back, and
+        // held keys auto-repeat as further presses on some platforms
+        if event.state() == ShortcutState::Released {
+            SHORTCUT_HELD.store(false, Ordering::SeqCst);
+            return;
+        }
+        if SHORTCUT_HELD.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if !accept_hotkey_trigger(&state_clone) {
+            return;
+        }
+        
         println!("🎯 GLOBAL SHORTCUT TRIGGERED! Option+Space pressed");
         
         // Handle shortcut press in async context
-        tauri::async_runtime::spawn(async move {
-            println!("🔄 Starting async shortcut handler...");
-            
-            // Handle the recording toggle directly without the State wrapper
-            let is_recording = {
-                let app_state = state_clone.lock().map_err(|e| e.to_string());
-                match app_state {
-                    Ok(state) => {
-                        println!("📊 Current recording state: {}", state.is_recording);
-                        state.is_recording
-                    },
-                    Err(e) => {
-                        println!("❌ Failed to lock app state: {}", e);
-                        return;
-                    }
-                }
-            };
-            
-            if is_recording {
-                println!("🛑 STOPPING RECORDING...");
-                
-                // Signal the recording to stop
-                {
-                    let recording_control = get_recording_control();
-                    let mut should_record = recording_control.lock().unwrap();
-                    *should_record = false;
-                    println!("✅ Recording control signal set to false");
-                }
-                
-                // Update app state
-                let backend_url = {
-                    let mut app_state = state_clone.lock().unwrap();
-                    app_state.is_recording = false;
-                    println!("✅ App recording state set to false");
-                    app_state.backend_url.clone()
-                };
-                
-                // Emit recording state change
-                let _ = emit_recording_state(app_handle_clone.clone(), false).await;
-                
-                // Give a moment for the recording to stop gracefully
-                tokio::time::sleep(Duration::from_millis(100)).await;
-                
-                // Call actual transcription function
-                println!("🎤 Starting transcription process...");
-                let transcription_result = stop_recording_and_transcribe_internal(backend_url).await;
-                
-                let transcribed_text = match transcription_result {
-                    Ok(text) => {
-                        println!("✅ Transcription successful: '{}'", text);
-                        text
-                    },
-                    Err(e) => {
-                        println!("❌ Transcription failed: {}", e);
-                        println!("🔄 Using fallback text");
-                        "Transcription failed".to_string()
-                    }
-                };
-                
-                // Hide overlay and type text
-                println!("🔒 Hiding overlay...");
-                match show_overlay(app_handle_clone.clone(), false).await {
-                    Ok(_) => println!("✅ Overlay hidden successfully"),
-                    Err(e) => println!("❌ Failed to hide overlay: {}", e),
-                }
-                
-                // Only type text if it's not empty and not an error message
-                if !transcribed_text.trim().is_empty() && !transcribed_text.contains("failed") {
-                    println!("⌨️  Starting to type text...");
-                    match type_text(transcribed_text.clone()).await {
-                        Ok(_) => println!("✅ Text typed successfully: '{}'", transcribed_text),
-                        Err(e) => println!("❌ Failed to type text: {}", e),
-                    }
-                } else {
-                    println!("⚠️ Skipping text typing due to empty or error transcription");
-                }
-            } else {
-                println!("🎙️ STARTING RECORDING...");
-                
-                // Start recording
-                {
-                    let mut app_state = state_clone.lock().unwrap();
-                    app_state.is_recording = true;
-                    println!("✅ App recording state set to true");
-                }
-                
-                // Emit recording state change
-                let _ = emit_recording_state(app_handle_clone.clone(), true).await;
-                
-                // Reset recording control to allow new recording
-                {
-                    let recording_control = get_recording_control();
-                    let mut should_record = recording_control.lock().unwrap();
-                    *should_record = true;
-                    println!("✅ Recording control signal set to true");
-                }
-                
-                // Show overlay
-                println!("👁️ Showing overlay...");
-                match show_overlay(app_handle_clone.clone(), true).await {
-                    Ok(_) => println!("✅ Overlay shown successfully"),
-                    Err(e) => println!("❌ Failed to show overlay: {}", e),
-                }
-                
-                // Start the actual recording process in a separate task
-                let backend_url = {
-                    let app_state = state_clone.lock().unwrap();
-                    app_state.backend_url.clone()
-                };
-                
-                tokio::spawn(async move {
-                    println!("🎤 Starting background recording task...");
-                    // This will run until the recording control is set to false
-                    let _result = stop_recording_and_transcribe_internal(backend_url).await;
-                    println!("🎤 Background recording task completed");
-                });
-            }
-            
-            println!("🎉 Shortcut handler completed successfully");
-        });
+        tauri::async_runtime::spawn(handle_recording_shortcut(app_handle_clone, state_clone));
     }).map_err(|e| {
         let error = format!("Failed to register shortcut event handler: {}", e);
         println!("❌ {}", error);
@@ -324,4 +708,309 @@ pub fn setup_shortcuts(app: &AppHandle, state: AppStateType) -> Result<(), Strin
     
     println!("✅ Global shortcut '{}' registered successfully", shortcut_str);
     Ok(())
-} 
\ No newline at end of file
+}
+
+// Register the optional shortcut that toggles incognito dictation
+pub fn setup_incognito_shortcut(app: &AppHandle, state: AppStateType) -> Result<(), String> {
+    let shortcut_str = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.incognito_shortcut.clone()
+    };
+    
+    let shortcut_str = match shortcut_str {
+        Some(shortcut_str) if !shortcut_str.trim().is_empty() => shortcut_str,
+        _ => {
+            println!("📋 No incognito shortcut configured");
+            return Ok(());
+        }
+    };
+    
+    println!("⌨️  Setting up incognito shortcut: {}", shortcut_str);
+    if let Some(low_level) = low_level_shortcut(&state, &shortcut_str) {
+        return register_low_level_shortcut(app, low_level, Arc::new(|app, edge| {
+            if edge == KeyEdge::Pressed {
+                toggle_incognito_internal(app);
+            }
+        }));
+    }
+    let shortcut = parse_shortcut(&shortcut_str)?;
+    
+    app.global_shortcut().on_shortcut(shortcut, move |app, _shortcut, event| {
+        if event.state() == ShortcutState::Pressed {
+            toggle_incognito_internal(app);
+        }
+    }).map_err(|e| {
+        let error = format!("Failed to register incognito shortcut: {}", e);
+        println!("❌ {}", error);
+        error
+    })?;
+    
+    println!("✅ Incognito shortcut '{}' registered successfully", shortcut_str);
+    Ok(())
+}
+
+// Start a recording whose text replaces the last inserted sentence, or stop the current one
+fn start_correction(app: &AppHandle, state: &AppStateType) {
+    if !accept_hotkey_trigger(state) {
+        return;
+    }
+    let is_recording = state.lock().map(|app_state| app_state.is_recording).unwrap_or(false);
+    if !is_recording {
+        println!("✏️ Re-dictating the last sentence");
+        CORRECTION_REQUESTED.store(true, Ordering::SeqCst);
+    }
+    tauri::async_runtime::spawn(handle_recording_shortcut(app.clone(), state.clone()));
+}
+
+// Start a recording transcribed with accurate_model, or stop the current one
+fn start_accurate_dictation(app: &AppHandle, state: &AppStateType) {
+    if !accept_hotkey_trigger(state) {
+        return;
+    }
+    let is_recording = state.lock().map(|app_state| app_state.is_recording).unwrap_or(false);
+    if !is_recording {
+        println!("🎯 Dictating with the accurate model");
+        ACCURATE_REQUESTED.store(true, Ordering::SeqCst);
+    }
+    tauri::async_runtime::spawn(handle_recording_shortcut(app.clone(), state.clone()));
+}
+
+// Register the optional shortcut that dictates with accurate_model instead of the current model
+pub fn setup_accurate_shortcut(app: &AppHandle, state: AppStateType) -> Result<(), String> {
+    let (shortcut_str, accurate_model) = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        (app_state.settings.accurate_shortcut.clone(), app_state.settings.accurate_model.clone())
+    };
+    
+    let shortcut_str = match shortcut_str {
+        Some(shortcut_str) if !shortcut_str.trim().is_empty() => shortcut_str,
+        _ => {
+            println!("📋 No accurate dictation shortcut configured");
+            return Ok(());
+        }
+    };
+    if accurate_model.is_none() {
+        println!("⚠️ accurate_shortcut is set without accurate_model; it dictates with the current model");
+    }
+    
+    println!("⌨️  Setting up accurate dictation shortcut: {}", shortcut_str);
+    if let Some(low_level) = low_level_shortcut(&state, &shortcut_str) {
+        let state = state.clone();
+        return register_low_level_shortcut(app, low_level, Arc::new(move |app, edge| {
+            if edge == KeyEdge::Pressed {
+                start_accurate_dictation(app, &state);
+            }
+        }));
+    }
+    let shortcut = parse_shortcut(&shortcut_str)?;
+    
+    app.global_shortcut().on_shortcut(shortcut, move |app, _shortcut, event| {
+        if event.state() == ShortcutState::Pressed {
+            start_accurate_dictation(app, &state);
+        }
+    }).map_err(|e| {
+        let error = format!("Failed to register accurate dictation shortcut: {}", e);
+        println!("❌ {}", error);
+        error
+    })?;
+    
+    println!("✅ Accurate dictation shortcut '{}' registered successfully", shortcut_str);
+    Ok(())
+}
+
+// Register the optional "fix that" shortcut that re-dictates the last inserted sentence
+pub fn setup_correction_shortcut(app: &AppHandle, state: AppStateType) -> Result<(), String> {
+    let shortcut_str = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.correction_shortcut.clone()
+    };
+    
+    let shortcut_str = match shortcut_str {
+        Some(shortcut_str) if !shortcut_str.trim().is_empty() => shortcut_str,
+        _ => {
+            println!("📋 No correction shortcut configured");
+            return Ok(());
+        }
+    };
+    
+    println!("⌨️  Setting up correction shortcut: {}", shortcut_str);
+    if let Some(low_level) = low_level_shortcut(&state, &shortcut_str) {
+        let state = state.clone();
+        return register_low_level_shortcut(app, low_level, Arc::new(move |app, edge| {
+            if edge == KeyEdge::Pressed {
+                start_correction(app, &state);
+            }
+        }));
+    }
+    let shortcut = parse_shortcut(&shortcut_str)?;
+    
+    app.global_shortcut().on_shortcut(shortcut, move |app, _shortcut, event| {
+        if event.state() == ShortcutState::Pressed {
+            start_correction(app, &state);
+        }
+    }).map_err(|e| {
+        let error = format!("Failed to register correction shortcut: {}", e);
+        println!("❌ {}", error);
+        error
+    })?;
+    
+    println!("✅ Correction shortcut '{}' registered successfully", shortcut_str);
+    Ok(())
+}
+
+// Register the stop and cancel shortcuts for the recording that just started
+fn register_recording_shortcuts(app: &AppHandle, state: AppStateType) {
+    let (main_shortcut, stop_shortcut, cancel_shortcut) = match state.lock() {
+        Ok(app_state) => (
+            app_state.settings.shortcut.clone(),
+            app_state.settings.stop_shortcut.clone(),
+            app_state.settings.cancel_shortcut.clone(),
+        ),
+        Err(_) => return,
+    };
+    
+    let bindings = [(stop_shortcut, false), (cancel_shortcut, true)];
+    for (shortcut_str, cancels) in bindings {
+        // The dictation shortcut already stops a recording
+        let shortcut_str = match shortcut_str {
+            Some(shortcut_str) if !shortcut_str.trim().is_empty() && shortcut_str != main_shortcut => shortcut_str,
+            _ => continue,
+        };
+        let shortcut = match parse_shortcut(&shortcut_str) {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                println!("⚠️ Invalid {} shortcut '{}': {}", if cancels { "cancel" } else { "stop" }, shortcut_str, e);
+                continue;
+            }
+        };
+        
+        let state = state.clone();
+        let registered = app.global_shortcut().on_shortcut(shortcut, move |app, _shortcut, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            let app = app.clone();
+            let state = state.clone();
+            if cancels {
+                tauri::async_runtime::spawn(async move {
+                    cancel_recording(&app, &state).await;
+                });
+            } else if state.lock().map(|app_state| app_state.is_recording).unwrap_or(false) && accept_hotkey_trigger(&state) {
+                tauri::async_runtime::spawn(handle_recording_shortcut(app, state));
+            }
+        });
+        match registered {
+            Ok(()) => {
+                println!("⌨️  {} shortcut '{}' active while recording", if cancels { "Cancel" } else { "Stop" }, shortcut_str);
+                if let Ok(mut registered) = RECORDING_SHORTCUTS.lock() {
+                    registered.push(shortcut);
+                }
+            }
+            Err(e) => println!("⚠️ Failed to register shortcut '{}': {}", shortcut_str, e),
+        }
+    }
+}
+
+// Release the stop and cancel shortcuts so the keys type normally again
+fn unregister_recording_shortcuts(app: &AppHandle) {
+    let shortcuts = match RECORDING_SHORTCUTS.lock() {
+        Ok(mut registered) => std::mem::take(&mut *registered),
+        Err(_) => return,
+    };
+    for shortcut in shortcuts {
+        if let Err(e) = app.global_shortcut().unregister(shortcut) {
+            println!("⚠️ Failed to unregister recording shortcut: {}", e);
+        }
+    }
+}
+
+// Show or hide the command palette, logging failures
+fn show_command_palette(app: &AppHandle) {
+    if let Err(e) = toggle_command_palette(app) {
+        println!("❌ {}", e);
+    }
+}
+
+// Register the optional shortcut that shows or hides the command palette
+pub fn setup_palette_shortcut(app: &AppHandle, state: AppStateType) -> Result<(), String> {
+    let shortcut_str = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.palette_shortcut.clone()
+    };
+    
+    let shortcut_str = match shortcut_str {
+        Some(shortcut_str) if !shortcut_str.trim().is_empty() => shortcut_str,
+        _ => {
+            println!("📋 No command palette shortcut configured");
+            return Ok(());
+        }
+    };
+    
+    println!("⌨️  Setting up command palette shortcut: {}", shortcut_str);
+    if let Some(low_level) = low_level_shortcut(&state, &shortcut_str) {
+        return register_low_level_shortcut(app, low_level, Arc::new(|app, edge| {
+            if edge == KeyEdge::Pressed {
+                show_command_palette(app);
+            }
+        }));
+    }
+    let shortcut = parse_shortcut(&shortcut_str)?;
+    
+    app.global_shortcut().on_shortcut(shortcut, move |app, _shortcut, event| {
+        if event.state() == ShortcutState::Pressed {
+            show_command_palette(app);
+        }
+    }).map_err(|e| {
+        let error = format!("Failed to register command palette shortcut: {}", e);
+        println!("❌ {}", error);
+        error
+    })?;
+    
+    println!("✅ Command palette shortcut '{}' registered successfully", shortcut_str);
+    Ok(())
+}
+
+fn show_history_picker(app: &AppHandle) {
+    if let Err(e) = toggle_history_picker(app) {
+        println!("❌ {}", e);
+    }
+}
+
+// Register the optional shortcut that shows or hides the recent dictations picker
+pub fn setup_history_picker_shortcut(app: &AppHandle, state: AppStateType) -> Result<(), String> {
+    let shortcut_str = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.history_picker_shortcut.clone()
+    };
+    
+    let shortcut_str = match shortcut_str {
+        Some(shortcut_str) if !shortcut_str.trim().is_empty() => shortcut_str,
+        _ => {
+            println!("📋 No history picker shortcut configured");
+            return Ok(());
+        }
+    };
+    
+    println!("⌨️  Setting up history picker shortcut: {}", shortcut_str);
+    if let Some(low_level) = low_level_shortcut(&state, &shortcut_str) {
+        return register_low_level_shortcut(app, low_level, Arc::new(|app, edge| {
+            if edge == KeyEdge::Pressed {
+                show_history_picker(app);
+            }
+        }));
+    }
+    let shortcut = parse_shortcut(&shortcut_str)?;
+    
+    app.global_shortcut().on_shortcut(shortcut, move |app, _shortcut, event| {
+        if event.state() == ShortcutState::Pressed {
+            show_history_picker(app);
+        }
+    }).map_err(|e| {
+        let error = format!("Failed to register history picker shortcut: {}", e);
+        println!("❌ {}", error);
+        error
+    })?;
+    
+    println!("✅ History picker shortcut '{}' registered successfully", shortcut_str);
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use crate::templates::normalize_name;
+use crate::sequencer::{insert_in_order, issue_insertion_ticket};
+use crate::shortcuts::{parse_shortcut, post_insert_actions, reregister_shortcuts};
+use crate::types::AppStateType;
+use crate::portable::app_config_dir;
+use crate::private_files::create_private_dir;
+
+// Spoken command inserting a snippet by name, e.g. "Insert my address"
+const SPOKEN_SNIPPET_PREFIX: &str = "insert ";
+
+// A saved piece of text inserted on demand instead of being dictated again
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    pub name: String,
+    pub text: String,
+    // Optional global shortcut inserting this snippet directly
+    #[serde(default)]
+    pub shortcut: Option<String>,
+    // Phrases that insert this snippet when a dictation is or starts with them
+    #[serde(default)]
+    pub triggers: Vec<String>,
+}
+
+// Resolve the snippets file location
+pub fn snippets_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app_config_dir(app)?;
+    Ok(config_dir.join("snippets.json"))
+}
+
+pub fn load_snippets(app: &AppHandle) -> Result<Vec<Snippet>, String> {
+    let path = snippets_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read snippets: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse snippets: {}", e))
+}
+
+fn save_snippets(app: &AppHandle, snippets: &[Snippet]) -> Result<(), String> {
+    let path = snippets_path(app)?;
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    
+    let contents = serde_json::to_string_pretty(snippets)
+        .map_err(|e| format!("Failed to serialize snippets: {}", e))?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write snippets: {}", e))
+}
+
+// Character edit distance between two phrases
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// Whether a spoken phrase matches a trigger within the allowed share of differing characters
+fn phrase_matches(spoken: &str, trigger: &str, tolerance: f64) -> bool {
+    if spoken == trigger {
+        return true;
+    }
+    let allowed = (trigger.chars().count() as f64 * tolerance.clamp(0.0, 1.0)).floor() as usize;
+    allowed > 0 && edit_distance(spoken, trigger) <= allowed
+}
+
+// Find a snippet whose trigger phrase the dictation starts with; returns it with the
+// number of dictated words the trigger covers
+fn match_trigger<'a>(snippets: &'a [Snippet], text: &str, tolerance: f64) -> Option<(&'a Snippet, usize)> {
+    let words: Vec<String> = text.split_whitespace().map(normalize_name).collect();
+    
+    // Prefer the longest trigger so "signature" doesn't shadow "signature formal"
+    snippets
+        .iter()
+        .flat_map(|snippet| snippet.triggers.iter().map(move |trigger| (snippet, normalize_name(trigger))))
+        .filter_map(|(snippet, trigger)| {
+            let count = trigger.split_whitespace().count();
+            if count == 0 || count > words.len() {
+                return None;
+            }
+            let spoken = words[..count].join(" ");
+            phrase_matches(&spoken, &trigger, tolerance).then_some((snippet, count, trigger.len()))
+        })
+        .max_by_key(|(_, _, length)| *length)
+        .map(|(snippet, count, _)| (snippet, count))
+}
+
+// Replace "insert <snippet name>" or a leading trigger phrase with the snippet text;
+// words dictated after a trigger are kept after the snippet
+pub fn expand_spoken_snippet(app: &AppHandle, text: &str) -> Option<String> {
+    let snippets = match load_snippets(app) {
+        Ok(snippets) => snippets,
+        Err(e) => {
+            println!("⚠️ {}", e);
+            return None;
+        }
+    };
+    
+    let normalized = normalize_name(text);
+    if let Some(name) = normalized.strip_prefix(SPOKEN_SNIPPET_PREFIX) {
+        if let Some(snippet) = snippets.iter().find(|snippet| normalize_name(&snippet.name) == name) {
+            println!("📎 Expanding spoken snippet '{}'", snippet.name);
+            return Some(snippet.text.clone());
+        }
+    }
+    
+    let tolerance = match app.try_state::<AppStateType>() {
+        Some(state) => state
+            .lock()
+            .map(|app_state| app_state.settings.snippet_trigger_tolerance)
+            .unwrap_or_default(),
+        None => 0.0,
+    };
+    let (snippet, count) = match_trigger(&snippets, text, tolerance)?;
+    println!("📎 Trigger phrase matched snippet '{}'", snippet.name);
+    
+    let rest = text.split_whitespace().skip(count).collect::<Vec<_>>().join(" ");
+    if rest.is_empty() {
+        Some(snippet.text.clone())
+    } else {
+        Some(format!("{} {}", snippet.text, rest))
+    }
+}
+
+// Type a snippet through the same ordered insertion pipeline as dictations
+pub async fn insert_snippet_text(app: &AppHandle, text: String) -> Result<(), String> {
+    let actions = match app.try_state::<AppStateType>() {
+        Some(state) => post_insert_actions(state.inner()),
+        None => Vec::new(),
+    };
+    let ticket = issue_insertion_ticket();
+    insert_in_order(ticket, Some(text), &actions).await
+}
+
+// Register the global shortcut of every snippet that has one
+pub fn setup_snippet_shortcuts(app: &AppHandle) -> Result<(), String> {
+    let snippets = load_snippets(app)?;
+    
+    for snippet in snippets {
+        let shortcut_str = match &snippet.shortcut {
+            Some(shortcut_str) if !shortcut_str.trim().is_empty() => shortcut_str.clone(),
+            _ => continue,
+        };
+        
+        // A bad binding on one snippet should not take the others down
+        let shortcut = match parse_shortcut(&shortcut_str) {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                println!("❌ Invalid shortcut for snippet '{}': {}", snippet.name, e);
+                continue;
+            }
+        };
+        
+        let name = snippet.name.clone();
+        let text = snippet.text.clone();
+        let result = app.global_shortcut().on_shortcut(shortcut, move |app, _shortcut, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            
+            println!("📎 Snippet shortcut pressed: '{}'", name);
+            let app = app.clone();
+            let text = text.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = insert_snippet_text(&app, text).await {
+                    println!("❌ Failed to insert snippet: {}", e);
+                }
+            });
+        });
+        
+        match result {
+            Ok(_) => println!("✅ Snippet shortcut '{}' registered for '{}'", shortcut_str, snippet.name),
+            Err(e) => println!("❌ Failed to register shortcut for snippet '{}': {}", snippet.name, e),
+        }
+    }
+    
+    Ok(())
+}
+
+// List saved snippets
+#[tauri::command]
+pub async fn list_snippets(app_handle: AppHandle) -> Result<Vec<Snippet>, String> {
+    load_snippets(&app_handle)
+}
+
+// Create or replace a snippet by name
+#[tauri::command]
+pub async fn save_snippet(app_handle: AppHandle, snippet: Snippet, state: tauri::State<'_, AppStateType>) -> Result<(), String> {
+    if snippet.name.trim().is_empty() {
+        return Err("Snippet name cannot be empty".to_string());
+    }
+    if let Some(shortcut) = snippet.shortcut.as_deref().filter(|shortcut| !shortcut.trim().is_empty()) {
+        parse_shortcut(shortcut)?;
+    }
+    
+    let mut snippets = load_snippets(&app_handle)?;
+    let had_shortcut = snippets
+        .iter()
+        .any(|existing| existing.name == snippet.name && existing.shortcut.is_some());
+    match snippets.iter_mut().find(|existing| existing.name == snippet.name) {
+        Some(existing) => *existing = snippet.clone(),
+        None => snippets.push(snippet.clone()),
+    }
+    save_snippets(&app_handle, &snippets)?;
+    
+    // Shortcut handlers capture the snippet text, so they are rebuilt on any change
+    if had_shortcut || snippet.shortcut.is_some() {
+        reregister_shortcuts(&app_handle, state.inner().clone())?;
+    }
+    
+    println!("📎 Snippet '{}' saved", snippet.name);
+    Ok(())
+}
+
+// Delete a snippet by name
+#[tauri::command]
+pub async fn delete_snippet(app_handle: AppHandle, name: String, state: tauri::State<'_, AppStateType>) -> Result<(), String> {
+    let mut snippets = load_snippets(&app_handle)?;
+    let had_shortcut = snippets
+        .iter()
+        .any(|snippet| snippet.name == name && snippet.shortcut.is_some());
+    snippets.retain(|snippet| snippet.name != name);
+    save_snippets(&app_handle, &snippets)?;
+    
+    if had_shortcut {
+        reregister_shortcuts(&app_handle, state.inner().clone())?;
+    }
+    
+    println!("🗑️ Snippet '{}' deleted", name);
+    Ok(())
+}
+
+// Insert a snippet by name at the cursor
+#[tauri::command]
+pub async fn insert_snippet(app_handle: AppHandle, name: String) -> Result<(), String> {
+    let snippets = load_snippets(&app_handle)?;
+    let snippet = snippets
+        .into_iter()
+        .find(|snippet| snippet.name == name)
+        .ok_or_else(|| format!("Unknown snippet: {}", name))?;
+    
+    insert_snippet_text(&app_handle, snippet.text).await
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use crate::types::TranscriptionResult;
+use crate::portable::app_data_dir;
+use crate::private_files::create_private_dir;
+
+// Sum of per-stage latencies across all recorded dictations, in milliseconds
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LatencyTotals {
+    pub capture_ms: u64,
+    pub encode_ms: u64,
+    pub upload_ms: u64,
+    pub backend_ms: u64,
+    pub post_process_ms: u64,
+    pub insert_ms: u64,
+}
+
+// Name used for dictations whose target application couldn't be detected
+const UNKNOWN_APP: &str = "Unknown";
+
+// Dictation totals for one target application
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppTotals {
+    pub dictations: u64,
+    pub words: u64,
+    pub audio_ms: u64,
+}
+
+// One application's share of all dictation, as returned by get_statistics_by_app
+#[derive(Clone, Serialize)]
+pub struct AppUsage {
+    pub app: String,
+    #[serde(flatten)]
+    pub totals: AppTotals,
+    // Fraction of all dictated words (0.0-1.0)
+    pub word_share: f64,
+}
+
+// Aggregated usage statistics persisted to statistics.json
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    pub total_dictations: u64,
+    pub total_words: u64,
+    pub total_audio_ms: u64,
+    // Time spent speaking vs. pausing, from transcript segments
+    pub total_speaking_ms: u64,
+    pub total_pause_ms: u64,
+    // Average pace over all dictations, weighted by speaking time
+    pub average_words_per_minute: f64,
+    pub latency_totals: LatencyTotals,
+    // Totals per application dictated into, keyed by application name
+    pub by_app: HashMap<String, AppTotals>,
+}
+
+// Resolve the statistics file location
+pub fn statistics_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let data_dir = app_data_dir(app)?;
+    Ok(data_dir.join("statistics.json"))
+}
+
+// Load statistics, starting fresh when none exist yet
+pub fn load_statistics(app: &AppHandle) -> Result<Statistics, String> {
+    let path = statistics_path(app)?;
+    if !path.exists() {
+        return Ok(Statistics::default());
+    }
+    
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read statistics: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse statistics: {}", e))
+}
+
+fn save_statistics(app: &AppHandle, statistics: &Statistics) -> Result<(), String> {
+    let path = statistics_path(app)?;
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    }
+    
+    let contents = serde_json::to_string_pretty(statistics)
+        .map_err(|e| format!("Failed to serialize statistics: {}", e))?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write statistics: {}", e))
+}
+
+// Add a finished dictation, typed into target_app, to the aggregated statistics
+pub fn record_dictation(app: &AppHandle, result: &TranscriptionResult, target_app: Option<&str>) -> Result<(), String> {
+    let mut statistics = load_statistics(app)?;
+    let timings = &result.timings;
+    let words = result.text.split_whitespace().count() as u64;
+    
+    statistics.total_dictations += 1;
+    statistics.total_words += words;
+    statistics.total_audio_ms += timings.capture_ms;
+    
+    let app_totals = statistics
+        .by_app
+        .entry(target_app.unwrap_or(UNKNOWN_APP).to_string())
+        .or_default();
+    app_totals.dictations += 1;
+    app_totals.words += words;
+    app_totals.audio_ms += timings.capture_ms;
+    
+    let rate = &result.speech_rate;
+    let spoken_words = (statistics.average_words_per_minute * statistics.total_speaking_ms as f64 / 60_000.0)
+        + rate.words as f64;
+    statistics.total_speaking_ms += rate.speaking_ms;
+    statistics.total_pause_ms += rate.pause_ms;
+    if statistics.total_speaking_ms > 0 {
+        statistics.average_words_per_minute = spoken_words * 60_000.0 / statistics.total_speaking_ms as f64;
+    }
+    
+    let totals = &mut statistics.latency_totals;
+    totals.capture_ms += timings.capture_ms;
+    totals.encode_ms += timings.encode_ms;
+    totals.upload_ms += timings.upload_ms;
+    totals.backend_ms += timings.backend_ms.unwrap_or(0);
+    totals.post_process_ms += timings.post_process_ms.unwrap_or(0);
+    totals.insert_ms += timings.insert_ms.unwrap_or(0);
+    
+    save_statistics(app, &statistics)
+}
+
+// Get aggregated dictation statistics
+#[tauri::command]
+pub async fn get_statistics(app_handle: AppHandle) -> Result<Statistics, String> {
+    load_statistics(&app_handle)
+}
+
+// Get dictation totals per target application, most dictated-into first
+#[tauri::command]
+pub async fn get_statistics_by_app(app_handle: AppHandle) -> Result<Vec<AppUsage>, String> {
+    let statistics = load_statistics(&app_handle)?;
+    let total_words: u64 = statistics.by_app.values().map(|totals| totals.words).sum();
+    
+    let mut usage: Vec<AppUsage> = statistics
+        .by_app
+        .into_iter()
+        .map(|(app, totals)| AppUsage {
+            word_share: if total_words > 0 { totals.words as f64 / total_words as f64 } else { 0.0 },
+            app,
+            totals,
+        })
+        .collect();
+    usage.sort_by(|a, b| b.totals.words.cmp(&a.totals.words).then_with(|| a.app.cmp(&b.app)));
+    Ok(usage)
+}
+
+// Reset all statistics
+#[tauri::command]
+pub async fn reset_statistics(app_handle: AppHandle) -> Result<(), String> {
+    save_statistics(&app_handle, &Statistics::default())?;
+    println!("🗑️ Statistics reset");
+    Ok(())
+}
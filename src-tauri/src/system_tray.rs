@@ -1,5 +1,91 @@
-use tauri::{AppHandle, Manager, menu::{Menu, MenuItem, PredefinedMenuItem}, tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent}};
-use crate::backend::start_backend;
+use tauri::{AppHandle, Listener, Manager, tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent}};
+use crate::backend::{language_name, set_language_internal, set_model_internal, start_backend, DICTATION_LANGUAGES, WHISPER_MODELS};
+use crate::types::{AppStateType, BackendStatusEvent, ModelStatusEvent, RecordingStateEvent, BACKEND_STATUS_EVENT, INCOGNITO_EVENT, LANGUAGE_CHANGED_EVENT, MODEL_LOADING_EVENT, MODEL_READY_EVENT, MODEL_UNLOADED_EVENT, RECORDING_STATE_EVENT, SCREEN_SHARING_EVENT, TRANSCRIPTION_EVENT};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::quit::{is_quitting, quit_gracefully};
+use crate::updater::UPDATE_TRAY_SECTION;
+use crate::privacy::{is_incognito, toggle_incognito_internal};
+use crate::history::{show_history_window, HISTORY_TRAY_SECTION};
+use crate::tray_menu::{build_tray_menu, handle_tray_click, rebuild_tray_menu, register_tray_section, TrayEntry, TraySection};
+use crate::window_manager::restore_settings_window_geometry;
+use crate::settings::{apply_settings, save_settings, Settings};
+
+// Identifier of the single tray icon
+pub const TRAY_ID: &str = "main";
+
+// What the tray tooltip and title currently reflect, fed by status events
+struct TrayStatus {
+    recording_since: Option<Instant>,
+    backend_offline: bool,
+    loading_model: Option<String>,
+    model_unloaded: bool,
+}
+
+static TRAY_STATUS: Mutex<TrayStatus> = Mutex::new(TrayStatus {
+    recording_since: None,
+    backend_offline: false,
+    loading_model: None,
+    model_unloaded: false,
+});
+
+// Describe the current state in one line, e.g. "Cursper — Recording 00:12"
+fn tray_status_text(app: &AppHandle, status: &TrayStatus) -> String {
+    let state_text = if let Some(since) = status.recording_since {
+        let elapsed = since.elapsed().as_secs();
+        format!("Recording {:02}:{:02}", elapsed / 60, elapsed % 60)
+    } else if status.backend_offline {
+        "Backend offline".to_string()
+    } else if let Some(model) = &status.loading_model {
+        format!("Loading {}…", model)
+    } else if status.model_unloaded {
+        "Model asleep".to_string()
+    } else {
+        let model = app
+            .state::<AppStateType>()
+            .lock()
+            .map(|app_state| app_state.settings.model.clone())
+            .unwrap_or_default();
+        format!("Ready ({})", model)
+    };
+    
+    let incognito = if is_incognito() { " · Incognito" } else { "" };
+    format!("Cursper — {}{}", state_text, incognito)
+}
+
+// Push the current status to the tray tooltip, and the title while recording
+fn refresh_tray_status(app: &AppHandle) {
+    let tray = match app.tray_by_id(TRAY_ID) {
+        Some(tray) => tray,
+        None => return,
+    };
+    
+    let (tooltip, title) = match TRAY_STATUS.lock() {
+        Ok(status) => {
+            let title = status.recording_since.map(|since| {
+                let elapsed = since.elapsed().as_secs();
+                format!("● {:02}:{:02}", elapsed / 60, elapsed % 60)
+            });
+            (tray_status_text(app, &status), title)
+        }
+        Err(_) => return,
+    };
+    
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        println!("⚠️ Failed to update tray tooltip: {}", e);
+    }
+    if let Err(e) = tray.set_title(title) {
+        println!("⚠️ Failed to update tray title: {}", e);
+    }
+}
+
+// Apply a change to the tray status and redraw it
+fn update_tray_status(app: &AppHandle, update: impl FnOnce(&mut TrayStatus)) {
+    if let Ok(mut status) = TRAY_STATUS.lock() {
+        update(&mut status);
+    }
+    refresh_tray_status(app);
+}
 
 // Test if global shortcuts and accessibility are working
 #[tauri::command]
@@ -51,53 +137,247 @@ pub async fn open_settings(app_handle: AppHandle) -> Result<(), String> {
     show_settings_window(&app_handle)
 }
 
+// Switch model from the tray; the loading label clears on "model-ready"
+fn select_model_from_tray(app: &AppHandle, model: String) {
+    println!("🧠 Model {} selected from tray menu", model);
+    update_tray_status(app, |status| status.loading_model = Some(model.clone()));
+    rebuild_tray_menu(app);
+    
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppStateType>().inner().clone();
+        if let Err(e) = set_model_internal(&app_handle, &state, model).await {
+            println!("❌ Failed to switch model from tray: {}", e);
+            update_tray_status(&app_handle, |status| status.loading_model = None);
+            rebuild_tray_menu(&app_handle);
+        }
+    });
+}
+
+// On/off settings offered as tray checkboxes, as (menu id, label)
+const SETTING_TOGGLES: &[(&str, &str)] = &[
+    ("toggle:show_overlay", "Show Overlay"),
+    ("toggle:history_enabled", "Save History"),
+    ("toggle:pause_media_while_recording", "Pause Media While Recording"),
+];
+
+// The setting a tray checkbox controls
+fn toggle_field<'a>(settings: &'a mut Settings, id: &str) -> Option<&'a mut bool> {
+    match id {
+        "toggle:show_overlay" => Some(&mut settings.show_overlay),
+        "toggle:history_enabled" => Some(&mut settings.history_enabled),
+        "toggle:pause_media_while_recording" => Some(&mut settings.pause_media_while_recording),
+        _ => None,
+    }
+}
+
+// Flip a setting from its tray checkbox, saving it like an edit in the settings window
+fn toggle_setting_from_tray(app: &AppHandle, id: &str) {
+    let state = app.state::<AppStateType>().inner().clone();
+    let mut settings = match state.lock() {
+        Ok(app_state) => app_state.settings.clone(),
+        Err(_) => return,
+    };
+    match toggle_field(&mut settings, id) {
+        Some(enabled) => {
+            *enabled = !*enabled;
+            println!("🔘 {} set to {} from tray menu", id.trim_start_matches("toggle:"), enabled);
+        }
+        None => return,
+    }
+    
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match save_settings(&app, &settings) {
+            Ok(_) => apply_settings(&app, &state, settings).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            println!("❌ Failed to change setting from tray: {}", e);
+            // The platform already flipped the checkmark; put it back
+            rebuild_tray_menu(&app);
+        }
+    });
+}
+
+fn current_settings(app: &AppHandle) -> Settings {
+    app.state::<AppStateType>()
+        .lock()
+        .map(|app_state| app_state.settings.clone())
+        .unwrap_or_default()
+}
+
+// Settings and History windows
+fn window_entries(_app: &AppHandle) -> Vec<TrayEntry> {
+    vec![
+        TrayEntry::item("settings", "Settings"),
+        TrayEntry::item("history", "History"),
+    ]
+}
+
+fn on_window_click(app: &AppHandle, id: &str) -> bool {
+    match id {
+        "settings" => {
+            println!("⚙️ Settings clicked from tray menu");
+            if let Err(e) = show_settings_window(app) {
+                println!("❌ Failed to show settings window: {}", e);
+            }
+        }
+        "history" => {
+            println!("📜 History clicked from tray menu");
+            if let Err(e) = show_history_window(app) {
+                println!("❌ {}", e);
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+// Model and language pickers; the model being loaded is checked, labelled and locks the list
+fn dictation_entries(app: &AppHandle) -> Vec<TrayEntry> {
+    let settings = current_settings(app);
+    let loading_model = TRAY_STATUS.lock().ok().and_then(|status| status.loading_model.clone());
+    let active_model = loading_model.clone().unwrap_or_else(|| settings.model.clone());
+    
+    let models = WHISPER_MODELS
+        .iter()
+        .map(|model| {
+            let is_active = *model == active_model;
+            let label = if is_active && loading_model.is_some() {
+                format!("{} (loading…)", model)
+            } else {
+                model.to_string()
+            };
+            let entry = TrayEntry::check(format!("model:{}", model), label, is_active);
+            if loading_model.is_some() { entry.disabled() } else { entry }
+        })
+        .collect();
+    
+    // Auto, recently used languages, then the rest
+    let current = settings.language.as_deref();
+    let mut languages = vec![TrayEntry::check("language:auto", "Auto-detect", current.is_none())];
+    if !settings.recent_languages.is_empty() {
+        languages.push(TrayEntry::Separator);
+        for code in &settings.recent_languages {
+            languages.push(TrayEntry::check(format!("language:{}", code), language_name(code), current == Some(code.as_str())));
+        }
+    }
+    languages.push(TrayEntry::Separator);
+    for (code, name) in DICTATION_LANGUAGES {
+        if settings.recent_languages.iter().any(|recent_code| recent_code.as_str() == *code) {
+            continue;
+        }
+        languages.push(TrayEntry::check(format!("language:{}", code), *name, current == Some(*code)));
+    }
+    
+    vec![
+        TrayEntry::submenu("Model", models),
+        TrayEntry::submenu("Language", languages),
+    ]
+}
+
+fn on_dictation_click(app: &AppHandle, id: &str) -> bool {
+    if let Some(model) = id.strip_prefix("model:") {
+        select_model_from_tray(app, model.to_string());
+    } else if let Some(code) = id.strip_prefix("language:") {
+        let language = match code {
+            "auto" => None,
+            code => Some(code.to_string()),
+        };
+        println!("🌍 Language selected from tray menu: {:?}", language);
+        let state = app.state::<AppStateType>().inner().clone();
+        if let Err(e) = set_language_internal(app, &state, language) {
+            println!("❌ Failed to set language from tray: {}", e);
+        }
+    } else {
+        return false;
+    }
+    true
+}
+
+fn backend_entries(_app: &AppHandle) -> Vec<TrayEntry> {
+    vec![
+        TrayEntry::item("start_backend", "Start Backend"),
+        TrayEntry::item("test_recording", "Test Recording"),
+    ]
+}
+
+fn on_backend_click(app: &AppHandle, id: &str) -> bool {
+    match id {
+        "start_backend" => {
+            println!("🐍 Start backend clicked from tray menu");
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = start_backend(app).await {
+                    println!("❌ Failed to start backend: {}", e);
+                }
+            });
+        }
+        "test_recording" => {
+            println!("🎤 Test recording clicked from tray menu");
+            // We'll need to access state here properly later
+        }
+        _ => return false,
+    }
+    true
+}
+
+// Incognito and the on/off settings
+fn toggle_entries(app: &AppHandle) -> Vec<TrayEntry> {
+    let mut settings = current_settings(app);
+    let mut entries = vec![TrayEntry::check("incognito", "Incognito Dictation", is_incognito())];
+    for (id, label) in SETTING_TOGGLES {
+        let checked = toggle_field(&mut settings, id).map(|enabled| *enabled).unwrap_or(false);
+        entries.push(TrayEntry::check(*id, *label, checked));
+    }
+    entries
+}
+
+fn on_toggle_click(app: &AppHandle, id: &str) -> bool {
+    if id == "incognito" {
+        println!("🕶️ Incognito toggled from tray menu");
+        toggle_incognito_internal(app);
+    } else if id.starts_with("toggle:") {
+        toggle_setting_from_tray(app, id);
+    } else {
+        return false;
+    }
+    true
+}
+
+fn quit_entries(_app: &AppHandle) -> Vec<TrayEntry> {
+    if is_quitting() {
+        return vec![TrayEntry::item("quit", "Quitting...").disabled()];
+    }
+    vec![TrayEntry::item("quit", "Quit")]
+}
+
+fn on_quit_click(app: &AppHandle, id: &str) -> bool {
+    if id != "quit" {
+        return false;
+    }
+    println!("🚪 Quit clicked from tray menu");
+    tauri::async_runtime::spawn(quit_gracefully(app.clone()));
+    true
+}
+
 // Create system tray with menu
 pub fn setup_system_tray(app: &AppHandle) -> Result<(), tauri::Error> {
-    let settings_i = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
-    let start_backend_i = MenuItem::with_id(app, "start_backend", "Start Backend", true, None::<&str>)?;
-    let test_recording_i = MenuItem::with_id(app, "test_recording", "Test Recording", true, None::<&str>)?;
-    let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    
-    let menu = Menu::with_items(app, &[
-        &settings_i,
-        &PredefinedMenuItem::separator(app)?,
-        &start_backend_i,
-        &test_recording_i,
-        &PredefinedMenuItem::separator(app)?,
-        &quit_i,
-    ])?;
-
-    let _tray = TrayIconBuilder::new()
+    register_tray_section(TraySection { order: 10, entries: window_entries, on_click: on_window_click });
+    register_tray_section(TraySection { order: 20, entries: dictation_entries, on_click: on_dictation_click });
+    register_tray_section(HISTORY_TRAY_SECTION);
+    register_tray_section(TraySection { order: 40, entries: backend_entries, on_click: on_backend_click });
+    register_tray_section(TraySection { order: 50, entries: toggle_entries, on_click: on_toggle_click });
+    register_tray_section(UPDATE_TRAY_SECTION);
+    register_tray_section(TraySection { order: 1000, entries: quit_entries, on_click: on_quit_click });
+    let menu = build_tray_menu(app)?;
+    
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .show_menu_on_left_click(false)
-        .on_menu_event(move |app, event| {
-            match event.id().as_ref() {
-                "settings" => {
-                    println!("⚙️ Settings clicked from tray menu");
-                    if let Err(e) = show_settings_window(app) {
-                        println!("❌ Failed to show settings window: {}", e);
-                    }
-                }
-                "start_backend" => {
-                    println!("🐍 Start backend clicked from tray menu");
-                    tauri::async_runtime::spawn(async move {
-                        if let Err(e) = start_backend().await {
-                            println!("❌ Failed to start backend: {}", e);
-                        }
-                    });
-                }
-                "test_recording" => {
-                    println!("🎤 Test recording clicked from tray menu");
-                    // We'll need to access state here properly later
-                }
-                "quit" => {
-                    println!("🚪 Quit clicked from tray menu");
-                    app.exit(0);
-                }
-                _ => {}
-            }
-        })
+        .on_menu_event(move |app, event| handle_tray_click(app, event.id().as_ref()))
         .on_tray_icon_event(|tray, event| {
             match event {
                 TrayIconEvent::Click {
@@ -122,7 +402,96 @@ pub fn setup_system_tray(app: &AppHandle) -> Result<(), tauri::Error> {
             }
         })
         .build(app)?;
-
+    
+    // Mark the tray while recording so headless mode still has an indicator
+    let recording_app = app.clone();
+    app.listen(RECORDING_STATE_EVENT, move |event| {
+        let is_recording = serde_json::from_str::<RecordingStateEvent>(event.payload())
+            .map(|state| state.is_recording)
+            .unwrap_or(false);
+        update_tray_status(&recording_app, |status| {
+            status.recording_since = if is_recording {
+                status.recording_since.or(Some(Instant::now()))
+            } else {
+                None
+            };
+        });
+    });
+    
+    let backend_app = app.clone();
+    app.listen(BACKEND_STATUS_EVENT, move |event| {
+        if let Ok(backend) = serde_json::from_str::<BackendStatusEvent>(event.payload()) {
+            update_tray_status(&backend_app, |status| status.backend_offline = !backend.online);
+        }
+    });
+    
+    let loading_app = app.clone();
+    app.listen(MODEL_LOADING_EVENT, move |event| {
+        if let Ok(model_status) = serde_json::from_str::<ModelStatusEvent>(event.payload()) {
+            update_tray_status(&loading_app, |status| status.loading_model = Some(model_status.model));
+            rebuild_tray_menu(&loading_app);
+        }
+    });
+    
+    let unloaded_app = app.clone();
+    app.listen(MODEL_UNLOADED_EVENT, move |_event| {
+        update_tray_status(&unloaded_app, |status| status.model_unloaded = true);
+    });
+    
+    // Incognito toggled from a shortcut or the palette updates the checkbox, and hides
+    // recent dictations
+    let incognito_app = app.clone();
+    app.listen(INCOGNITO_EVENT, move |_event| {
+        refresh_tray_status(&incognito_app);
+        rebuild_tray_menu(&incognito_app);
+    });
+    
+    // Recent dictations are hidden while the screen is shared
+    let sharing_app = app.clone();
+    app.listen(SCREEN_SHARING_EVENT, move |_event| {
+        rebuild_tray_menu(&sharing_app);
+    });
+    
+    // List new dictations under recent ones
+    let transcription_app = app.clone();
+    app.listen(TRANSCRIPTION_EVENT, move |_event| {
+        rebuild_tray_menu(&transcription_app);
+    });
+    
+    // Tick the recording timer once a second
+    let timer_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            let recording = TRAY_STATUS
+                .lock()
+                .map(|status| status.recording_since.is_some())
+                .unwrap_or(false);
+            if recording {
+                refresh_tray_status(&timer_app);
+            }
+        }
+    });
+    
+    // Keep checkmarks and pinned recents in sync with language changes
+    let language_app = app.clone();
+    app.listen(LANGUAGE_CHANGED_EVENT, move |_event| {
+        rebuild_tray_menu(&language_app);
+    });
+    
+    // Clear the loading state once the backend reports the model is ready
+    let ready_app = app.clone();
+    app.listen(MODEL_READY_EVENT, move |_event| {
+        update_tray_status(&ready_app, |status| {
+            status.loading_model = None;
+            status.model_unloaded = false;
+            status.backend_offline = false;
+        });
+        rebuild_tray_menu(&ready_app);
+    });
+    
+    refresh_tray_status(app);
+    
     println!("✅ System tray created successfully");
     Ok(())
 }
@@ -134,6 +503,7 @@ pub fn show_settings_window(app: &AppHandle) -> Result<(), String> {
     match app.get_webview_window("main") {
         Some(window) => {
             println!("📋 Found existing main window");
+            restore_settings_window_geometry(app);
             window.show().map_err(|e| {
                 let error = format!("Failed to show existing window: {}", e);
                 println!("❌ {}", error);
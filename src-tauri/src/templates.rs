@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+use crate::portable::app_config_dir;
+use crate::private_files::create_private_dir;
+
+// Marker replaced by the dictated text
+pub const DICTATION_PLACEHOLDER: &str = "{dictation}";
+
+// Spoken prefix selecting a template, e.g. "Template standup update. Finished the API."
+const SPOKEN_TEMPLATE_PREFIX: &str = "template ";
+
+// Template picked from the quick-pick window, used by the next dictation only
+static SELECTED_TEMPLATE: Mutex<Option<String>> = Mutex::new(None);
+
+// A saved text snippet with a {dictation} placeholder
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DictationTemplate {
+    pub name: String,
+    pub body: String,
+}
+
+// Resolve the templates file location
+pub fn templates_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app_config_dir(app)?;
+    Ok(config_dir.join("templates.json"))
+}
+
+pub fn load_templates(app: &AppHandle) -> Result<Vec<DictationTemplate>, String> {
+    let path = templates_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read templates: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse templates: {}", e))
+}
+
+fn save_templates(app: &AppHandle, templates: &[DictationTemplate]) -> Result<(), String> {
+    let path = templates_path(app)?;
+    if let Some(parent) = path.parent() {
+        create_private_dir(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    
+    let contents = serde_json::to_string_pretty(templates)
+        .map_err(|e| format!("Failed to serialize templates: {}", e))?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write templates: {}", e))
+}
+
+// Lowercase and strip punctuation so spoken names match saved ones; snippets use it too
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// Find a template named at the start of the dictation; returns it with the remaining text
+fn match_spoken_template<'a>(templates: &'a [DictationTemplate], text: &str) -> Option<(&'a DictationTemplate, String)> {
+    let normalized = normalize_name(text);
+    let rest = normalized.strip_prefix(SPOKEN_TEMPLATE_PREFIX)?;
+    
+    // Prefer the longest name so "standup" doesn't shadow "standup update"
+    let template = templates
+        .iter()
+        .filter(|template| {
+            let name = normalize_name(&template.name);
+            !name.is_empty() && (rest == name || rest.starts_with(&format!("{} ", name)))
+        })
+        .max_by_key(|template| template.name.len())?;
+    
+    // Drop the same number of words from the original text to keep its punctuation
+    let skip = SPOKEN_TEMPLATE_PREFIX.split_whitespace().count()
+        + normalize_name(&template.name).split_whitespace().count();
+    let dictation = text
+        .split_whitespace()
+        .skip(skip)
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some((template, dictation))
+}
+
+// Insert the dictation into the template body
+pub fn fill_template(template: &DictationTemplate, dictation: &str) -> String {
+    if template.body.contains(DICTATION_PLACEHOLDER) {
+        template.body.replace(DICTATION_PLACEHOLDER, dictation)
+    } else {
+        format!("{}{}", template.body, dictation)
+    }
+}
+
+// Apply the template picked in the quick-pick window or named at the start of the dictation
+pub fn apply_template(app: &AppHandle, text: &str) -> String {
+    let templates = match load_templates(app) {
+        Ok(templates) => templates,
+        Err(e) => {
+            println!("⚠️ {}", e);
+            return text.to_string();
+        }
+    };
+    
+    let selected = SELECTED_TEMPLATE.lock().ok().and_then(|mut selected| selected.take());
+    if let Some(name) = selected {
+        match templates.iter().find(|template| template.name == name) {
+            Some(template) => {
+                println!("📄 Applying selected template '{}'", template.name);
+                return fill_template(template, text.trim());
+            }
+            None => println!("⚠️ Selected template '{}' no longer exists", name),
+        }
+    }
+    
+    match match_spoken_template(&templates, text) {
+        Some((template, dictation)) => {
+            println!("📄 Applying spoken template '{}'", template.name);
+            fill_template(template, &dictation)
+        }
+        None => text.to_string(),
+    }
+}
+
+// List saved templates
+#[tauri::command]
+pub async fn list_templates(app_handle: AppHandle) -> Result<Vec<DictationTemplate>, String> {
+    load_templates(&app_handle)
+}
+
+// Create or replace a template by name
+#[tauri::command]
+pub async fn save_template(app_handle: AppHandle, template: DictationTemplate) -> Result<(), String> {
+    if template.name.trim().is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    
+    let mut templates = load_templates(&app_handle)?;
+    match templates.iter_mut().find(|existing| existing.name == template.name) {
+        Some(existing) => existing.body = template.body.clone(),
+        None => templates.push(template.clone()),
+    }
+    save_templates(&app_handle, &templates)?;
+    
+    println!("📄 Template '{}' saved", template.name);
+    Ok(())
+}
+
+// Delete a template by name
+#[tauri::command]
+pub async fn delete_template(app_handle: AppHandle, name: String) -> Result<(), String> {
+    let mut templates = load_templates(&app_handle)?;
+    templates.retain(|template| template.name != name);
+    save_templates(&app_handle, &templates)?;
+    
+    println!("🗑️ Template '{}' deleted", name);
+    Ok(())
+}
+
+// Use a template for the next dictation (called by the quick-pick window); None clears it
+#[tauri::command]
+pub async fn select_template(app_handle: AppHandle, name: Option<String>) -> Result<(), String> {
+    if let Some(name) = &name {
+        let templates = load_templates(&app_handle)?;
+        if !templates.iter().any(|template| &template.name == name) {
+            return Err(format!("Unknown template: {}", name));
+        }
+    }
+    
+    let mut selected = SELECTED_TEMPLATE.lock().map_err(|e| e.to_string())?;
+    *selected = name;
+    Ok(())
+}
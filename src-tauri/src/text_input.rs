@@ -1,13 +1,675 @@
+use enigo::{Direction, Enigo, Key, Keyboard};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::AppHandle;
+use crate::privacy::loggable;
+use crate::app_profiles::AppProfile;
+use cursper_core::ascii::to_ascii;
+use cursper_core::bidi::contains_rtl;
+use cursper_core::cjk::contains_cjk;
+use cursper_core::spacing::fit_to_context;
+
+// Compatibility mode: type through osascript/PowerShell/xdotool instead of in-process events
+static USE_HELPER_PROCESSES: AtomicBool = AtomicBool::new(false);
+
+pub fn set_use_helper_processes(enabled: bool) {
+    USE_HELPER_PROCESSES.store(enabled, Ordering::SeqCst);
+}
+
+pub fn use_helper_processes() -> bool {
+    USE_HELPER_PROCESSES.load(Ordering::SeqCst)
+}
+
+// Fit inserted text to the text around the caret, where it can be read
+static SMART_SPACING: AtomicBool = AtomicBool::new(false);
+
+pub fn set_smart_spacing(enabled: bool) {
+    SMART_SPACING.store(enabled, Ordering::SeqCst);
+}
+
+pub fn smart_spacing() -> bool {
+    SMART_SPACING.load(Ordering::SeqCst)
+}
+
+// Paste right-to-left text through the clipboard in auto insertion mode: typing it key by
+// key hands editors one character at a time and they reorder it as each arrives
+static PASTE_RTL_TEXT: AtomicBool = AtomicBool::new(true);
+
+pub fn set_paste_rtl_text(enabled: bool) {
+    PASTE_RTL_TEXT.store(enabled, Ordering::SeqCst);
+}
+
+fn paste_rtl_text() -> bool {
+    PASTE_RTL_TEXT.load(Ordering::SeqCst)
+}
+
+// How dictated text reaches the focused field
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsertionMethod {
+    // Paste CJK and right-to-left text, and anything while an input method is composing;
+    // type everything else
+    #[default]
+    Auto,
+    // Always simulate keystrokes
+    Type,
+    // Always paste through the clipboard
+    Paste,
+    // Type slowly and in plain ASCII without touching the clipboard, for remote desktop and
+    // VM windows that only pass keystrokes through
+    RemoteDesktop,
+}
+
+// Pause after each character typed into a remote desktop or VM window
+static REMOTE_DESKTOP_DELAY_MS: AtomicU64 = AtomicU64::new(40);
+
+pub fn set_remote_desktop_delay_ms(delay_ms: u64) {
+    REMOTE_DESKTOP_DELAY_MS.store(delay_ms, Ordering::SeqCst);
+}
+
+// How text is inserted into one application
+#[derive(Clone, Copy, Debug, Default)]
+struct InsertionOptions {
+    method: InsertionMethod,
+    // Pause before typing starts, for apps that drop input arriving right after they regain focus
+    pre_insert_delay_ms: u64,
+}
+
+// The global insertion options and overrides by application, from the app profiles
+static INSERTION_OPTIONS: Mutex<Option<(InsertionOptions, HashMap<String, InsertionOptions>)>> = Mutex::new(None);
+
+pub fn set_insertion_options(method: InsertionMethod, pre_insert_delay_ms: u64, profiles: &[AppProfile]) {
+    let by_app = profiles
+        .iter()
+        .filter(|profile| profile.insertion_method.is_some() || profile.pre_insert_delay_ms.is_some())
+        .map(|profile| {
+            let options = InsertionOptions {
+                method: profile.insertion_method.unwrap_or(method),
+                pre_insert_delay_ms: profile.pre_insert_delay_ms.unwrap_or(pre_insert_delay_ms),
+            };
+            (profile.app.trim().to_lowercase(), options)
+        })
+        .collect();
+    if let Ok(mut current) = INSERTION_OPTIONS.lock() {
+        *current = Some((InsertionOptions { method, pre_insert_delay_ms }, by_app));
+    }
+}
+
+// Insertion options for an application, falling back to the global ones
+fn insertion_options_for(app: Option<&str>) -> InsertionOptions {
+    let (options, by_app) = INSERTION_OPTIONS
+        .lock()
+        .ok()
+        .and_then(|options| options.clone())
+        .unwrap_or_default();
+    app.and_then(|app| by_app.get(&app.trim().to_lowercase()).copied())
+        .unwrap_or(options)
+}
+
+// The application being dictated into and whether it had an input method on, captured
+// as recording starts so inserting doesn't have to look either up again
+#[derive(Clone, Debug, Default)]
+struct InsertionTarget {
+    app: Option<String>,
+    input_method_active: bool,
+}
+
+static INSERTION_TARGET: Mutex<Option<InsertionTarget>> = Mutex::new(None);
+
+fn insertion_target() -> InsertionTarget {
+    INSERTION_TARGET
+        .lock()
+        .ok()
+        .and_then(|target| target.clone())
+        .unwrap_or_default()
+}
+
+// Remember where the next dictation goes; the input method is only looked up when auto
+// insertion could use it
+pub async fn remember_insertion_target(app_handle: &AppHandle, app: Option<String>) {
+    let input_method_active = insertion_options_for(app.as_deref()).method == InsertionMethod::Auto
+        && input_method_active(app_handle).await;
+    if input_method_active {
+        println!("🈶 Input method active, dictation will be pasted");
+    }
+    if let Ok(mut target) = INSERTION_TARGET.lock() {
+        *target = Some(InsertionTarget { app, input_method_active });
+    }
+}
+
+// Insertion options for the application dictated into
+fn insertion_options() -> InsertionOptions {
+    insertion_options_for(insertion_target().app.as_deref())
+}
+
+// Whether the focused field has an input method (IME) switched on, e.g. Japanese kana
+// input or Pinyin, which would catch simulated keystrokes in its composition window.
+// The selected input source is an input mode rather than a plain keyboard layout; Text
+// Input Sources may only be queried on the main thread.
+#[cfg(target_os = "macos")]
+async fn input_method_active(app_handle: &AppHandle) -> bool {
+    use core_foundation::base::{CFRelease, CFTypeRef, TCFType};
+    use core_foundation::string::{CFString, CFStringRef};
+    
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        fn TISCopyCurrentKeyboardInputSource() -> CFTypeRef;
+        fn TISGetInputSourceProperty(source: CFTypeRef, key: CFStringRef) -> CFTypeRef;
+        static kTISPropertyInputSourceType: CFStringRef;
+        static kTISTypeKeyboardInputMode: CFStringRef;
+    }
+    
+    let (answer, active) = tokio::sync::oneshot::channel();
+    let queried = app_handle.run_on_main_thread(move || {
+        let active = unsafe {
+            let source = TISCopyCurrentKeyboardInputSource();
+            if source.is_null() {
+                false
+            } else {
+                let kind = TISGetInputSourceProperty(source, kTISPropertyInputSourceType);
+                let active = !kind.is_null()
+                    && CFString::wrap_under_get_rule(kind as CFStringRef) == CFString::wrap_under_get_rule(kTISTypeKeyboardInputMode);
+                CFRelease(source);
+                active
+            }
+        };
+        let _ = answer.send(active);
+    });
+    queried.is_ok() && active.await.unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+async fn input_method_active(_app_handle: &AppHandle) -> bool {
+    use windows_sys::Win32::UI::Input::Ime::ImmGetDefaultIMEWnd;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::GetKeyboardLayout;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId, SendMessageW, WM_IME_CONTROL};
+    const IMC_GETOPENSTATUS: usize = 0x0005;
+    // Primary language IDs of Chinese, Japanese and Korean keyboard layouts
+    const IME_LANGUAGES: [u16; 3] = [0x04, 0x11, 0x12];
+    
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return false;
+        }
+        let thread = GetWindowThreadProcessId(window, std::ptr::null_mut());
+        let language = (GetKeyboardLayout(thread) as usize & 0xFFFF) as u16 & 0x3FF;
+        if !IME_LANGUAGES.contains(&language) {
+            return false;
+        }
+        let ime_window = ImmGetDefaultIMEWnd(window);
+        !ime_window.is_null() && SendMessageW(ime_window, WM_IME_CONTROL, IMC_GETOPENSTATUS, 0) != 0
+    }
+}
+
+// fcitx5 reports state 2 while an input method is on; otherwise ask IBus whether its
+// engine is more than a keyboard layout
+#[cfg(target_os = "linux")]
+async fn input_method_active(_app_handle: &AppHandle) -> bool {
+    if let Some(active) = fcitx_input_method_active().await {
+        return active;
+    }
+    ibus_global_engine()
+        .await
+        .is_some_and(|engine| !engine.is_empty() && !engine.starts_with("xkb:"))
+}
+
+// None when fcitx5 isn't running
+#[cfg(target_os = "linux")]
+async fn fcitx_input_method_active() -> Option<bool> {
+    let connection = zbus::Connection::session().await.ok()?;
+    let controller = zbus::Proxy::new(&connection, "org.fcitx.Fcitx5", "/controller", "org.fcitx.Fcitx.Controller1")
+        .await
+        .ok()?;
+    let state: i32 = controller.call("State", &()).await.ok()?;
+    Some(state == 2)
+}
+
+// Address of IBus's own bus, from the environment or the file ibus-daemon writes for
+// this machine and display
+#[cfg(target_os = "linux")]
+fn ibus_address() -> Option<String> {
+    if let Ok(address) = std::env::var("IBUS_ADDRESS") {
+        return Some(address);
+    }
+    let machine_id = std::fs::read_to_string("/etc/machine-id")
+        .or_else(|_| std::fs::read_to_string("/var/lib/dbus/machine-id"))
+        .ok()?;
+    let display = std::env::var("WAYLAND_DISPLAY").or_else(|_| std::env::var("DISPLAY")).ok()?;
+    // ":0.0" is display 0 on host "unix"; Wayland sockets keep their whole name
+    let (host, number) = match display.split_once(':') {
+        Some((host, rest)) => (if host.is_empty() { "unix" } else { host }, rest.split('.').next().unwrap_or("0")),
+        None => ("unix", display.as_str()),
+    };
+    let config_dir = std::env::var("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|_| std::env::var("HOME").map(|home| std::path::PathBuf::from(home).join(".config")))
+        .ok()?;
+    let path = config_dir.join("ibus/bus").join(format!("{}-{}-{}", machine_id.trim(), host, number));
+    std::fs::read_to_string(path)
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("IBUS_ADDRESS="))
+        .map(str::to_string)
+}
+
+// Name of IBus's current engine, e.g. "xkb:us::eng" or "anthy"
+#[cfg(target_os = "linux")]
+async fn ibus_global_engine() -> Option<String> {
+    use zbus::zvariant::{OwnedValue, Value};
+    
+    let connection = zbus::connection::Builder::address(ibus_address()?.as_str())
+        .ok()?
+        .build()
+        .await
+        .ok()?;
+    let ibus = zbus::Proxy::new(&connection, "org.freedesktop.IBus", "/org/freedesktop/IBus", "org.freedesktop.IBus")
+        .await
+        .ok()?;
+    let engine: OwnedValue = ibus.get_property("GlobalEngine").await.ok()?;
+    // An IBusEngineDesc serializes as ("IBusEngineDesc", attachments, name, ...)
+    let fields = match &*engine {
+        Value::Value(inner) => match inner.as_ref() {
+            Value::Structure(desc) => desc.fields(),
+            _ => return None,
+        },
+        Value::Structure(desc) => desc.fields(),
+        _ => return None,
+    };
+    match fields.get(2)? {
+        Value::Str(name) => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+async fn input_method_active(_app_handle: &AppHandle) -> bool {
+    false
+}
+
+// Whether to paste rather than type this text
+fn should_paste(method: InsertionMethod, text: &str) -> bool {
+    match method {
+        InsertionMethod::Type | InsertionMethod::RemoteDesktop => false,
+        InsertionMethod::Paste => true,
+        InsertionMethod::Auto => {
+            (paste_rtl_text() && contains_rtl(text)) || contains_cjk(text) || insertion_target().input_method_active
+        }
+    }
+}
+
+// Time for the focused app to read the clipboard before its previous contents are restored
+const PASTE_RESTORE_DELAY: Duration = Duration::from_millis(200);
+
+// Kept open for the app's lifetime: on Linux the clipboard contents go away with their owner
+static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+// How the Linux helper process types through xdotool
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct XdotoolOptions {
+    // Delay between keystrokes; some toolkits drop keys typed faster than this
+    pub delay_ms: u64,
+    // Delays for particular applications, by window class as get_focused_application reports it
+    pub app_delay_ms: HashMap<String, u64>,
+    // Release held modifiers (e.g. the shortcut's) while typing
+    pub clear_modifiers: bool,
+    // Press non-ASCII characters as keysyms with `xdotool key`; `xdotool type` garbles
+    // characters that need a layout switch
+    pub keysym_fallback: bool,
+}
+
+impl Default for XdotoolOptions {
+    fn default() -> Self {
+        Self {
+            delay_ms: 12,
+            app_delay_ms: HashMap::new(),
+            clear_modifiers: false,
+            keysym_fallback: true,
+        }
+    }
+}
+
+static XDOTOOL_OPTIONS: Mutex<Option<XdotoolOptions>> = Mutex::new(None);
+
+pub fn set_xdotool_options(options: XdotoolOptions) {
+    if let Ok(mut current) = XDOTOOL_OPTIONS.lock() {
+        *current = Some(options);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn xdotool_options() -> XdotoolOptions {
+    XDOTOOL_OPTIONS
+        .lock()
+        .ok()
+        .and_then(|options| options.clone())
+        .unwrap_or_default()
+}
+
+// An xdotool invocation honouring the configured options
+#[cfg(target_os = "linux")]
+fn xdotool(subcommand: &str, options: &XdotoolOptions) -> std::process::Command {
+    let mut command = std::process::Command::new("xdotool");
+    command.arg(subcommand);
+    if options.clear_modifiers {
+        command.arg("--clearmodifiers");
+    }
+    command
+}
+
+// Keystroke delay for the application dictated into, falling back to the global delay
+#[cfg(target_os = "linux")]
+fn typing_delay_ms(options: &XdotoolOptions) -> u64 {
+    if options.app_delay_ms.is_empty() {
+        return options.delay_ms;
+    }
+    insertion_target()
+        .app
+        .and_then(|app| {
+            options
+                .app_delay_ms
+                .iter()
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case(app.trim()))
+                .map(|(_, delay)| *delay)
+        })
+        .unwrap_or(options.delay_ms)
+}
+
+// A stretch of text for `xdotool type`, or characters to press as keysyms
+#[cfg(target_os = "linux")]
+enum XdotoolRun {
+    Type(String),
+    Keys(Vec<String>),
+}
+
+// Split text so ASCII goes through `xdotool type` and everything else is pressed by its
+// Unicode keysym (e.g. "U00E9" for é), which works whatever the active layout
+#[cfg(target_os = "linux")]
+fn xdotool_runs(text: &str, keysym_fallback: bool) -> Vec<XdotoolRun> {
+    if !keysym_fallback {
+        return vec![XdotoolRun::Type(text.to_string())];
+    }
+    let mut runs = Vec::new();
+    for c in text.chars() {
+        match (runs.last_mut(), c.is_ascii()) {
+            (Some(XdotoolRun::Type(run)), true) => run.push(c),
+            (Some(XdotoolRun::Keys(keys)), false) => keys.push(format!("U{:04X}", c as u32)),
+            (_, true) => runs.push(XdotoolRun::Type(c.to_string())),
+            (_, false) => runs.push(XdotoolRun::Keys(vec![format!("U{:04X}", c as u32)])),
+        }
+    }
+    runs
+}
+
+// Synthesize keyboard input in-process; runs on a blocking thread since it may sleep between events
+async fn with_enigo<F>(input: F) -> Result<(), String>
+where
+    F: FnOnce(&mut Enigo) -> Result<(), enigo::InputError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let mut enigo = Enigo::new(&enigo::Settings::default())
+            .map_err(|e| format!("Failed to connect to input system: {}", e))?;
+        input(&mut enigo).map_err(|e| format!("Failed to send input: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Input task failed: {}", e))?
+}
+
+// Something to do right after a transcription has been typed
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostInsertAction {
+    AppendSpace,
+    PressEnter,
+    PressTab,
+}
+
+// Press a single key (space, Enter or Tab) in-process
+async fn press_key(action: PostInsertAction) -> Result<(), String> {
+    let key = match action {
+        PostInsertAction::AppendSpace => Key::Space,
+        PostInsertAction::PressEnter => Key::Return,
+        PostInsertAction::PressTab => Key::Tab,
+    };
+    with_enigo(move |enigo| enigo.key(key, Direction::Click)).await
+}
+
+// Press a single key (space, Enter or Tab) using platform-specific helper processes
+fn press_key_with_helper(action: PostInsertAction) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let output = {
+        let script = match action {
+            PostInsertAction::AppendSpace => r#"tell application "System Events" to keystroke " ""#,
+            PostInsertAction::PressEnter => r#"tell application "System Events" to key code 36"#,
+            PostInsertAction::PressTab => r#"tell application "System Events" to key code 48"#,
+        };
+        std::process::Command::new("osascript").arg("-e").arg(script).output()
+    };
+    
+    #[cfg(target_os = "windows")]
+    let output = {
+        let keys = match action {
+            PostInsertAction::AppendSpace => " ",
+            PostInsertAction::PressEnter => "{ENTER}",
+            PostInsertAction::PressTab => "{TAB}",
+        };
+        let script = format!(
+            r#"Add-Type -AssemblyName System.Windows.Forms; [System.Windows.Forms.SendKeys]::SendWait('{}')"#,
+            keys
+        );
+        std::process::Command::new("powershell").arg("-Command").arg(&script).output()
+    };
+    
+    #[cfg(target_os = "linux")]
+    let output = {
+        let key = match action {
+            PostInsertAction::AppendSpace => "space",
+            PostInsertAction::PressEnter => "Return",
+            PostInsertAction::PressTab => "Tab",
+        };
+        xdotool("key", &xdotool_options()).arg(key).output()
+    };
+    
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    let output: std::io::Result<std::process::Output> = Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Key presses not supported on this platform",
+    ));
+    
+    let output = output.map_err(|e| format!("Failed to press key: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("Key press failed: {}", String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+// Run the configured post-insertion actions in order
+pub async fn run_post_insert_actions(actions: &[PostInsertAction]) -> Result<(), String> {
+    for action in actions {
+        println!("⌨️ Post-insertion action: {:?}", action);
+        if !use_helper_processes() {
+            match press_key(*action).await {
+                Ok(_) => continue,
+                Err(e) => println!("⚠️ {}, falling back to helper process", e),
+            }
+        }
+        press_key_with_helper(*action)?;
+    }
+    Ok(())
+}
+
+// Delete characters before the caret, e.g. to replace a misheard sentence
+pub async fn press_backspaces(count: usize) -> Result<(), String> {
+    if count == 0 {
+        return Ok(());
+    }
+    println!("⌫ Pressing backspace {} times", count);
+    if !use_helper_processes() {
+        match with_enigo(move |enigo| (0..count).try_for_each(|_| enigo.key(Key::Backspace, Direction::Click))).await {
+            Ok(_) => return Ok(()),
+            Err(e) => println!("⚠️ {}, falling back to helper process", e),
+        }
+    }
+    
+    #[cfg(target_os = "macos")]
+    let output = {
+        let script = format!(
+            "tell application \"System Events\"\nrepeat {} times\nkey code 51\nend repeat\nend tell",
+            count
+        );
+        std::process::Command::new("osascript").arg("-e").arg(&script).output()
+    };
+    
+    #[cfg(target_os = "windows")]
+    let output = {
+        let script = format!(
+            r#"Add-Type -AssemblyName System.Windows.Forms; [System.Windows.Forms.SendKeys]::SendWait('{{BACKSPACE {}}}')"#,
+            count
+        );
+        std::process::Command::new("powershell").arg("-Command").arg(&script).output()
+    };
+    
+    #[cfg(target_os = "linux")]
+    let output = xdotool("key", &xdotool_options())
+        .args(["--repeat", &count.to_string(), "BackSpace"])
+        .output();
+    
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    let output: std::io::Result<std::process::Output> = Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Key presses not supported on this platform",
+    ));
+    
+    let output = output.map_err(|e| format!("Failed to press backspace: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("Backspace failed: {}", String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+// Insert text in one piece by pasting it, then put the previous clipboard text back
+async fn paste_text(text: String) -> Result<(), String> {
+    let previous = tokio::task::spawn_blocking(move || {
+        let mut clipboard = CLIPBOARD.lock().map_err(|e| e.to_string())?;
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new().map_err(|e| format!("Failed to open clipboard: {}", e))?);
+        }
+        let clipboard = clipboard.as_mut().ok_or("Clipboard unavailable")?;
+        let previous = clipboard.get_text().ok();
+        clipboard
+            .set_text(text)
+            .map_err(|e| format!("Failed to copy text to clipboard: {}", e))?;
+        Ok::<_, String>(previous)
+    })
+    .await
+    .map_err(|e| format!("Clipboard task failed: {}", e))??;
+    
+    let modifier = if cfg!(target_os = "macos") { Key::Meta } else { Key::Control };
+    let pasted = with_enigo(move |enigo| {
+        enigo.key(modifier, Direction::Press)?;
+        let result = enigo.key(Key::Unicode('v'), Direction::Click);
+        enigo.key(modifier, Direction::Release)?;
+        result
+    })
+    .await;
+    
+    tokio::time::sleep(PASTE_RESTORE_DELAY).await;
+    if let Some(previous) = previous {
+        let restored = tokio::task::spawn_blocking(move || {
+            CLIPBOARD
+                .lock()
+                .ok()
+                .and_then(|mut clipboard| clipboard.as_mut().map(|clipboard| clipboard.set_text(previous)))
+        })
+        .await;
+        if !matches!(restored, Ok(Some(Ok(_)))) {
+            println!("⚠️ Failed to restore previous clipboard contents");
+        }
+    }
+    pasted
+}
+
 // Type text at cursor position using platform-specific APIs
 #[tauri::command]
 pub async fn type_text(text: String) -> Result<(), String> {
-    println!("⌨️ type_text called with: '{}'", text);
+    println!("⌨️ type_text called with: '{}'", loggable(&text));
     
     if text.trim().is_empty() {
         println!("⚠️ Empty text provided, skipping typing");
         return Ok(());
     }
     
+    let options = insertion_options();
+    let paste = should_paste(options.method, &text);
+    if options.pre_insert_delay_ms > 0 {
+        println!("⏳ Waiting {}ms before inserting", options.pre_insert_delay_ms);
+        tokio::time::sleep(Duration::from_millis(options.pre_insert_delay_ms)).await;
+    }
+    if options.method == InsertionMethod::RemoteDesktop {
+        return type_for_remote_desktop(&text).await;
+    }
+    if paste {
+        match paste_text(text.clone()).await {
+            Ok(_) => {
+                println!("✅ Text pasted");
+                return Ok(());
+            }
+            Err(e) => println!("⚠️ {}, typing instead", e),
+        }
+    }
+    
+    if !use_helper_processes() {
+        let input = text.clone();
+        match with_enigo(move |enigo| enigo.text(&input)).await {
+            Ok(_) => {
+                println!("✅ Text typed successfully in-process");
+                return Ok(());
+            }
+            Err(e) => println!("⚠️ {}, falling back to helper process", e),
+        }
+    }
+    
+    type_text_with_helper(&text)
+}
+
+// Type one character at a time in plain ASCII, which remote desktop clients and VM
+// consoles forward reliably; the clipboard is left alone since it may be shared with the
+// remote machine
+async fn type_for_remote_desktop(text: &str) -> Result<(), String> {
+    let text = to_ascii(text);
+    let delay = Duration::from_millis(REMOTE_DESKTOP_DELAY_MS.load(Ordering::SeqCst));
+    println!("🖥️ Typing for remote desktop with {}ms between keys", delay.as_millis());
+    
+    if !use_helper_processes() {
+        let input = text.clone();
+        let typed = with_enigo(move |enigo| {
+            input.chars().try_for_each(|c| {
+                enigo.text(&c.to_string())?;
+                std::thread::sleep(delay);
+                Ok(())
+            })
+        })
+        .await;
+        match typed {
+            Ok(_) => {
+                println!("✅ Text typed successfully in-process");
+                return Ok(());
+            }
+            Err(e) => println!("⚠️ {}, falling back to helper process", e),
+        }
+    }
+    
+    type_text_with_helper(&text)
+}
+
+// Type text by spawning a platform-specific helper process (compatibility fallback)
+fn type_text_with_helper(text: &str) -> Result<(), String> {
     #[cfg(target_os = "macos")]
     {
         println!("🍎 Using AppleScript to type text on macOS");
@@ -25,7 +687,7 @@ pub async fn type_text(text: String) -> Result<(), String> {
             escaped_text
         );
         
-        println!("📝 AppleScript: {}", script);
+        println!("📝 AppleScript: {}", loggable(&script));
         
         let output = std::process::Command::new("osascript")
             .arg("-e")
@@ -82,25 +744,37 @@ pub async fn type_text(text: String) -> Result<(), String> {
     {
         println!("🐧 Using xdotool to type text on Linux");
         
-        let output = std::process::Command::new("xdotool")
-            .arg("type")
-            .arg("--delay")
-            .arg("12") // 12ms delay between keystrokes
-            .arg(&text)
-            .output()
-            .map_err(|e| format!("Failed to execute xdotool: {}", e))?;
-        
-        if output.status.success() {
-            println!("✅ Text typed successfully via xdotool");
-            Ok(())
-        } else {
-            let error = format!(
-                "xdotool failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-            println!("❌ {}", error);
-            Err(error)
+        let options = xdotool_options();
+        let delay = typing_delay_ms(&options).to_string();
+        for run in xdotool_runs(text, options.keysym_fallback) {
+            let mut command = match run {
+                XdotoolRun::Type(text) => {
+                    let mut command = xdotool("type", &options);
+                    command.args(["--delay", &delay]).arg(text);
+                    command
+                }
+                XdotoolRun::Keys(keysyms) => {
+                    let mut command = xdotool("key", &options);
+                    command.args(["--delay", &delay]).args(keysyms);
+                    command
+                }
+            };
+            let output = command
+                .output()
+                .map_err(|e| format!("Failed to execute xdotool: {}", e))?;
+            
+            if !output.status.success() {
+                let error = format!(
+                    "xdotool failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                println!("❌ {}", error);
+                return Err(error);
+            }
         }
+        
+        println!("✅ Text typed successfully via xdotool");
+        Ok(())
     }
     
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
@@ -109,4 +783,67 @@ pub async fn type_text(text: String) -> Result<(), String> {
         println!("❌ {}", error);
         Err(error)
     }
-} 
\ No newline at end of file
+}
+
+// How much of the field on each side of the caret to look at
+const CARET_CONTEXT_CHARS: usize = 64;
+
+// Text before and after the caret in the focused field, read through the accessibility API
+#[cfg(target_os = "macos")]
+fn caret_context() -> Option<(String, String)> {
+    // AXSelectedTextRange comes back as {start, end} with a 1-based start
+    let script = r#"tell application "System Events"
+    set focusedElement to value of attribute "AXFocusedUIElement" of (first application process whose frontmost is true)
+    set fieldText to value of attribute "AXValue" of focusedElement
+    set caretRange to value of attribute "AXSelectedTextRange" of focusedElement
+end tell
+return ((item 1 of caretRange) - 1 as text) & linefeed & fieldText"#;
+
+    let output = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (offset, field) = stdout.split_once('\n')?;
+    let field = field.strip_suffix('\n').unwrap_or(field);
+    
+    // Accessibility offsets count UTF-16 code units
+    let units: Vec<u16> = field.encode_utf16().collect();
+    let offset = offset.trim().parse::<usize>().ok()?.min(units.len());
+    let before = String::from_utf16_lossy(&units[..offset]);
+    let after = String::from_utf16_lossy(&units[offset..]);
+    
+    let skip = before.chars().count().saturating_sub(CARET_CONTEXT_CHARS);
+    Some((
+        before.chars().skip(skip).collect(),
+        after.chars().take(CARET_CONTEXT_CHARS).collect(),
+    ))
+}
+
+// Other platforms have no accessibility lookup wired up yet; text is inserted as is
+#[cfg(not(target_os = "macos"))]
+fn caret_context() -> Option<(String, String)> {
+    None
+}
+
+// Adjust spacing and capitalization to the surrounding text when smart spacing is on
+// and the focused field can be read
+pub async fn fit_to_caret(text: String) -> String {
+    if !smart_spacing() {
+        return text;
+    }
+    
+    match tokio::task::spawn_blocking(caret_context).await.ok().flatten() {
+        Some((before, after)) => {
+            let fitted = fit_to_context(&text, &before, &after);
+            println!("🔤 Fitted to surrounding text: '{}'", loggable(&fitted));
+            fitted
+        }
+        None => text,
+    }
+}
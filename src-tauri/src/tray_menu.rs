@@ -0,0 +1,120 @@
+use std::sync::Mutex;
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::AppHandle;
+use crate::system_tray::TRAY_ID;
+
+// One entry of the tray menu, described from the current state on every rebuild
+pub enum TrayEntry {
+    Item { id: String, label: String, enabled: bool },
+    Check { id: String, label: String, checked: bool, enabled: bool },
+    Submenu { label: String, entries: Vec<TrayEntry> },
+    Separator,
+}
+
+impl TrayEntry {
+    pub fn item(id: impl Into<String>, label: impl Into<String>) -> Self {
+        TrayEntry::Item { id: id.into(), label: label.into(), enabled: true }
+    }
+    
+    pub fn check(id: impl Into<String>, label: impl Into<String>, checked: bool) -> Self {
+        TrayEntry::Check { id: id.into(), label: label.into(), checked, enabled: true }
+    }
+    
+    pub fn submenu(label: impl Into<String>, entries: Vec<TrayEntry>) -> Self {
+        TrayEntry::Submenu { label: label.into(), entries }
+    }
+    
+    // Show the entry greyed out
+    pub fn disabled(mut self) -> Self {
+        match &mut self {
+            TrayEntry::Item { enabled, .. } | TrayEntry::Check { enabled, .. } => *enabled = false,
+            TrayEntry::Submenu { .. } | TrayEntry::Separator => {}
+        }
+        self
+    }
+}
+
+// A block of entries contributed by a module; the rendered menu separates sections
+#[derive(Clone, Copy)]
+pub struct TraySection {
+    // Sections are shown in ascending order
+    pub order: u32,
+    pub entries: fn(&AppHandle) -> Vec<TrayEntry>,
+    // Handle a click on an entry; false when the id isn't one of this section's
+    pub on_click: fn(&AppHandle, &str) -> bool,
+}
+
+static TRAY_SECTIONS: Mutex<Vec<TraySection>> = Mutex::new(Vec::new());
+
+// Add a section to the tray menu; it shows up on the next rebuild
+pub fn register_tray_section(section: TraySection) {
+    if let Ok(mut sections) = TRAY_SECTIONS.lock() {
+        sections.push(section);
+        sections.sort_by_key(|section| section.order);
+    }
+}
+
+fn tray_sections() -> Vec<TraySection> {
+    TRAY_SECTIONS.lock().map(|sections| sections.clone()).unwrap_or_default()
+}
+
+fn render_entry(app: &AppHandle, entry: &TrayEntry) -> tauri::Result<Box<dyn IsMenuItem<tauri::Wry>>> {
+    Ok(match entry {
+        TrayEntry::Item { id, label, enabled } => Box::new(MenuItem::with_id(app, id, label, *enabled, None::<&str>)?),
+        TrayEntry::Check { id, label, checked, enabled } => {
+            Box::new(CheckMenuItem::with_id(app, id, label, *enabled, *checked, None::<&str>)?)
+        }
+        TrayEntry::Submenu { label, entries } => {
+            let submenu = Submenu::new(app, label, true)?;
+            for entry in entries {
+                submenu.append(render_entry(app, entry)?.as_ref())?;
+            }
+            Box::new(submenu)
+        }
+        TrayEntry::Separator => Box::new(PredefinedMenuItem::separator(app)?),
+    })
+}
+
+// Render every registered section into a new menu
+pub fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let menu = Menu::new(app)?;
+    let mut first = true;
+    for section in tray_sections() {
+        let entries = (section.entries)(app);
+        if entries.is_empty() {
+            continue;
+        }
+        if !first {
+            menu.append(&PredefinedMenuItem::separator(app)?)?;
+        }
+        first = false;
+        for entry in &entries {
+            menu.append(render_entry(app, entry)?.as_ref())?;
+        }
+    }
+    Ok(menu)
+}
+
+// Replace the tray menu with one rendered from the current state, e.g. after a setting
+// it shows changed
+pub fn rebuild_tray_menu(app: &AppHandle) {
+    let tray = match app.tray_by_id(TRAY_ID) {
+        Some(tray) => tray,
+        None => return,
+    };
+    match build_tray_menu(app) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                println!("⚠️ Failed to update tray menu: {}", e);
+            }
+        }
+        Err(e) => println!("⚠️ Failed to rebuild tray menu: {}", e),
+    }
+}
+
+// Pass a menu click to the section owning the entry
+pub fn handle_tray_click(app: &AppHandle, id: &str) {
+    if !tray_sections().iter().any(|section| (section.on_click)(app, id)) {
+        println!("⚠️ Unhandled tray menu entry: {}", id);
+    }
+}
@@ -1,6 +1,12 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use crate::settings::Settings;
+use cursper_core::recorder::Recorder;
+
+pub use cursper_core::transcript::{SpeechRate, StageTimings, TranscriptSegment, TranscriptionResult, WordTimestamp};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct CursorPosition {
@@ -11,29 +17,271 @@ pub struct CursorPosition {
 #[derive(Clone, Serialize, Deserialize)]
 pub struct AppState {
     pub is_recording: bool,
-    pub current_model: String,
-    pub current_shortcut: String,
     pub shortcuts: HashMap<String, String>,
-    pub backend_url: String,
+    pub settings: Settings,
+    pub model_sleeping: bool,
+    pub screen_sharing: bool,
+    // Application focused when the current recording started
+    #[serde(skip)]
+    pub focused_app: Option<String>,
+    // Model chosen for the current recording by the accurate shortcut, overriding settings.model
+    #[serde(skip)]
+    pub dictation_model: Option<String>,
+    #[serde(skip, default = "Instant::now")]
+    pub last_model_activity: Instant,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         Self {
             is_recording: false,
-            current_model: "base".to_string(),
-            current_shortcut: "Option+Space".to_string(),
             shortcuts: HashMap::new(),
-            backend_url: "http://127.0.0.1:8788".to_string(),
+            settings: Settings::default(),
+            model_sleeping: false,
+            screen_sharing: false,
+            focused_app: None,
+            dictation_model: None,
+            last_model_activity: Instant::now(),
         }
     }
 }
 
 pub type AppStateType = Arc<Mutex<AppState>>;
 
-// Global recording control
-static RECORDING_CONTROL: std::sync::OnceLock<Arc<Mutex<bool>>> = std::sync::OnceLock::new();
+// Event names emitted to the frontend
+pub const RECORDING_STATE_EVENT: &str = "recording-state-changed";
+pub const TRANSCRIPTION_EVENT: &str = "transcription-completed";
+pub const BACKEND_STATUS_EVENT: &str = "backend-status-changed";
+pub const MODEL_UNLOADED_EVENT: &str = "model-unloaded";
+pub const MODEL_LOADING_EVENT: &str = "model-loading";
+pub const MODEL_READY_EVENT: &str = "model-ready";
+pub const INCOGNITO_EVENT: &str = "incognito-changed";
+pub const SETTINGS_RELOADED_EVENT: &str = "settings-reloaded";
+pub const LANGUAGE_CHANGED_EVENT: &str = "language-changed";
+pub const SCREEN_SHARING_EVENT: &str = "screen-sharing-changed";
+pub const WORD_TIMESTAMPS_EVENT: &str = "word-timestamps";
+pub const STATE_RECOVERED_EVENT: &str = "state-recovered";
+pub const RECORDING_CANCELLED_EVENT: &str = "recording-cancelled";
+pub const RECORDING_COUNTDOWN_EVENT: &str = "recording-countdown";
+pub const TRANSCRIPTION_ATTEMPT_EVENT: &str = "transcription-attempt";
+pub const BUDGET_WARNING_EVENT: &str = "budget-warning";
+pub const SPEECH_RATE_EVENT: &str = "speech-rate";
+pub const BACKEND_SETUP_EVENT: &str = "backend-setup-progress";
+pub const AUDIO_SAMPLES_DROPPED_EVENT: &str = "audio-samples-dropped";
+pub const PLAYBACK_EVENT: &str = "history-playback";
+pub const UPLOAD_PROGRESS_EVENT: &str = "upload-progress";
+pub const UPLOAD_QUALITY_EVENT: &str = "upload-quality-changed";
+pub const UPDATE_AVAILABLE_EVENT: &str = "update-available";
+pub const UPDATE_PROGRESS_EVENT: &str = "update-download-progress";
+
+// Payload for "recording-state-changed"
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecordingStateEvent {
+    pub is_recording: bool,
+}
+
+// Payload for "transcription-completed"
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TranscriptionEvent {
+    pub success: bool,
+    pub result: Option<TranscriptionResult>,
+    pub error: Option<String>,
+}
+
+// Payload for "backend-status-changed"
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BackendStatusEvent {
+    pub online: bool,
+    pub backend_url: String,
+    pub message: Option<String>,
+}
+
+// Payload for "model-unloaded", "model-loading" and "model-ready"
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ModelStatusEvent {
+    pub model: String,
+}
+
+// Payload for "incognito-changed"
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct IncognitoEvent {
+    pub enabled: bool,
+}
+
+// Payload for "settings-reloaded"
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SettingsReloadedEvent {
+    pub path: String,
+}
+
+// Payload for "language-changed"
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LanguageChangedEvent {
+    pub language: Option<String>,
+}
+
+// Payload for "screen-sharing-changed"
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScreenSharingEvent {
+    pub sharing: bool,
+}
+
+// Payload for "word-timestamps", used for karaoke-style highlighting
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WordTimestampsEvent {
+    pub words: Vec<WordTimestamp>,
+}
+
+// Payload for "state-recovered", emitted after rebuilding a poisoned state
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StateRecoveredEvent {
+    pub reason: String,
+}
+
+// Payload for "recording-cancelled", shown as a toast
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecordingCancelledEvent {
+    pub reason: String,
+    pub duration_ms: u64,
+}
+
+// Payload for "recording-countdown", ticking down before capture starts
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecordingCountdownEvent {
+    pub remaining_ms: u64,
+}
+
+// Payload for "transcription-attempt", one per provider tried in the failover chain
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TranscriptionAttemptEvent {
+    pub provider: String,
+    pub attempt: u32,
+    pub success: bool,
+    pub error: Option<String>,
+    // Provider tried next when this attempt failed
+    pub next_provider: Option<String>,
+}
+
+// Payload for "budget-warning", emitted when a provider nears a daily limit
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BudgetWarningEvent {
+    pub provider: String,
+    // "audio_seconds" or "cost"
+    pub budget: String,
+    pub used: f64,
+    pub limit: f64,
+}
+
+// Payload for "speech-rate", feeding the live WPM readout in the overlay
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SpeechRateEvent {
+    pub job_id: u64,
+    pub speech_rate: SpeechRate,
+}
+
+// Payload for "backend-setup-progress", emitted while the backend virtualenv is built
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BackendSetupEvent {
+    // "venv", "install", "done" or "failed"
+    pub stage: String,
+    pub message: String,
+    // Rough overall progress from 0.0 to 1.0
+    pub progress: f32,
+}
+
+// Payload for "audio-samples-dropped", emitted when the recorder fell behind the microphone
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SamplesDroppedEvent {
+    pub dropped_samples: u64,
+    pub dropped_ms: u64,
+}
+
+// Payload for "history-playback": a history entry's recording started or stopped playing
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PlaybackEvent {
+    pub id: u64,
+    pub playing: bool,
+}
+
+// Payload for "upload-progress", emitted while a long recording is sent to a provider
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadProgressEvent {
+    // Pass to cancel_upload to abort this upload
+    pub job_id: u64,
+    pub provider: String,
+    pub bytes_sent: u64,
+    pub total_bytes: u64,
+}
+
+// Payload for "upload-quality-changed": uploads switched to compressed audio on a slow
+// connection, or back to lossless once it recovered
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadQualityEvent {
+    pub compressed: bool,
+    // Throughput of the upload that triggered the switch
+    pub throughput_kbps: f64,
+}
+
+// Payload for "update-available", also returned by check_for_updates
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    // Release notes, if the release has any
+    pub notes: Option<String>,
+}
+
+// Payload for "update-download-progress", emitted while install_update downloads
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateProgressEvent {
+    pub downloaded_bytes: u64,
+    // None when the server doesn't send a content length
+    pub total_bytes: Option<u64>,
+}
+
+// Expose JSON schemas of all event payloads so the frontend can generate matching types
+#[tauri::command]
+pub fn get_event_schemas() -> Result<HashMap<String, serde_json::Value>, String> {
+    let schemas = [
+        (RECORDING_STATE_EVENT, schemars::schema_for!(RecordingStateEvent)),
+        (TRANSCRIPTION_EVENT, schemars::schema_for!(TranscriptionEvent)),
+        (BACKEND_STATUS_EVENT, schemars::schema_for!(BackendStatusEvent)),
+        (MODEL_UNLOADED_EVENT, schemars::schema_for!(ModelStatusEvent)),
+        (MODEL_LOADING_EVENT, schemars::schema_for!(ModelStatusEvent)),
+        (MODEL_READY_EVENT, schemars::schema_for!(ModelStatusEvent)),
+        (INCOGNITO_EVENT, schemars::schema_for!(IncognitoEvent)),
+        (SETTINGS_RELOADED_EVENT, schemars::schema_for!(SettingsReloadedEvent)),
+        (LANGUAGE_CHANGED_EVENT, schemars::schema_for!(LanguageChangedEvent)),
+        (SCREEN_SHARING_EVENT, schemars::schema_for!(ScreenSharingEvent)),
+        (WORD_TIMESTAMPS_EVENT, schemars::schema_for!(WordTimestampsEvent)),
+        (STATE_RECOVERED_EVENT, schemars::schema_for!(StateRecoveredEvent)),
+        (RECORDING_CANCELLED_EVENT, schemars::schema_for!(RecordingCancelledEvent)),
+        (RECORDING_COUNTDOWN_EVENT, schemars::schema_for!(RecordingCountdownEvent)),
+        (TRANSCRIPTION_ATTEMPT_EVENT, schemars::schema_for!(TranscriptionAttemptEvent)),
+        (BUDGET_WARNING_EVENT, schemars::schema_for!(BudgetWarningEvent)),
+        (SPEECH_RATE_EVENT, schemars::schema_for!(SpeechRateEvent)),
+        (BACKEND_SETUP_EVENT, schemars::schema_for!(BackendSetupEvent)),
+        (AUDIO_SAMPLES_DROPPED_EVENT, schemars::schema_for!(SamplesDroppedEvent)),
+        (PLAYBACK_EVENT, schemars::schema_for!(PlaybackEvent)),
+        (UPLOAD_PROGRESS_EVENT, schemars::schema_for!(UploadProgressEvent)),
+        (UPLOAD_QUALITY_EVENT, schemars::schema_for!(UploadQualityEvent)),
+        (UPDATE_AVAILABLE_EVENT, schemars::schema_for!(UpdateInfo)),
+        (UPDATE_PROGRESS_EVENT, schemars::schema_for!(UpdateProgressEvent)),
+    ];
+    
+    let mut result = HashMap::new();
+    for (event, schema) in schemas {
+        let value = serde_json::to_value(schema)
+            .map_err(|e| format!("Failed to serialize schema for {}: {}", event, e))?;
+        result.insert(event.to_string(), value);
+    }
+    
+    Ok(result)
+}
+
+// Actor owning the microphone; started on first use and kept for the app's lifetime
+static RECORDER: std::sync::OnceLock<Recorder> = std::sync::OnceLock::new();
 
-pub fn get_recording_control() -> Arc<Mutex<bool>> {
-    RECORDING_CONTROL.get_or_init(|| Arc::new(Mutex::new(false))).clone()
+pub fn recorder() -> &'static Recorder {
+    RECORDER.get_or_init(Recorder::spawn)
 } 
\ No newline at end of file
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_updater::{Update, UpdaterExt};
+use crate::quit::{cancel_exit, prepare_exit};
+use crate::tray_menu::{rebuild_tray_menu, TrayEntry, TraySection};
+use crate::types::{AppStateType, UpdateInfo, UpdateProgressEvent, UPDATE_AVAILABLE_EVENT, UPDATE_PROGRESS_EVENT};
+
+// Update manifests by channel. Beta builds are published to a rolling "beta" release.
+const STABLE_UPDATE_ENDPOINT: &str = "https://github.com/R4YGULAR/cursper/releases/latest/download/latest.json";
+const BETA_UPDATE_ENDPOINT: &str = "https://github.com/R4YGULAR/cursper/releases/download/beta/latest.json";
+
+// Public key of the release signing key, set by the release build; without it updates
+// can't be verified, so the updater stays off
+const UPDATER_PUBKEY: Option<&str> = option_env!("CURSPER_UPDATER_PUBKEY");
+
+// Time after launch before the first automatic check, so startup isn't slowed down
+const UPDATE_CHECK_STARTUP_DELAY: Duration = Duration::from_secs(30);
+
+// How often the background task looks at whether a check is due
+const UPDATE_CHECK_POLL_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+// Release channel the updater follows
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    Stable,
+    // Pre-releases, published ahead of stable
+    Beta,
+}
+
+impl UpdateChannel {
+    fn endpoint(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => STABLE_UPDATE_ENDPOINT,
+            UpdateChannel::Beta => BETA_UPDATE_ENDPOINT,
+        }
+    }
+}
+
+// Update found by the latest check, installed by install_update
+static PENDING_UPDATE: Mutex<Option<Update>> = Mutex::new(None);
+
+// When updates were last checked, manually or automatically
+static LAST_UPDATE_CHECK: Mutex<Option<Instant>> = Mutex::new(None);
+
+fn pending_update() -> Option<Update> {
+    PENDING_UPDATE.lock().ok().and_then(|update| update.clone())
+}
+
+// Drop the update found earlier, e.g. after switching channels
+pub fn forget_pending_update() {
+    if let Ok(mut update) = PENDING_UPDATE.lock() {
+        *update = None;
+    }
+}
+
+fn update_info(update: &Update) -> UpdateInfo {
+    UpdateInfo {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        notes: update.body.clone().filter(|notes| !notes.trim().is_empty()),
+    }
+}
+
+// Look for a newer release on the configured channel and remember it for install_update
+async fn check_channel(app: &AppHandle, state: &AppStateType) -> Result<Option<UpdateInfo>, String> {
+    let pubkey = UPDATER_PUBKEY.ok_or("Updates aren't available in this build")?;
+    let (channel, proxy_url) = {
+        let app_state = state.lock().map_err(|e| e.to_string())?;
+        (app_state.settings.update_channel, app_state.settings.proxy_url.clone())
+    };
+    if let Ok(mut last_check) = LAST_UPDATE_CHECK.lock() {
+        *last_check = Some(Instant::now());
+    }
+    
+    let endpoint = url::Url::parse(channel.endpoint()).map_err(|e| format!("Invalid update endpoint: {}", e))?;
+    let mut builder = app
+        .updater_builder()
+        .pubkey(pubkey)
+        .endpoints(vec![endpoint])
+        .map_err(|e| format!("Invalid update endpoint: {}", e))?;
+    if let Some(proxy) = proxy_url.as_deref().and_then(|proxy| url::Url::parse(proxy).ok()) {
+        builder = builder.proxy(proxy);
+    }
+    let update = builder
+        .build()
+        .map_err(|e| format!("Failed to set up the updater: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+    
+    let info = update.as_ref().map(update_info);
+    match &info {
+        Some(info) => {
+            println!("⬆️ Update available: {} -> {} ({:?} channel)", info.current_version, info.version, channel);
+            if let Err(e) = app.emit(UPDATE_AVAILABLE_EVENT, info.clone()) {
+                println!("⚠️ Failed to emit update availability: {}", e);
+            }
+        }
+        None => println!("✅ cursper is up to date ({:?} channel)", channel),
+    }
+    if let Ok(mut pending) = PENDING_UPDATE.lock() {
+        *pending = update;
+    }
+    rebuild_tray_menu(app);
+    Ok(info)
+}
+
+// Check the configured channel for a newer release
+#[tauri::command]
+pub async fn check_for_updates(app_handle: AppHandle, state: tauri::State<'_, AppStateType>) -> Result<Option<UpdateInfo>, String> {
+    check_channel(&app_handle, state.inner()).await
+}
+
+// Download the update found by check_for_updates, install it once in-flight dictations
+// are done, and restart into the new version
+#[tauri::command]
+pub async fn install_update(app_handle: AppHandle) -> Result<(), String> {
+    let update = pending_update().ok_or("No update to install, check for updates first")?;
+    println!("⬇️ Downloading update {}...", update.version);
+    
+    let progress_app = app_handle.clone();
+    let mut downloaded_bytes = 0u64;
+    let bytes = update
+        .download(
+            move |chunk_length, total_bytes| {
+                downloaded_bytes += chunk_length as u64;
+                let event = UpdateProgressEvent { downloaded_bytes, total_bytes };
+                if let Err(e) = progress_app.emit(UPDATE_PROGRESS_EVENT, event) {
+                    println!("⚠️ Failed to emit update progress: {}", e);
+                }
+            },
+            || println!("✅ Update downloaded"),
+        )
+        .await
+        .map_err(|e| format!("Failed to download update: {}", e))?;
+    
+    if !prepare_exit(&app_handle).await {
+        return Err("Update postponed to keep the dictation in progress".to_string());
+    }
+    if let Err(e) = update.install(bytes) {
+        // The app keeps running, so quitting from the tray has to work again
+        cancel_exit(&app_handle);
+        return Err(format!("Failed to install update: {}", e));
+    }
+    println!("🔄 Restarting into {}", update.version);
+    app_handle.restart();
+}
+
+// Check automatically every update_check_interval_hours
+pub fn spawn_update_checker(app_handle: AppHandle, state: AppStateType) {
+    if UPDATER_PUBKEY.is_none() {
+        println!("ℹ️ Update checks disabled, this build has no updater key");
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        println!("⬆️ Update checker started");
+        tokio::time::sleep(UPDATE_CHECK_STARTUP_DELAY).await;
+        
+        loop {
+            let interval_hours = state
+                .lock()
+                .map(|app_state| app_state.settings.update_check_interval_hours)
+                .unwrap_or(0);
+            let due = interval_hours > 0
+                && LAST_UPDATE_CHECK
+                    .lock()
+                    .map(|last_check| last_check.map_or(true, |at| at.elapsed() >= Duration::from_secs(interval_hours * 3600)))
+                    .unwrap_or(false);
+            if due {
+                if let Err(e) = check_channel(&app_handle, &state).await {
+                    println!("⚠️ {}", e);
+                }
+            }
+            
+            tokio::time::sleep(UPDATE_CHECK_POLL_INTERVAL).await;
+        }
+    });
+}
+
+fn update_entries(_app: &AppHandle) -> Vec<TrayEntry> {
+    match pending_update() {
+        Some(update) => vec![TrayEntry::item("update:install", format!("Install Update {}", update.version))],
+        None => vec![TrayEntry::item("update:check", "Check for Updates")],
+    }
+}
+
+fn show_message(app: &AppHandle, message: String, kind: MessageDialogKind) {
+    app.dialog().message(message).title("cursper Updates").kind(kind).show(|_| {});
+}
+
+fn on_update_click(app: &AppHandle, id: &str) -> bool {
+    let app = app.clone();
+    match id {
+        "update:check" => {
+            println!("⬆️ Checking for updates from tray menu");
+            tauri::async_runtime::spawn(async move {
+                let state = match app.try_state::<AppStateType>() {
+                    Some(state) => state.inner().clone(),
+                    None => return,
+                };
+                match check_channel(&app, &state).await {
+                    Ok(Some(info)) => confirm_install(app, info),
+                    Ok(None) => show_message(&app, "You're running the latest version.".to_string(), MessageDialogKind::Info),
+                    Err(e) => show_message(&app, e, MessageDialogKind::Error),
+                }
+            });
+        }
+        "update:install" => match pending_update() {
+            Some(update) => confirm_install(app, update_info(&update)),
+            None => rebuild_tray_menu(&app),
+        },
+        _ => return false,
+    }
+    true
+}
+
+// Offer to install a found update right away
+fn confirm_install(app: AppHandle, info: UpdateInfo) {
+    let message = match &info.notes {
+        Some(notes) => format!("cursper {} is available (you have {}).\n\n{}", info.version, info.current_version, notes),
+        None => format!("cursper {} is available (you have {}).", info.version, info.current_version),
+    };
+    let dialog_app = app.clone();
+    dialog_app
+        .dialog()
+        .message(message)
+        .title("cursper Updates")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom("Install and Restart".to_string(), "Later".to_string()))
+        .show(move |install| {
+            if !install {
+                return;
+            }
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = install_update(app.clone()).await {
+                    println!("❌ {}", e);
+                    show_message(&app, e, MessageDialogKind::Error);
+                }
+            });
+        });
+}
+
+// "Check for Updates", or "Install Update" once one was found
+pub const UPDATE_TRAY_SECTION: TraySection = TraySection {
+    order: 900,
+    entries: update_entries,
+    on_click: on_update_click,
+};
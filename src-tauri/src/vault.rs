@@ -0,0 +1,170 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::AppHandle;
+use crate::history::encrypt_history_file;
+use crate::history_index::clear_history_index;
+use crate::recordings::encrypt_recordings;
+
+// Where the history key lives in the OS keychain (Keychain, Credential Manager or
+// the Secret Service)
+const KEYCHAIN_SERVICE: &str = "cursper";
+const KEYCHAIN_ACCOUNT: &str = "history-encryption-key";
+
+// Encrypted files start with MAGIC, then a random nonce, then the XChaCha20-Poly1305
+// ciphertext. History lines hold the same bytes hex-encoded after ENCRYPTED_LINE_PREFIX.
+const MAGIC: &[u8] = b"CURSPER-ENC1";
+const NONCE_LEN: usize = 24;
+pub const ENCRYPTED_LINE_PREFIX: &str = "enc:";
+
+// Error returned when reading history or recordings while they are locked
+pub const HISTORY_LOCKED_ERROR: &str = "History is locked";
+
+// Encrypt new history entries and recordings at rest
+static ENCRYPT_HISTORY: AtomicBool = AtomicBool::new(false);
+
+// Key held in memory after unlock_history; reading needs it while encryption is on
+static UNLOCKED_KEY: Mutex<Option<Key>> = Mutex::new(None);
+
+pub fn set_history_encryption(enabled: bool) {
+    ENCRYPT_HISTORY.store(enabled, Ordering::SeqCst);
+}
+
+pub fn history_encryption() -> bool {
+    ENCRYPT_HISTORY.load(Ordering::SeqCst)
+}
+
+pub fn is_history_locked() -> bool {
+    history_encryption() && unlocked_key().is_none()
+}
+
+fn unlocked_key() -> Option<Key> {
+    UNLOCKED_KEY.lock().ok().and_then(|key| *key)
+}
+
+// Read the key from the keychain, creating it the first time
+fn load_key() -> Result<Key, String> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)
+        .map_err(|e| format!("Failed to open the OS keychain: {}", e))?;
+    match entry.get_password() {
+        Ok(encoded) => {
+            let bytes = hex::decode(encoded.trim()).map_err(|e| format!("History key in the keychain is corrupt: {}", e))?;
+            if bytes.len() != 32 {
+                return Err("History key in the keychain has the wrong length".to_string());
+            }
+            Ok(*Key::from_slice(&bytes))
+        }
+        Err(keyring::Error::NoEntry) => {
+            let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+            entry
+                .set_password(&hex::encode(key))
+                .map_err(|e| format!("Failed to store history key in the keychain: {}", e))?;
+            println!("🔑 Created history encryption key in the OS keychain");
+            Ok(key)
+        }
+        Err(e) => Err(format!("Failed to read history key from the keychain: {}", e)),
+    }
+}
+
+// Dictations keep being saved, encrypted, while history is locked
+fn writing_key() -> Result<Key, String> {
+    match unlocked_key() {
+        Some(key) => Ok(key),
+        None => load_key(),
+    }
+}
+
+// With encryption off, data encrypted earlier stays readable without unlocking
+fn reading_key() -> Result<Key, String> {
+    match unlocked_key() {
+        Some(key) => Ok(key),
+        None if history_encryption() => Err(HISTORY_LOCKED_ERROR.to_string()),
+        None => load_key(),
+    }
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn encrypt(data: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = XChaCha20Poly1305::new(&writing_key()?);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, data)
+        .map_err(|_| "Failed to encrypt history data".to_string())?;
+    
+    let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+// Decrypt data written by encrypt; anything else is returned unchanged
+pub fn decrypt(data: &[u8]) -> Result<Vec<u8>, String> {
+    if !is_encrypted(data) {
+        return Ok(data.to_vec());
+    }
+    let body = &data[MAGIC.len()..];
+    if body.len() < NONCE_LEN {
+        return Err("Encrypted data is truncated".to_string());
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(&reading_key()?)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt history data (wrong key or corrupted file)".to_string())
+}
+
+pub fn encrypt_line(line: &str) -> Result<String, String> {
+    Ok(format!("{}{}", ENCRYPTED_LINE_PREFIX, hex::encode(encrypt(line.as_bytes())?)))
+}
+
+// Decrypt a history line written by encrypt_line; plain lines are returned unchanged
+pub fn decrypt_line(line: &str) -> Result<String, String> {
+    let encoded = match line.strip_prefix(ENCRYPTED_LINE_PREFIX) {
+        Some(encoded) => encoded,
+        None => return Ok(line.to_string()),
+    };
+    let bytes = hex::decode(encoded.trim()).map_err(|e| format!("Encrypted history line is corrupt: {}", e))?;
+    String::from_utf8(decrypt(&bytes)?).map_err(|e| format!("Decrypted history line is not text: {}", e))
+}
+
+// Encrypt history and recordings written before encryption was turned on, and drop the
+// plaintext search index
+pub fn encrypt_existing_data(app: &AppHandle) -> Result<(), String> {
+    println!("🔐 Encrypting existing history and recordings...");
+    clear_history_index(app)?;
+    encrypt_history_file(app)?;
+    let encrypted = encrypt_recordings(app)?;
+    println!("✅ Encrypted history and {} recordings", encrypted);
+    Ok(())
+}
+
+// Load the key from the keychain so history and recordings can be read
+#[tauri::command]
+pub async fn unlock_history() -> Result<(), String> {
+    let key = load_key()?;
+    if let Ok(mut unlocked) = UNLOCKED_KEY.lock() {
+        *unlocked = Some(key);
+    }
+    println!("🔓 History unlocked");
+    Ok(())
+}
+
+// Forget the in-memory key; reading history needs unlock_history again
+#[tauri::command]
+pub async fn lock_history() -> Result<(), String> {
+    if let Ok(mut unlocked) = UNLOCKED_KEY.lock() {
+        *unlocked = None;
+    }
+    println!("🔒 History locked");
+    Ok(())
+}
+
+// Whether history is encrypted and currently locked
+#[tauri::command]
+pub async fn get_history_locked() -> Result<bool, String> {
+    Ok(is_history_locked())
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use crate::crash::breadcrumb;
+use crate::settings::load_settings;
+use crate::types::{recorder, AppState, AppStateType, StateRecoveredEvent, STATE_RECOVERED_EVENT};
+
+// How often the watchdog checks the state mutex for poisoning
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+
+// Rebuild AppState from persisted settings after a panic poisoned the mutex
+pub fn recover_poisoned_state(app: &AppHandle, state: &AppStateType) -> bool {
+    if !state.is_poisoned() {
+        return false;
+    }
+    
+    println!("🚑 App state mutex poisoned, rebuilding from persisted settings...");
+    breadcrumb("state mutex poisoned");
+    
+    let settings = load_settings(app);
+    {
+        let mut app_state = match state.lock() {
+            Ok(app_state) => app_state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // Keep the registered shortcuts, everything else starts fresh
+        let shortcuts = app_state.shortcuts.clone();
+        *app_state = AppState {
+            shortcuts,
+            settings,
+            ..AppState::default()
+        };
+    }
+    state.clear_poison();
+    
+    // The rebuilt state isn't recording, so neither should the microphone be
+    if let Err(e) = recorder().cancel() {
+        println!("⚠️ Failed to cancel recording: {}", e);
+    }
+    
+    if let Err(e) = app.emit(STATE_RECOVERED_EVENT, StateRecoveredEvent {
+        reason: "state mutex poisoned by a panic".to_string(),
+    }) {
+        println!("⚠️ Failed to emit state recovery: {}", e);
+    }
+    
+    println!("✅ App state recovered");
+    true
+}
+
+// Periodically recover the state mutex so commands don't keep failing after a panic
+pub fn spawn_state_watchdog(app_handle: AppHandle, state: AppStateType) {
+    tauri::async_runtime::spawn(async move {
+        println!("🐕 State watchdog started");
+        
+        loop {
+            recover_poisoned_state(&app_handle, &state);
+            tokio::time::sleep(WATCHDOG_INTERVAL).await;
+        }
+    });
+}
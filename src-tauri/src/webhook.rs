@@ -0,0 +1,116 @@
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::http::state_http_client;
+use crate::privacy::is_incognito;
+use crate::types::{AppStateType, TranscriptionResult};
+
+// Header carrying "sha256=<hex HMAC of the body>" when a secret is configured
+const SIGNATURE_HEADER: &str = "X-Cursper-Signature";
+
+// Receivers like n8n can hang; never keep a delivery around for long
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Body posted to the webhook for each final transcription
+#[derive(Clone, Serialize)]
+pub struct WebhookPayload {
+    pub text: String,
+    pub duration_ms: u64,
+    pub app: Option<String>,
+    pub language: Option<String>,
+    // Unix seconds
+    pub timestamp: u64,
+}
+
+// Hex HMAC-SHA256 of the body, so receivers can verify the request came from this app
+fn sign(secret: &str, body: &[u8]) -> Result<String, String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| format!("Invalid webhook secret: {}", e))?;
+    mac.update(body);
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn post_webhook(client: &reqwest::Client, url: &str, secret: Option<&str>, payload: &WebhookPayload) -> Result<(), String> {
+    let body = serde_json::to_vec(payload)
+        .map_err(|e| format!("Failed to serialize webhook payload: {}", e))?;
+    
+    let mut request = client
+        .post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .header("Content-Type", "application/json");
+    if let Some(secret) = secret.filter(|secret| !secret.is_empty()) {
+        request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &body)?));
+    }
+    
+    let response = request
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Webhook request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Webhook returned {}", response.status()));
+    }
+    Ok(())
+}
+
+// Configured webhook URL and secret, if a webhook is set
+fn webhook_target(state: &AppStateType) -> Option<(String, Option<String>)> {
+    let app_state = state.lock().ok()?;
+    let url = app_state
+        .settings
+        .webhook_url
+        .clone()
+        .filter(|url| !url.trim().is_empty())?;
+    Some((url, app_state.settings.webhook_secret.clone()))
+}
+
+// Post a final transcription to the configured webhook in the background; incognito
+// dictations are never sent
+pub fn send_transcription_webhook(state: &AppStateType, result: &TranscriptionResult) {
+    if is_incognito() {
+        return;
+    }
+    let (url, secret) = match webhook_target(state) {
+        Some(target) => target,
+        None => return,
+    };
+    
+    let payload = WebhookPayload {
+        text: result.text.clone(),
+        duration_ms: result.timings.capture_ms,
+        app: state.lock().ok().and_then(|app_state| app_state.focused_app.clone()),
+        language: result.language.clone(),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or(0),
+    };
+    let client = state_http_client(state);
+    
+    tauri::async_runtime::spawn(async move {
+        match post_webhook(&client, &url, secret.as_deref(), &payload).await {
+            Ok(_) => println!("🪝 Transcription sent to webhook"),
+            Err(e) => println!("⚠️ {}", e),
+        }
+    });
+}
+
+// Send a sample payload to the configured webhook so the user can check their receiver
+#[tauri::command]
+pub async fn test_webhook(state: tauri::State<'_, AppStateType>) -> Result<(), String> {
+    let (url, secret) = webhook_target(state.inner()).ok_or("No webhook URL configured")?;
+    let payload = WebhookPayload {
+        text: "This is a test dictation from Cursper.".to_string(),
+        duration_ms: 0,
+        app: None,
+        language: Some("en".to_string()),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or(0),
+    };
+    post_webhook(&state_http_client(state.inner()), &url, secret.as_deref(), &payload).await?;
+    println!("✅ Test webhook delivered");
+    Ok(())
+}
@@ -1,11 +1,368 @@
-use tauri::{AppHandle, Manager, Position, PhysicalPosition};
-use crate::types::CursorPosition;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Monitor, Position, PhysicalPosition, PhysicalSize, Size, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+use crate::settings::save_settings;
+use crate::types::{AppStateType, CursorPosition};
+use crate::text_input::use_helper_processes;
+
+// Label of the overlay window declared in tauri.conf.json, used on the primary monitor
+const PRIMARY_OVERLAY_LABEL: &str = "overlay";
+
+// How long the focus regression test waits for the platform to settle focus after show()
+const FOCUS_SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+// How often monitors are checked for hot-plug changes
+const MONITOR_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+// Label of the docked status bar window, created on first use
+const STATUS_BAR_LABEL: &str = "status-bar";
+
+// Height of the docked status bar in logical pixels
+const STATUS_BAR_HEIGHT: f64 = 32.0;
+
+// Label of the settings window declared in tauri.conf.json
+pub const SETTINGS_WINDOW_LABEL: &str = "main";
+
+// How long the settings window has to stay put before its geometry is saved, so a drag
+// doesn't rewrite the settings file on every step
+const GEOMETRY_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+static NEXT_OVERLAY_ID: AtomicU32 = AtomicU32::new(1);
+
+// Bumped on every move or resize of the settings window; only the latest one is saved
+static GEOMETRY_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// Where the recording indicator is shown
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayPosition {
+    // Small overlay next to the cursor
+    Cursor,
+    // Slim bar docked to the top edge of the cursor's monitor
+    TopBar,
+    // Slim bar docked to the bottom edge of the cursor's monitor
+    BottomBar,
+    // Small overlay at overlay_fixed_position; follows the cursor until a spot is pinned
+    Fixed,
+}
+
+// Screen position in physical pixels
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+// Outer position and inner size of a window in physical pixels
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// One pre-created overlay window per connected monitor
+pub struct OverlayPool(pub Mutex<Vec<OverlaySlot>>);
+
+pub struct OverlaySlot {
+    pub monitor: String,
+    pub label: String,
+}
+
+// Stable key for a monitor; falls back to its geometry when unnamed
+fn monitor_key(monitor: &Monitor) -> String {
+    match monitor.name() {
+        Some(name) => name.clone(),
+        None => format!(
+            "{}x{}@{},{}",
+            monitor.size().width,
+            monitor.size().height,
+            monitor.position().x,
+            monitor.position().y
+        ),
+    }
+}
+
+// Keep an indicator window from ever taking keyboard focus, so show() can't redirect
+// the transcription into it, and let clicks pass through to the app underneath
+fn make_non_activating(window: &WebviewWindow) {
+    if let Err(e) = window.set_focusable(false) {
+        println!("⚠️ Failed to make {} non-focusable: {}", window.label(), e);
+    }
+    if let Err(e) = window.set_ignore_cursor_events(true) {
+        println!("⚠️ Failed to make {} click-through: {}", window.label(), e);
+    }
+}
+
+// Create an overlay window matching the configured one, placed on the given monitor
+fn create_overlay_window(app: &AppHandle, monitor: &Monitor) -> Result<String, String> {
+    let label = format!("{}-{}", PRIMARY_OVERLAY_LABEL, NEXT_OVERLAY_ID.fetch_add(1, Ordering::SeqCst));
+    let scale = monitor.scale_factor();
+    
+    let builder = WebviewWindowBuilder::new(app, &label, WebviewUrl::App("/overlay".into()))
+        .title("Cursper Overlay")
+        .inner_size(512.0, 512.0)
+        .position(monitor.position().x as f64 / scale, monitor.position().y as f64 / scale)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(false)
+        .focusable(false)
+        .accept_first_mouse(true)
+        .visible(false);
+    #[cfg(not(target_os = "macos"))]
+    let builder = builder.transparent(true);
+    
+    let window = builder
+        .build()
+        .map_err(|e| format!("Failed to create overlay window: {}", e))?;
+    make_non_activating(&window);
+    
+    println!("🪟 Created overlay window {} for monitor {}", label, monitor_key(monitor));
+    Ok(label)
+}
+
+// Bring the overlay pool in line with the connected monitors
+pub fn sync_overlay_pool(app: &AppHandle) -> Result<(), String> {
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {}", e))?;
+    let primary_key = app
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map(|monitor| monitor_key(&monitor));
+    
+    let pool = match app.try_state::<OverlayPool>() {
+        Some(pool) => pool,
+        None => return Err("Overlay pool not initialized".to_string()),
+    };
+    let mut slots = pool.0.lock().map_err(|e| e.to_string())?;
+    let keys: Vec<String> = monitors.iter().map(monitor_key).collect();
+    
+    // Close windows of monitors that were unplugged
+    slots.retain(|slot| {
+        if keys.contains(&slot.monitor) {
+            return true;
+        }
+        println!("🔌 Monitor {} disconnected", slot.monitor);
+        if slot.label != PRIMARY_OVERLAY_LABEL {
+            if let Some(window) = app.get_webview_window(&slot.label) {
+                let _ = window.destroy();
+            }
+        }
+        false
+    });
+    
+    for (monitor, key) in monitors.iter().zip(keys) {
+        if slots.iter().any(|slot| slot.monitor == key) {
+            continue;
+        }
+        
+        // The configured window serves the primary monitor (or the first one if unknown)
+        let primary_free = !slots.iter().any(|slot| slot.label == PRIMARY_OVERLAY_LABEL);
+        let is_primary = primary_key.as_deref().map(|primary| primary == key).unwrap_or(primary_free);
+        let label = if is_primary && primary_free {
+            if let Some(window) = app.get_webview_window(PRIMARY_OVERLAY_LABEL) {
+                make_non_activating(&window);
+            }
+            PRIMARY_OVERLAY_LABEL.to_string()
+        } else {
+            match create_overlay_window(app, monitor) {
+                Ok(label) => label,
+                Err(e) => {
+                    println!("⚠️ {}", e);
+                    continue;
+                }
+            }
+        };
+        
+        println!("🖥️ Overlay {} assigned to monitor {}", label, key);
+        slots.push(OverlaySlot { monitor: key, label });
+    }
+    
+    Ok(())
+}
+
+// Build the overlay pool and keep it updated as monitors come and go
+pub fn setup_overlay_pool(app: &AppHandle) {
+    app.manage(OverlayPool(Mutex::new(Vec::new())));
+    if let Err(e) = sync_overlay_pool(app) {
+        println!("⚠️ Failed to build overlay pool: {}", e);
+    }
+    
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(MONITOR_CHECK_INTERVAL).await;
+            if let Err(e) = sync_overlay_pool(&app_handle) {
+                println!("⚠️ Failed to refresh overlay pool: {}", e);
+            }
+        }
+    });
+}
+
+// Labels of all pooled overlay windows
+fn overlay_labels(app: &AppHandle) -> Vec<String> {
+    app.try_state::<OverlayPool>()
+        .and_then(|pool| pool.0.lock().ok().map(|slots| slots.iter().map(|slot| slot.label.clone()).collect()))
+        .unwrap_or_else(|| vec![PRIMARY_OVERLAY_LABEL.to_string()])
+}
+
+// Pick the overlay window living on the monitor under the cursor
+fn overlay_label_for_point(app: &AppHandle, x: i32, y: i32) -> String {
+    let key = match app.monitor_from_point(x as f64, y as f64) {
+        Ok(Some(monitor)) => monitor_key(&monitor),
+        _ => return PRIMARY_OVERLAY_LABEL.to_string(),
+    };
+    
+    app.try_state::<OverlayPool>()
+        .and_then(|pool| {
+            pool.0
+                .lock()
+                .ok()
+                .and_then(|slots| slots.iter().find(|slot| slot.monitor == key).map(|slot| slot.label.clone()))
+        })
+        .unwrap_or_else(|| PRIMARY_OVERLAY_LABEL.to_string())
+}
+
+// Get the status bar window, creating it the first time it's needed
+fn status_bar_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window(STATUS_BAR_LABEL) {
+        return Ok(window);
+    }
+    
+    let builder = WebviewWindowBuilder::new(app, STATUS_BAR_LABEL, WebviewUrl::App("/status-bar".into()))
+        .title("Cursper Status")
+        .inner_size(800.0, STATUS_BAR_HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(false)
+        .focusable(false)
+        .accept_first_mouse(true)
+        .visible(false);
+    #[cfg(not(target_os = "macos"))]
+    let builder = builder.transparent(true);
+    
+    let window = builder
+        .build()
+        .map_err(|e| format!("Failed to create status bar window: {}", e))?;
+    make_non_activating(&window);
+    println!("🪟 Created status bar window");
+    Ok(window)
+}
+
+// Dock the status bar across the top or bottom edge of the monitor under the cursor
+fn show_status_bar(app: &AppHandle, cursor: &CursorPosition, position: OverlayPosition) -> Result<(), String> {
+    let monitor = match app.monitor_from_point(cursor.x as f64, cursor.y as f64) {
+        Ok(Some(monitor)) => monitor,
+        _ => app
+            .primary_monitor()
+            .map_err(|e| format!("Failed to read primary monitor: {}", e))?
+            .ok_or_else(|| "No monitor to dock the status bar to".to_string())?,
+    };
+    
+    let height = (STATUS_BAR_HEIGHT * monitor.scale_factor()).round() as u32;
+    let y = match position {
+        OverlayPosition::BottomBar => monitor.position().y + monitor.size().height as i32 - height as i32,
+        _ => monitor.position().y,
+    };
+    
+    let window = status_bar_window(app)?;
+    window
+        .set_size(Size::Physical(PhysicalSize {
+            width: monitor.size().width,
+            height,
+        }))
+        .map_err(|e| format!("Failed to size status bar: {}", e))?;
+    window
+        .set_position(Position::Physical(PhysicalPosition {
+            x: monitor.position().x,
+            y,
+        }))
+        .map_err(|e| format!("Failed to position status bar: {}", e))?;
+    window.show().map_err(|e| format!("Failed to show status bar: {}", e))?;
+    window
+        .set_always_on_top(true)
+        .map_err(|e| format!("Failed to set status bar always on top: {}", e))?;
+    
+    println!("✅ Status bar docked to monitor {} ({:?})", monitor_key(&monitor), position);
+    Ok(())
+}
+
+// Last position we managed to read, used when the compositor won't tell us
+#[cfg(target_os = "linux")]
+static LAST_CURSOR_POSITION: std::sync::Mutex<Option<(i32, i32)>> = std::sync::Mutex::new(None);
+
+// Query the pointer through X11 (also works for XWayland clients)
+#[cfg(target_os = "linux")]
+fn x11_cursor_position() -> Result<CursorPosition, String> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::ConnectionExt;
+    
+    let (conn, screen_num) = x11rb::connect(None)
+        .map_err(|e| format!("Failed to connect to X server: {}", e))?;
+    let root = conn.setup().roots[screen_num].root;
+    let reply = conn
+        .query_pointer(root)
+        .map_err(|e| format!("Failed to query pointer: {}", e))?
+        .reply()
+        .map_err(|e| format!("Failed to query pointer: {}", e))?;
+    
+    Ok(CursorPosition {
+        x: reply.root_x as i32,
+        y: reply.root_y as i32,
+    })
+}
+
+// Ask Wayland compositors that expose the pointer position over IPC
+#[cfg(target_os = "linux")]
+fn wayland_cursor_position() -> Result<CursorPosition, String> {
+    if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+        let output = std::process::Command::new("hyprctl")
+            .arg("cursorpos")
+            .output()
+            .map_err(|e| format!("Failed to run hyprctl: {}", e))?;
+        let output_str = String::from_utf8_lossy(&output.stdout);
+        
+        // Parse the output like "123, 456"
+        let coords: Vec<&str> = output_str.trim().split(", ").collect();
+        if coords.len() == 2 {
+            if let (Ok(x), Ok(y)) = (coords[0].parse::<i32>(), coords[1].parse::<i32>()) {
+                return Ok(CursorPosition { x, y });
+            }
+        }
+        return Err(format!("Could not parse hyprctl output: '{}'", output_str.trim()));
+    }
+    
+    Err("Compositor does not expose the pointer position".to_string())
+}
 
 // Get cursor position using platform-specific APIs
 #[tauri::command]
-pub async fn get_cursor_position() -> Result<CursorPosition, String> {
+pub async fn get_cursor_position(app_handle: AppHandle) -> Result<CursorPosition, String> {
     println!("📍 get_cursor_position called");
     
+    // Ask the windowing system directly; helper processes are only a fallback
+    if !use_helper_processes() {
+        match app_handle.cursor_position() {
+            Ok(position) => {
+                println!("✅ Cursor position: x={}, y={}", position.x, position.y);
+                return Ok(CursorPosition {
+                    x: position.x as i32,
+                    y: position.y as i32,
+                });
+            }
+            Err(e) => println!("⚠️ In-process cursor query failed: {}, falling back", e),
+        }
+    }
+    
     #[cfg(target_os = "macos")]
     {
         println!("🍎 Getting cursor position on macOS using NSEvent");
@@ -14,7 +371,7 @@ pub async fn get_cursor_position() -> Result<CursorPosition, String> {
             .arg("-e")
             .arg("tell application \"System Events\" to return (get position of mouse cursor)")
             .output();
-            
+        
         match output {
             Ok(result) => {
                 let output_str = String::from_utf8_lossy(&result.stdout);
@@ -40,9 +397,42 @@ pub async fn get_cursor_position() -> Result<CursorPosition, String> {
         Ok(CursorPosition { x: 400, y: 300 })
     }
     
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "linux")]
+    {
+        let is_wayland = std::env::var("WAYLAND_DISPLAY").is_ok();
+        println!("🐧 Getting cursor position on Linux ({})", if is_wayland { "Wayland" } else { "X11" });
+        
+        // On Wayland, XWayland only reports the pointer while it's over an X client,
+        // so prefer the compositor and keep X11 as a fallback
+        let position = if is_wayland {
+            wayland_cursor_position().or_else(|_| x11_cursor_position())
+        } else {
+            x11_cursor_position()
+        };
+        
+        match position {
+            Ok(position) => {
+                println!("✅ Cursor position: x={}, y={}", position.x, position.y);
+                if let Ok(mut last) = LAST_CURSOR_POSITION.lock() {
+                    *last = Some((position.x, position.y));
+                }
+                return Ok(position);
+            }
+            Err(e) => println!("⚠️ Could not read cursor position: {}", e),
+        }
+        
+        if let Some((x, y)) = LAST_CURSOR_POSITION.lock().ok().and_then(|last| *last) {
+            println!("📍 Using last known position");
+            return Ok(CursorPosition { x, y });
+        }
+        
+        println!("📍 Using fallback position (center of screen)");
+        Ok(CursorPosition { x: 400, y: 300 })
+    }
+    
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
     {
-        println!("📍 Using default position for non-macOS platform");
+        println!("📍 Using default position for this platform");
         // Default position for other platforms
         Ok(CursorPosition { x: 400, y: 300 })
     }
@@ -53,23 +443,64 @@ pub async fn get_cursor_position() -> Result<CursorPosition, String> {
 pub async fn show_overlay(app_handle: AppHandle, show: bool) -> Result<(), String> {
     println!("👁️ show_overlay called with show={}", show);
     
-    let overlay_window = app_handle.get_webview_window("overlay")
-        .ok_or_else(|| {
-            let error = "Overlay window not found".to_string();
-            println!("❌ {}", error);
-            error
-        })?;
-    
-    println!("✅ Overlay window found successfully");
+    // Keep the overlay off-screen while the screen is being shared
+    let (screen_sharing, overlay_position, pinned_position) = match app_handle.try_state::<AppStateType>() {
+        Some(state) => {
+            let app_state = state.lock().map_err(|e| e.to_string())?;
+            (
+                app_state.settings.pause_overlay_while_sharing && app_state.screen_sharing,
+                app_state.settings.overlay_position,
+                app_state.settings.overlay_fixed_position,
+            )
+        }
+        None => (false, OverlayPosition::Cursor, None),
+    };
     
-    if show {
-        println!("📍 Getting cursor position...");
-        let cursor_pos = get_cursor_position().await?;
-        println!("📍 Cursor position: x={}, y={}", cursor_pos.x, cursor_pos.y);
+    if show && screen_sharing {
+        println!("🖥️ Screen sharing active, not showing overlay");
+    } else if show && matches!(overlay_position, OverlayPosition::TopBar | OverlayPosition::BottomBar) {
+        let cursor_pos = get_cursor_position(app_handle.clone()).await?;
+        for label in overlay_labels(&app_handle) {
+            if let Some(window) = app_handle.get_webview_window(&label) {
+                let _ = window.hide();
+            }
+        }
+        show_status_bar(&app_handle, &cursor_pos, overlay_position).map_err(|e| {
+            println!("❌ {}", e);
+            e
+        })?;
+    } else if show {
+        // A pinned overlay ignores the cursor, unless its monitor was unplugged
+        let (new_x, new_y) = match pinned_position {
+            Some(pinned) if overlay_position == OverlayPosition::Fixed && is_on_screen(&app_handle, pinned.x, pinned.y) => {
+                println!("📌 Overlay pinned at x={}, y={}", pinned.x, pinned.y);
+                (pinned.x, pinned.y)
+            }
+            _ => {
+                println!("📍 Getting cursor position...");
+                let cursor_pos = get_cursor_position(app_handle.clone()).await?;
+                println!("📍 Cursor position: x={}, y={}", cursor_pos.x, cursor_pos.y);
+                (cursor_pos.x + 10, cursor_pos.y + 10)
+            }
+        };
+        
+        // Use the overlay on that monitor and hide the others
+        let label = overlay_label_for_point(&app_handle, new_x, new_y);
+        for other in overlay_labels(&app_handle) {
+            if other != label {
+                if let Some(window) = app_handle.get_webview_window(&other) {
+                    let _ = window.hide();
+                }
+            }
+        }
+        
+        let overlay_window = app_handle.get_webview_window(&label)
+            .ok_or_else(|| {
+                let error = format!("Overlay window {} not found", label);
+                println!("❌ {}", error);
+                error
+            })?;
         
-        // Position overlay near cursor
-        let new_x = cursor_pos.x + 10;
-        let new_y = cursor_pos.y + 10;
         println!("📍 Setting overlay position to: x={}, y={}", new_x, new_y);
         
         overlay_window.set_position(Position::Physical(PhysicalPosition {
@@ -83,6 +514,10 @@ pub async fn show_overlay(app_handle: AppHandle, show: bool) -> Result<(), Strin
         
         println!("✅ Overlay position set successfully");
         
+        if let Some(status_bar) = app_handle.get_webview_window(STATUS_BAR_LABEL) {
+            let _ = status_bar.hide();
+        }
+        
         println!("👁️ Showing overlay window...");
         overlay_window.show().map_err(|e| {
             let error = format!("Failed to show overlay: {}", e);
@@ -99,16 +534,160 @@ pub async fn show_overlay(app_handle: AppHandle, show: bool) -> Result<(), Strin
         
         println!("✅ Overlay shown and set to always on top");
     } else {
-        println!("🔒 Hiding overlay window...");
-        overlay_window.hide().map_err(|e| {
-            let error = format!("Failed to hide overlay: {}", e);
-            println!("❌ {}", error);
-            error
-        })?;
+        println!("🔒 Hiding overlay windows...");
+        for label in overlay_labels(&app_handle) {
+            if let Some(overlay_window) = app_handle.get_webview_window(&label) {
+                overlay_window.hide().map_err(|e| {
+                    let error = format!("Failed to hide overlay: {}", e);
+                    println!("❌ {}", error);
+                    error
+                })?;
+            }
+        }
+        if let Some(status_bar) = app_handle.get_webview_window(STATUS_BAR_LABEL) {
+            status_bar.hide().map_err(|e| format!("Failed to hide status bar: {}", e))?;
+        }
         
         println!("✅ Overlay hidden successfully");
     }
     
     println!("✅ show_overlay completed successfully");
     Ok(())
-} 
\ No newline at end of file
+}
+
+// Whether a point is on a connected monitor, so nothing is restored off-screen after the
+// monitor it was on went away
+fn is_on_screen(app: &AppHandle, x: i32, y: i32) -> bool {
+    matches!(app.monitor_from_point(x as f64, y as f64), Ok(Some(_)))
+}
+
+// Pin the overlay where the cursor is now and switch it to the fixed position
+pub async fn pin_overlay_at_cursor(app_handle: &AppHandle, state: &AppStateType) -> Result<(), String> {
+    let cursor = get_cursor_position(app_handle.clone()).await?;
+    let settings = {
+        let mut app_state = state.lock().map_err(|e| e.to_string())?;
+        app_state.settings.overlay_position = OverlayPosition::Fixed;
+        app_state.settings.overlay_fixed_position = Some(WindowPosition { x: cursor.x, y: cursor.y });
+        app_state.settings.clone()
+    };
+    save_settings(app_handle, &settings)?;
+    println!("📌 Overlay pinned at x={}, y={}", cursor.x, cursor.y);
+    Ok(())
+}
+
+// Move the settings window to where it was last left, if that is still on screen
+pub fn restore_settings_window_geometry(app: &AppHandle) {
+    let geometry = match app.try_state::<AppStateType>() {
+        Some(state) => state.lock().ok().and_then(|app_state| app_state.settings.settings_window),
+        None => None,
+    };
+    let (geometry, window) = match (geometry, app.get_webview_window(SETTINGS_WINDOW_LABEL)) {
+        (Some(geometry), Some(window)) => (geometry, window),
+        _ => return,
+    };
+    if !is_on_screen(app, geometry.x, geometry.y) {
+        println!("⚠️ Saved settings window position is off-screen, leaving it centered");
+        return;
+    }
+    
+    if let Err(e) = window.set_size(Size::Physical(PhysicalSize {
+        width: geometry.width,
+        height: geometry.height,
+    })) {
+        println!("⚠️ Failed to restore settings window size: {}", e);
+    }
+    if let Err(e) = window.set_position(Position::Physical(PhysicalPosition {
+        x: geometry.x,
+        y: geometry.y,
+    })) {
+        println!("⚠️ Failed to restore settings window position: {}", e);
+    }
+}
+
+fn save_settings_window_geometry(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    let position = window.outer_position().map_err(|e| format!("Failed to read window position: {}", e))?;
+    let size = window.inner_size().map_err(|e| format!("Failed to read window size: {}", e))?;
+    let geometry = WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    
+    let state = app.try_state::<AppStateType>().ok_or_else(|| "App state not available".to_string())?;
+    let settings = {
+        let mut app_state = state.lock().map_err(|e| e.to_string())?;
+        if app_state.settings.settings_window == Some(geometry) {
+            return Ok(());
+        }
+        app_state.settings.settings_window = Some(geometry);
+        app_state.settings.clone()
+    };
+    save_settings(app, &settings)
+}
+
+// Restore the settings window's geometry and save it whenever the window is moved or resized
+pub fn setup_settings_window_geometry(app: &AppHandle) {
+    restore_settings_window_geometry(app);
+    
+    let window = match app.get_webview_window(SETTINGS_WINDOW_LABEL) {
+        Some(window) => window,
+        None => return,
+    };
+    let app_handle = app.clone();
+    let tracked = window.clone();
+    window.on_window_event(move |event| {
+        if !matches!(event, WindowEvent::Moved(_) | WindowEvent::Resized(_)) {
+            return;
+        }
+        // Minimizing reports a zero size; keep the geometry from before
+        if tracked.is_minimized().unwrap_or(false) {
+            return;
+        }
+        
+        let generation = GEOMETRY_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        let app_handle = app_handle.clone();
+        let window = tracked.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(GEOMETRY_SAVE_DELAY).await;
+            if GEOMETRY_GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            if let Err(e) = save_settings_window_geometry(&app_handle, &window) {
+                println!("⚠️ Failed to save settings window geometry: {}", e);
+            }
+        });
+    });
+}
+
+// Show the overlay the way a dictation does and fail if any indicator window took focus
+#[tauri::command]
+pub async fn test_overlay_focus(app_handle: AppHandle) -> Result<(), String> {
+    println!("🧪 Testing that the overlay never takes focus...");
+    
+    show_overlay(app_handle.clone(), true).await?;
+    tokio::time::sleep(FOCUS_SETTLE_DELAY).await;
+    
+    let mut labels = overlay_labels(&app_handle);
+    labels.push(STATUS_BAR_LABEL.to_string());
+    let focused: Vec<String> = labels
+        .into_iter()
+        .filter(|label| {
+            app_handle
+                .get_webview_window(label)
+                .and_then(|window| window.is_focused().ok())
+                .unwrap_or(false)
+        })
+        .collect();
+    
+    show_overlay(app_handle, false).await?;
+    
+    if focused.is_empty() {
+        println!("✅ Overlay focus test passed");
+        Ok(())
+    } else {
+        let error = format!("Overlay window took keyboard focus: {}", focused.join(", "));
+        println!("❌ {}", error);
+        Err(error)
+    }
+}